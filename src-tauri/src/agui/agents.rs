@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock, RwLockWriteGuard};
+
+use crate::error::KataraError;
+
+/// An AG-UI agent ID bound to a Katara session, so one CopilotKit app can
+/// talk to several projects (`/agent/frontend/run`, `/agent/docs/run`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Agent {
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    /// Where its session starts; the default working directory if unset.
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    /// The session its runs go to. A new one is started in `working_dir`
+    /// when this is unset or the session has ended.
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// Registered agents, in the order they were created. Persisted across
+/// restarts. An agent ID that isn't registered routes as before.
+pub struct AgentRegistry {
+    agents: RwLock<Vec<Agent>>,
+    /// Held from a change until it's on disk, so saves land in order.
+    saving: Mutex<()>,
+}
+
+impl AgentRegistry {
+    pub fn load() -> Self {
+        let agents = match store_path() {
+            Ok(path) if path.exists() => std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+                .unwrap_or_else(|e| {
+                    eprintln!("[katara] Failed to load agents: {}", e);
+                    Vec::new()
+                }),
+            _ => Vec::new(),
+        };
+        Self {
+            agents: RwLock::new(agents),
+            saving: Mutex::new(()),
+        }
+    }
+
+    pub async fn list(&self) -> Vec<Agent> {
+        self.agents.read().await.clone()
+    }
+
+    pub async fn get(&self, id: &str) -> Option<Agent> {
+        self.agents
+            .read()
+            .await
+            .iter()
+            .find(|a| a.id == id)
+            .cloned()
+    }
+
+    pub async fn create(
+        &self,
+        id: String,
+        description: String,
+        working_dir: Option<String>,
+    ) -> Result<Agent, KataraError> {
+        // The ID goes in the AG-UI URL path
+        let valid = !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid {
+            return Err(KataraError::Config(format!(
+                "Agent ID {:?} may only use letters, digits, '-' and '_'",
+                id
+            )));
+        }
+        let mut agents = self.agents.write().await;
+        if agents.iter().any(|a| a.id == id) {
+            return Err(KataraError::Config(format!("Agent {} already exists", id)));
+        }
+        let agent = Agent {
+            id,
+            description,
+            working_dir: working_dir.filter(|d| !d.is_empty()),
+            session_id: None,
+        };
+        agents.push(agent.clone());
+        self.save(agents).await;
+        Ok(agent)
+    }
+
+    /// Bind the agent to a session, or unbind it with `None` so its next
+    /// run starts a new one.
+    pub async fn assign(&self, id: &str, session_id: Option<String>) -> Result<Agent, KataraError> {
+        let mut agents = self.agents.write().await;
+        let agent = agents
+            .iter_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| KataraError::Config(format!("No agent named {}", id)))?;
+        agent.session_id = session_id;
+        let agent = agent.clone();
+        self.save(agents).await;
+        Ok(agent)
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<(), KataraError> {
+        let mut agents = self.agents.write().await;
+        let before = agents.len();
+        agents.retain(|a| a.id != id);
+        if agents.len() == before {
+            return Err(KataraError::Config(format!("No agent named {}", id)));
+        }
+        self.save(agents).await;
+        Ok(())
+    }
+
+    /// Write the agents to disk on a blocking thread, without holding the
+    /// registry lock while the file is written.
+    async fn save(&self, agents: RwLockWriteGuard<'_, Vec<Agent>>) {
+        let _saving = self.saving.lock().await;
+        let snapshot = agents.clone();
+        drop(agents);
+        let result = tokio::task::spawn_blocking(move || write(&snapshot))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|written| written);
+        if let Err(e) = result {
+            eprintln!("[katara] Failed to save agents: {}", e);
+        }
+    }
+}
+
+fn write(agents: &[Agent]) -> Result<(), String> {
+    let path = store_path().map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(agents).map_err(|e| e.to_string())?;
+    // Write-then-rename so a crash mid-write can't lose every agent
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+fn store_path() -> Result<PathBuf, KataraError> {
+    Ok(crate::config::katara_dir()?.join("agents.json"))
+}
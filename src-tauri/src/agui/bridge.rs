@@ -11,6 +11,8 @@ pub struct BridgeState {
     block_tool_ids: std::collections::HashMap<u64, String>,
     /// Whether we've received any streaming text events
     has_streamed_text: bool,
+    /// Whether we've received any streaming thinking events
+    has_streamed_thinking: bool,
     /// Tool IDs that were already streamed
     streamed_tool_ids: std::collections::HashSet<String>,
 }
@@ -27,15 +29,16 @@ impl BridgeState {
 /// and the AG-UI protocol that CopilotKit's frontend understands.
 ///
 /// Message lifecycle from Claude CLI:
-///   1. stream_event(content_block_start)  — new text or tool_use block
-///   2. stream_event(content_block_delta)  — token-by-token text or partial JSON
+///   1. stream_event(content_block_start)  — new thinking, text or tool_use block
+///   2. stream_event(content_block_delta)  — token-by-token thinking, text or partial JSON
 ///   3. stream_event(content_block_stop)   — block finished
 ///   4. assistant                          — final complete message (all blocks)
 ///   5. result                             — turn complete
 ///
 /// We emit AG-UI events from streaming events for real-time display.
-/// The final `assistant` message is used only for tool_use blocks
-/// that weren't already streamed.
+/// The final `assistant` message is used only for blocks that weren't
+/// already streamed. Thinking goes out as THINKING_* events, so the UI can
+/// show the reasoning apart from the answer.
 pub fn translate_claude_message(
     msg: &ClaudeMessage,
     thread_id: &str,
@@ -77,6 +80,13 @@ pub fn translate_claude_message(
                             role: "assistant".into(),
                         });
                         bridge.has_streamed_text = true;
+                    } else if block_type == "thinking" {
+                        events.push(AguiEvent::ThinkingStart { title: None });
+                        events.push(AguiEvent::ThinkingTextMessageStart {});
+                        bridge.has_streamed_thinking = true;
+                    } else if block_type == "redacted_thinking" {
+                        events.push(AguiEvent::ThinkingStart { title: None });
+                        bridge.has_streamed_thinking = true;
                     } else if block_type == "tool_use" {
                         let cb = stream.event.extra.get("content_block");
                         let tool_id = cb
@@ -113,6 +123,12 @@ pub fn translate_claude_message(
                                     delta: text.clone(),
                                 });
                             }
+                        } else if delta.delta_type == "thinking_delta" {
+                            if let Some(ref thinking) = delta.thinking {
+                                events.push(AguiEvent::ThinkingTextMessageContent {
+                                    delta: thinking.clone(),
+                                });
+                            }
                         } else if delta.delta_type == "input_json_delta" {
                             if let Some(ref partial) = delta.partial_json {
                                 let tool_id = bridge
@@ -140,6 +156,13 @@ pub fn translate_claude_message(
                                 message_id: msg_id,
                             });
                         }
+                        Some("thinking") => {
+                            events.push(AguiEvent::ThinkingTextMessageEnd {});
+                            events.push(AguiEvent::ThinkingEnd {});
+                        }
+                        Some("redacted_thinking") => {
+                            events.push(AguiEvent::ThinkingEnd {});
+                        }
                         Some("tool_use") => {
                             let tool_id = bridge
                                 .block_tool_ids
@@ -204,7 +227,18 @@ pub fn translate_claude_message(
                             });
                         }
                     }
-                    ContentBlock::ToolResult { .. } => {}
+                    ContentBlock::Thinking { thinking, .. } => {
+                        if !bridge.has_streamed_thinking {
+                            events.push(AguiEvent::ThinkingStart { title: None });
+                            events.push(AguiEvent::ThinkingTextMessageStart {});
+                            events.push(AguiEvent::ThinkingTextMessageContent {
+                                delta: thinking.clone(),
+                            });
+                            events.push(AguiEvent::ThinkingTextMessageEnd {});
+                            events.push(AguiEvent::ThinkingEnd {});
+                        }
+                    }
+                    ContentBlock::ToolResult { .. } | ContentBlock::RedactedThinking { .. } => {}
                 }
             }
         }
@@ -235,3 +269,148 @@ pub fn translate_claude_message(
 
     events
 }
+
+/// Rebuilds a session's stored history as AG-UI messages, for a
+/// MESSAGES_SNAPSHOT when CopilotKit (re)connects to a thread.
+///
+/// User turns become `user` messages, assistant text and tool calls become
+/// `assistant` messages (the CLI sends one `assistant` message per content
+/// block, so blocks sharing a message ID are merged), and tool results
+/// become `tool` messages. Everything else is skipped.
+pub fn history_to_messages(
+    history: &[std::sync::Arc<serde_json::Value>],
+) -> Vec<serde_json::Value> {
+    let mut messages: Vec<serde_json::Value> = Vec::new();
+
+    for entry in history {
+        match entry.get("type").and_then(|t| t.as_str()) {
+            Some("user_message") => {
+                let Some(content) = entry.get("content").and_then(|c| c.as_str()) else {
+                    continue;
+                };
+                let id = entry
+                    .get("id")
+                    .and_then(|i| i.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("user-{}", messages.len()));
+                messages.push(serde_json::json!({
+                    "id": id,
+                    "role": "user",
+                    "content": content,
+                }));
+            }
+
+            Some("assistant") => {
+                let Some(message) = entry.get("message") else {
+                    continue;
+                };
+                let id = message
+                    .get("id")
+                    .and_then(|i| i.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("assistant-{}", messages.len()));
+                let blocks = message
+                    .get("content")
+                    .and_then(|c| c.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+
+                let mut text = Vec::new();
+                let mut tool_calls = Vec::new();
+                for block in &blocks {
+                    match block.get("type").and_then(|t| t.as_str()) {
+                        Some("text") => {
+                            if let Some(t) = block.get("text").and_then(|t| t.as_str()) {
+                                text.push(t.to_string());
+                            }
+                        }
+                        Some("tool_use") => tool_calls.push(serde_json::json!({
+                            "id": block.get("id").cloned().unwrap_or_default(),
+                            "type": "function",
+                            "function": {
+                                "name": block.get("name").cloned().unwrap_or_default(),
+                                "arguments": block
+                                    .get("input")
+                                    .map(|i| i.to_string())
+                                    .unwrap_or_else(|| "{}".into()),
+                            },
+                        })),
+                        _ => {}
+                    }
+                }
+                if text.is_empty() && tool_calls.is_empty() {
+                    continue;
+                }
+
+                // Merge into the previous message if it's the same one
+                let previous = messages
+                    .last_mut()
+                    .filter(|m| m.get("id").and_then(|i| i.as_str()) == Some(id.as_str()));
+                if let Some(previous) = previous {
+                    if !text.is_empty() {
+                        let mut content = previous
+                            .get("content")
+                            .and_then(|c| c.as_str())
+                            .unwrap_or("")
+                            .to_string();
+                        content.push_str(&text.join(""));
+                        previous["content"] = content.into();
+                    }
+                    if !tool_calls.is_empty() {
+                        match previous.get_mut("toolCalls").and_then(|t| t.as_array_mut()) {
+                            Some(existing) => existing.extend(tool_calls),
+                            None => previous["toolCalls"] = tool_calls.into(),
+                        }
+                    }
+                    continue;
+                }
+
+                let mut assistant = serde_json::json!({
+                    "id": id,
+                    "role": "assistant",
+                    "content": text.join(""),
+                });
+                if !tool_calls.is_empty() {
+                    assistant["toolCalls"] = tool_calls.into();
+                }
+                messages.push(assistant);
+            }
+
+            // Tool results come back from the CLI as user messages
+            Some("user") => {
+                let blocks = entry
+                    .get("message")
+                    .and_then(|m| m.get("content"))
+                    .and_then(|c| c.as_array());
+                for block in blocks.into_iter().flatten() {
+                    if block.get("type").and_then(|t| t.as_str()) != Some("tool_result") {
+                        continue;
+                    }
+                    let Some(tool_use_id) = block.get("tool_use_id").and_then(|i| i.as_str())
+                    else {
+                        continue;
+                    };
+                    let content = match block.get("content") {
+                        Some(serde_json::Value::String(s)) => s.clone(),
+                        Some(serde_json::Value::Array(parts)) => parts
+                            .iter()
+                            .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        _ => String::new(),
+                    };
+                    messages.push(serde_json::json!({
+                        "id": format!("result-{}", tool_use_id),
+                        "role": "tool",
+                        "toolCallId": tool_use_id,
+                        "content": content,
+                    }));
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    messages
+}
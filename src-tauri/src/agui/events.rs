@@ -80,9 +80,34 @@ pub enum AguiEvent {
         tool_call_id: String,
     },
 
+    #[serde(rename = "THINKING_START")]
+    ThinkingStart {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+    },
+
+    #[serde(rename = "THINKING_END")]
+    ThinkingEnd {},
+
+    #[serde(rename = "THINKING_TEXT_MESSAGE_START")]
+    ThinkingTextMessageStart {},
+
+    #[serde(rename = "THINKING_TEXT_MESSAGE_CONTENT")]
+    ThinkingTextMessageContent { delta: String },
+
+    #[serde(rename = "THINKING_TEXT_MESSAGE_END")]
+    ThinkingTextMessageEnd {},
+
     #[serde(rename = "STATE_SNAPSHOT")]
     StateSnapshot { snapshot: serde_json::Value },
 
+    /// JSON Patch (RFC 6902) operations against the last snapshot.
+    #[serde(rename = "STATE_DELTA")]
+    StateDelta { delta: Vec<serde_json::Value> },
+
+    #[serde(rename = "MESSAGES_SNAPSHOT")]
+    MessagesSnapshot { messages: Vec<serde_json::Value> },
+
     #[serde(rename = "CUSTOM")]
     Custom {
         name: String,
@@ -1,3 +1,6 @@
+pub mod agents;
 pub mod bridge;
 pub mod events;
+pub mod replay;
 pub mod server;
+pub mod state;
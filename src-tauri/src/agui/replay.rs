@@ -0,0 +1,176 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::{broadcast, mpsc};
+
+use crate::agui::events::AguiEvent;
+use crate::state::AppState;
+
+/// How long a finished run's events stay around for a client to resume.
+const RETAIN_FINISHED: Duration = Duration::from_secs(300);
+
+/// Events kept per run; a longer run drops its oldest.
+const MAX_EVENTS_PER_RUN: usize = 20_000;
+
+/// One AG-UI event as sent, numbered from 1 within its run.
+#[derive(Debug, Clone)]
+pub struct SseEvent {
+    pub seq: u64,
+    pub data: String,
+}
+
+struct BufferedRun {
+    thread_id: String,
+    events: VecDeque<SseEvent>,
+    /// `None` once the run has finished.
+    live: Option<broadcast::Sender<SseEvent>>,
+    finished_at: Option<Instant>,
+}
+
+/// The events of recent AG-UI runs, keyed by run ID, so a client whose
+/// connection dropped can reconnect with `Last-Event-ID` and pick up where
+/// it left off. Runs keep going while nobody is connected.
+#[derive(Default)]
+pub struct RunBuffers {
+    runs: std::sync::Mutex<HashMap<String, BufferedRun>>,
+}
+
+impl RunBuffers {
+    fn start(&self, run_id: &str, thread_id: &str) {
+        let mut runs = self.lock();
+        let (live, _) = broadcast::channel(256);
+        runs.insert(
+            run_id.to_string(),
+            BufferedRun {
+                thread_id: thread_id.to_string(),
+                events: VecDeque::new(),
+                live: Some(live),
+                finished_at: None,
+            },
+        );
+    }
+
+    fn push(&self, run_id: &str, data: String) {
+        let mut runs = self.lock();
+        let Some(run) = runs.get_mut(run_id) else {
+            return;
+        };
+        let seq = run.events.back().map_or(1, |e| e.seq + 1);
+        let event = SseEvent { seq, data };
+        if let Some(live) = &run.live {
+            let _ = live.send(event.clone());
+        }
+        run.events.push_back(event);
+        if run.events.len() > MAX_EVENTS_PER_RUN {
+            run.events.pop_front();
+        }
+    }
+
+    fn finish(&self, run_id: &str) {
+        let mut runs = self.lock();
+        if let Some(run) = runs.get_mut(run_id) {
+            run.live = None;
+            run.finished_at = Some(Instant::now());
+        }
+    }
+
+    /// Whether `run_id` is still buffered and belongs to `thread_id`.
+    pub fn contains(&self, run_id: &str, thread_id: &str) -> bool {
+        self.lock()
+            .get(run_id)
+            .is_some_and(|run| run.thread_id == thread_id)
+    }
+
+    /// The buffered events after `after`, and a receiver for the ones still
+    /// to come unless the run has finished. Taken under one lock, so
+    /// nothing falls between them.
+    fn subscribe(
+        &self,
+        run_id: &str,
+        after: u64,
+    ) -> Option<(Vec<SseEvent>, Option<broadcast::Receiver<SseEvent>>)> {
+        let runs = self.lock();
+        let run = runs.get(run_id)?;
+        let backlog = run
+            .events
+            .iter()
+            .filter(|e| e.seq > after)
+            .cloned()
+            .collect();
+        Some((backlog, run.live.as_ref().map(|l| l.subscribe())))
+    }
+
+    /// The buffered runs, with finished ones past their retention dropped
+    /// so they can't be resumed, whether or not a new run has started since.
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, BufferedRun>> {
+        let mut runs = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        runs.retain(|_, run| {
+            run.finished_at
+                .is_none_or(|at| at.elapsed() < RETAIN_FINISHED)
+        });
+        runs
+    }
+}
+
+/// Buffer the run's events as they're produced, until `events` closes.
+pub fn record(
+    state: Arc<AppState>,
+    run_id: String,
+    thread_id: &str,
+    mut events: mpsc::Receiver<AguiEvent>,
+) {
+    state.agui_buffers.start(&run_id, thread_id);
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            let data = serde_json::to_string(&event).unwrap_or_default();
+            state.agui_buffers.push(&run_id, data);
+        }
+        state.agui_buffers.finish(&run_id);
+    });
+}
+
+/// The run's events after `after`, then live ones until it finishes.
+pub fn follow(state: Arc<AppState>, run_id: String, after: u64) -> mpsc::Receiver<SseEvent> {
+    let (tx, rx) = mpsc::channel(128);
+    tokio::spawn(async move {
+        let mut last = after;
+        // Each pass catches up from the buffer, then follows live events
+        // until the run finishes or this receiver falls behind
+        loop {
+            let Some((backlog, live)) = state.agui_buffers.subscribe(&run_id, last) else {
+                return;
+            };
+            for event in backlog {
+                last = event.seq;
+                if tx.send(event).await.is_err() {
+                    return; // Client disconnected
+                }
+            }
+            let Some(mut live) = live else {
+                return;
+            };
+            while let Ok(event) = live.recv().await {
+                if event.seq <= last {
+                    continue;
+                }
+                last = event.seq;
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    rx
+}
+
+/// The SSE event ID for an event: `<run ID>:<seq>`.
+pub fn event_id(run_id: &str, seq: u64) -> String {
+    format!("{}:{}", run_id, seq)
+}
+
+/// The run ID and sequence number in a `Last-Event-ID`.
+pub fn parse_event_id(id: &str) -> Option<(String, u64)> {
+    let (run_id, seq) = id.rsplit_once(':')?;
+    Some((run_id.to_string(), seq.parse().ok()?))
+}
@@ -1,24 +1,40 @@
+use std::collections::HashMap;
 use std::convert::Infallible;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    http::Request,
+    extract::{Extension, Path, State},
+    http::{HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::{self, Next},
     response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use futures_util::stream::Stream;
 use tokio_stream::StreamExt;
-use tower_http::cors::CorsLayer;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 
-use tauri::Emitter;
-
-use crate::agui::bridge::{translate_claude_message, BridgeState};
+use crate::agui::agents::Agent;
+use crate::agui::bridge::{self, translate_claude_message, BridgeState};
 use crate::agui::events::{AguiEvent, RunAgentInput};
+use crate::agui::replay;
+use crate::auth;
+use crate::commands::skills::{finish_skill_run, start_skill_run};
 use crate::error::KataraError;
+use crate::instance;
+use crate::mcp::frontend_tools::FrontendTool;
+use crate::mcp::server::SERVER_NAME as MCP_SERVER_NAME;
+use crate::process::session::{Session, SessionStatus};
+use crate::remote::pairing::PairedDevice;
+use crate::sink::Sink;
+use crate::skills::manager as skill_mgr;
+use crate::skills::tools::{self, SkillTool};
 use crate::state::AppState;
-use crate::websocket::protocol::ClaudeMessage;
+use crate::websocket::protocol::{ClaudeMessage, WsEvent};
+
+/// How long a run waits for a CLI to connect before giving up.
+const SESSION_WAIT: std::time::Duration = std::time::Duration::from_secs(15);
 
 /// Creates the Axum router with AG-UI endpoints.
 ///
@@ -26,18 +42,29 @@ use crate::websocket::protocol::ClaudeMessage;
 ///   - POST /agent/{agentId}/run  — main SSE streaming endpoint
 ///   - GET  /info                 — agent discovery
 ///   - POST /agent/{agentId}/stop/{threadId} — stop a running agent
+///   - GET  /thread/{threadId}/messages — a thread's history
 ///
 /// We also keep /api/copilotkit as a fallback for older CopilotKit versions.
-fn create_router(state: Arc<AppState>) -> Router {
+///
+/// Every AG-UI, REST and OpenAI-compatible endpoint needs the per-launch
+/// auth token as a bearer token, and browsers may only call from the
+/// origins in settings.
+fn create_router(state: Arc<AppState>, sink: Sink) -> Router {
+    let agui = agui_routes()
+        // OpenAI-compatible endpoints, so OpenAI clients can drive a session
+        .route("/v1/chat/completions", post(crate::openai::server::chat_completions))
+        .route("/v1/models", get(crate::openai::server::models))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token));
+
     Router::new()
-        // AG-UI v1.51 endpoints (primary)
-        .route("/agent/{agent_id}/run", post(agui_handler_with_agent))
-        .route("/agent/{agent_id}/connect", post(agui_handler_with_agent))
-        // Legacy / fallback endpoints
-        .route("/api/copilotkit", post(agui_handler_legacy))
-        // Info / discovery (GET for REST transport, POST for single transport)
-        .route("/info", get(info_handler).post(info_handler_post))
-        .route("/api/copilotkit/info", get(info_handler).post(info_handler_post))
+        .merge(agui)
+        // MCP endpoint spawned sessions use to control the app
+        .route(
+            "/mcp",
+            post(crate::mcp::server::handle).get(crate::mcp::server::notifications),
+        )
+        // Live CLI messages for web UIs and dashboards
+        .route("/ws/ui", get(crate::websocket::ui::handle))
         // Catch-all fallback for debugging unmatched requests
         .fallback(|req: Request<axum::body::Body>| async move {
             println!(
@@ -47,69 +74,239 @@ fn create_router(state: Arc<AppState>) -> Router {
             );
             (axum::http::StatusCode::NOT_FOUND, "Not Found")
         })
-        .layer(CorsLayer::permissive())
+        .layer(cors(&state))
+        .layer(Extension(sink))
         .with_state(state)
 }
 
+/// The AG-UI and REST routes, without authentication. Also served to paired
+/// devices by the remote access server.
+pub(crate) fn agui_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        // AG-UI v1.51 endpoints (primary)
+        .route("/agent/{agent_id}/run", post(agui_handler_with_agent))
+        .route("/agent/{agent_id}/connect", post(agui_handler_with_agent))
+        .route("/agent/{agent_id}/stop/{thread_id}", post(stop_handler))
+        // Thread history, for clients that can't call Tauri commands
+        .route("/thread/{thread_id}/messages", get(thread_messages_handler))
+        // Legacy / fallback endpoints
+        .route("/api/copilotkit", post(agui_handler_legacy))
+        // Info / discovery (GET for REST transport, POST for single transport)
+        .route("/info", get(info_handler).post(info_handler_post))
+        .route("/api/copilotkit/info", get(info_handler).post(info_handler_post))
+        // REST API for scripts, e.g. /api/v1/sessions
+        .nest("/api/v1", crate::api::server::router())
+}
+
+/// Browsers may only call from the origins in settings.
+pub(crate) fn cors(state: &Arc<AppState>) -> CorsLayer {
+    let state = state.clone();
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            origin_allowed(&state, origin)
+        }))
+        .allow_methods(Any)
+        .allow_headers(Any)
+}
+
+/// Reject requests without the auth token. MCP, `/ws/ui` and the OpenAI
+/// endpoints check it themselves.
+async fn require_token(
+    State(state): State<Arc<AppState>>,
+    request: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    if !auth::bearer_matches(request.headers(), &state.auth_token) {
+        println!(
+            "[katara] Rejected request without a valid token: {} {}",
+            request.method(),
+            request.uri()
+        );
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether a browser at `origin` may call the server, per
+/// `server.allowed_origins`. Read on every request, so changes apply
+/// without a restart.
+fn origin_allowed(state: &AppState, origin: &HeaderValue) -> bool {
+    let Ok(origin) = origin.to_str() else {
+        return false;
+    };
+    state
+        .settings()
+        .server
+        .allowed_origins
+        .iter()
+        .any(|allowed| allowed == "*" || allowed.trim_end_matches('/') == origin)
+}
+
 /// GET /api/copilotkit/info — CopilotKit runtime discovery endpoint.
 ///
 /// Returns agent metadata so CopilotKit knows what agents are available.
 /// CopilotKit expects agents as an object keyed by agent ID, not an array.
-async fn info_handler() -> Json<serde_json::Value> {
+async fn info_handler(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
     println!("[katara] /info endpoint hit — returning agent discovery response");
-    Json(serde_json::json!({
-        "agents": {
-            "default": {
-                "description": "Claude Code AI agent"
-            }
-        },
-        "version": "1.0.0"
-    }))
+    Json(agent_info(&state).await)
 }
 
 /// POST /info — CopilotKit "single" transport info endpoint.
 ///
 /// Same response as GET /info but accepts POST with `{ "method": "info" }` body.
-async fn info_handler_post() -> Json<serde_json::Value> {
+async fn info_handler_post(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
     println!("[katara] /info endpoint hit (POST) — returning agent discovery response");
-    Json(serde_json::json!({
-        "agents": {
-            "default": {
-                "description": "Claude Code AI agent"
+    Json(agent_info(&state).await)
+}
+
+/// Agent discovery response: "default" plus the registered agents. Enabled
+/// skills are listed as actions the chat can invoke; see
+/// `run_skill_invocation`.
+async fn agent_info(state: &AppState) -> serde_json::Value {
+    let default_dir = state.settings().default_working_dir;
+    let skills = skill_tools(state, default_dir.clone()).await;
+    let mut agents = serde_json::Map::new();
+    agents.insert(
+        "default".into(),
+        serde_json::json!({
+            "description": "Claude Code AI agent",
+            "skills": skills
+        }),
+    );
+    for agent in state.agents.list().await {
+        let skills = match &agent.working_dir {
+            Some(dir) if Some(dir) != default_dir.as_ref() => {
+                skill_tools(state, Some(dir.clone())).await
             }
-        },
+            _ => skills.clone(),
+        };
+        let description = match (agent.description.is_empty(), &agent.working_dir) {
+            (false, _) => agent.description.clone(),
+            (true, Some(dir)) => format!("Claude Code agent in {}", dir),
+            (true, None) => "Claude Code AI agent".to_string(),
+        };
+        agents.insert(
+            agent.id,
+            serde_json::json!({
+                "description": description,
+                "skills": skills
+            }),
+        );
+    }
+    serde_json::json!({
+        "agents": agents,
         "version": "1.0.0"
-    }))
+    })
+}
+
+/// Actions for the enabled skills in the configured skills directory, plus
+/// the project skills of `working_dir`. Listed on a blocking thread, since
+/// every skill file is read and parsed.
+async fn skill_tools(state: &AppState, working_dir: Option<String>) -> Vec<SkillTool> {
+    let dir = state.settings().skills_directory;
+    let listed = tokio::task::spawn_blocking(move || {
+        skill_mgr::list_layered_skills(&dir, working_dir.as_deref())
+    })
+    .await
+    .map_err(|e| KataraError::Skill(e.to_string()))
+    .and_then(|listed| listed);
+    match listed {
+        Ok(skills) => tools::skill_tools(&skills),
+        Err(e) => {
+            eprintln!("[katara] Failed to list skills: {}", e);
+            Vec::new()
+        }
+    }
 }
 
 /// POST /agent/{agentId}/run — AG-UI SSE endpoint (CopilotKit v1.51).
 /// Route with path parameter delegates to the shared handler.
 async fn agui_handler_with_agent(
     State(state): State<Arc<AppState>>,
+    Extension(sink): Extension<Sink>,
+    device: Option<Extension<PairedDevice>>,
     Path(agent_id): Path<String>,
+    headers: HeaderMap,
     Json(input): Json<RunAgentInput>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     println!("[katara] AG-UI run request for agent: {}", agent_id);
-    agui_handler_inner(state, input).await
+    let remote = device.is_some();
+    agui_handler_inner(
+        state,
+        sink,
+        input,
+        Some(agent_id),
+        last_event_id(&headers),
+        remote,
+    )
+    .await
 }
 
 /// POST /api/copilotkit — legacy fallback endpoint.
 async fn agui_handler_legacy(
     State(state): State<Arc<AppState>>,
+    Extension(sink): Extension<Sink>,
+    device: Option<Extension<PairedDevice>>,
+    headers: HeaderMap,
     Json(input): Json<RunAgentInput>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     println!("[katara] AG-UI run request (legacy endpoint)");
-    agui_handler_inner(state, input).await
+    let remote = device.is_some();
+    agui_handler_inner(state, sink, input, None, last_event_id(&headers), remote).await
+}
+
+fn last_event_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
 }
 
 /// Shared AG-UI handler logic.
 ///
 /// Receives RunAgentInput from CopilotKit, forwards the user message to Claude
-/// via WebSocket, and streams back AG-UI events as SSE.
+/// via WebSocket, and streams back AG-UI events as SSE. A skill invocation in
+/// `forwardedProps.skill` runs that skill instead. A registered `agent_id`
+/// routes to that agent's session only.
+///
+/// Events are numbered and buffered per run; a request with `Last-Event-ID`
+/// resumes the run it names rather than starting a new one.
+///
+/// A `remote` request, from a paired device, can't choose the directory a
+/// session or skill runs in.
 async fn agui_handler_inner(
     state: Arc<AppState>,
+    sink: Sink,
     input: RunAgentInput,
+    agent_id: Option<String>,
+    last_event_id: Option<String>,
+    remote: bool,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    if let Some((resumed, after)) = last_event_id.as_deref().and_then(replay::parse_event_id) {
+        let same_thread = input
+            .thread_id
+            .as_deref()
+            .is_some_and(|thread_id| state.agui_buffers.contains(&resumed, thread_id));
+        if same_thread {
+            println!(
+                "[katara] AG-UI resuming run {} after event {}",
+                resumed, after
+            );
+            return event_stream(state, resumed, after);
+        }
+        // Running the input again would send the user's message twice
+        let thread_id = input.thread_id.unwrap_or_default();
+        let (tx, rx) = tokio::sync::mpsc::channel::<AguiEvent>(1);
+        let _ = tx.try_send(AguiEvent::RunError {
+            thread_id: thread_id.clone(),
+            run_id: resumed.clone(),
+            message: format!("Run {} can no longer be resumed", resumed),
+        });
+        drop(tx);
+        replay::record(state.clone(), resumed.clone(), &thread_id, rx);
+        return event_stream(state, resumed, 0);
+    }
+
     let thread_id = input
         .thread_id
         .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
@@ -133,6 +330,77 @@ async fn agui_handler_inner(
             })
             .await;
 
+        // A run that only carries frontend tool results continues the turn
+        // that called them, rather than starting a new one
+        let tool_results = frontend_tool_results(input.messages.as_deref().unwrap_or_default());
+
+        // Rebuild the conversation for a thread that already has a session,
+        // so a reconnecting frontend doesn't start from an empty chat.
+        // The incoming user turn is kept at the end, as the client has it.
+        let history = if tool_results.is_empty() {
+            thread_history(&state_clone, &thread_id_clone).await
+        } else {
+            None
+        };
+        let snapshot_sent = match history {
+            Some(history) => {
+                let mut messages = bridge::history_to_messages(&history);
+                if let Some(last) = input
+                    .messages
+                    .as_ref()
+                    .and_then(|msgs| msgs.last())
+                    .filter(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"))
+                {
+                    messages.push(last.clone());
+                }
+                let _ = tx.send(AguiEvent::MessagesSnapshot { messages }).await;
+                true
+            }
+            None => false,
+        };
+
+        // Shared state: the frontend's copy wins; without one, send ours so
+        // a reconnecting frontend picks it back up
+        let sent_state = input
+            .state
+            .clone()
+            .filter(|s| s.as_object().is_some_and(|o| !o.is_empty()));
+        match sent_state {
+            Some(shared) => state_clone.shared_state.set(&thread_id_clone, shared).await,
+            None => {
+                if let Some(snapshot) = state_clone.shared_state.get(&thread_id_clone).await {
+                    let _ = tx.send(AguiEvent::StateSnapshot { snapshot }).await;
+                }
+            }
+        }
+
+        if !tool_results.is_empty() {
+            continue_with_tool_results(
+                &state_clone,
+                tool_results,
+                &thread_id_clone,
+                &run_id_clone,
+                &tx,
+            )
+            .await;
+            return;
+        }
+
+        // Skill invocation: run the skill in its own session and stream it
+        if let Some(invocation) = input.forwarded_props.as_ref().and_then(|p| p.get("skill")) {
+            run_skill_invocation(
+                &state_clone,
+                sink,
+                invocation,
+                &thread_id_clone,
+                &run_id_clone,
+                &tx,
+                remote,
+            )
+            .await;
+            return;
+        }
+
         // 2. Extract last user message from CopilotKit input
         let user_message = input
             .messages
@@ -147,6 +415,16 @@ async fn agui_handler_inner(
             .to_string();
 
         if user_message.is_empty() {
+            // A plain reconnect: the snapshot was all it needed
+            if snapshot_sent {
+                let _ = tx
+                    .send(AguiEvent::RunFinished {
+                        thread_id: thread_id_clone,
+                        run_id: run_id_clone,
+                    })
+                    .await;
+                return;
+            }
             let _ = tx
                 .send(AguiEvent::RunError {
                     thread_id: thread_id_clone,
@@ -197,9 +475,16 @@ async fn agui_handler_inner(
             String::new()
         };
 
-        // 3b. Build Gen-UI tool context from CopilotKit's tools array.
-        //     This tells Claude about frontend-registered actions it can invoke.
-        let tools_context = if let Some(ref tools) = input.tools {
+        // 3b. Frontend-registered actions reach Claude as tools on Katara's
+        //     MCP server. Without it, describe them in the prompt instead.
+        let frontend_tools: Vec<FrontendTool> = input
+            .tools
+            .iter()
+            .flatten()
+            .filter_map(FrontendTool::from_agui)
+            .collect();
+        let mcp_enabled = state_clone.settings().server.mcp_enabled;
+        let tools_context = if let Some(tools) = input.tools.as_ref().filter(|_| !mcp_enabled) {
             let tool_descriptions: Vec<String> = tools
                 .iter()
                 .filter_map(|t| {
@@ -232,12 +517,37 @@ async fn agui_handler_inner(
             String::new()
         };
 
-        // 4. Combine readable context + tools context + user message
-        let full_message = format!("{}{}{}", readable_context, tools_context, user_message);
+        // 3c. Show Claude the thread's shared state, and how to change it.
+        let shared_state_context = match state_clone.shared_state.get(&thread_id_clone).await {
+            Some(shared) => format!(
+                "\n\n[SHARED STATE — kept in sync with the app's UI.{}]\n{}\n\n",
+                if state_clone.settings().server.mcp_enabled {
+                    " Change it with the mcp__katara__update_shared_state tool:"
+                } else {
+                    ""
+                },
+                serde_json::to_string_pretty(&shared).unwrap_or_default()
+            ),
+            None => String::new(),
+        };
+
+        // 4. Combine readable context + tools context + shared state + user message
+        let full_message = format!(
+            "{}{}{}{}",
+            readable_context, tools_context, shared_state_context, user_message
+        );
 
-        // 5. Resolve which session to route to.
-        //    Priority: thread_to_session map > forwardedProps.activeSessionId > first available
-        let target_session_id = {
+        // 5. Resolve which session to route to. A registered agent uses only
+        //    its own session. Otherwise, priority: thread_to_session map >
+        //    forwardedProps.activeSessionId > first available
+        let spawn_guard = thread_lock(&state_clone, &thread_id_clone).await;
+        let agent = match &agent_id {
+            Some(id) => state_clone.agents.get(id).await,
+            None => None,
+        };
+        let target_session_id = if let Some(ref agent) = agent {
+            agent.session_id.clone()
+        } else {
             // Check thread mapping first
             let thread_map = state_clone.thread_to_session.read().await;
             if let Some(sid) = thread_map.get(&thread_id_clone) {
@@ -254,11 +564,86 @@ async fn agui_handler_inner(
             }
         };
 
+        // 5b. Start a session if there's nothing to route to: the thread's
+        //     session is gone, or none is running or starting. Uses
+        //     forwardedProps.workingDir, the agent's or the thread's old
+        //     project if it's a known one, or the default working
+        //     directory; without any of them, fall through and wait as
+        //     before. The thread is mapped to the new session before its
+        //     lock is let go, so the thread's next run uses it.
+        let spawned = auto_spawn_dir(
+            &state_clone,
+            target_session_id.as_deref(),
+            input.forwarded_props.as_ref().filter(|_| !remote),
+            agent.as_ref(),
+        )
+        .await;
+        let wait_for_target = spawned.is_some() || agent.is_some();
+        let target_session_id = match spawned {
+            Some(working_dir) => {
+                println!(
+                    "[katara] AG-UI starting a session in {} for thread {}",
+                    working_dir, thread_id_clone
+                );
+                let started = crate::commands::claude::start_session(
+                    &state_clone,
+                    sink.clone(),
+                    working_dir,
+                    Default::default(),
+                )
+                .await;
+                match started {
+                    Ok(session_id) => {
+                        state_clone
+                            .thread_to_session
+                            .write()
+                            .await
+                            .insert(thread_id_clone.clone(), session_id.clone());
+                        // Before the CLI asks for its tools
+                        state_clone
+                            .frontend_tools
+                            .register(&session_id, frontend_tools.clone())
+                            .await;
+                        if let Some(ref agent) = agent {
+                            if let Err(e) = state_clone
+                                .agents
+                                .assign(&agent.id, Some(session_id.clone()))
+                                .await
+                            {
+                                eprintln!("[katara] Failed to bind agent {}: {}", agent.id, e);
+                            }
+                        }
+                        Some(session_id)
+                    }
+                    Err(e) => {
+                        let _ = tx
+                            .send(AguiEvent::RunError {
+                                thread_id: thread_id_clone,
+                                run_id: run_id_clone,
+                                message: format!("Failed to start a Claude session: {}", e),
+                            })
+                            .await;
+                        return;
+                    }
+                }
+            }
+            None => target_session_id,
+        };
+        drop(spawn_guard);
+
         // 6. Find the target session (or first available) and send the message.
-        //    Wait up to 15s for a CLI to connect.
+        //    Wait up to 15s for a CLI to connect, checking again each time one does.
         let (resolved_session_id, cli_sid, ws_tx) = {
-            let mut found = None;
-            for attempt in 0..30 {
+            let waiting_since = tokio::time::Instant::now();
+            let deadline = waiting_since + SESSION_WAIT;
+            let mut attempt = 0;
+            let found = loop {
+                // Register before checking, so a CLI connecting in between
+                // still wakes us
+                let connected = state_clone.session_connected.notified();
+                tokio::pin!(connected);
+                connected.as_mut().enable();
+
                 let mut sessions = state_clone.sessions.write().await;
 
                 // Log session state on first attempt for debugging
@@ -283,7 +668,8 @@ async fn agui_handler_inner(
                     );
                 }
 
-                // Try target session first, fall back to first available.
+                // Try target session first, fall back to first available
+                // (unless the target was just started for this run).
                 // Resolve key first to avoid double mutable borrow.
                 let resolved_key = if let Some(ref target) = target_session_id {
                     if sessions.get(target).map_or(false, |s| s.ws_sender.is_some()) {
@@ -295,6 +681,9 @@ async fn agui_handler_inner(
                     None
                 }
                 .or_else(|| {
+                    if wait_for_target {
+                        return None;
+                    }
                     sessions
                         .iter()
                         .find(|(_, s)| s.ws_sender.is_some())
@@ -308,7 +697,7 @@ async fn agui_handler_inner(
                         .duration_since(std::time::UNIX_EPOCH)
                         .unwrap_or_default()
                         .as_millis();
-                    session.message_history.push(serde_json::json!({
+                    session.history.push(serde_json::json!({
                         "type": "user_message",
                         "content": user_message,
                         "timestamp": ts,
@@ -319,17 +708,20 @@ async fn agui_handler_inner(
                     let cli_sid = session.cli_session_id.clone().unwrap_or_default();
                     let ws_tx = session.ws_sender.clone();
                     if attempt > 0 {
-                        println!("[katara] AG-UI found session after {}ms wait", attempt * 500);
+                        println!(
+                            "[katara] AG-UI found session after {}ms wait",
+                            waiting_since.elapsed().as_millis()
+                        );
                     }
-                    found = Some((session_id, cli_sid, ws_tx));
-                    break;
+                    break Some((session_id, cli_sid, ws_tx));
                 }
 
-                drop(sessions); // Release lock before sleeping
-                if attempt < 29 {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                drop(sessions); // Release lock before waiting
+                if tokio::time::timeout_at(deadline, connected).await.is_err() {
+                    break None;
                 }
-            }
+                attempt += 1;
+            };
 
             match found {
                 Some(result) => result,
@@ -339,7 +731,7 @@ async fn agui_handler_inner(
                         .send(AguiEvent::RunError {
                             thread_id: thread_id_clone,
                             run_id: run_id_clone,
-                            message: "No active Claude session. Start a session first.".into(),
+                            message: "No active Claude session. Start a session or set a default working directory.".into(),
                         })
                         .await;
                     return;
@@ -347,6 +739,11 @@ async fn agui_handler_inner(
             }
         };
 
+        state_clone
+            .frontend_tools
+            .register(&resolved_session_id, frontend_tools)
+            .await;
+
         // Store thread <-> session mapping for future requests
         {
             state_clone
@@ -373,65 +770,531 @@ async fn agui_handler_inner(
 
         // 7. Subscribe to Claude events and translate to AG-UI.
         //    Filter events to only process those from the resolved session.
-        let mut event_rx = state_clone.event_tx.subscribe();
-        let mut bridge = BridgeState::new();
-
-        loop {
-            match event_rx.recv().await {
-                Ok(ws_event) => {
-                    // Only process events from the session this thread is routed to
-                    if ws_event.session_id != resolved_session_id {
-                        continue;
+        let event_rx = state_clone.event_tx.subscribe();
+        stream_session_events(
+            &state_clone,
+            event_rx,
+            &resolved_session_id,
+            &thread_id_clone,
+            &run_id_clone,
+            &tx,
+        )
+        .await;
+    });
+
+    // Buffer the events, so the run survives a dropped connection, and
+    // stream them from the buffer
+    replay::record(state.clone(), run_id.clone(), &thread_id, rx);
+    event_stream(state, run_id, 0)
+}
+
+/// The run's events after `after` as SSE, each with its event ID.
+fn event_stream(
+    state: Arc<AppState>,
+    run_id: String,
+    after: u64,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = replay::follow(state, run_id.clone(), after);
+    let stream = tokio_stream::wrappers::ReceiverStream::new(events).map(move |event| {
+        Ok::<_, Infallible>(
+            Event::default()
+                .id(replay::event_id(&run_id, event.seq))
+                .data(event.data),
+        )
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// The stored history of the session mapped to `thread_id`, or `None` if
+/// the thread is new or its session has nothing yet.
+async fn thread_history(state: &AppState, thread_id: &str) -> Option<Vec<Arc<serde_json::Value>>> {
+    let session_id = state
+        .thread_to_session
+        .read()
+        .await
+        .get(thread_id)
+        .cloned()?;
+    let history = state.sessions.read().await.get(&session_id)?.history.all();
+    (!history.is_empty()).then_some(history)
+}
+
+/// Where to start a session for an AG-UI run, or `None` to use the
+/// existing ones. A session is needed when the run's target session is
+/// missing or finished, or when there's no target and either the run is
+/// for a registered agent or nothing is running or starting.
+async fn auto_spawn_dir(
+    state: &AppState,
+    target: Option<&str>,
+    forwarded_props: Option<&serde_json::Value>,
+    agent: Option<&Agent>,
+) -> Option<String> {
+    let sessions = state.sessions.read().await;
+    let usable = |s: &Session| {
+        !matches!(
+            s.status,
+            SessionStatus::Terminated | SessionStatus::Error(_)
+        )
+    };
+    let target_session = target.and_then(|id| sessions.get(id));
+    let needed = match (target, target_session) {
+        (Some(_), Some(session)) => !usable(session),
+        (Some(_), None) => true,
+        (None, _) => agent.is_some() || !sessions.values().any(usable),
+    };
+    if !needed {
+        return None;
+    }
+    let requested = forwarded_props
+        .and_then(|p| p.get("workingDir").or_else(|| p.get("working_dir")))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| agent.and_then(|a| a.working_dir.clone()))
+        .or_else(|| target_session.map(|s| s.working_dir.clone()));
+    let open_dirs: Vec<String> = sessions.values().map(|s| s.working_dir.clone()).collect();
+    drop(sessions);
+    let requested = requested.filter(|dir| {
+        let known = known_project(state, dir, &open_dirs);
+        if !known {
+            println!(
+                "[katara] AG-UI won't start a session in {}, which isn't a known project",
+                dir
+            );
+        }
+        known
+    });
+    let dir = crate::commands::claude::default_working_dir(state, requested).ok()?;
+    std::path::Path::new(&dir).is_dir().then_some(dir)
+}
+
+/// Whether `dir` is an existing directory that's the default working
+/// directory, a recent project, or where a session already runs. AG-UI
+/// runs only start sessions in those, not anywhere a client names.
+fn known_project(state: &AppState, dir: &str, open_dirs: &[String]) -> bool {
+    let Ok(dir) = std::fs::canonicalize(dir) else {
+        return false;
+    };
+    if !dir.is_dir() {
+        return false;
+    }
+    let mut known = crate::config::recent::recent_projects();
+    known.extend(state.settings().default_working_dir);
+    known.extend(open_dirs.iter().cloned());
+    known
+        .iter()
+        .any(|p| std::fs::canonicalize(p).is_ok_and(|p| p == dir))
+}
+
+/// Take the lock a thread's runs hold while deciding whether to start a
+/// session. Locks no run holds any more are dropped on the way.
+async fn thread_lock(state: &AppState, thread_id: &str) -> tokio::sync::OwnedMutexGuard<()> {
+    let lock = {
+        let mut locks = state
+            .thread_spawns
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry(thread_id.to_string()).or_default().clone()
+    };
+    lock.lock_owned().await
+}
+
+/// The trailing `tool` messages of a run's input, as (tool name, result)
+/// pairs. Names come from the assistant tool calls they answer.
+fn frontend_tool_results(messages: &[serde_json::Value]) -> Vec<(String, String)> {
+    let names: HashMap<&str, &str> = messages
+        .iter()
+        .filter_map(|m| m.get("toolCalls")?.as_array())
+        .flatten()
+        .filter_map(|call| {
+            let id = call.get("id")?.as_str()?;
+            let name = call.get("function")?.get("name")?.as_str()?;
+            Some((id, name))
+        })
+        .collect();
+    let mut results: Vec<(String, String)> = messages
+        .iter()
+        .rev()
+        .take_while(|m| m.get("role").and_then(|r| r.as_str()) == Some("tool"))
+        .filter_map(|m| {
+            let id = m.get("toolCallId")?.as_str()?;
+            let content = match m.get("content") {
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+                None => String::new(),
+            };
+            Some((names.get(id)?.to_string(), content))
+        })
+        .collect();
+    results.reverse();
+    results
+}
+
+/// Hand frontend tool results to the MCP calls waiting on them and stream
+/// the rest of the turn.
+async fn continue_with_tool_results(
+    state: &AppState,
+    results: Vec<(String, String)>,
+    thread_id: &str,
+    run_id: &str,
+    tx: &tokio::sync::mpsc::Sender<AguiEvent>,
+) {
+    let session_id = state.thread_to_session.read().await.get(thread_id).cloned();
+    // Subscribe first, so nothing the CLI sends once unblocked is missed
+    let event_rx = state.event_tx.subscribe();
+    let resolved = match &session_id {
+        Some(session_id) => state.frontend_tools.resolve(session_id, results).await,
+        None => 0,
+    };
+    let Some(session_id) = session_id.filter(|_| resolved > 0) else {
+        // Nothing was waiting on them, say after a restart
+        println!(
+            "[katara] AG-UI got tool results for thread {} with no call waiting",
+            thread_id
+        );
+        let _ = tx
+            .send(AguiEvent::RunFinished {
+                thread_id: thread_id.to_string(),
+                run_id: run_id.to_string(),
+            })
+            .await;
+        return;
+    };
+    println!(
+        "[katara] AG-UI returned {} frontend tool result(s) to session {}",
+        resolved, session_id
+    );
+    stream_session_events(state, event_rx, &session_id, thread_id, run_id, tx).await;
+}
+
+/// A run streaming to a client, so the stop endpoint can end it.
+pub struct ActiveRun {
+    pub run_id: String,
+    /// Ends the stream: `Ok` with RUN_FINISHED, `Err` with RUN_ERROR.
+    pub stop: tokio::sync::oneshot::Sender<Result<(), String>>,
+}
+
+/// Translate a session's Claude events to AG-UI until its turn finishes,
+/// the run is stopped or the client disconnects.
+async fn stream_session_events(
+    state: &AppState,
+    mut event_rx: tokio::sync::broadcast::Receiver<WsEvent>,
+    session_id: &str,
+    thread_id: &str,
+    run_id: &str,
+    tx: &tokio::sync::mpsc::Sender<AguiEvent>,
+) {
+    let mut bridge = BridgeState::new();
+    let frontend_prefix = format!("mcp__{}__", MCP_SERVER_NAME);
+    // Whether Claude called a frontend tool, and whether the CLI streams
+    let mut called_frontend_tool = false;
+    let mut streaming = false;
+    let mut state_rx = state.shared_state.subscribe();
+    let (stop_tx, mut stop_rx) = tokio::sync::oneshot::channel();
+    state.agui_runs.lock().await.insert(
+        thread_id.to_string(),
+        ActiveRun {
+            run_id: run_id.to_string(),
+            stop: stop_tx,
+        },
+    );
+
+    loop {
+        let received = tokio::select! {
+            received = event_rx.recv() => received,
+            stopped = &mut stop_rx => {
+                let event = match stopped {
+                    Ok(Err(message)) => AguiEvent::RunError {
+                        thread_id: thread_id.to_string(),
+                        run_id: run_id.to_string(),
+                        message,
+                    },
+                    _ => AguiEvent::RunFinished {
+                        thread_id: thread_id.to_string(),
+                        run_id: run_id.to_string(),
+                    },
+                };
+                let _ = tx.send(event).await;
+                break;
+            }
+            change = state_rx.recv() => {
+                // A lagging receiver just misses deltas
+                if let Ok(change) = change {
+                    if change.thread_id == thread_id
+                        && tx.send(AguiEvent::StateDelta { delta: change.delta }).await.is_err()
+                    {
+                        break;
                     }
+                }
+                continue;
+            }
+        };
+        match received {
+            Ok(ws_event) => {
+                // Only process events from the session this thread is routed to
+                if ws_event.session_id != session_id {
+                    continue;
+                }
 
-                    let agui_events = translate_claude_message(
-                        &ws_event.message,
-                        &thread_id_clone,
-                        &run_id_clone,
-                        &mut bridge,
-                    );
+                let agui_events =
+                    translate_claude_message(&ws_event.message, thread_id, run_id, &mut bridge);
 
-                    let mut is_finished = false;
-                    for event in agui_events {
-                        if matches!(event, AguiEvent::RunFinished { .. }) {
-                            is_finished = true;
+                let mut is_finished = false;
+                for mut event in agui_events {
+                    // Frontend tools reach the CLI through Katara's MCP
+                    // server; the frontend knows them by their own names
+                    if let AguiEvent::ToolCallStart { tool_call_name, .. } = &mut event {
+                        if let Some(name) = tool_call_name.strip_prefix(&frontend_prefix) {
+                            if state.frontend_tools.contains(session_id, name).await {
+                                *tool_call_name = name.to_string();
+                                called_frontend_tool = true;
+                            }
                         }
-                        if tx.send(event).await.is_err() {
-                            return; // Client disconnected
+                    }
+                    // Keep the CLI's init details from replacing the shared
+                    // state; they go in under "session" and out as a delta
+                    if let AguiEvent::StateSnapshot { snapshot } = &event {
+                        if state.shared_state.get(thread_id).await.is_some() {
+                            let patch = serde_json::json!({ "session": snapshot });
+                            state.shared_state.merge(thread_id, &patch).await;
+                            continue;
                         }
                     }
-
-                    if is_finished {
+                    if matches!(event, AguiEvent::RunFinished { .. }) {
+                        is_finished = true;
+                    }
+                    if tx.send(event).await.is_err() {
+                        is_finished = true; // The run's buffer is gone
                         break;
                     }
+                }
 
-                    // Also break on Result message directly
-                    if matches!(ws_event.message, ClaudeMessage::Result(_)) {
-                        break;
+                if is_finished {
+                    break;
+                }
+
+                // The CLI now waits on the frontend, which runs its tools
+                // once the run is over and sends the results with the next
+                // one. End the run once the whole message is in.
+                let message_done = match &ws_event.message {
+                    ClaudeMessage::StreamEvent(stream) => {
+                        streaming = true;
+                        stream.event.event_type == "message_stop"
                     }
+                    ClaudeMessage::Assistant(_) => !streaming,
+                    _ => false,
+                };
+                if called_frontend_tool && message_done {
+                    let _ = tx
+                        .send(AguiEvent::RunFinished {
+                            thread_id: thread_id.to_string(),
+                            run_id: run_id.to_string(),
+                        })
+                        .await;
+                    break;
+                }
+
+                // Also break on Result message directly
+                if matches!(ws_event.message, ClaudeMessage::Result(_)) {
+                    break;
                 }
-                Err(_) => break, // Broadcast channel closed
             }
+            Err(_) => break, // Broadcast channel closed
         }
+    }
+
+    // Unregister, unless a newer run on the thread already replaced this one
+    let mut runs = state.agui_runs.lock().await;
+    if runs.get(thread_id).is_some_and(|r| r.run_id == run_id) {
+        runs.remove(thread_id);
+    }
+}
+
+/// POST /agent/{agentId}/stop/{threadId} — interrupt the thread's session
+/// and end its running stream with RUN_FINISHED, or RUN_ERROR if the
+/// interrupt couldn't be sent.
+async fn stop_handler(
+    State(state): State<Arc<AppState>>,
+    Path((agent_id, thread_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, KataraError> {
+    println!(
+        "[katara] AG-UI stop request for agent {} thread {}",
+        agent_id, thread_id
+    );
+    let session_id = state
+        .thread_to_session
+        .read()
+        .await
+        .get(&thread_id)
+        .cloned()
+        .ok_or_else(|| KataraError::SessionNotFound(format!("thread {}", thread_id)))?;
+
+    let interrupted = crate::commands::claude::send_interrupt(&state, &session_id).await;
+    let run = state.agui_runs.lock().await.remove(&thread_id);
+    let run_id = run.map(|run| {
+        let outcome = match &interrupted {
+            Ok(()) => Ok(()),
+            Err(e) => Err(format!("Failed to stop: {}", e)),
+        };
+        let _ = run.stop.send(outcome);
+        run.run_id
     });
+    interrupted?;
+    Ok(Json(serde_json::json!({
+        "threadId": thread_id,
+        "runId": run_id,
+        "sessionId": session_id,
+        "stopped": true,
+    })))
+}
+
+/// GET /thread/{threadId}/messages — the thread's conversation as AG-UI
+/// messages, the same ones a MESSAGES_SNAPSHOT carries.
+async fn thread_messages_handler(
+    State(state): State<Arc<AppState>>,
+    Path(thread_id): Path<String>,
+) -> Result<Json<serde_json::Value>, KataraError> {
+    let session_id = state
+        .thread_to_session
+        .read()
+        .await
+        .get(&thread_id)
+        .cloned()
+        .ok_or_else(|| KataraError::SessionNotFound(format!("thread {}", thread_id)))?;
+    let history = state
+        .sessions
+        .read()
+        .await
+        .get(&session_id)
+        .ok_or_else(|| KataraError::SessionNotFound(session_id.clone()))?
+        .history
+        .all();
+    Ok(Json(serde_json::json!({
+        "threadId": thread_id,
+        "sessionId": session_id,
+        "messages": bridge::history_to_messages(&history),
+    })))
+}
 
-    // Convert mpsc receiver to SSE stream
-    let stream = tokio_stream::wrappers::ReceiverStream::new(rx).map(|event| {
-        let json = serde_json::to_string(&event).unwrap_or_default();
-        Ok::<_, Infallible>(Event::default().data(json))
+/// Run a skill requested by CopilotKit through `forwardedProps.skill`:
+/// `{ "tool": "skill_x", "inputs": {...}, "workingDir": "..." }`. Only the
+/// actions `/info` advertises can run; there's no running a file by path.
+/// A `remote` invocation's `workingDir` is ignored.
+///
+/// The skill runs in a fresh session like `run_skill` (and is recorded in the
+/// run history); its output streams into this thread, and follow-up messages
+/// on the thread go to that session.
+async fn run_skill_invocation(
+    state: &Arc<AppState>,
+    sink: Sink,
+    invocation: &serde_json::Value,
+    thread_id: &str,
+    run_id: &str,
+    tx: &tokio::sync::mpsc::Sender<AguiEvent>,
+    remote: bool,
+) {
+    // Default to the directory of the session this thread was talking to
+    let thread_session = state.thread_to_session.read().await.get(thread_id).cloned();
+    let thread_dir = match thread_session {
+        Some(sid) => state
+            .sessions
+            .read()
+            .await
+            .get(&sid)
+            .map(|s| s.working_dir.clone()),
+        None => None,
+    };
+    let requested = invocation
+        .get("workingDir")
+        .filter(|_| !remote)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .or(thread_dir);
+    // Then the configured default; never the home directory or cwd
+    let working_dir = match crate::commands::claude::default_working_dir(state, requested) {
+        Ok(dir) => dir,
+        Err(e) => {
+            let _ = tx
+                .send(AguiEvent::RunError {
+                    thread_id: thread_id.to_string(),
+                    run_id: run_id.to_string(),
+                    message: e.to_string(),
+                })
+                .await;
+            return;
+        }
+    };
+
+    let tool = match invocation.get("tool").and_then(|v| v.as_str()) {
+        Some(name) => skill_tools(state, Some(working_dir.clone()))
+            .await
+            .into_iter()
+            .find(|t| t.name == name),
+        None => None,
+    };
+    let Some(tool) = tool else {
+        let _ = tx
+            .send(AguiEvent::RunError {
+                thread_id: thread_id.to_string(),
+                run_id: run_id.to_string(),
+                message: format!("Unknown skill: {}", invocation),
+            })
+            .await;
+        return;
+    };
+    let path = tool.path;
+
+    let inputs = invocation
+        .get("inputs")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
+    println!("[katara] AG-UI skill invocation: {}", path);
+    // Subscribe before the session starts so no output is missed
+    let event_rx = state.event_tx.subscribe();
+    let result = start_skill_run(state, sink, path, inputs, working_dir, None, None).await;
+    let started = match result {
+        Ok(started) => started,
+        Err(e) => {
+            let _ = tx
+                .send(AguiEvent::RunError {
+                    thread_id: thread_id.to_string(),
+                    run_id: run_id.to_string(),
+                    message: e.to_string(),
+                })
+                .await;
+            return;
+        }
+    };
+
+    let session_id = started.run.session_id.clone();
+    state
+        .thread_to_session
+        .write()
+        .await
+        .insert(thread_id.to_string(), session_id.clone());
+    state
+        .session_to_thread
+        .write()
+        .await
+        .insert(session_id.clone(), thread_id.to_string());
+
+    // Record the outcome even if the client disconnects mid-stream
+    let run_state = state.clone();
+    tokio::spawn(async move {
+        finish_skill_run(&run_state, started).await;
     });
 
-    Sse::new(stream).keep_alive(KeepAlive::default())
+    stream_session_events(state, event_rx, &session_id, thread_id, run_id, tx).await;
 }
 
 /// Starts the Axum HTTP server and emits the port to the frontend.
-pub async fn start_agui_server(
-    state: Arc<AppState>,
-    app_handle: tauri::AppHandle,
-) -> Result<(), KataraError> {
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+pub async fn start_agui_server(state: Arc<AppState>, sink: Sink) -> Result<(), KataraError> {
+    let port = state.settings().server.agui_port;
+    let listener = instance::bind(port, "AG-UI")
         .await
-        .map_err(|e| KataraError::WebSocket(e.to_string()))?;
+        .map_err(|e| {
+            KataraError::WebSocket(format!("Can't listen on port {}: {}", port.unwrap_or(0), e))
+        })?;
 
     let port = listener
         .local_addr()
@@ -440,11 +1303,12 @@ pub async fn start_agui_server(
 
     *state.axum_port.write().await = port;
     println!("[katara] AG-UI server listening on port {}", port);
+    instance::write_discovery(&state).await;
 
     // Notify frontend of the AG-UI port (CopilotKit runtimeUrl)
-    let _ = app_handle.emit("agui:port", port);
+    sink.emit("agui:port", port);
 
-    let router = create_router(state);
+    let router = create_router(state, sink);
     axum::serve(listener, router.into_make_service())
         .await
         .map_err(|e| KataraError::WebSocket(e.to_string()))?;
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+use tokio::sync::{broadcast, RwLock};
+
+/// A change to a thread's shared state, as a JSON Patch (RFC 6902).
+#[derive(Debug, Clone)]
+pub struct StateChange {
+    pub thread_id: String,
+    pub delta: Vec<Value>,
+}
+
+/// State shared between a CopilotKit frontend (`useCoAgent`) and Claude,
+/// one object per AG-UI thread. The frontend sends its copy with each run;
+/// Claude changes it through the `update_shared_state` MCP tool, and the
+/// change reaches the frontend as a STATE_DELTA.
+pub struct SharedState {
+    threads: RwLock<HashMap<String, Value>>,
+    changes: broadcast::Sender<StateChange>,
+}
+
+impl Default for SharedState {
+    fn default() -> Self {
+        let (changes, _) = broadcast::channel(64);
+        Self {
+            threads: RwLock::new(HashMap::new()),
+            changes,
+        }
+    }
+}
+
+impl SharedState {
+    pub async fn get(&self, thread_id: &str) -> Option<Value> {
+        self.threads.read().await.get(thread_id).cloned()
+    }
+
+    /// Replace the thread's state with the frontend's copy. Not announced,
+    /// since the frontend already has it.
+    pub async fn set(&self, thread_id: &str, state: Value) {
+        self.threads
+            .write()
+            .await
+            .insert(thread_id.to_string(), state);
+    }
+
+    /// Apply a JSON Merge Patch (RFC 7396) and announce what changed.
+    /// Returns the new state.
+    pub async fn merge(&self, thread_id: &str, patch: &Value) -> Value {
+        let mut threads = self.threads.write().await;
+        let current = threads
+            .entry(thread_id.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        let before = current.clone();
+        merge_patch(current, patch);
+        let delta = diff(&before, current);
+        if !delta.is_empty() {
+            // Nobody listening just means no run is streaming
+            let _ = self.changes.send(StateChange {
+                thread_id: thread_id.to_string(),
+                delta,
+            });
+        }
+        current.clone()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<StateChange> {
+        self.changes.subscribe()
+    }
+
+    pub async fn remove(&self, thread_id: &str) {
+        self.threads.write().await.remove(thread_id);
+    }
+}
+
+/// RFC 7396: objects merge key by key, `null` removes a key, anything
+/// else replaces.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// The JSON Patch that turns `before` into `after`. Objects are compared
+/// key by key; anything else that differs, arrays included, is replaced
+/// whole.
+pub fn diff(before: &Value, after: &Value) -> Vec<Value> {
+    let mut ops = Vec::new();
+    diff_at("", before, after, &mut ops);
+    ops
+}
+
+fn diff_at(path: &str, before: &Value, after: &Value, ops: &mut Vec<Value>) {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for key in before.keys().filter(|k| !after.contains_key(*k)) {
+                ops.push(serde_json::json!({ "op": "remove", "path": pointer(path, key) }));
+            }
+            for (key, value) in after {
+                let child = pointer(path, key);
+                match before.get(key) {
+                    Some(old) => diff_at(&child, old, value, ops),
+                    None => {
+                        ops.push(serde_json::json!({ "op": "add", "path": child, "value": value }))
+                    }
+                }
+            }
+        }
+        _ if before != after => {
+            ops.push(serde_json::json!({ "op": "replace", "path": path, "value": after }));
+        }
+        _ => {}
+    }
+}
+
+/// Append `key` to a JSON Pointer, escaping `~` and `/`.
+fn pointer(path: &str, key: &str) -> String {
+    format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"))
+}
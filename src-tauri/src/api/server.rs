@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path, Query, State};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::claude::{self, SessionCost};
+use crate::config::recent;
+use crate::error::KataraError;
+use crate::process::history::HistoryPage;
+use crate::process::manager::SpawnOptions;
+use crate::process::session::{PendingApproval, SessionStatus};
+use crate::remote::pairing::PairedDevice;
+use crate::sink::Sink;
+use crate::state::AppState;
+
+/// The session routes, mounted at `/api/v1` on the AG-UI server behind its
+/// auth token, so scripts and CI jobs can drive sessions without the app's
+/// IPC. Each does what the Tauri command of the same name does. Paired
+/// devices reach the same routes through the remote access server.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/sessions", get(list_sessions).post(spawn_session))
+        .route(
+            "/sessions/{session_id}/messages",
+            get(get_messages).post(send_message),
+        )
+        .route(
+            "/sessions/{session_id}/approvals/{request_id}",
+            post(approve_tool),
+        )
+        .route("/sessions/{session_id}/interrupt", post(interrupt))
+        .route("/sessions/{session_id}/cost", get(get_cost))
+}
+
+#[derive(Serialize)]
+struct ApiSession {
+    id: String,
+    status: SessionStatus,
+    working_dir: String,
+    model: Option<String>,
+    permission_mode: String,
+    /// Tool requests waiting for `approvals/{request_id}`.
+    pending_approvals: Vec<PendingApproval>,
+}
+
+/// GET /api/v1/sessions — every session.
+async fn list_sessions(State(state): State<Arc<AppState>>) -> Json<Vec<ApiSession>> {
+    let sessions = state.sessions.read().await;
+    Json(
+        sessions
+            .values()
+            .map(|s| ApiSession {
+                id: s.id.clone(),
+                status: s.status.clone(),
+                working_dir: s.working_dir.clone(),
+                model: s.model.clone(),
+                permission_mode: s.permission_mode.clone(),
+                pending_approvals: s.pending_approvals.clone(),
+            })
+            .collect(),
+    )
+}
+
+#[derive(Deserialize)]
+struct SpawnRequest {
+    working_dir: Option<String>,
+    initial_prompt: Option<String>,
+    model: Option<String>,
+    permission_mode: Option<String>,
+    env: Option<HashMap<String, String>>,
+    output_style: Option<String>,
+    env_profile: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SpawnResponse {
+    session_id: String,
+}
+
+/// POST /api/v1/sessions — start a session, in the default working
+/// directory unless `working_dir` is given. A paired device can't set
+/// `working_dir`, `permission_mode` or `env`, so its sessions start in the
+/// default directory with the configured permissions; it can pick one of
+/// the env profiles instead.
+async fn spawn_session(
+    State(state): State<Arc<AppState>>,
+    Extension(sink): Extension<Sink>,
+    device: Option<Extension<PairedDevice>>,
+    Json(body): Json<SpawnRequest>,
+) -> Result<Json<SpawnResponse>, KataraError> {
+    if device.is_some() {
+        if body.working_dir.is_some() {
+            return Err(KataraError::Remote(
+                "Paired devices can't set a session's working directory".into(),
+            ));
+        }
+        if body.permission_mode.is_some() {
+            return Err(KataraError::Remote(
+                "Paired devices can't set a session's permission mode".into(),
+            ));
+        }
+        if body.env.as_ref().is_some_and(|env| !env.is_empty()) {
+            return Err(KataraError::Remote(
+                "Paired devices can't set a session's environment".into(),
+            ));
+        }
+    }
+    let opts = SpawnOptions {
+        initial_prompt: body.initial_prompt,
+        model: body.model,
+        permission_mode: body.permission_mode,
+        env_profile: body.env_profile,
+        env: body.env.unwrap_or_default(),
+        output_style: body.output_style,
+        ..Default::default()
+    };
+    let working_dir = claude::default_working_dir(&state, body.working_dir)?;
+    recent::add_recent_project(&working_dir);
+    let session_id = claude::start_session(&state, sink, working_dir, opts).await?;
+    println!("[katara] REST API started session {}", session_id);
+    Ok(Json(SpawnResponse { session_id }))
+}
+
+#[derive(Deserialize)]
+struct MessagesQuery {
+    cursor: Option<u64>,
+}
+
+/// GET /api/v1/sessions/{id}/messages?cursor= — history since `cursor`,
+/// or all of it.
+async fn get_messages(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(query): Query<MessagesQuery>,
+) -> Result<Json<HistoryPage>, KataraError> {
+    let history = claude::session_history(&state, &session_id).await?;
+    Ok(Json(history.since(query.cursor.unwrap_or(0))))
+}
+
+#[derive(Deserialize)]
+struct SendMessage {
+    content: String,
+}
+
+/// POST /api/v1/sessions/{id}/messages — send a user turn.
+async fn send_message(
+    State(state): State<Arc<AppState>>,
+    device: Option<Extension<PairedDevice>>,
+    Path(session_id): Path<String>,
+    Json(body): Json<SendMessage>,
+) -> Result<(), KataraError> {
+    if let Some(Extension(device)) = &device {
+        println!(
+            "[katara] Remote message to session {} from {}",
+            session_id, device.name
+        );
+    }
+    claude::send_user_message(&state, &session_id, body.content).await
+}
+
+#[derive(Deserialize)]
+struct ApprovalResponse {
+    approved: bool,
+    updated_input: Option<serde_json::Value>,
+}
+
+/// POST /api/v1/sessions/{id}/approvals/{request_id} — answer a tool
+/// request. An answer from a paired device also tells the desktop UI to
+/// drop its prompt.
+async fn approve_tool(
+    State(state): State<Arc<AppState>>,
+    Extension(sink): Extension<Sink>,
+    device: Option<Extension<PairedDevice>>,
+    Path((session_id, request_id)): Path<(String, String)>,
+    Json(body): Json<ApprovalResponse>,
+) -> Result<(), KataraError> {
+    if let Some(Extension(device)) = &device {
+        println!(
+            "[katara] Remote {} of {} in session {} from {}",
+            if body.approved { "approval" } else { "denial" },
+            request_id,
+            session_id,
+            device.name
+        );
+    }
+    claude::respond_to_tool(
+        &state,
+        &session_id,
+        request_id.clone(),
+        body.approved,
+        body.updated_input,
+    )
+    .await?;
+    if let Some(Extension(device)) = device {
+        sink.emit(
+            "remote:approval",
+            serde_json::json!({
+                "session_id": session_id,
+                "request_id": request_id,
+                "approved": body.approved,
+                "device_id": device.id,
+            }),
+        );
+    }
+    Ok(())
+}
+
+/// POST /api/v1/sessions/{id}/interrupt — stop the running turn.
+async fn interrupt(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<(), KataraError> {
+    claude::send_interrupt(&state, &session_id).await
+}
+
+/// GET /api/v1/sessions/{id}/cost — token usage and estimated cost.
+async fn get_cost(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<SessionCost>, KataraError> {
+    Ok(Json(claude::session_cost(&state, session_id).await?))
+}
@@ -0,0 +1,50 @@
+use axum::http::HeaderMap;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// The token in an `Authorization: Bearer` header.
+pub fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+/// Subprotocol a WebSocket client offers along with `bearer.<token>`, since
+/// browsers can't set headers on a WebSocket. The server answers with it.
+pub const WS_PROTOCOL: &str = "katara";
+
+/// The bearer token, or for a WebSocket from a browser, the token in a
+/// `bearer.<token>` entry of `Sec-WebSocket-Protocol`. Unlike a query
+/// string, neither ends up in access logs or browser history.
+pub fn request_token(headers: &HeaderMap) -> Option<&str> {
+    bearer(headers).or_else(|| {
+        headers
+            .get("sec-websocket-protocol")?
+            .to_str()
+            .ok()?
+            .split(',')
+            .find_map(|protocol| protocol.trim().strip_prefix("bearer."))
+    })
+}
+
+/// Whether the request's bearer token is `expected`. An empty `expected`
+/// matches nothing.
+pub fn bearer_matches(headers: &HeaderMap, expected: &str) -> bool {
+    bearer(headers).is_some_and(|token| token_matches(token, expected))
+}
+
+/// Compare a presented token in constant time, so response timing doesn't
+/// tell an attacker how much of a guess was right.
+pub fn token_matches(token: &str, expected: &str) -> bool {
+    !expected.is_empty() && bool::from(token.as_bytes().ct_eq(expected.as_bytes()))
+}
+
+/// SHA-256 of a token, hex-encoded, for storing a token without keeping
+/// the token itself.
+pub fn token_hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use crate::agui::agents::Agent;
+use crate::error::KataraError;
+use crate::state::AppState;
+
+/// The registered AG-UI agents, as listed by `/info`.
+#[tauri::command]
+pub async fn list_agui_agents(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<Agent>, KataraError> {
+    Ok(state.agents.list().await)
+}
+
+/// Register an agent ID that CopilotKit can run at `/agent/{id}/run`. Its
+/// first run starts a session in `working_dir` unless one is assigned.
+#[tauri::command]
+pub async fn create_agui_agent(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+    description: Option<String>,
+    working_dir: Option<String>,
+) -> Result<Agent, KataraError> {
+    state
+        .agents
+        .create(id, description.unwrap_or_default(), working_dir)
+        .await
+}
+
+/// Route the agent's runs to `session_id`, or with `None`, to a new
+/// session on its next run.
+#[tauri::command]
+pub async fn assign_agui_agent(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+    session_id: Option<String>,
+) -> Result<Agent, KataraError> {
+    if let Some(ref session_id) = session_id {
+        if !state.sessions.read().await.contains_key(session_id) {
+            return Err(KataraError::SessionNotFound(session_id.clone()));
+        }
+    }
+    state.agents.assign(&id, session_id).await
+}
+
+/// Unregister the agent. Its session keeps running.
+#[tauri::command]
+pub async fn delete_agui_agent(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+) -> Result<(), KataraError> {
+    state.agents.delete(&id).await
+}
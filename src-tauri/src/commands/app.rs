@@ -4,11 +4,15 @@ use serde::Serialize;
 
 use crate::error::KataraError;
 use crate::state::AppState;
+use crate::updater::{self, UpdateInfo};
+use crate::window_scope::WindowScope;
 
 #[derive(Serialize)]
 pub struct PortInfo {
     pub ws_port: u16,
     pub axum_port: u16,
+    /// Bearer token the AG-UI server requires. Changes every launch.
+    pub auth_token: String,
 }
 
 #[tauri::command]
@@ -16,9 +20,76 @@ pub async fn get_ports(state: tauri::State<'_, Arc<AppState>>) -> Result<PortInf
     Ok(PortInfo {
         ws_port: *state.ws_port.read().await,
         axum_port: *state.axum_port.read().await,
+        auth_token: state.auth_token.clone(),
     })
 }
 
+/// Limit the calling window's `claude:*` and `terminal:*` events to these
+/// sessions and terminals. Empty lists make it receive everything no other
+/// window has claimed again.
+#[tauri::command]
+pub async fn set_window_scope(
+    state: tauri::State<'_, Arc<AppState>>,
+    window: tauri::WebviewWindow,
+    sessions: Vec<String>,
+    terminals: Vec<String>,
+) -> Result<(), KataraError> {
+    let scope = WindowScope {
+        sessions: sessions.into_iter().collect(),
+        terminals: terminals.into_iter().collect(),
+    };
+    let mut scopes = state
+        .window_scopes
+        .write()
+        .unwrap_or_else(|e| e.into_inner());
+    if scope.is_empty() {
+        scopes.remove(window.label());
+    } else {
+        scopes.insert(window.label().to_string(), scope);
+    }
+    Ok(())
+}
+
+/// The calling window's scope; `None` if it receives all unclaimed events.
+#[tauri::command]
+pub async fn get_window_scope(
+    state: tauri::State<'_, Arc<AppState>>,
+    window: tauri::WebviewWindow,
+) -> Result<Option<WindowScope>, KataraError> {
+    Ok(state
+        .window_scopes
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(window.label())
+        .cloned())
+}
+
+/// The newest release on the configured update channel, or `None` if this
+/// build is current.
+#[tauri::command]
+pub async fn check_for_updates(
+    app_handle: tauri::AppHandle,
+) -> Result<Option<UpdateInfo>, KataraError> {
+    updater::check(&app_handle).await
+}
+
+/// Download the available update. Progress is emitted as `update:progress`
+/// and `update:ready` follows once a restart will apply it.
+#[tauri::command]
+pub async fn download_update(app_handle: tauri::AppHandle) -> Result<UpdateInfo, KataraError> {
+    updater::download(&app_handle).await
+}
+
+/// Stop sessions (waiting for running turns unless `force`), install the
+/// downloaded update and restart.
+#[tauri::command]
+pub async fn apply_update(
+    app_handle: tauri::AppHandle,
+    force: Option<bool>,
+) -> Result<(), KataraError> {
+    updater::apply(&app_handle, force.unwrap_or(false)).await
+}
+
 #[tauri::command]
 pub async fn get_version() -> Result<String, KataraError> {
     Ok(env!("CARGO_PKG_VERSION").to_string())
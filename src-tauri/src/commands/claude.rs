@@ -1,16 +1,33 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use serde::Serialize;
 use tauri::Emitter;
 
+use crate::auth;
+use crate::config::{env_profiles, recent};
 use crate::error::KataraError;
+use crate::git::{self, checkpoint};
+use crate::mcp::server as mcp_server;
+use crate::process::cli_sessions::{self, ResumableSession};
+use crate::process::fs_watch::SessionWatcher;
+use crate::process::history::{HistoryPage, MessageHistory};
 use crate::process::manager;
+use crate::process::recovery;
 use crate::process::session::{Session, SessionStatus};
+use crate::process::transcript::{self, PersistedSession, Transcript};
+use crate::quick_prompt;
+use crate::sink::{self, Sink};
 use crate::state::AppState;
+use crate::stats::activity;
+use crate::terminal::procinfo;
 use crate::websocket::protocol::{
     ControlRequestPayload, ControlResponseBody, ControlResponsePayload, ServerMessage,
 };
 
+/// Sessions `list_resumable_sessions` returns unless told otherwise.
+const DEFAULT_RESUMABLE_LIMIT: usize = 50;
+
 #[derive(Debug, Serialize)]
 pub struct SessionInfo {
     pub id: String,
@@ -32,30 +49,136 @@ pub struct SessionCost {
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn spawn_session(
     state: tauri::State<'_, Arc<AppState>>,
     app_handle: tauri::AppHandle,
-    working_dir: String,
+    working_dir: Option<String>,
     initial_prompt: Option<String>,
     model: Option<String>,
     permission_mode: Option<String>,
+    env: Option<HashMap<String, String>>,
+    output_style: Option<String>,
+    env_profile: Option<String>,
+) -> Result<String, KataraError> {
+    let opts = manager::SpawnOptions {
+        initial_prompt,
+        model,
+        permission_mode,
+        env_profile,
+        env: env.unwrap_or_default(),
+        output_style,
+        ..Default::default()
+    };
+    let working_dir = default_working_dir(&state, working_dir)?;
+    recent::add_recent_project(&working_dir);
+    start_session(state.inner(), sink::tauri(app_handle), working_dir, opts).await
+}
+
+/// The env profile's variables (secrets resolved), overridden by `env`.
+pub(crate) fn session_env(
+    state: &AppState,
+    env_profile: Option<&str>,
+    env: Option<HashMap<String, String>>,
+) -> Result<HashMap<String, String>, KataraError> {
+    let mut merged = match env_profile {
+        Some(name) => env_profiles::resolve_env_profile(&state.settings().env_profiles, name)?,
+        None => HashMap::new(),
+    };
+    merged.extend(env.unwrap_or_default());
+    Ok(merged)
+}
+
+/// `working_dir`, or the `default_working_dir` setting when none is given.
+pub(crate) fn default_working_dir(
+    state: &AppState,
+    working_dir: Option<String>,
+) -> Result<String, KataraError> {
+    if let Some(dir) = working_dir.filter(|d| !d.is_empty()) {
+        return Ok(dir);
+    }
+    state
+        .settings()
+        .default_working_dir
+        .filter(|d| !d.is_empty())
+        .ok_or_else(|| {
+            KataraError::Config("No working directory given and no default is set".into())
+        })
+}
+
+/// Register a new session and spawn its Claude CLI.
+///
+/// Shared by `spawn_session`, `resume_session` and `run_skill`. Model,
+/// permission mode and CLI path fall back to the app settings.
+pub(crate) async fn start_session(
+    state: &Arc<AppState>,
+    sink: Sink,
+    working_dir: String,
+    mut opts: manager::SpawnOptions,
 ) -> Result<String, KataraError> {
     let session_id = uuid::Uuid::new_v4().to_string();
+    let settings = state.settings();
+    if opts.model.as_deref().is_none_or(str::is_empty) {
+        opts.model = Some(settings.default_model);
+    }
+    if opts.permission_mode.as_deref().is_none_or(str::is_empty) {
+        opts.permission_mode = Some(settings.default_permission_mode);
+    }
+    if opts.claude_path.is_none() {
+        opts.claude_path = settings.claude_binary_path.filter(|p| !p.is_empty());
+    }
+    opts.proxy = settings.proxy;
+    // The session keeps the profile's name, not the secrets it resolves to
+    let env_overrides = std::mem::take(&mut opts.env);
+    opts.env = session_env(
+        state,
+        opts.env_profile.as_deref(),
+        Some(env_overrides.clone()),
+    )?;
     let ws_port = *state.ws_port.read().await;
 
     if ws_port == 0 {
-        return Err(KataraError::WebSocket(
-            "WebSocket server not ready yet".into(),
-        ));
+        return Err(KataraError::WsNotReady);
+    }
+
+    // The session's own MCP token, which tells the server who's calling
+    let axum_port = *state.axum_port.read().await;
+    let mut mcp_token_hash = None;
+    if settings.server.mcp_enabled && axum_port != 0 {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        mcp_token_hash = Some(auth::token_hash(&token));
+        opts.mcp_config = Some(mcp_server::session_mcp_config(axum_port, &token));
     }
 
     // Insert session BEFORE spawning CLI so it exists when system/init arrives
-    let session = Session::new(
+    let mut session = Session::new(
         session_id.clone(),
         working_dir.clone(),
-        model.clone(),
-        permission_mode.clone(),
+        opts.model.clone(),
+        opts.permission_mode.clone(),
     );
+    session.env = opts.env.clone();
+    session.env_profile = opts.env_profile.clone();
+    session.env_overrides = env_overrides;
+    session.mcp_token_hash = mcp_token_hash;
+    session.fs_watcher = match SessionWatcher::start(sink.clone(), session_id.clone(), &working_dir)
+    {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            eprintln!("[katara] Not watching files for {}: {}", session_id, e);
+            None
+        }
+    };
+    session
+        .history
+        .set_limit(settings.history.max_messages_per_session);
+    if settings.history.persist_transcripts {
+        transcript::write_meta(&session);
+        match Transcript::open(&session_id) {
+            Ok(t) => session.history.persist_to(t),
+            Err(e) => eprintln!("[katara] Not saving a transcript for {}: {}", session_id, e),
+        }
+    }
     state
         .sessions
         .write()
@@ -70,7 +193,8 @@ pub async fn spawn_session(
         .push_back(session_id.clone());
 
     // Notify frontend of new session
-    let _ = app_handle.emit(
+    sink.emit_for_session(
+        &session_id,
         "claude:status",
         serde_json::json!({
             "session_id": &session_id,
@@ -78,58 +202,98 @@ pub async fn spawn_session(
         }),
     );
 
+    // Snapshot the tree before the agent touches it, as turn 0
+    if settings.checkpoints && git::repo::is_repo(&working_dir).await {
+        if let Err(e) = checkpoint::create(&working_dir, &session_id, "Session start").await {
+            eprintln!("[katara] Failed to checkpoint session {}: {}", session_id, e);
+        }
+    }
+
     // Spawn the Claude CLI process
-    let child = manager::spawn_claude(
-        ws_port,
-        &session_id,
-        &working_dir,
-        initial_prompt.as_deref(),
-        model.as_deref(),
-        permission_mode.as_deref(),
-        None,
-    )
-    .await?;
+    let child = match manager::spawn_claude(ws_port, &session_id, &working_dir, &opts).await {
+        Ok(child) => child,
+        Err(e) => {
+            abandon_session(state, &sink, &session_id, &e).await;
+            return Err(e);
+        }
+    };
 
     // Store the process handle
     {
         let mut sessions = state.sessions.write().await;
         if let Some(s) = sessions.get_mut(&session_id) {
+            s.pid = child.id();
+            s.pid_started = s.pid.and_then(procinfo::start_time);
             s.process = Some(child);
         }
     }
+    recovery::save(state).await;
+    activity::session_started(&session_id, &working_dir, opts.model.as_deref());
 
     // Start monitoring the process lifecycle
-    let arc_state: Arc<AppState> = state.inner().clone();
-    manager::monitor_process(arc_state, app_handle, session_id.clone());
+    manager::monitor_process(state.clone(), sink, session_id.clone());
 
     Ok(session_id)
 }
 
+/// Undo what `start_session` set up for a CLI that failed to spawn: the
+/// session (with its watcher and MCP token), its place in the pending
+/// connections and its MCP config, so nothing is left waiting for it.
+async fn abandon_session(state: &AppState, sink: &Sink, session_id: &str, error: &KataraError) {
+    state.sessions.write().await.remove(session_id);
+    state
+        .pending_connections
+        .lock()
+        .await
+        .retain(|id| id != session_id);
+    manager::remove_mcp_config(session_id);
+    sink.emit_for_session(
+        session_id,
+        "claude:status",
+        serde_json::json!({
+            "session_id": session_id,
+            "status": SessionStatus::Error(error.to_string()),
+        }),
+    );
+}
+
 #[tauri::command]
 pub async fn kill_session(
     state: tauri::State<'_, Arc<AppState>>,
     session_id: String,
 ) -> Result<(), KataraError> {
+    end_session(&state, &session_id).await;
+    Ok(())
+}
+
+/// Kill a session's CLI and forget the session. Shared by `kill_session`
+/// and sandboxed skill runs.
+pub(crate) async fn end_session(state: &AppState, session_id: &str) {
     let mut sessions = state.sessions.write().await;
-    if let Some(mut session) = sessions.remove(&session_id) {
+    if let Some(mut session) = sessions.remove(session_id) {
         if let Some(ref mut child) = session.process {
             let _ = child.kill().await;
+        } else if let Some(pid) = session.pid {
+            // Re-adopted after a crash; not our child
+            procinfo::terminate(pid);
         }
         session.status = SessionStatus::Terminated;
     }
     drop(sessions);
 
+    state.frontend_tools.remove(session_id).await;
+
     // Clean up thread <-> session mappings
     let thread_id = state
         .session_to_thread
         .write()
         .await
-        .remove(&session_id);
+        .remove(session_id);
     if let Some(tid) = thread_id {
         state.thread_to_session.write().await.remove(&tid);
+        state.shared_state.remove(&tid).await;
     }
-
-    Ok(())
+    recovery::save(state).await;
 }
 
 #[tauri::command]
@@ -137,20 +301,30 @@ pub async fn send_message(
     state: tauri::State<'_, Arc<AppState>>,
     session_id: String,
     content: String,
+) -> Result<(), KataraError> {
+    send_user_message(&state, &session_id, content).await
+}
+
+/// Send a user turn to a session. Shared by `send_message` and the quick
+/// prompt.
+pub(crate) async fn send_user_message(
+    state: &AppState,
+    session_id: &str,
+    content: String,
 ) -> Result<(), KataraError> {
     // Store user message in history BEFORE forwarding to CLI (Companion pattern).
     // This ensures user messages persist even if the CLI doesn't echo them back.
     let (cli_sid, ws_tx) = {
-        let mut sessions = state.sessions.write().await;
+        let sessions = state.sessions.read().await;
         let session = sessions
-            .get_mut(&session_id)
-            .ok_or(KataraError::SessionNotFound(session_id.clone()))?;
+            .get(session_id)
+            .ok_or(KataraError::SessionNotFound(session_id.to_string()))?;
 
         let ts = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
             .as_millis();
-        session.message_history.push(serde_json::json!({
+        session.history.push(serde_json::json!({
             "type": "user_message",
             "content": content,
             "timestamp": ts,
@@ -172,9 +346,7 @@ pub async fn send_message(
     };
 
     let json = serde_json::to_string(&msg).map_err(KataraError::Serde)?;
-    let tx = ws_tx.ok_or(KataraError::WebSocket(
-        "No WebSocket connection for this session".into(),
-    ))?;
+    let tx = ws_tx.ok_or(KataraError::NotConnected(session_id.to_string()))?;
     tx.send(format!("{}\n", json))
         .await
         .map_err(|e| KataraError::WebSocket(e.to_string()))?;
@@ -182,6 +354,59 @@ pub async fn send_message(
     Ok(())
 }
 
+/// Route text from the quick prompt window: send it to the most recently
+/// started Active or Idle session, or start a session with it in the last
+/// used project. Returns the session it went to.
+#[tauri::command]
+pub async fn submit_quick_prompt(
+    state: tauri::State<'_, Arc<AppState>>,
+    app_handle: tauri::AppHandle,
+    content: String,
+) -> Result<String, KataraError> {
+    if content.trim().is_empty() {
+        return Err(KataraError::Config("Prompt is empty".into()));
+    }
+    let target = state
+        .sessions
+        .read()
+        .await
+        .values()
+        .filter(|s| matches!(s.status, SessionStatus::Active | SessionStatus::Idle))
+        .max_by_key(|s| s.created_at)
+        .map(|s| s.id.clone());
+
+    let session_id = match target {
+        Some(session_id) => {
+            send_user_message(&state, &session_id, content).await?;
+            session_id
+        }
+        None => {
+            let working_dir = match recent::recent_projects().into_iter().next() {
+                Some(dir) => dir,
+                None => default_working_dir(&state, None)?,
+            };
+            let opts = manager::SpawnOptions {
+                initial_prompt: Some(content),
+                ..Default::default()
+            };
+            start_session(
+                state.inner(),
+                sink::tauri(app_handle.clone()),
+                working_dir,
+                opts,
+            )
+            .await?
+        }
+    };
+
+    quick_prompt::close(&app_handle);
+    let _ = app_handle.emit(
+        "quick-prompt:submitted",
+        serde_json::json!({ "session_id": &session_id }),
+    );
+    Ok(session_id)
+}
+
 #[tauri::command]
 pub async fn approve_tool(
     state: tauri::State<'_, Arc<AppState>>,
@@ -190,10 +415,40 @@ pub async fn approve_tool(
     approved: bool,
     updated_input: Option<serde_json::Value>,
 ) -> Result<(), KataraError> {
-    let sessions = state.sessions.read().await;
+    respond_to_tool(&state, &session_id, request_id, approved, updated_input).await
+}
+
+/// Answer a `can_use_tool` request. Shared by `approve_tool` and the tray.
+pub(crate) async fn respond_to_tool(
+    state: &AppState,
+    session_id: &str,
+    request_id: String,
+    approved: bool,
+    updated_input: Option<serde_json::Value>,
+) -> Result<(), KataraError> {
+    let mut sessions = state.sessions.write().await;
     let session = sessions
-        .get(&session_id)
-        .ok_or(KataraError::SessionNotFound(session_id.clone()))?;
+        .get_mut(session_id)
+        .ok_or(KataraError::SessionNotFound(session_id.to_string()))?;
+    if let Some(pending) = session
+        .pending_approvals
+        .iter()
+        .find(|p| p.request_id == request_id)
+    {
+        activity::approval(
+            session_id,
+            &session.working_dir,
+            &pending.tool_name,
+            approved,
+            false,
+        );
+    }
+    session
+        .pending_approvals
+        .retain(|p| p.request_id != request_id);
+    // Don't block other sessions while the response is sent
+    let sessions = sessions.downgrade();
+    let session = &sessions[session_id];
 
     // For allow responses, always include updatedInput (Companion pattern).
     // If not provided, default to empty object {}.
@@ -234,10 +489,16 @@ pub async fn interrupt_session(
     state: tauri::State<'_, Arc<AppState>>,
     session_id: String,
 ) -> Result<(), KataraError> {
+    send_interrupt(&state, &session_id).await
+}
+
+/// Send an interrupt to a session. Shared by `interrupt_session` and the
+/// tray.
+pub(crate) async fn send_interrupt(state: &AppState, session_id: &str) -> Result<(), KataraError> {
     let sessions = state.sessions.read().await;
     let session = sessions
-        .get(&session_id)
-        .ok_or(KataraError::SessionNotFound(session_id.clone()))?;
+        .get(session_id)
+        .ok_or(KataraError::SessionNotFound(session_id.to_string()))?;
 
     let msg = ServerMessage::ControlRequest {
         request_id: uuid::Uuid::new_v4().to_string(),
@@ -260,13 +521,35 @@ pub async fn interrupt_session(
 pub async fn get_message_history(
     state: tauri::State<'_, Arc<AppState>>,
     session_id: String,
-) -> Result<Vec<serde_json::Value>, KataraError> {
+) -> Result<Vec<Arc<serde_json::Value>>, KataraError> {
+    Ok(session_history(&state, &session_id).await?.all())
+}
+
+/// Messages stored since `cursor` (the `cursor` of the previous page, or 0
+/// for everything), so the frontend can catch up without refetching the
+/// whole history.
+#[tauri::command]
+pub async fn get_message_history_since(
+    state: tauri::State<'_, Arc<AppState>>,
+    session_id: String,
+    cursor: Option<u64>,
+) -> Result<HistoryPage, KataraError> {
+    Ok(session_history(&state, &session_id)
+        .await?
+        .since(cursor.unwrap_or(0)))
+}
+
+/// The session's history handle, so it can be read without holding the
+/// sessions lock.
+pub(crate) async fn session_history(
+    state: &AppState,
+    session_id: &str,
+) -> Result<MessageHistory, KataraError> {
     let sessions = state.sessions.read().await;
     let session = sessions
-        .get(&session_id)
-        .ok_or(KataraError::SessionNotFound(session_id.clone()))?;
-
-    Ok(session.message_history.clone())
+        .get(session_id)
+        .ok_or(KataraError::SessionNotFound(session_id.to_string()))?;
+    Ok(session.history.clone())
 }
 
 #[tauri::command]
@@ -307,6 +590,15 @@ pub async fn set_permission_mode(
 pub async fn get_session_cost(
     state: tauri::State<'_, Arc<AppState>>,
     session_id: String,
+) -> Result<SessionCost, KataraError> {
+    session_cost(&state, session_id).await
+}
+
+/// A session's token usage and what it cost. Shared by `get_session_cost`
+/// and the REST API.
+pub(crate) async fn session_cost(
+    state: &AppState,
+    session_id: String,
 ) -> Result<SessionCost, KataraError> {
     let sessions = state.sessions.read().await;
     let session = sessions
@@ -316,21 +608,13 @@ pub async fn get_session_cost(
     let u = &session.usage_totals;
     let model_name = session.model.as_deref().unwrap_or("claude-sonnet-4-5-20250929");
 
-    // Pricing per million tokens (input, output, cache_write, cache_read)
-    let (input_per_m, output_per_m, cache_write_per_m, cache_read_per_m) =
-        if model_name.contains("opus") {
-            (15.0, 75.0, 18.75, 1.5)
-        } else if model_name.contains("haiku") {
-            (0.80, 4.0, 1.0, 0.08)
-        } else {
-            // Sonnet (default)
-            (3.0, 15.0, 3.75, 0.30)
-        };
-
-    let cost = (u.input_tokens as f64 * input_per_m
-        + u.output_tokens as f64 * output_per_m
-        + u.cache_creation_input_tokens as f64 * cache_write_per_m
-        + u.cache_read_input_tokens as f64 * cache_read_per_m)
+    // Pricing per million tokens, from the live settings
+    let price = state.settings().pricing_for(model_name);
+
+    let cost = (u.input_tokens as f64 * price.input
+        + u.output_tokens as f64 * price.output
+        + u.cache_creation_input_tokens as f64 * price.cache_write
+        + u.cache_read_input_tokens as f64 * price.cache_read)
         / 1_000_000.0;
 
     Ok(SessionCost {
@@ -344,8 +628,23 @@ pub async fn get_session_cost(
     })
 }
 
+/// Conversations the Claude CLI has saved in `~/.claude/projects`, most
+/// recently used first, so one can be picked for `resume_session`. With
+/// `working_dir`, only those started there.
+#[tauri::command]
+pub async fn list_resumable_sessions(
+    working_dir: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ResumableSession>, KataraError> {
+    let limit = limit.unwrap_or(DEFAULT_RESUMABLE_LIMIT);
+    tokio::task::spawn_blocking(move || cli_sessions::list(working_dir.as_deref(), limit))
+        .await
+        .map_err(|e| KataraError::Process(format!("Failed to list CLI sessions: {}", e)))
+}
+
 /// Resume a previous Claude CLI session using its CLI session ID.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn resume_session(
     state: tauri::State<'_, Arc<AppState>>,
     app_handle: tauri::AppHandle,
@@ -353,62 +652,102 @@ pub async fn resume_session(
     cli_session_id: String,
     model: Option<String>,
     permission_mode: Option<String>,
+    env: Option<HashMap<String, String>>,
+    output_style: Option<String>,
+    env_profile: Option<String>,
 ) -> Result<String, KataraError> {
-    let session_id = uuid::Uuid::new_v4().to_string();
-    let ws_port = *state.ws_port.read().await;
+    let opts = manager::SpawnOptions {
+        model,
+        permission_mode,
+        resume_session_id: Some(cli_session_id),
+        env_profile,
+        env: env.unwrap_or_default(),
+        output_style,
+        ..Default::default()
+    };
+    recent::add_recent_project(&working_dir);
+    start_session(state.inner(), sink::tauri(app_handle), working_dir, opts).await
+}
 
-    if ws_port == 0 {
-        return Err(KataraError::WebSocket(
-            "WebSocket server not ready yet".into(),
-        ));
+/// Conversations saved to disk, most recent first, including ones from
+/// before the last restart.
+#[tauri::command]
+pub async fn load_persisted_sessions(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<PersistedSession>, KataraError> {
+    let open: Vec<String> = state.sessions.read().await.keys().cloned().collect();
+    tokio::task::spawn_blocking(move || transcript::list(&open))
+        .await
+        .map_err(|e| KataraError::Process(format!("Failed to list transcripts: {}", e)))
+}
+
+/// Every message saved for a session.
+#[tauri::command]
+pub async fn get_persisted_transcript(
+    session_id: String,
+) -> Result<Vec<serde_json::Value>, KataraError> {
+    transcript::load(&session_id)
+        .map(|(_, messages)| messages)
+        .ok_or(KataraError::SessionNotFound(session_id))
+}
+
+/// Put a saved conversation back in the session list, read-only, so its
+/// history can be shown with `get_message_history`. Continue it with
+/// `resume_session` and its `cli_session_id`.
+#[tauri::command]
+pub async fn reopen_persisted_session(
+    state: tauri::State<'_, Arc<AppState>>,
+    app_handle: tauri::AppHandle,
+    session_id: String,
+) -> Result<SessionInfo, KataraError> {
+    if let Some(s) = state.sessions.read().await.get(&session_id) {
+        return Ok(SessionInfo {
+            id: s.id.clone(),
+            status: s.status.clone(),
+            working_dir: s.working_dir.clone(),
+            model: s.model.clone(),
+            permission_mode: s.permission_mode.clone(),
+        });
     }
+    let (saved, messages) = transcript::load(&session_id)
+        .ok_or_else(|| KataraError::SessionNotFound(session_id.clone()))?;
 
-    let session = Session::new(
+    let mut session = Session::new(
         session_id.clone(),
-        working_dir.clone(),
-        model.clone(),
-        permission_mode.clone(),
+        saved.working_dir,
+        saved.model,
+        Some(saved.permission_mode),
     );
-    state
-        .sessions
-        .write()
-        .await
-        .insert(session_id.clone(), session);
-
-    state
-        .pending_connections
-        .lock()
-        .await
-        .push_back(session_id.clone());
-
-    let _ = app_handle.emit(
+    session.status = SessionStatus::Terminated;
+    session.cli_session_id = saved.cli_session_id;
+    session
+        .history
+        .set_limit(state.settings().history.max_messages_per_session);
+    for message in messages {
+        session.history.push(message);
+    }
+    let info = SessionInfo {
+        id: session.id.clone(),
+        status: session.status.clone(),
+        working_dir: session.working_dir.clone(),
+        model: session.model.clone(),
+        permission_mode: session.permission_mode.clone(),
+    };
+    state.sessions.write().await.insert(session_id.clone(), session);
+    sink::tauri(app_handle).emit_for_session(
+        &session_id,
         "claude:status",
         serde_json::json!({
             "session_id": &session_id,
-            "status": SessionStatus::Starting,
+            "status": SessionStatus::Terminated,
         }),
     );
+    Ok(info)
+}
 
-    let child = manager::spawn_claude(
-        ws_port,
-        &session_id,
-        &working_dir,
-        None,
-        model.as_deref(),
-        permission_mode.as_deref(),
-        Some(&cli_session_id),
-    )
-    .await?;
-
-    {
-        let mut sessions = state.sessions.write().await;
-        if let Some(s) = sessions.get_mut(&session_id) {
-            s.process = Some(child);
-        }
-    }
-
-    let arc_state: Arc<AppState> = state.inner().clone();
-    manager::monitor_process(arc_state, app_handle, session_id.clone());
-
-    Ok(session_id)
+/// Delete a saved conversation from disk. An open session keeps its
+/// in-memory history.
+#[tauri::command]
+pub async fn delete_persisted_session(session_id: String) -> Result<(), KataraError> {
+    transcript::delete(&session_id)
 }
@@ -1,5 +1,22 @@
-use crate::config::manager::{self as config_mgr, AppSettings, ClaudeMdEntry};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::config::agents::{self, AgentDefinition};
+use crate::config::backup::{self, BackupInfo};
+use crate::config::claude_settings::{self, ClaudeSettingsEntry, SettingsError};
+use crate::config::env_profiles::{self, EnvProfile};
+use crate::config::hooks::{self, HookTestResult, HooksConfig};
+use crate::config::init::{self, InitOptions, InitResult};
+use crate::config::keybindings::{self, Keybindings};
+use crate::config::lint::{self, ConfigWarning};
+use crate::config::manager::{self as config_mgr, AppSettings, ClaudeMdEntry, TerminalProfile};
+use crate::config::memory::{self, MemoryLevel, ResolvedClaudeMd};
+use crate::config::output_styles::{self, OutputStyle};
+use crate::config::secrets;
+use crate::config::settings_diff::{self, SettingsDiff};
+use crate::config::themes::{self, Theme};
 use crate::error::KataraError;
+use crate::state::AppState;
 
 #[tauri::command]
 pub async fn read_claude_md(
@@ -9,17 +26,450 @@ pub async fn read_claude_md(
     config_mgr::read_claude_md(&level, project_dir.as_deref())
 }
 
+/// Every CLAUDE.md level in one call, with sizes and modification times.
+#[tauri::command]
+pub async fn get_memory_overview(
+    project_dir: Option<String>,
+) -> Result<Vec<MemoryLevel>, KataraError> {
+    memory::memory_overview(project_dir.as_deref())
+}
+
+/// The AGENTS.md in the same directory as the level's CLAUDE.md.
+#[tauri::command]
+pub async fn read_agents_md(
+    level: String,
+    project_dir: Option<String>,
+) -> Result<ClaudeMdEntry, KataraError> {
+    memory::read_agents_md(&level, project_dir.as_deref())
+}
+
+/// Make the level's AGENTS.md a symlink or copy of its CLAUDE.md
+/// (`source` = `claude_md`), or the other way round (`agents_md`).
+#[tauri::command]
+pub async fn link_memory_files(
+    level: String,
+    project_dir: Option<String>,
+    source: String,
+    mode: String,
+    overwrite: Option<bool>,
+) -> Result<MemoryLevel, KataraError> {
+    memory::link_memory_files(
+        &level,
+        project_dir.as_deref(),
+        &source,
+        &mode,
+        overwrite.unwrap_or(false),
+    )
+}
+
+/// A CLAUDE.md with its `@path` imports expanded, as Claude sees it.
+#[tauri::command]
+pub async fn resolve_claude_md(
+    level: String,
+    project_dir: Option<String>,
+) -> Result<ResolvedClaudeMd, KataraError> {
+    memory::resolve_claude_md(&level, project_dir.as_deref())
+}
+
+/// Set up a project for Claude Code: CLAUDE.md, `.claude/settings.json`,
+/// `.claude/commands/` and a `.gitignore` entry. Existing files are kept
+/// unless `options.overwrite` is set.
+#[tauri::command]
+pub async fn init_project(
+    working_dir: String,
+    options: Option<InitOptions>,
+) -> Result<InitResult, KataraError> {
+    init::init_project(&working_dir, &options.unwrap_or_default())
+}
+
+/// The merged Claude Code settings, with the level each value came from
+/// and the values it overrode.
+#[tauri::command]
+pub async fn diff_settings(project_dir: Option<String>) -> Result<SettingsDiff, KataraError> {
+    settings_diff::diff_settings(project_dir.as_deref())
+}
+
+/// Check settings and CLAUDE.md files at every level for conflicts and
+/// likely mistakes.
+#[tauri::command]
+pub async fn lint_config(project_dir: Option<String>) -> Result<Vec<ConfigWarning>, KataraError> {
+    lint::lint_config(project_dir.as_deref())
+}
+
+/// Watch the CLAUDE.md and settings files of `project_dir` (replacing the
+/// previously watched project) for `config:changed` events.
+#[tauri::command]
+pub async fn watch_project_config(
+    state: tauri::State<'_, Arc<AppState>>,
+    project_dir: Option<String>,
+) -> Result<(), KataraError> {
+    let watcher = state
+        .config_watcher
+        .get()
+        .ok_or_else(|| KataraError::Config("Config watcher is not running".into()))?;
+    watcher.set_project(project_dir);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn write_claude_md(path: String, content: String) -> Result<(), KataraError> {
     config_mgr::write_claude_md(&path, &content)
 }
 
+/// Write an AGENTS.md. A symlinked one writes through to its target.
+#[tauri::command]
+pub async fn write_agents_md(path: String, content: String) -> Result<(), KataraError> {
+    config_mgr::write_claude_md(&path, &content)
+}
+
+/// Read Claude Code's `settings.json` at `user`, `project` or `local`
+/// (`settings.local.json`) level, or the managed `enterprise` settings.
+#[tauri::command]
+pub async fn read_claude_settings(
+    level: String,
+    project_dir: Option<String>,
+) -> Result<ClaudeSettingsEntry, KataraError> {
+    claude_settings::read_claude_settings(&level, project_dir.as_deref())
+}
+
+/// Write Claude Code's settings; fails with per-field errors if the known
+/// keys don't have the expected structure.
+#[tauri::command]
+pub async fn write_claude_settings(
+    level: String,
+    project_dir: Option<String>,
+    settings: Value,
+) -> Result<(), KataraError> {
+    claude_settings::write_claude_settings(&level, project_dir.as_deref(), &settings)
+}
+
+#[tauri::command]
+pub async fn validate_claude_settings(settings: Value) -> Result<Vec<SettingsError>, KataraError> {
+    Ok(claude_settings::validate_settings(&settings))
+}
+
+/// Hooks from Claude Code's settings at a level, by event.
+#[tauri::command]
+pub async fn read_hooks(
+    level: String,
+    project_dir: Option<String>,
+) -> Result<HooksConfig, KataraError> {
+    hooks::read_hooks(&level, project_dir.as_deref())
+}
+
+/// Replace the hooks at a level; other settings are kept.
+#[tauri::command]
+pub async fn write_hooks(
+    level: String,
+    project_dir: Option<String>,
+    hooks: HooksConfig,
+) -> Result<(), KataraError> {
+    hooks::write_hooks(&level, project_dir.as_deref(), &hooks)
+}
+
+/// Fire a hook command with a sample payload for `event` (or `payload`)
+/// and report what it did.
+#[tauri::command]
+pub async fn test_hook(
+    command: String,
+    event: String,
+    payload: Option<Value>,
+    project_dir: Option<String>,
+    timeout: Option<u64>,
+) -> Result<HookTestResult, KataraError> {
+    hooks::test_hook(&command, &event, payload, project_dir.as_deref(), timeout).await
+}
+
+/// Subagents from `~/.claude/agents` and, with a project, its
+/// `.claude/agents`.
+#[tauri::command]
+pub async fn list_agents(project_dir: Option<String>) -> Result<Vec<AgentDefinition>, KataraError> {
+    agents::list_agents(project_dir.as_deref())
+}
+
+#[tauri::command]
+pub async fn read_agent(path: String) -> Result<AgentDefinition, KataraError> {
+    agents::read_agent(&path)
+}
+
+/// Save a subagent at `user` or `project` level, after validating its
+/// frontmatter.
+#[tauri::command]
+pub async fn write_agent(
+    level: String,
+    project_dir: Option<String>,
+    agent: AgentDefinition,
+) -> Result<AgentDefinition, KataraError> {
+    agents::write_agent(&level, project_dir.as_deref(), &agent)
+}
+
+#[tauri::command]
+pub async fn delete_agent(path: String) -> Result<(), KataraError> {
+    agents::delete_agent(&path)
+}
+
+/// Built-in, user and project output styles.
+#[tauri::command]
+pub async fn list_output_styles(
+    project_dir: Option<String>,
+) -> Result<Vec<OutputStyle>, KataraError> {
+    output_styles::list_output_styles(project_dir.as_deref())
+}
+
+#[tauri::command]
+pub async fn read_output_style(path: String) -> Result<OutputStyle, KataraError> {
+    output_styles::read_output_style(&path)
+}
+
+/// Save an output style at `user` or `project` level.
+#[tauri::command]
+pub async fn write_output_style(
+    level: String,
+    project_dir: Option<String>,
+    style: OutputStyle,
+) -> Result<OutputStyle, KataraError> {
+    output_styles::write_output_style(&level, project_dir.as_deref(), &style)
+}
+
+/// Delete an output style from the user or `project_dir`'s styles.
+#[tauri::command]
+pub async fn delete_output_style(
+    path: String,
+    project_dir: Option<String>,
+) -> Result<(), KataraError> {
+    output_styles::delete_output_style(&path, project_dir.as_deref())
+}
+
 #[tauri::command]
 pub async fn read_settings() -> Result<AppSettings, KataraError> {
     config_mgr::read_settings()
 }
 
+/// Replace all settings. The config directory is backed up first.
+#[tauri::command]
+pub async fn write_settings(
+    state: tauri::State<'_, Arc<AppState>>,
+    settings: AppSettings,
+) -> Result<(), KataraError> {
+    backup::auto_backup("write-settings");
+    let settings = config_mgr::modify_settings(|current| {
+        *current = settings;
+        Ok(())
+    })?;
+    state.set_settings(settings);
+    Ok(())
+}
+
+/// Merge a partial settings object into the stored settings and return the
+/// result, so concurrent edits of different fields don't overwrite each other.
+#[tauri::command]
+pub async fn update_settings(
+    state: tauri::State<'_, Arc<AppState>>,
+    patch: Value,
+) -> Result<AppSettings, KataraError> {
+    let settings = config_mgr::update_settings(&patch)?;
+    state.set_settings(settings.clone());
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn get_keybindings() -> Result<Keybindings, KataraError> {
+    keybindings::get_keybindings()
+}
+
+/// Bind an action to a chord (e.g. `Ctrl+Shift+P`), or unbind it with
+/// `None`. Fails if another action already uses the chord. Returns all
+/// bindings, normalized.
+#[tauri::command]
+pub async fn set_keybinding(
+    state: tauri::State<'_, Arc<AppState>>,
+    action: String,
+    chord: Option<String>,
+) -> Result<Keybindings, KataraError> {
+    let settings = keybindings::set_keybinding(&action, chord.as_deref())?;
+    state.set_settings(settings.clone());
+    Ok(settings.keybindings)
+}
+
+/// Replace all keybindings, e.g. when importing a preset.
+#[tauri::command]
+pub async fn set_keybindings(
+    state: tauri::State<'_, Arc<AppState>>,
+    bindings: Keybindings,
+) -> Result<Keybindings, KataraError> {
+    let settings = keybindings::set_keybindings(bindings)?;
+    state.set_settings(settings.clone());
+    Ok(settings.keybindings)
+}
+
+#[tauri::command]
+pub async fn list_terminal_profiles() -> Result<Vec<TerminalProfile>, KataraError> {
+    Ok(config_mgr::read_settings()?.terminal.profiles)
+}
+
+#[tauri::command]
+pub async fn create_terminal_profile(
+    state: tauri::State<'_, Arc<AppState>>,
+    profile: TerminalProfile,
+) -> Result<(), KataraError> {
+    state.set_settings(config_mgr::create_terminal_profile(profile)?);
+    Ok(())
+}
+
+/// Update the profile called `name`. `profile.name` may differ to rename it.
+#[tauri::command]
+pub async fn update_terminal_profile(
+    state: tauri::State<'_, Arc<AppState>>,
+    name: String,
+    profile: TerminalProfile,
+) -> Result<(), KataraError> {
+    state.set_settings(config_mgr::update_terminal_profile(&name, profile)?);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_terminal_profile(
+    state: tauri::State<'_, Arc<AppState>>,
+    name: String,
+) -> Result<(), KataraError> {
+    backup::auto_backup("delete-profile");
+    state.set_settings(config_mgr::delete_terminal_profile(&name)?);
+    Ok(())
+}
+
+/// Store a secret in the OS keychain, for env profiles to reference by name.
+#[tauri::command]
+pub async fn set_secret(name: String, value: String) -> Result<(), KataraError> {
+    secrets::set_secret(&name, &value)
+}
+
+/// Names of stored secrets; values are never sent to the frontend.
+#[tauri::command]
+pub async fn get_secret_names() -> Result<Vec<String>, KataraError> {
+    secrets::get_secret_names()
+}
+
+#[tauri::command]
+pub async fn delete_secret(name: String) -> Result<(), KataraError> {
+    secrets::delete_secret(&name)
+}
+
+/// Env profiles; secret values are returned as references, not read.
+#[tauri::command]
+pub async fn list_env_profiles() -> Result<Vec<EnvProfile>, KataraError> {
+    env_profiles::list_env_profiles()
+}
+
+#[tauri::command]
+pub async fn create_env_profile(
+    state: tauri::State<'_, Arc<AppState>>,
+    profile: EnvProfile,
+) -> Result<(), KataraError> {
+    state.set_settings(env_profiles::create_env_profile(profile)?);
+    Ok(())
+}
+
+/// Update the env profile called `name`. `profile.name` may differ to
+/// rename it.
+#[tauri::command]
+pub async fn update_env_profile(
+    state: tauri::State<'_, Arc<AppState>>,
+    name: String,
+    profile: EnvProfile,
+) -> Result<(), KataraError> {
+    state.set_settings(env_profiles::update_env_profile(&name, profile)?);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_env_profile(
+    state: tauri::State<'_, Arc<AppState>>,
+    name: String,
+) -> Result<(), KataraError> {
+    backup::auto_backup("delete-env-profile");
+    state.set_settings(env_profiles::delete_env_profile(&name)?);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_themes() -> Result<Vec<Theme>, KataraError> {
+    themes::list_themes()
+}
+
+#[tauri::command]
+pub async fn create_theme(
+    state: tauri::State<'_, Arc<AppState>>,
+    theme: Theme,
+) -> Result<(), KataraError> {
+    state.set_settings(themes::create_theme(theme)?);
+    Ok(())
+}
+
+/// Update the theme called `name`. `theme.name` may differ to rename it.
+#[tauri::command]
+pub async fn update_theme(
+    state: tauri::State<'_, Arc<AppState>>,
+    name: String,
+    theme: Theme,
+) -> Result<(), KataraError> {
+    state.set_settings(themes::update_theme(&name, theme)?);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_theme(
+    state: tauri::State<'_, Arc<AppState>>,
+    name: String,
+) -> Result<(), KataraError> {
+    state.set_settings(themes::delete_theme(&name)?);
+    Ok(())
+}
+
+/// Save a theme as a JSON file to share.
+#[tauri::command]
+pub async fn export_theme(name: String, path: String) -> Result<(), KataraError> {
+    themes::export_theme(&name, &path)
+}
+
+/// Add a theme from an exported JSON file.
+#[tauri::command]
+pub async fn import_theme(
+    state: tauri::State<'_, Arc<AppState>>,
+    path: String,
+    overwrite: Option<bool>,
+) -> Result<Theme, KataraError> {
+    let (theme, settings) = themes::import_theme(&path, overwrite.unwrap_or(false))?;
+    state.set_settings(settings);
+    Ok(theme)
+}
+
+/// Snapshot the Katara config directory into the backups directory.
+#[tauri::command]
+pub async fn create_config_backup() -> Result<BackupInfo, KataraError> {
+    backup::create_backup("manual")
+}
+
+/// Manual and automatic backups, newest first.
+#[tauri::command]
+pub async fn list_config_backups() -> Result<Vec<BackupInfo>, KataraError> {
+    backup::list_backups()
+}
+
+/// Restore a backup or an exported config archive. Returns the restored
+/// files; the state before the restore is backed up.
+#[tauri::command]
+pub async fn restore_config_backup(path: String) -> Result<Vec<String>, KataraError> {
+    backup::restore_backup(&path)
+}
+
+/// Save the Katara config directory as a zip, e.g. to move to another machine.
+#[tauri::command]
+pub async fn export_config(zip_path: String) -> Result<(), KataraError> {
+    backup::export_config(&zip_path)
+}
+
+/// Load an exported config archive; same as restoring a backup.
 #[tauri::command]
-pub async fn write_settings(settings: AppSettings) -> Result<(), KataraError> {
-    config_mgr::write_settings(&settings)
+pub async fn import_config(zip_path: String) -> Result<Vec<String>, KataraError> {
+    backup::restore_backup(&zip_path)
 }
@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use crate::commands::claude;
+use crate::error::KataraError;
+use crate::git::checkpoint::{self, Checkpoint};
+use crate::git::github::{self, GithubItem, PullRequestDraft};
+use crate::git::repo::{self, GitCommit, GitDiff, GitStatus, DEFAULT_LOG_LIMIT};
+use crate::process::session::SessionStatus;
+use crate::sink;
+use crate::state::AppState;
+
+/// Branch and changed files of a session's working directory.
+#[tauri::command]
+pub async fn git_status(working_dir: String) -> Result<GitStatus, KataraError> {
+    repo::status(&working_dir).await
+}
+
+/// What changed since the last commit, optionally for a single file or
+/// directory.
+#[tauri::command]
+pub async fn git_diff(working_dir: String, path: Option<String>) -> Result<GitDiff, KataraError> {
+    repo::diff(&working_dir, path.as_deref()).await
+}
+
+/// Recent commits on the current branch, newest first.
+#[tauri::command]
+pub async fn git_log(
+    working_dir: String,
+    limit: Option<usize>,
+) -> Result<Vec<GitCommit>, KataraError> {
+    repo::log(&working_dir, limit.unwrap_or(DEFAULT_LOG_LIMIT)).await
+}
+
+/// Checkpoints recorded for a session, newest first. Empty unless the
+/// `checkpoints` setting was on while it ran.
+#[tauri::command]
+pub async fn list_checkpoints(
+    state: tauri::State<'_, Arc<AppState>>,
+    session_id: String,
+) -> Result<Vec<Checkpoint>, KataraError> {
+    let (working_dir, _) = session_working_dir(&state, &session_id).await?;
+    checkpoint::list(&working_dir, &session_id).await
+}
+
+/// Undo agent edits by restoring the working tree to its checkpoint after
+/// `turn` (0 is the session start). Refused while the session is working.
+#[tauri::command]
+pub async fn rollback_to_turn(
+    state: tauri::State<'_, Arc<AppState>>,
+    app_handle: tauri::AppHandle,
+    session_id: String,
+    turn: u32,
+) -> Result<Checkpoint, KataraError> {
+    let (working_dir, status) = session_working_dir(&state, &session_id).await?;
+    if matches!(status, SessionStatus::Starting | SessionStatus::Active) {
+        return Err(KataraError::Git(
+            "Session is working; interrupt it before rolling back".into(),
+        ));
+    }
+    let restored = checkpoint::rollback(&working_dir, &session_id, turn).await?;
+    repo::refresh_session_status(sink::tauri(app_handle), session_id, working_dir);
+    Ok(restored)
+}
+
+/// Fetch an issue or pull request (number, `#number` or URL) from the
+/// repository in `working_dir` with `gh`.
+#[tauri::command]
+pub async fn fetch_github_item(
+    working_dir: String,
+    reference: String,
+) -> Result<GithubItem, KataraError> {
+    github::fetch(&working_dir, &reference).await
+}
+
+/// Send an issue or pull request to a session as context, followed by
+/// `message` if given.
+#[tauri::command]
+pub async fn send_github_context(
+    state: tauri::State<'_, Arc<AppState>>,
+    session_id: String,
+    reference: String,
+    message: Option<String>,
+) -> Result<GithubItem, KataraError> {
+    let (working_dir, _) = session_working_dir(&state, &session_id).await?;
+    let item = github::fetch(&working_dir, &reference).await?;
+    let mut content = item.to_context();
+    if let Some(message) = message.filter(|m| !m.trim().is_empty()) {
+        content.push_str(&format!("\n\n{}", message));
+    }
+    claude::send_user_message(&state, &session_id, content).await?;
+    Ok(item)
+}
+
+/// Draft a pull request title and body for the session's branch, for the
+/// user to review before `create_pull_request`.
+#[tauri::command]
+pub async fn draft_pull_request(
+    state: tauri::State<'_, Arc<AppState>>,
+    session_id: String,
+    base: Option<String>,
+) -> Result<PullRequestDraft, KataraError> {
+    let (working_dir, _) = session_working_dir(&state, &session_id).await?;
+    github::draft_pull_request(&working_dir, base.as_deref(), &state.settings()).await
+}
+
+/// Push the session's branch and open a pull request. A title or body left
+/// out is drafted by Claude. Returns the pull request's URL.
+#[tauri::command]
+pub async fn create_pull_request(
+    state: tauri::State<'_, Arc<AppState>>,
+    session_id: String,
+    title: Option<String>,
+    body: Option<String>,
+    base: Option<String>,
+    draft: Option<bool>,
+) -> Result<String, KataraError> {
+    let (working_dir, _) = session_working_dir(&state, &session_id).await?;
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    let pr = match (title, body) {
+        (Some(title), Some(body)) => PullRequestDraft {
+            title,
+            body,
+            head: github::current_branch(&working_dir).await?,
+            base: match base {
+                Some(base) => base,
+                None => github::default_branch(&working_dir).await?,
+            },
+        },
+        (title, body) => {
+            let mut drafted =
+                github::draft_pull_request(&working_dir, base.as_deref(), &state.settings())
+                    .await?;
+            drafted.title = title.unwrap_or(drafted.title);
+            drafted.body = body.unwrap_or(drafted.body);
+            drafted
+        }
+    };
+    github::create_pull_request(&working_dir, &pr, draft.unwrap_or(false)).await
+}
+
+async fn session_working_dir(
+    state: &AppState,
+    session_id: &str,
+) -> Result<(String, SessionStatus), KataraError> {
+    let sessions = state.sessions.read().await;
+    let session = sessions
+        .get(session_id)
+        .ok_or_else(|| KataraError::SessionNotFound(session_id.to_string()))?;
+    Ok((session.working_dir.clone(), session.status.clone()))
+}
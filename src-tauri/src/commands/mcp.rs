@@ -0,0 +1,27 @@
+use crate::error::KataraError;
+use crate::mcp::config::{self as mcp_config, McpServerConfig, McpServerEntry};
+use crate::mcp::health::{self, McpHealth};
+
+/// MCP servers configured for a project (or only the user's, without one).
+#[tauri::command]
+pub async fn list_mcp_servers(
+    project_dir: Option<String>,
+) -> Result<Vec<McpServerEntry>, KataraError> {
+    mcp_config::list_servers(project_dir.as_deref())
+}
+
+/// Start or connect to an MCP server, run the handshake and list its tools
+/// and resources. Pass `config` to check a server before saving it;
+/// otherwise the configured server called `name` is used.
+#[tauri::command]
+pub async fn check_mcp_server(
+    name: String,
+    project_dir: Option<String>,
+    config: Option<McpServerConfig>,
+) -> Result<McpHealth, KataraError> {
+    let config = match config {
+        Some(config) => config,
+        None => mcp_config::find_server(&name, project_dir.as_deref())?.config,
+    };
+    Ok(health::check_server(&name, &config, project_dir.as_deref()).await)
+}
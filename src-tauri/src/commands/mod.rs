@@ -1,5 +1,12 @@
+pub mod agents;
 pub mod app;
 pub mod claude;
 pub mod config;
+pub mod git;
+pub mod mcp;
+pub mod plugins;
+pub mod remote;
+pub mod search;
 pub mod skills;
+pub mod stats;
 pub mod terminal;
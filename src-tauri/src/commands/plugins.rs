@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use crate::error::KataraError;
+use crate::plugins::host::PluginInfo;
+use crate::state::AppState;
+
+/// The plugins in settings, with what each running one offers.
+#[tauri::command]
+pub async fn list_plugins(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<PluginInfo>, KataraError> {
+    let configs = state.settings().plugins;
+    Ok(state.plugins.list(&configs).await)
+}
+
+/// Call a command a plugin registered in its manifest.
+#[tauri::command]
+pub async fn invoke_plugin(
+    state: tauri::State<'_, Arc<AppState>>,
+    plugin: String,
+    command: String,
+    args: Option<serde_json::Value>,
+) -> Result<serde_json::Value, KataraError> {
+    state
+        .plugins
+        .invoke(&plugin, &command, args.unwrap_or(serde_json::Value::Null))
+        .await
+}
+
+/// Restart every plugin with the current settings.
+#[tauri::command]
+pub async fn reload_plugins(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<PluginInfo>, KataraError> {
+    let configs = state.settings().plugins;
+    state.plugins.start(&configs, &state.event_tx).await;
+    Ok(state.plugins.list(&configs).await)
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use crate::error::KataraError;
+use crate::remote::pairing::{self, PairedDevice, PairingCode};
+use crate::remote::server::{self, RemoteAccessInfo, RemoteStatus};
+use crate::state::AppState;
+
+/// Where the remote server is listening; `None` if remote access is off or
+/// failed to start.
+#[tauri::command]
+pub async fn get_remote_status(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Option<RemoteStatus>, KataraError> {
+    Ok(state.remote.read().await.clone())
+}
+
+/// The URL, a new pairing code and the QR payload for using Katara from
+/// another machine; `None` if remote access is off or failed to start.
+#[tauri::command]
+pub async fn get_remote_access_info(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Option<RemoteAccessInfo>, KataraError> {
+    Ok(server::access_info(&state).await)
+}
+
+/// A code for pairing a phone or another machine, valid for a few minutes.
+#[tauri::command]
+pub async fn create_pairing_code() -> Result<PairingCode, KataraError> {
+    Ok(pairing::new_code())
+}
+
+#[tauri::command]
+pub async fn list_paired_devices() -> Result<Vec<PairedDevice>, KataraError> {
+    Ok(pairing::list_devices())
+}
+
+#[tauri::command]
+pub async fn revoke_device(device_id: String) -> Result<(), KataraError> {
+    pairing::revoke(&device_id)
+}
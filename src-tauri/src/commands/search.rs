@@ -0,0 +1,39 @@
+use crate::error::KataraError;
+use crate::search::content::{
+    self, ContentSearchOptions, ContentSearchResult, MAX_CONTEXT, MAX_RESULTS,
+};
+use crate::search::files::{self, FileMatch, DEFAULT_LIMIT};
+
+/// Fuzzy-find files under `working_dir` for @-mention completion.
+/// `.gitignore`d files are skipped. Pass `refresh` after creating or
+/// deleting files to rebuild the cached file list immediately.
+#[tauri::command]
+pub async fn search_project_files(
+    working_dir: String,
+    query: String,
+    limit: Option<usize>,
+    refresh: Option<bool>,
+) -> Result<Vec<FileMatch>, KataraError> {
+    files::search(
+        &working_dir,
+        &query,
+        limit.unwrap_or(DEFAULT_LIMIT),
+        refresh.unwrap_or(false),
+    )
+    .await
+}
+
+/// Search file contents under `working_dir` for `pattern` (literal unless
+/// `options.regex`), returning matching lines with surrounding context.
+#[tauri::command]
+pub async fn search_project_content(
+    working_dir: String,
+    pattern: String,
+    options: Option<ContentSearchOptions>,
+) -> Result<ContentSearchResult, KataraError> {
+    let mut options = options.unwrap_or_default();
+    // Keep a single request from holding the whole tree in memory
+    options.context = options.context.min(MAX_CONTEXT);
+    options.max_results = options.max_results.min(MAX_RESULTS);
+    content::search(&working_dir, &pattern, options).await
+}
@@ -1,18 +1,56 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::commands::claude::{self, start_session};
 use crate::error::KataraError;
+use crate::process::manager::SpawnOptions;
+use crate::sink::{self, Sink};
+use crate::skills::builtin;
+use crate::skills::bundle::{self, BundleImport, ConflictStrategy};
+use crate::skills::import::{self, RepoImport};
+use crate::skills::inputs::validate_inputs;
 use crate::skills::manager as skill_mgr;
-use crate::skills::parser::ParsedSkill;
+use crate::skills::outputs;
+use crate::skills::parser::{ParsedSkill, SkillOutput};
+use crate::skills::preview::{self, SkillPreview};
+use crate::skills::runs::{self, SkillRun, SkillRunStatus};
+use crate::skills::sandbox;
+use crate::skills::scaffold::{self, SkillTemplate};
+use crate::skills::search;
+use crate::skills::slash::{self, CommandExport};
+use crate::skills::template;
+use crate::skills::validate::{self, Diagnostic};
+use crate::skills::versions::{self, SkillVersion};
+use crate::state::AppState;
+use crate::websocket::protocol::WsEvent;
 
+/// List skills from the user skills directory, plus the project skills of
+/// `working_dir` (which shadow same-named user skills) when given.
 #[tauri::command]
-pub async fn list_skills(skills_dir: Option<String>) -> Result<Vec<ParsedSkill>, KataraError> {
-    let dir = skills_dir.unwrap_or_else(|| {
-        dirs::home_dir()
-            .unwrap_or_default()
-            .join(".claude")
-            .join("skills")
-            .display()
-            .to_string()
-    });
-    skill_mgr::list_skills(&dir)
+pub async fn list_skills(
+    skills_dir: Option<String>,
+    working_dir: Option<String>,
+) -> Result<Vec<ParsedSkill>, KataraError> {
+    let dir = skills_dir.unwrap_or_else(default_skills_dir);
+    skill_mgr::list_layered_skills(&dir, working_dir.as_deref())
+}
+
+/// Search skills by name, description, tags and prompt content, best
+/// matches first. Only skills carrying every tag in `tags` are returned.
+#[tauri::command]
+pub async fn search_skills(
+    query: String,
+    tags: Option<Vec<String>>,
+    skills_dir: Option<String>,
+    working_dir: Option<String>,
+) -> Result<Vec<ParsedSkill>, KataraError> {
+    let skills = list_skills(skills_dir, working_dir).await?;
+    Ok(search::search_skills(
+        skills,
+        &query,
+        &tags.unwrap_or_default(),
+    ))
 }
 
 #[tauri::command]
@@ -20,12 +58,327 @@ pub async fn read_skill(path: String) -> Result<ParsedSkill, KataraError> {
     skill_mgr::read_skill(&path)
 }
 
+/// Save a skill. With `expected_hash` (the `content_hash` the editor loaded),
+/// the write fails with a conflict if the file changed on disk since.
+#[tauri::command]
+pub async fn write_skill(
+    path: String,
+    content: String,
+    expected_hash: Option<String>,
+) -> Result<(), KataraError> {
+    skill_mgr::write_skill_checked(&path, &content, expected_hash.as_deref())
+}
+
+#[tauri::command]
+pub async fn delete_skill(path: String, skills_dir: Option<String>) -> Result<(), KataraError> {
+    let dir = skills_dir.unwrap_or_else(default_skills_dir);
+    skill_mgr::delete_skill(&path, &dir)
+}
+
+/// Copy the starter skills shipped with the app into the skills directory.
+/// Existing files are never overwritten; `reinstall` brings back starter
+/// skills the user deleted. Returns the paths written.
+#[tauri::command]
+pub async fn install_builtin_skills(
+    skills_dir: Option<String>,
+    reinstall: Option<bool>,
+) -> Result<Vec<String>, KataraError> {
+    let dir = skills_dir.unwrap_or_else(default_skills_dir);
+    builtin::install(&dir, reinstall.unwrap_or(false))
+}
+
+/// Built-in templates for `create_skill_from_template`.
+#[tauri::command]
+pub async fn list_skill_templates() -> Result<Vec<SkillTemplate>, KataraError> {
+    Ok(scaffold::TEMPLATES.to_vec())
+}
+
+/// Create a new skill from a built-in template (`simple`, `form` or
+/// `structured`) in `dir`, defaulting to the skills directory.
+#[tauri::command]
+pub async fn create_skill_from_template(
+    template_name: String,
+    name: String,
+    dir: Option<String>,
+) -> Result<ParsedSkill, KataraError> {
+    let dir = dir.unwrap_or_else(default_skills_dir);
+    scaffold::create_from_template(&template_name, &name, &dir)
+}
+
+/// Download a skill file (e.g. a GitHub link) into the skills directory.
+#[tauri::command]
+pub async fn import_skill_from_url(
+    url: String,
+    skills_dir: Option<String>,
+) -> Result<ParsedSkill, KataraError> {
+    let dir = skills_dir.unwrap_or_else(default_skills_dir);
+    import::import_from_url(&url, &dir).await
+}
+
+/// Import the skills of a GitHub repository, optionally only under `subdir`.
+#[tauri::command]
+pub async fn import_skills_from_repo(
+    repo: String,
+    subdir: Option<String>,
+    skills_dir: Option<String>,
+) -> Result<RepoImport, KataraError> {
+    let dir = skills_dir.unwrap_or_else(default_skills_dir);
+    import::import_from_repo(&repo, subdir.as_deref(), &dir).await
+}
+
+/// Save a skill as a Claude Code slash command in the project's (or the
+/// user's) `.claude/commands` directory.
+#[tauri::command]
+pub async fn export_skill_as_command(
+    path: String,
+    project_dir: Option<String>,
+) -> Result<CommandExport, KataraError> {
+    let skill = skill_mgr::read_skill(&path)?;
+    slash::export_as_command(&skill, project_dir.as_deref())
+}
+
+/// Turn a Claude Code slash command file into a skill.
+#[tauri::command]
+pub async fn import_command_as_skill(
+    path: String,
+    skills_dir: Option<String>,
+) -> Result<ParsedSkill, KataraError> {
+    let dir = skills_dir.unwrap_or_else(default_skills_dir);
+    slash::import_command(&path, &dir)
+}
+
+/// Pack skill files into a zip bundle. Returns the number of skills written.
+#[tauri::command]
+pub async fn export_skills(paths: Vec<String>, zip_path: String) -> Result<usize, KataraError> {
+    bundle::export_skills(&paths, &zip_path)
+}
+
+/// Install the skills from a zip bundle. Without `on_conflict`, nothing is
+/// written when a bundled skill clashes with an installed one; the
+/// conflicts are returned instead.
+#[tauri::command]
+pub async fn import_skill_bundle(
+    zip_path: String,
+    on_conflict: Option<ConflictStrategy>,
+    skills_dir: Option<String>,
+) -> Result<BundleImport, KataraError> {
+    let dir = skills_dir.unwrap_or_else(default_skills_dir);
+    bundle::import_bundle(&zip_path, &dir, on_conflict)
+}
+
+/// Saved versions of a skill, newest first.
 #[tauri::command]
-pub async fn write_skill(path: String, content: String) -> Result<(), KataraError> {
-    skill_mgr::write_skill(&path, &content)
+pub async fn list_skill_versions(path: String) -> Result<Vec<SkillVersion>, KataraError> {
+    versions::list(&path)
 }
 
+/// Content of one saved version, for previewing before a restore.
 #[tauri::command]
-pub async fn delete_skill(path: String) -> Result<(), KataraError> {
-    skill_mgr::delete_skill(&path)
+pub async fn read_skill_version(path: String, version_id: String) -> Result<String, KataraError> {
+    versions::read(&path, &version_id)
+}
+
+/// Roll a skill back to a saved version.
+#[tauri::command]
+pub async fn restore_skill_version(
+    path: String,
+    version_id: String,
+) -> Result<ParsedSkill, KataraError> {
+    skill_mgr::restore_skill_version(&path, &version_id)
+}
+
+/// Copy a skill under a new name. Returns the new skill.
+#[tauri::command]
+pub async fn duplicate_skill(
+    path: String,
+    new_name: String,
+    skills_dir: Option<String>,
+) -> Result<ParsedSkill, KataraError> {
+    let dir = skills_dir.unwrap_or_else(default_skills_dir);
+    skill_mgr::duplicate_skill(&path, &new_name, &dir)
+}
+
+/// Rename a skill and its file. Returns the skill at its new path.
+#[tauri::command]
+pub async fn rename_skill(
+    path: String,
+    new_name: String,
+    skills_dir: Option<String>,
+) -> Result<ParsedSkill, KataraError> {
+    let dir = skills_dir.unwrap_or_else(default_skills_dir);
+    skill_mgr::rename_skill(&path, &new_name, &dir)
+}
+
+/// Check skill file content and return line-level diagnostics for the editor.
+#[tauri::command]
+pub async fn validate_skill(content: String) -> Result<Vec<Diagnostic>, KataraError> {
+    Ok(validate::validate_skill(&content))
+}
+
+/// Render a skill's prompt template with the given input values.
+#[tauri::command]
+pub async fn render_skill(
+    path: String,
+    inputs: HashMap<String, serde_json::Value>,
+) -> Result<String, KataraError> {
+    let skill = skill_mgr::read_skill(&path)?;
+    template::render_prompt(&skill, &inputs)
+}
+
+/// Dry-run a skill: the rendered prompt and the model, permission mode and
+/// tools `run_skill` would use, without contacting Claude.
+#[tauri::command]
+pub async fn preview_skill(
+    path: String,
+    inputs: Option<HashMap<String, serde_json::Value>>,
+    model: Option<String>,
+    permission_mode: Option<String>,
+) -> Result<SkillPreview, KataraError> {
+    let skill = skill_mgr::read_skill(&path)?;
+    let inputs = validate_inputs(&skill.metadata, inputs.unwrap_or_default())
+        .map_err(KataraError::InvalidInputs)?;
+    preview::preview(&skill, &inputs, model, permission_mode)
+}
+
+/// Run a skill: render its prompt, start a Claude session with it, and wait
+/// for the response.
+///
+/// The skill's own `model`, `permission_mode` and `allowed_tools` take
+/// precedence over the values passed here. The returned run carries the
+/// final response and the skill's declared outputs extracted from it; the
+/// session stays open for follow-up messages. Inputs are checked against the
+/// skill's declarations first, failing with field-level errors. Skills marked
+/// `sandbox: true` run in plan mode on a temporary copy of `working_dir`,
+/// which is deleted, along with the session, once the run finishes.
+#[tauri::command]
+pub async fn run_skill(
+    state: tauri::State<'_, Arc<AppState>>,
+    app_handle: tauri::AppHandle,
+    path: String,
+    inputs: Option<HashMap<String, serde_json::Value>>,
+    working_dir: String,
+    model: Option<String>,
+    permission_mode: Option<String>,
+) -> Result<SkillRun, KataraError> {
+    let started = start_skill_run(
+        state.inner(),
+        sink::tauri(app_handle),
+        path,
+        inputs.unwrap_or_default(),
+        working_dir,
+        model,
+        permission_mode,
+    )
+    .await?;
+    Ok(finish_skill_run(&state, started).await)
+}
+
+/// A skill run whose session has been started but not yet finished.
+pub(crate) struct StartedRun {
+    pub run: SkillRun,
+    events: broadcast::Receiver<WsEvent>,
+    outputs: Vec<SkillOutput>,
+}
+
+/// Render a skill and start a Claude session with it, recording the run.
+pub(crate) async fn start_skill_run(
+    state: &Arc<AppState>,
+    sink: Sink,
+    path: String,
+    inputs: HashMap<String, serde_json::Value>,
+    working_dir: String,
+    model: Option<String>,
+    permission_mode: Option<String>,
+) -> Result<StartedRun, KataraError> {
+    let skill = skill_mgr::read_skill(&path)?;
+    if !skill.metadata.enabled {
+        return Err(KataraError::Skill(format!(
+            "Skill {} is disabled",
+            skill.metadata.name
+        )));
+    }
+    let inputs = validate_inputs(&skill.metadata, inputs).map_err(KataraError::InvalidInputs)?;
+    let preview = preview::preview(&skill, &inputs, model, permission_mode)?;
+
+    // Sandboxed skills get their own copy of the project to work on
+    let sandbox_dir = if preview.sandbox {
+        let source = working_dir.clone();
+        let copy = tokio::task::spawn_blocking(move || sandbox::working_copy(&source))
+            .await
+            .map_err(|e| KataraError::Skill(format!("Failed to copy the project: {}", e)))??;
+        Some(copy.display().to_string())
+    } else {
+        None
+    };
+    let session_dir = sandbox_dir.clone().unwrap_or_else(|| working_dir.clone());
+
+    let meta = skill.metadata;
+    let opts = SpawnOptions {
+        initial_prompt: Some(preview.prompt),
+        model: preview.model,
+        permission_mode: preview.permission_mode,
+        allowed_tools: preview.allowed_tools,
+        ..Default::default()
+    };
+
+    // Subscribe before spawning so a fast result can't slip past
+    let events = state.event_tx.subscribe();
+    let session_id = match start_session(state, sink, session_dir, opts).await {
+        Ok(session_id) => session_id,
+        Err(e) => {
+            if let Some(copy) = sandbox_dir {
+                let _ = tokio::task::spawn_blocking(move || sandbox::remove_copy(&copy)).await;
+            }
+            return Err(e);
+        }
+    };
+
+    let mut run = SkillRun::new(path, meta.name, session_id, working_dir, inputs);
+    run.sandbox_dir = sandbox_dir;
+    runs::record(state, &run).await;
+    Ok(StartedRun {
+        run,
+        events,
+        outputs: meta.outputs,
+    })
+}
+
+/// Wait for a started run's response and record the outcome.
+pub(crate) async fn finish_skill_run(state: &AppState, started: StartedRun) -> SkillRun {
+    let mut run = started.run;
+    match runs::wait_for_result(state, started.events, &run.session_id).await {
+        Ok(turn) => {
+            run.outputs = outputs::extract_outputs(&started.outputs, &turn.text);
+            run.result = Some(turn.text);
+            run.cost_usd = turn.cost_usd;
+            run.duration_ms = turn.duration_ms;
+            run.finish(SkillRunStatus::Succeeded);
+        }
+        Err(e) => run.finish(SkillRunStatus::Failed(e)),
+    }
+    runs::record(state, &run).await;
+    // A sandboxed session can't go on without its copy
+    if let Some(copy) = run.sandbox_dir.clone() {
+        claude::end_session(state, &run.session_id).await;
+        let _ = tokio::task::spawn_blocking(move || sandbox::remove_copy(&copy)).await;
+    }
+    run
+}
+
+/// Past runs of a skill (or of all skills), newest first.
+#[tauri::command]
+pub async fn list_skill_runs(
+    state: tauri::State<'_, Arc<AppState>>,
+    skill_path: Option<String>,
+) -> Result<Vec<SkillRun>, KataraError> {
+    Ok(runs::list(&state, skill_path.as_deref()).await)
+}
+
+fn default_skills_dir() -> String {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".claude")
+        .join("skills")
+        .display()
+        .to_string()
 }
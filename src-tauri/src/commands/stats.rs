@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use crate::error::KataraError;
+use crate::state::AppState;
+use crate::stats::dashboard::{self, DashboardRange, DashboardStats};
+
+/// Activity dashboard data for `range` (the last 30 days by default),
+/// aggregated from the local activity log and skill run history.
+#[tauri::command]
+pub async fn get_dashboard_stats(
+    state: tauri::State<'_, Arc<AppState>>,
+    range: Option<DashboardRange>,
+) -> Result<DashboardStats, KataraError> {
+    let skill_runs = state.skill_runs.read().await.clone();
+    let range = range.unwrap_or_default();
+    tokio::task::spawn_blocking(move || dashboard::compute(range, &skill_runs))
+        .await
+        .map_err(|e| KataraError::Config(format!("Failed to compute stats: {}", e)))
+}
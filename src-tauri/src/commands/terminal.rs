@@ -1,24 +1,139 @@
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use crate::config::env_profiles;
+use crate::config::manager as config_mgr;
 use crate::error::KataraError;
 use crate::state::AppState;
-use crate::terminal::pty::PtyHandle;
+use crate::terminal::exec::{self, ExecResult};
+use crate::terminal::layout::{self, TerminalLayout};
+use crate::terminal::persist;
+use crate::terminal::procinfo;
+use crate::terminal::pty::{
+    self, CloseResult, PtyHandle, TerminalAttach, TerminalInfo, TerminalOptions,
+};
+use crate::terminal::scrollback::SearchMatch;
+use crate::terminal::shell::ShellConfig;
 
+/// Spawn a new terminal.
+///
+/// `profile` names a saved terminal profile supplying shell, env and cwd.
+/// When `session_id` links the terminal to a Claude session, the terminal
+/// inherits that session's environment (and working directory if neither
+/// `cwd` nor the profile gives one). Explicit arguments override the
+/// profile, which overrides the session. `env_profile` names a saved env
+/// profile; env profiles (the session's, then this one) go under all the
+/// other variables, and only their names are saved. SSH profiles
+/// always connect to their host; the local `cwd` and session directory
+/// don't apply.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn spawn_terminal(
     state: tauri::State<'_, Arc<AppState>>,
     app_handle: tauri::AppHandle,
     rows: u16,
     cols: u16,
     cwd: Option<String>,
+    shell: Option<ShellConfig>,
+    env: Option<HashMap<String, String>>,
+    session_id: Option<String>,
+    profile: Option<String>,
+    env_profile: Option<String>,
 ) -> Result<String, KataraError> {
     let id = uuid::Uuid::new_v4().to_string();
-    let handle =
-        PtyHandle::spawn(id.clone(), rows, cols, cwd, app_handle).map_err(KataraError::Terminal)?;
-    state.terminals.write().await.insert(id.clone(), handle);
+
+    let profile = match profile {
+        Some(ref name) => Some(config_mgr::get_terminal_profile(name)?),
+        None => None,
+    };
+
+    let ssh = profile.as_ref().and_then(|p| p.ssh.clone());
+    let remote = ssh.is_some();
+
+    // Explicit shell wins, then the profile's, then the configured default
+    let shell = match ssh {
+        Some(ssh) => Some(ssh.to_shell_config()),
+        None => match shell.or_else(|| profile.as_ref().and_then(|p| p.shell.clone())) {
+            Some(s) => Some(s),
+            None => state.settings().terminal.default_shell,
+        },
+    };
+
+    let mut cwd = if remote {
+        None
+    } else {
+        cwd.or_else(|| profile.as_ref().and_then(|p| p.cwd.clone()))
+    };
+    let mut env_profiles = Vec::new();
+    let mut session_env = HashMap::new();
+    let mut merged_env = HashMap::new();
+    if let Some(ref sid) = session_id {
+        let sessions = state.sessions.read().await;
+        let session = sessions
+            .get(sid)
+            .ok_or(KataraError::SessionNotFound(sid.clone()))?;
+        env_profiles.extend(session.env_profile.clone());
+        session_env = session.env_overrides.clone();
+        if cwd.is_none() && !remote {
+            cwd = Some(session.working_dir.clone());
+        }
+    }
+    if let Some(p) = profile {
+        merged_env.extend(p.env);
+    }
+    if let Some(name) = env_profile {
+        // Fail here with a typed error rather than when the shell spawns
+        env_profiles::resolve_env_profile(&state.settings().env_profiles, &name)?;
+        env_profiles.push(name);
+    }
+    merged_env.extend(env.unwrap_or_default());
+
+    let opts = TerminalOptions {
+        rows,
+        cols,
+        cwd,
+        shell,
+        env_profiles,
+        session_env,
+        env: merged_env,
+        title: None,
+        banner: None,
+        remote,
+    };
+
+    let arc_state: Arc<AppState> = state.inner().clone();
+    let handle = PtyHandle::spawn(id.clone(), opts, arc_state.clone(), app_handle.clone())
+        .map_err(KataraError::Terminal)?;
+    let mut terminals = state.terminals.write().await;
+    terminals.insert(id.clone(), handle);
+    terminals[&id].watch_exit(arc_state, app_handle);
+    pty::apply_scrollback_limits(&terminals, &state.settings().terminal);
+    persist::save(&terminals);
     Ok(id)
 }
 
+/// Attach a frontend to a running terminal.
+///
+/// Terminals keep running and buffering output with no frontend listening,
+/// so after a webview reload the UI calls this to redraw. `since` is the
+/// last `terminal:data` offset the caller has seen; omit it to replay all
+/// buffered output. Drop later events whose offset is at or below
+/// `replay.end`.
+#[tauri::command]
+pub async fn attach_terminal(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+    since: Option<u64>,
+) -> Result<TerminalAttach, KataraError> {
+    let terminals = state.terminals.read().await;
+    let handle = terminals
+        .get(&id)
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+    handle.attach(since).map_err(KataraError::Terminal)
+}
+
 #[tauri::command]
 pub async fn write_terminal(
     state: tauri::State<'_, Arc<AppState>>,
@@ -28,13 +143,28 @@ pub async fn write_terminal(
     let terminals = state.terminals.read().await;
     let handle = terminals
         .get(&id)
-        .ok_or(KataraError::Terminal(format!("Terminal {} not found", id)))?;
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
     handle
         .write(data.as_bytes())
         .map_err(KataraError::Terminal)?;
     Ok(())
 }
 
+/// Paste text into a terminal, using bracketed paste when the app supports it.
+#[tauri::command]
+pub async fn paste_terminal(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+    text: String,
+) -> Result<(), KataraError> {
+    let terminals = state.terminals.read().await;
+    let handle = terminals
+        .get(&id)
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+    handle.paste(&text).map_err(KataraError::Terminal)?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn resize_terminal(
     state: tauri::State<'_, Arc<AppState>>,
@@ -45,17 +175,332 @@ pub async fn resize_terminal(
     let terminals = state.terminals.read().await;
     let handle = terminals
         .get(&id)
-        .ok_or(KataraError::Terminal(format!("Terminal {} not found", id)))?;
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
     handle.resize(rows, cols).map_err(KataraError::Terminal)?;
     Ok(())
 }
 
+/// How long a foreground job gets to exit after SIGTERM before it's killed.
+const CLOSE_GRACE: Duration = Duration::from_secs(3);
+
+/// Close a terminal.
+///
+/// If a job (e.g. a long build) is still running in the foreground, nothing is
+/// killed unless `force` is set; the result asks the caller to confirm. With
+/// `force`, the job gets SIGTERM and a grace period before being killed.
 #[tauri::command]
 pub async fn kill_terminal(
     state: tauri::State<'_, Arc<AppState>>,
     id: String,
+    force: Option<bool>,
+) -> Result<CloseResult, KataraError> {
+    let running = match state.terminals.read().await.get(&id) {
+        Some(handle) => handle.foreground_process(),
+        // Already gone (e.g. the shell exited on its own)
+        None => return Ok(CloseResult::Closed),
+    };
+
+    if let Some(process) = running {
+        if !force.unwrap_or(false) {
+            return Ok(CloseResult::ConfirmRequired { process });
+        }
+
+        procinfo::terminate_group(process.pid);
+        let deadline = Instant::now() + CLOSE_GRACE;
+        loop {
+            let still_running = state
+                .terminals
+                .read()
+                .await
+                .get(&id)
+                .and_then(PtyHandle::foreground_process)
+                .is_some_and(|p| p.pid == process.pid);
+            if !still_running {
+                break;
+            }
+            if Instant::now() >= deadline {
+                procinfo::kill_group(process.pid);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    // Dropping PtyHandle kills the shell and closes the PTY; the exit
+    // watcher then emits terminal:exit.
+    let mut terminals = state.terminals.write().await;
+    if terminals.remove(&id).is_some() {
+        pty::apply_scrollback_limits(&terminals, &state.settings().terminal);
+        persist::save(&terminals);
+    }
+    Ok(CloseResult::Closed)
+}
+
+/// Stop reading output from a terminal until `resume_terminal` is called.
+///
+/// The program writing to the terminal blocks once the kernel buffer fills,
+/// so nothing is lost. Useful while the window is hidden.
+#[tauri::command]
+pub async fn pause_terminal(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+) -> Result<(), KataraError> {
+    let terminals = state.terminals.read().await;
+    let handle = terminals
+        .get(&id)
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+    handle.set_paused(true);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resume_terminal(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+) -> Result<(), KataraError> {
+    let terminals = state.terminals.read().await;
+    let handle = terminals
+        .get(&id)
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+    handle.set_paused(false);
+    Ok(())
+}
+
+/// Acknowledge `terminal:data` the frontend has finished rendering.
+///
+/// `chars` is the total `data.length` processed since the last ack. Once a
+/// frontend starts acking, reading pauses automatically whenever it falls
+/// too far behind.
+#[tauri::command]
+pub async fn ack_terminal(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+    chars: usize,
+) -> Result<(), KataraError> {
+    let terminals = state.terminals.read().await;
+    let handle = terminals
+        .get(&id)
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+    handle.ack(chars);
+    Ok(())
+}
+
+/// Discard a terminal's server-side scrollback to free memory.
+#[tauri::command]
+pub async fn clear_terminal_buffer(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
 ) -> Result<(), KataraError> {
-    // Dropping PtyHandle closes the PTY
-    state.terminals.write().await.remove(&id);
+    let terminals = state.terminals.read().await;
+    let handle = terminals
+        .get(&id)
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+    handle.clear_buffer().map_err(KataraError::Terminal)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_terminals(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<TerminalInfo>, KataraError> {
+    let terminals = state.terminals.read().await;
+    Ok(terminals.values().map(PtyHandle::info).collect())
+}
+
+/// Respawn the terminals that were open when the app last closed.
+///
+/// Each shell starts in its last known cwd (if it still exists) with its
+/// original shell and environment, and the scrollback opens with a note that
+/// the session was restored. Terminals that are already open are skipped.
+#[tauri::command]
+pub async fn restore_terminals(
+    state: tauri::State<'_, Arc<AppState>>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<TerminalInfo>, KataraError> {
+    let saved = persist::load().map_err(KataraError::Terminal)?;
+    let arc_state: Arc<AppState> = state.inner().clone();
+
+    let mut terminals = state.terminals.write().await;
+    let mut restored = Vec::new();
+    for t in saved {
+        if terminals.contains_key(&t.id) {
+            continue;
+        }
+        // A remote terminal's cwd is a path on the remote host
+        let cwd = t.cwd.filter(|dir| !t.remote && Path::new(dir).is_dir());
+        let mut banner = match cwd {
+            Some(ref dir) => format!("\x1b[2m[Session restored in {}]\x1b[0m\r\n", dir),
+            None => "\x1b[2m[Session restored]\x1b[0m\r\n".to_string(),
+        };
+        if !t.dropped_env.is_empty() {
+            banner.push_str(&format!(
+                "\x1b[2m[Not restored: {}]\x1b[0m\r\n",
+                t.dropped_env.join(", ")
+            ));
+        }
+        let opts = TerminalOptions {
+            rows: t.rows,
+            cols: t.cols,
+            cwd,
+            shell: t.shell,
+            env_profiles: t.env_profiles,
+            session_env: HashMap::new(),
+            env: t.env,
+            title: t.title,
+            banner: Some(banner),
+            remote: t.remote,
+        };
+        match PtyHandle::spawn(t.id.clone(), opts, arc_state.clone(), app_handle.clone()) {
+            Ok(handle) => {
+                restored.push(handle.info());
+                terminals.insert(t.id.clone(), handle);
+                terminals[&t.id].watch_exit(arc_state.clone(), app_handle.clone());
+            }
+            Err(e) => eprintln!("[katara] Failed to restore terminal {}: {}", t.id, e),
+        }
+    }
+    pty::apply_scrollback_limits(&terminals, &state.settings().terminal);
+    persist::save(&terminals);
+    Ok(restored)
+}
+
+/// Save the terminal pane layout (tabs, splits, sizes) for a workspace.
+#[tauri::command]
+pub async fn save_terminal_layout(
+    workspace: String,
+    layout: TerminalLayout,
+) -> Result<(), KataraError> {
+    layout::save(&workspace, layout).map_err(KataraError::Terminal)
+}
+
+/// Get the saved terminal pane layout for a workspace, if there is one.
+#[tauri::command]
+pub async fn get_terminal_layout(workspace: String) -> Result<Option<TerminalLayout>, KataraError> {
+    layout::get(&workspace).map_err(KataraError::Terminal)
+}
+
+/// Run a command and capture its output and exit code.
+///
+/// With `terminal_id`, the command runs visibly in that terminal (POSIX shells
+/// only), in whatever directory the terminal is in, so `cwd` is rejected.
+/// Without it, the command runs in a throwaway PTY in `cwd`.
+#[tauri::command]
+pub async fn run_in_terminal(
+    state: tauri::State<'_, Arc<AppState>>,
+    terminal_id: Option<String>,
+    command: String,
+    cwd: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<ExecResult, KataraError> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(60_000));
+
+    if let Some(id) = terminal_id {
+        if cwd.is_some() {
+            return Err(KataraError::Terminal(
+                "cwd can't be used with terminal_id; the command runs in the terminal's directory"
+                    .into(),
+            ));
+        }
+        let (rx, token) = {
+            let terminals = state.terminals.read().await;
+            let handle = terminals
+                .get(&id)
+                .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+            exec::start_marked_command(handle, &command).map_err(KataraError::Terminal)?
+        };
+        return Ok(exec::collect_marked_output(rx, &token, timeout).await);
+    }
+
+    let settings = state.settings();
+    exec::run_captured(
+        &command,
+        cwd.as_deref(),
+        &settings.proxy.env(),
+        settings.terminal.default_shell.as_ref(),
+        timeout,
+    )
+    .await
+    .map_err(KataraError::Terminal)
+}
+
+/// Start recording a terminal's input and output (asciicast v2).
+#[tauri::command]
+pub async fn start_recording(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+) -> Result<(), KataraError> {
+    let terminals = state.terminals.read().await;
+    let handle = terminals
+        .get(&id)
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+    handle.start_recording().map_err(KataraError::Terminal)
+}
+
+#[tauri::command]
+pub async fn stop_recording(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+) -> Result<(), KataraError> {
+    let terminals = state.terminals.read().await;
+    let handle = terminals
+        .get(&id)
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+    handle.stop_recording().map_err(KataraError::Terminal)
+}
+
+/// Write a terminal's recording to `path` as an asciicast v2 (.cast) file.
+#[tauri::command]
+pub async fn export_recording(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+    path: String,
+    title: Option<String>,
+) -> Result<(), KataraError> {
+    let cast = {
+        let terminals = state.terminals.read().await;
+        let handle = terminals
+            .get(&id)
+            .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+        handle
+            .export_recording(title.as_deref())
+            .map_err(KataraError::Terminal)?
+    };
+
+    if let Some(parent) = std::path::Path::new(&path).parent() {
+        std::fs::create_dir_all(parent).map_err(KataraError::Io)?;
+    }
+    std::fs::write(&path, cast).map_err(KataraError::Io)?;
     Ok(())
 }
+
+/// Search a terminal's scrollback without shipping the buffer over IPC.
+///
+/// `query` is literal text unless `regex` is set. Matching is
+/// case-insensitive unless `case_sensitive` is set.
+#[tauri::command]
+pub async fn search_terminal(
+    state: tauri::State<'_, Arc<AppState>>,
+    id: String,
+    query: String,
+    regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchMatch>, KataraError> {
+    let pattern = if regex.unwrap_or(false) {
+        query
+    } else {
+        regex::escape(&query)
+    };
+    let pattern = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!case_sensitive.unwrap_or(false))
+        .build()
+        .map_err(|e| KataraError::Terminal(format!("Invalid search pattern: {}", e)))?;
+
+    let terminals = state.terminals.read().await;
+    let handle = terminals
+        .get(&id)
+        .ok_or(KataraError::TerminalNotFound(id.clone()))?;
+    handle
+        .search(&pattern, limit.unwrap_or(1000))
+        .map_err(KataraError::Terminal)
+}
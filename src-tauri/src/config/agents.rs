@@ -0,0 +1,231 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::path::{Path, PathBuf};
+
+use crate::error::KataraError;
+
+/// Model aliases a subagent may use besides full model ids.
+pub const AGENT_MODELS: &[&str] = &["sonnet", "opus", "haiku", "inherit"];
+
+/// Colors the CLI shows subagents in.
+pub const AGENT_COLORS: &[&str] = &[
+    "red", "blue", "green", "yellow", "purple", "orange", "pink", "cyan",
+];
+
+/// A subagent definition from `.claude/agents/<name>.md`: frontmatter plus
+/// the system prompt as the body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentDefinition {
+    pub name: String,
+    pub description: String,
+    /// Tools the subagent may use; `None` inherits all of the session's tools.
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub prompt: String,
+    /// `user` or `project`; filled in when read from disk.
+    #[serde(default)]
+    pub level: String,
+    #[serde(default)]
+    pub path: String,
+}
+
+#[derive(Deserialize)]
+struct Frontmatter {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// Comma-separated in the CLI's docs, but a YAML list works too.
+    #[serde(default)]
+    tools: Option<Value>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+/// User subagents plus, with a project, the project's. Project agents come
+/// first: they take precedence over user agents with the same name.
+pub fn list_agents(project_dir: Option<&str>) -> Result<Vec<AgentDefinition>, KataraError> {
+    let mut agents = Vec::new();
+    if let Some(dir) = project_dir {
+        agents.extend(agents_in(&agents_dir("project", Some(dir))?)?);
+    }
+    agents.extend(agents_in(&agents_dir("user", None)?)?);
+    Ok(agents)
+}
+
+/// Read and parse one subagent file.
+pub fn read_agent(path: &str) -> Result<AgentDefinition, KataraError> {
+    let content = std::fs::read_to_string(path).map_err(KataraError::Io)?;
+    parse_agent(&content, path)
+}
+
+/// Validate and save a subagent as `<name>.md` in the agents directory of
+/// `level`. Saving under a new name leaves the old file in place; delete it
+/// to rename.
+pub fn write_agent(
+    level: &str,
+    project_dir: Option<&str>,
+    agent: &AgentDefinition,
+) -> Result<AgentDefinition, KataraError> {
+    validate_agent(agent)?;
+
+    let mut frontmatter = Mapping::new();
+    frontmatter.insert("name".into(), agent.name.clone().into());
+    frontmatter.insert("description".into(), agent.description.clone().into());
+    if let Some(tools) = &agent.tools {
+        frontmatter.insert("tools".into(), tools.join(", ").into());
+    }
+    if let Some(model) = &agent.model {
+        frontmatter.insert("model".into(), model.clone().into());
+    }
+    if let Some(color) = &agent.color {
+        frontmatter.insert("color".into(), color.clone().into());
+    }
+    let yaml =
+        serde_yaml::to_string(&frontmatter).map_err(|e| KataraError::Config(e.to_string()))?;
+    let content = format!("---\n{}---\n\n{}\n", yaml, agent.prompt.trim());
+
+    let dir = agents_dir(level, project_dir)?;
+    std::fs::create_dir_all(&dir).map_err(KataraError::Io)?;
+    let path = dir.join(format!("{}.md", agent.name));
+    std::fs::write(&path, content).map_err(KataraError::Io)?;
+    read_agent(&path.display().to_string())
+}
+
+/// Delete a subagent file.
+pub fn delete_agent(path: &str) -> Result<(), KataraError> {
+    if Path::new(path).extension().is_none_or(|e| e != "md") {
+        return Err(KataraError::Config(format!("Not an agent file: {}", path)));
+    }
+    std::fs::remove_file(path).map_err(KataraError::Io)
+}
+
+/// Check the frontmatter fields the CLI relies on.
+pub fn validate_agent(agent: &AgentDefinition) -> Result<(), KataraError> {
+    let mut problems = Vec::new();
+    let name = Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").expect("name pattern is valid");
+    if agent.name.is_empty() {
+        problems.push("name is required".to_string());
+    } else if !name.is_match(&agent.name) {
+        problems.push("name must be lowercase letters, digits and hyphens".to_string());
+    }
+    if agent.description.trim().is_empty() {
+        problems.push("description is required".to_string());
+    }
+    let tool = Regex::new(r"^[A-Za-z_][\w-]*$").expect("tool pattern is valid");
+    for t in agent.tools.iter().flatten() {
+        if !tool.is_match(t) {
+            problems.push(format!("\"{}\" is not a tool name", t));
+        }
+    }
+    if let Some(model) = &agent.model {
+        if !AGENT_MODELS.contains(&model.as_str()) && !model.starts_with("claude-") {
+            problems.push(format!(
+                "model must be one of {} or a model id",
+                AGENT_MODELS.join(", ")
+            ));
+        }
+    }
+    if let Some(color) = &agent.color {
+        if !AGENT_COLORS.contains(&color.as_str()) {
+            problems.push(format!("color must be one of {}", AGENT_COLORS.join(", ")));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(KataraError::Config(format!(
+            "Invalid agent: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+/// Directory holding subagents at `user` or `project` level.
+pub fn agents_dir(level: &str, project_dir: Option<&str>) -> Result<PathBuf, KataraError> {
+    match level {
+        "user" => Ok(dirs::home_dir()
+            .unwrap_or_default()
+            .join(".claude")
+            .join("agents")),
+        "project" => {
+            let dir = project_dir.ok_or(KataraError::Config("No project directory".into()))?;
+            Ok(Path::new(dir).join(".claude").join("agents"))
+        }
+        _ => Err(KataraError::Config(format!("Unknown level: {}", level))),
+    }
+}
+
+fn agents_in(dir: &Path) -> Result<Vec<AgentDefinition>, KataraError> {
+    let pattern = format!("{}/*.md", glob::Pattern::escape(&dir.display().to_string()));
+    let mut agents = Vec::new();
+    for path in glob::glob(&pattern)
+        .map_err(|e| KataraError::Config(e.to_string()))?
+        .filter_map(Result::ok)
+    {
+        let content = std::fs::read_to_string(&path).map_err(KataraError::Io)?;
+        // Skip markdown files that aren't agent definitions
+        if let Ok(agent) = parse_agent(&content, &path.display().to_string()) {
+            agents.push(agent);
+        }
+    }
+    agents.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(agents)
+}
+
+fn parse_agent(content: &str, path: &str) -> Result<AgentDefinition, KataraError> {
+    let after_first = content
+        .trim_start()
+        .strip_prefix("---")
+        .ok_or_else(|| KataraError::Config("Agent file has no frontmatter".into()))?;
+    let end_idx = after_first
+        .find("\n---")
+        .ok_or_else(|| KataraError::Config("Unclosed frontmatter".into()))?;
+    let frontmatter: Frontmatter = serde_yaml::from_str(&after_first[..end_idx])
+        .map_err(|e| KataraError::Config(format!("Invalid agent frontmatter: {}", e)))?;
+    if frontmatter.name.is_empty() {
+        return Err(KataraError::Config("Agent has no name".into()));
+    }
+
+    let tools = match frontmatter.tools {
+        Some(Value::String(s)) => Some(
+            s.split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(String::from)
+                .collect(),
+        ),
+        Some(Value::Sequence(seq)) => Some(
+            seq.iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect(),
+        ),
+        _ => None,
+    };
+    let level = if Path::new(path).starts_with(agents_dir("user", None)?) {
+        "user"
+    } else {
+        "project"
+    };
+
+    Ok(AgentDefinition {
+        name: frontmatter.name,
+        description: frontmatter.description,
+        tools,
+        model: frontmatter.model,
+        color: frontmatter.color,
+        prompt: after_first[end_idx + 4..].trim().to_string(),
+        level: level.to_string(),
+        path: path.to_string(),
+    })
+}
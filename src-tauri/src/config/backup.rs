@@ -0,0 +1,271 @@
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config;
+use crate::config::manager::{self, AppSettings};
+use crate::error::KataraError;
+
+/// Automatic backups kept; older ones are deleted. Manual backups are kept.
+const MAX_AUTO_BACKUPS: usize = 20;
+
+/// Destructive writes within this long of the last automatic backup don't
+/// take another one: the earlier backup already has the state before the
+/// burst of edits.
+const AUTO_BACKUP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Largest file accepted from an archive.
+const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Top-level entries of the config directory never archived or restored:
+/// the remote access key and paired devices (restoring them would hand the
+/// archive's author access), and terminals, transcripts and the crash
+/// snapshot of sessions, which can carry secrets typed or printed in a
+/// session.
+const EXCLUDED: &[&str] = &["remote", "sessions.json", "terminals.json", "transcripts"];
+
+/// A snapshot of the Katara config directory.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    /// Unix timestamp (milliseconds).
+    pub created_at: u64,
+    pub size: u64,
+    /// `manual`, or what triggered an automatic backup.
+    pub reason: String,
+}
+
+/// Snapshot the Katara config directory (settings, layouts, skill history,
+/// ...) into a zip in the backups directory.
+pub fn create_backup(reason: &str) -> Result<BackupInfo, KataraError> {
+    let created_at = now_millis();
+    let dir = backups_dir()?;
+    std::fs::create_dir_all(&dir).map_err(KataraError::Io)?;
+    let path = dir.join(format!("katara-{}-{}.zip", created_at, slug(reason)));
+    write_archive(&path)?;
+    backup_info(&path).ok_or_else(|| KataraError::Config("Backup was not written".into()))
+}
+
+/// Write the Katara config directory to a zip at `zip_path`, for moving to
+/// another machine.
+pub fn export_config(zip_path: &str) -> Result<(), KataraError> {
+    write_archive(Path::new(zip_path))
+}
+
+/// Back up the config directory before a destructive write, unless an
+/// automatic backup was taken recently. Failures are logged, never fatal.
+pub fn auto_backup(reason: &str) {
+    let recent = list_backups().ok().and_then(|backups| {
+        backups
+            .into_iter()
+            .find(|b| b.reason != "manual")
+            .map(|b| now_millis().saturating_sub(b.created_at))
+    });
+    if recent.is_some_and(|age| age < AUTO_BACKUP_INTERVAL.as_millis() as u64) {
+        return;
+    }
+    if !config::katara_dir().is_ok_and(|dir| dir.exists()) {
+        return;
+    }
+    match create_backup(reason) {
+        Ok(_) => prune_auto_backups(),
+        Err(e) => eprintln!("[katara] Automatic backup failed: {}", e),
+    }
+}
+
+/// Backups in the backups directory, newest first.
+pub fn list_backups() -> Result<Vec<BackupInfo>, KataraError> {
+    let dir = backups_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<BackupInfo> = std::fs::read_dir(&dir)
+        .map_err(KataraError::Io)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| backup_info(&entry.path()))
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    Ok(backups)
+}
+
+/// Replace the config directory's files with those in an archive (a backup
+/// or an exported config). The current state is backed up first, so a
+/// restore can be undone. Files not in the archive, and `EXCLUDED` ones
+/// that are, are left alone, and so are the settings `keep_trusted` keeps.
+pub fn restore_backup(zip_path: &str) -> Result<Vec<String>, KataraError> {
+    let file = std::fs::File::open(zip_path).map_err(KataraError::Io)?;
+    let mut archive = zip::ZipArchive::new(file).map_err(zip_error)?;
+
+    // Read and check everything before touching the config directory
+    let mut files = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(zip_error)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(relative) = entry.enclosed_name() else {
+            return Err(KataraError::Config(format!(
+                "Archive entry {} points outside the config directory",
+                entry.name()
+            )));
+        };
+        if entry.size() > MAX_ENTRY_BYTES {
+            return Err(KataraError::Config(format!(
+                "Archive entry {} is too large",
+                entry.name()
+            )));
+        }
+        if excluded(&relative) {
+            continue;
+        }
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).map_err(KataraError::Io)?;
+        files.push((relative, content));
+    }
+    if files.is_empty() {
+        return Err(KataraError::Config("Archive is empty".into()));
+    }
+    if let Some((_, settings)) = files
+        .iter_mut()
+        .find(|(p, _)| p == Path::new("settings.json"))
+    {
+        let mut archived = serde_json::from_slice::<AppSettings>(settings)
+            .map_err(|e| KataraError::Config(format!("Archive has invalid settings: {}", e)))?;
+        keep_trusted(&mut archived, manager::read_settings().unwrap_or_default());
+        *settings = serde_json::to_vec_pretty(&archived).map_err(KataraError::Serde)?;
+    }
+
+    let dir = config::katara_dir()?;
+    if dir.exists() {
+        create_backup("pre-restore")?;
+    }
+    let mut restored = Vec::new();
+    for (relative, content) in files {
+        let target = dir.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(KataraError::Io)?;
+        }
+        std::fs::write(&target, content).map_err(KataraError::Io)?;
+        restored.push(relative.to_string_lossy().replace('\\', "/"));
+    }
+    Ok(restored)
+}
+
+/// Put back the current values of settings that run executables or widen
+/// who can reach the app. Taking an archive's would hand its author a
+/// command to run or a way in.
+fn keep_trusted(archived: &mut AppSettings, current: AppSettings) {
+    archived.plugins = current.plugins;
+    archived.claude_binary_path = current.claude_binary_path;
+    // Shells and SSH targets, and env that can preload code into them
+    archived.terminal.default_shell = current.terminal.default_shell;
+    archived.terminal.profiles = current.terminal.profiles;
+    archived.env_profiles = current.env_profiles;
+    archived.server.mcp_enabled = current.server.mcp_enabled;
+    archived.server.mcp_shell_tools = current.server.mcp_shell_tools;
+    archived.server.allowed_origins = current.server.allowed_origins;
+    archived.remote = current.remote;
+    archived.proxy = current.proxy;
+}
+
+fn write_archive(zip_path: &Path) -> Result<(), KataraError> {
+    let dir = config::katara_dir()?;
+    // Settings and history are nobody else's business
+    let mut open = std::fs::OpenOptions::new();
+    open.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        open.mode(0o600);
+    }
+    let file = open.open(zip_path).map_err(KataraError::Io)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let pattern = format!("{}/**/*", glob::Pattern::escape(&dir.display().to_string()));
+    let paths = glob::glob(&pattern).map_err(|e| KataraError::Config(e.to_string()))?;
+    for path in paths.filter_map(Result::ok).filter(|p| p.is_file()) {
+        let Ok(relative) = path.strip_prefix(&dir) else {
+            continue;
+        };
+        if excluded(relative) {
+            continue;
+        }
+        let content = std::fs::read(&path).map_err(KataraError::Io)?;
+        zip.start_file(relative.to_string_lossy().replace('\\', "/"), options)
+            .map_err(zip_error)?;
+        zip.write_all(&content).map_err(KataraError::Io)?;
+    }
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+fn excluded(relative: &Path) -> bool {
+    relative
+        .components()
+        .next()
+        .is_some_and(|first| EXCLUDED.iter().any(|e| first.as_os_str() == *e))
+}
+
+fn prune_auto_backups() {
+    let Ok(backups) = list_backups() else {
+        return;
+    };
+    for backup in backups
+        .iter()
+        .filter(|b| b.reason != "manual")
+        .skip(MAX_AUTO_BACKUPS)
+    {
+        let _ = std::fs::remove_file(&backup.path);
+    }
+}
+
+/// Parse `katara-<millis>-<reason>.zip`.
+fn backup_info(path: &Path) -> Option<BackupInfo> {
+    let name = path.file_name()?.to_str()?;
+    let rest = name.strip_prefix("katara-")?.strip_suffix(".zip")?;
+    let (created_at, reason) = rest.split_once('-')?;
+    Some(BackupInfo {
+        path: path.display().to_string(),
+        created_at: created_at.parse().ok()?,
+        size: std::fs::metadata(path).ok()?.len(),
+        reason: reason.to_string(),
+    })
+}
+
+fn slug(reason: &str) -> String {
+    let slug: String = reason
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        "manual".into()
+    } else {
+        slug.into()
+    }
+}
+
+/// Next to the config directory, so backups don't contain older backups.
+fn backups_dir() -> Result<PathBuf, KataraError> {
+    Ok(config::katara_dir()?.with_file_name("katara-backups"))
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn zip_error(e: zip::result::ZipError) -> KataraError {
+    KataraError::Config(format!("Zip error: {}", e))
+}
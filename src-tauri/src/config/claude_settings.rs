@@ -0,0 +1,303 @@
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use crate::error::KataraError;
+
+/// Claude Code's permission modes.
+pub const PERMISSION_MODES: &[&str] = &["default", "acceptEdits", "plan", "bypassPermissions"];
+
+/// Events hooks can be attached to.
+pub const HOOK_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
+/// A Claude Code `settings.json` at one level.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaudeSettingsEntry {
+    pub level: String,
+    pub path: String,
+    /// The file's JSON object; empty if the file doesn't exist.
+    pub settings: Value,
+    pub exists: bool,
+}
+
+/// A problem at one location in a settings file.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsError {
+    /// Location of the offending value, e.g. `permissions.allow[2]`.
+    pub field: String,
+    pub message: String,
+}
+
+/// Read Claude Code's settings at the given level (`user`, `project`,
+/// `local` or the read-only `enterprise`).
+pub fn read_claude_settings(
+    level: &str,
+    project_dir: Option<&str>,
+) -> Result<ClaudeSettingsEntry, KataraError> {
+    let path = settings_path(level, project_dir)?;
+    let exists = path.exists();
+    let settings = if exists {
+        let content = std::fs::read_to_string(&path).map_err(KataraError::Io)?;
+        if content.trim().is_empty() {
+            Value::Object(Map::new())
+        } else {
+            serde_json::from_str(&content).map_err(KataraError::Serde)?
+        }
+    } else {
+        Value::Object(Map::new())
+    };
+
+    Ok(ClaudeSettingsEntry {
+        level: level.to_string(),
+        path: path.display().to_string(),
+        settings,
+        exists,
+    })
+}
+
+/// Validate and write Claude Code's settings at the given level. Nothing is
+/// written if validation fails.
+pub fn write_claude_settings(
+    level: &str,
+    project_dir: Option<&str>,
+    settings: &Value,
+) -> Result<(), KataraError> {
+    if level == "enterprise" {
+        return Err(KataraError::Config("Managed settings are read-only".into()));
+    }
+    let errors = validate_settings(settings);
+    if !errors.is_empty() {
+        return Err(KataraError::InvalidSettings(errors));
+    }
+
+    let path = settings_path(level, project_dir)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(KataraError::Io)?;
+    }
+    let mut content = serde_json::to_string_pretty(settings).map_err(KataraError::Serde)?;
+    content.push('\n');
+    std::fs::write(&path, content).map_err(KataraError::Io)?;
+    Ok(())
+}
+
+/// Check the structure of the keys Katara knows about (`permissions`,
+/// `hooks`, `env` and common scalar settings). Unknown keys are left alone
+/// so settings from newer CLI versions aren't rejected.
+pub fn validate_settings(settings: &Value) -> Vec<SettingsError> {
+    let mut errors = Vec::new();
+    let Some(root) = settings.as_object() else {
+        errors.push(error("", "Settings must be a JSON object"));
+        return errors;
+    };
+
+    for (key, value) in root {
+        match key.as_str() {
+            "permissions" => check_permissions(value, &mut errors),
+            "hooks" => check_hooks(value, &mut errors),
+            "env" => check_string_map(key, value, &mut errors),
+            "model" | "apiKeyHelper" | "outputStyle" | "awsAuthRefresh" | "awsCredentialExport" => {
+                check_string(key, value, &mut errors)
+            }
+            "includeCoAuthoredBy" | "enableAllProjectMcpServers" | "disableAllHooks"
+                if !value.is_boolean() =>
+            {
+                errors.push(error(key, "must be true or false"))
+            }
+            "cleanupPeriodDays" if !value.is_u64() => {
+                errors.push(error(key, "must be a whole number of days"))
+            }
+            "enabledMcpjsonServers" | "disabledMcpjsonServers" => {
+                check_string_array(key, value, &mut errors)
+            }
+            "forceLoginMethod" => check_one_of(key, value, &["claudeai", "console"], &mut errors),
+            "statusLine" => check_command_object(key, value, &mut errors),
+            _ => {}
+        }
+    }
+    errors
+}
+
+/// Path of Claude Code's settings file at a level. `enterprise` is the
+/// admin-managed file, which overrides every other level.
+pub fn settings_path(level: &str, project_dir: Option<&str>) -> Result<PathBuf, KataraError> {
+    match level {
+        "enterprise" => Ok(if cfg!(windows) {
+            PathBuf::from(std::env::var("PROGRAMDATA").unwrap_or_default())
+                .join("ClaudeCode")
+                .join("managed-settings.json")
+        } else if cfg!(target_os = "macos") {
+            PathBuf::from("/Library/Application Support/ClaudeCode/managed-settings.json")
+        } else {
+            PathBuf::from("/etc/claude-code/managed-settings.json")
+        }),
+        "user" => Ok(dirs::home_dir()
+            .unwrap_or_default()
+            .join(".claude")
+            .join("settings.json")),
+        "project" => Ok(project_claude_dir(project_dir)?.join("settings.json")),
+        "local" => Ok(project_claude_dir(project_dir)?.join("settings.local.json")),
+        _ => Err(KataraError::Config(format!("Unknown level: {}", level))),
+    }
+}
+
+fn project_claude_dir(project_dir: Option<&str>) -> Result<PathBuf, KataraError> {
+    let dir = project_dir.ok_or(KataraError::Config("No project directory".into()))?;
+    Ok(Path::new(dir).join(".claude"))
+}
+
+fn check_permissions(value: &Value, errors: &mut Vec<SettingsError>) {
+    let Some(permissions) = value.as_object() else {
+        errors.push(error("permissions", "must be an object"));
+        return;
+    };
+    let rule = Regex::new(r"^[A-Za-z_][\w-]*(\(.*\))?$").expect("rule pattern is valid");
+
+    for (key, value) in permissions {
+        let field = format!("permissions.{}", key);
+        match key.as_str() {
+            "allow" | "deny" | "ask" => {
+                check_string_array(&field, value, errors);
+                for (i, item) in value.as_array().into_iter().flatten().enumerate() {
+                    if item.as_str().is_some_and(|r| !rule.is_match(r)) {
+                        errors.push(error(
+                            &format!("{}[{}]", field, i),
+                            "must be a tool name, optionally with a specifier: Tool(specifier)",
+                        ));
+                    }
+                }
+            }
+            "additionalDirectories" => check_string_array(&field, value, errors),
+            "defaultMode" => check_one_of(&field, value, PERMISSION_MODES, errors),
+            "disableBypassPermissionsMode" => check_one_of(&field, value, &["disable"], errors),
+            _ => {}
+        }
+    }
+}
+
+fn check_hooks(value: &Value, errors: &mut Vec<SettingsError>) {
+    let Some(events) = value.as_object() else {
+        errors.push(error("hooks", "must be an object"));
+        return;
+    };
+
+    // Events this version doesn't know may be from a newer CLI, so they're
+    // only a lint warning
+    for (event, matchers) in events {
+        let field = format!("hooks.{}", event);
+        let Some(matchers) = matchers.as_array() else {
+            errors.push(error(&field, "must be a list of matchers"));
+            continue;
+        };
+        for (i, matcher) in matchers.iter().enumerate() {
+            let field = format!("{}[{}]", field, i);
+            let Some(matcher) = matcher.as_object() else {
+                errors.push(error(&field, "must be an object with `hooks`"));
+                continue;
+            };
+            if let Some(pattern) = matcher.get("matcher") {
+                let field = format!("{}.matcher", field);
+                match pattern.as_str() {
+                    Some("" | "*") => {}
+                    Some(pattern) => {
+                        if let Err(e) = Regex::new(pattern) {
+                            errors.push(error(&field, &format!("is not a valid pattern: {}", e)));
+                        }
+                    }
+                    None => check_string(&field, pattern, errors),
+                }
+            }
+            let Some(hooks) = matcher.get("hooks").and_then(Value::as_array) else {
+                errors.push(error(
+                    &format!("{}.hooks", field),
+                    "must be a list of hooks",
+                ));
+                continue;
+            };
+            for (j, hook) in hooks.iter().enumerate() {
+                let field = format!("{}.hooks[{}]", field, j);
+                check_command_object(&field, hook, errors);
+                if let Some(timeout) = hook.get("timeout") {
+                    if !timeout.is_u64() {
+                        errors.push(error(
+                            &format!("{}.timeout", field),
+                            "must be a whole number of seconds",
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `{ "type": "command", "command": "..." }`, as used by hooks and the
+/// status line.
+fn check_command_object(field: &str, value: &Value, errors: &mut Vec<SettingsError>) {
+    let Some(object) = value.as_object() else {
+        errors.push(error(field, "must be an object"));
+        return;
+    };
+    match object.get("type") {
+        Some(t) => check_one_of(&format!("{}.type", field), t, &["command"], errors),
+        None => errors.push(error(&format!("{}.type", field), "is required")),
+    }
+    match object.get("command") {
+        Some(Value::String(c)) if !c.trim().is_empty() => {}
+        Some(Value::String(_)) | None => {
+            errors.push(error(&format!("{}.command", field), "is required"))
+        }
+        Some(_) => errors.push(error(&format!("{}.command", field), "must be a string")),
+    }
+}
+
+fn check_string(field: &str, value: &Value, errors: &mut Vec<SettingsError>) {
+    if !value.is_string() {
+        errors.push(error(field, "must be a string"));
+    }
+}
+
+fn check_string_array(field: &str, value: &Value, errors: &mut Vec<SettingsError>) {
+    let Some(items) = value.as_array() else {
+        errors.push(error(field, "must be a list of strings"));
+        return;
+    };
+    for (i, item) in items.iter().enumerate() {
+        check_string(&format!("{}[{}]", field, i), item, errors);
+    }
+}
+
+fn check_string_map(field: &str, value: &Value, errors: &mut Vec<SettingsError>) {
+    let Some(map) = value.as_object() else {
+        errors.push(error(field, "must be an object of strings"));
+        return;
+    };
+    for (key, value) in map {
+        check_string(&format!("{}.{}", field, key), value, errors);
+    }
+}
+
+fn check_one_of(field: &str, value: &Value, allowed: &[&str], errors: &mut Vec<SettingsError>) {
+    if !value.as_str().is_some_and(|v| allowed.contains(&v)) {
+        errors.push(error(
+            field,
+            &format!("must be one of: {}", allowed.join(", ")),
+        ));
+    }
+}
+
+fn error(field: &str, message: &str) -> SettingsError {
+    SettingsError {
+        field: field.to_string(),
+        message: message.to_string(),
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::manager::{self, AppSettings};
+use crate::config::secrets;
+use crate::error::KataraError;
+
+/// A named set of environment variables, selectable when spawning a
+/// session or terminal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvProfile {
+    pub name: String,
+    #[serde(default)]
+    pub env: BTreeMap<String, EnvValue>,
+}
+
+/// An environment variable's value: written in settings, or the name of a
+/// secret in the OS keychain so it never lands in settings.json.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum EnvValue {
+    Plain(String),
+    Secret { secret: String },
+}
+
+impl EnvProfile {
+    pub(crate) fn validate(&self) -> Result<(), KataraError> {
+        if self.name.trim().is_empty() {
+            return Err(KataraError::Config("Env profile name is required".into()));
+        }
+        for (key, value) in &self.env {
+            let valid_key = key.chars().next().is_some_and(|c| !c.is_ascii_digit())
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_key {
+                return Err(KataraError::Config(format!(
+                    "Env profile {}: invalid variable name {:?}",
+                    self.name, key
+                )));
+            }
+            if matches!(value, EnvValue::Secret { secret } if secret.trim().is_empty()) {
+                return Err(KataraError::Config(format!(
+                    "Env profile {}: {} references an empty secret name",
+                    self.name, key
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn list_env_profiles() -> Result<Vec<EnvProfile>, KataraError> {
+    Ok(manager::read_settings()?.env_profiles)
+}
+
+/// Add a new env profile. Names must be unique.
+pub fn create_env_profile(profile: EnvProfile) -> Result<AppSettings, KataraError> {
+    manager::modify_settings(|settings| {
+        if settings.env_profiles.iter().any(|p| p.name == profile.name) {
+            return Err(KataraError::Config(format!(
+                "Env profile {} already exists",
+                profile.name
+            )));
+        }
+        settings.env_profiles.push(profile);
+        Ok(())
+    })
+}
+
+/// Replace the profile called `name`; `profile.name` may rename it.
+pub fn update_env_profile(name: &str, profile: EnvProfile) -> Result<AppSettings, KataraError> {
+    manager::modify_settings(|settings| {
+        let profiles = &mut settings.env_profiles;
+        if profile.name != name && profiles.iter().any(|p| p.name == profile.name) {
+            return Err(KataraError::Config(format!(
+                "Env profile {} already exists",
+                profile.name
+            )));
+        }
+        let slot = profiles
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| not_found(name))?;
+        *slot = profile;
+        Ok(())
+    })
+}
+
+/// Remove the profile called `name`.
+pub fn delete_env_profile(name: &str) -> Result<AppSettings, KataraError> {
+    manager::modify_settings(|settings| {
+        let before = settings.env_profiles.len();
+        settings.env_profiles.retain(|p| p.name != name);
+        if settings.env_profiles.len() == before {
+            return Err(not_found(name));
+        }
+        Ok(())
+    })
+}
+
+/// The variables of profile `name`, with secrets read from the keychain.
+/// Fails if a referenced secret is missing rather than spawning without it.
+pub fn resolve_env_profile(
+    profiles: &[EnvProfile],
+    name: &str,
+) -> Result<HashMap<String, String>, KataraError> {
+    let profile = profiles
+        .iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| not_found(name))?;
+    profile
+        .env
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                EnvValue::Plain(value) => value.clone(),
+                EnvValue::Secret { secret } => secrets::get_secret(secret)?,
+            };
+            Ok((key.clone(), value))
+        })
+        .collect()
+}
+
+fn not_found(name: &str) -> KataraError {
+    KataraError::Config(format!("Env profile {} not found", name))
+}
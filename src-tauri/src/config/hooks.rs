@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::config::claude_settings::{self, HOOK_EVENTS};
+use crate::error::KataraError;
+
+/// The CLI's default hook timeout.
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Exit code with which a hook blocks the action it was called for.
+const BLOCKING_EXIT_CODE: i32 = 2;
+
+/// Hooks of one settings file, by event name.
+pub type HooksConfig = BTreeMap<String, Vec<HookMatcher>>;
+
+/// Hooks run for the tools whose names match `matcher` (a regex; empty or
+/// `*` matches everything, and events without tools ignore it).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookMatcher {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<String>,
+    pub hooks: Vec<HookCommand>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookCommand {
+    #[serde(rename = "type")]
+    pub hook_type: String,
+    pub command: String,
+    /// Seconds before the CLI gives up on the hook.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// What a hook did when fired with a sample payload.
+#[derive(Debug, Clone, Serialize)]
+pub struct HookTestResult {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Stdout parsed as JSON, for hooks that answer with a decision object.
+    pub json: Option<Value>,
+    /// Exit code 2: the CLI would block the tool call / prompt / stop.
+    pub blocked: bool,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    /// The payload the hook received on stdin.
+    pub payload: Value,
+}
+
+/// Hooks configured in Claude Code's settings at a level.
+pub fn read_hooks(level: &str, project_dir: Option<&str>) -> Result<HooksConfig, KataraError> {
+    let entry = claude_settings::read_claude_settings(level, project_dir)?;
+    match entry.settings.get("hooks") {
+        Some(hooks) => serde_json::from_value(hooks.clone()).map_err(KataraError::Serde),
+        None => Ok(HooksConfig::new()),
+    }
+}
+
+/// Replace the hooks in Claude Code's settings at a level, keeping every
+/// other setting. Validated like any settings write.
+pub fn write_hooks(
+    level: &str,
+    project_dir: Option<&str>,
+    hooks: &HooksConfig,
+) -> Result<(), KataraError> {
+    let entry = claude_settings::read_claude_settings(level, project_dir)?;
+    let mut settings = match entry.settings {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    let hooks: HooksConfig = hooks
+        .iter()
+        .filter(|(_, matchers)| !matchers.is_empty())
+        .map(|(event, matchers)| (event.clone(), matchers.clone()))
+        .collect();
+    if hooks.is_empty() {
+        settings.remove("hooks");
+    } else {
+        settings.insert(
+            "hooks".into(),
+            serde_json::to_value(&hooks).map_err(KataraError::Serde)?,
+        );
+    }
+    claude_settings::write_claude_settings(level, project_dir, &Value::Object(settings))
+}
+
+/// Run a hook command the way the CLI would for `event`: through the shell,
+/// in `project_dir`, with a JSON payload on stdin. `payload` replaces the
+/// sample payload for the event.
+pub async fn test_hook(
+    command: &str,
+    event: &str,
+    payload: Option<Value>,
+    project_dir: Option<&str>,
+    timeout: Option<u64>,
+) -> Result<HookTestResult, KataraError> {
+    if !HOOK_EVENTS.contains(&event) {
+        return Err(KataraError::Config(format!(
+            "Unknown hook event: {}",
+            event
+        )));
+    }
+    let cwd = project_dir
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    let payload = payload.unwrap_or_else(|| sample_payload(event, &cwd.display().to_string()));
+
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    let started = Instant::now();
+    let mut child = cmd
+        .current_dir(&cwd)
+        .env("CLAUDE_PROJECT_DIR", &cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| KataraError::Process(format!("Failed to run hook: {}", e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // Hooks that ignore stdin may exit before reading it
+        let _ = stdin.write_all(payload.to_string().as_bytes()).await;
+    }
+
+    let limit = Duration::from_secs(timeout.unwrap_or(DEFAULT_TIMEOUT_SECS));
+    let (output, timed_out) = match tokio::time::timeout(limit, child.wait_with_output()).await {
+        Ok(output) => (
+            Some(output.map_err(|e| KataraError::Process(e.to_string()))?),
+            false,
+        ),
+        // Dropping the future kills the hook
+        Err(_) => (None, true),
+    };
+
+    let exit_code = output.as_ref().and_then(|o| o.status.code());
+    let stdout = output
+        .as_ref()
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default();
+    let stderr = output
+        .as_ref()
+        .map(|o| String::from_utf8_lossy(&o.stderr).to_string())
+        .unwrap_or_default();
+    Ok(HookTestResult {
+        exit_code,
+        json: serde_json::from_str(stdout.trim()).ok(),
+        stdout,
+        stderr,
+        blocked: exit_code == Some(BLOCKING_EXIT_CODE),
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+        payload,
+    })
+}
+
+/// A representative payload for each event, shaped like the CLI's.
+fn sample_payload(event: &str, cwd: &str) -> Value {
+    let mut payload = json!({
+        "session_id": "katara-hook-test",
+        "transcript_path": "",
+        "cwd": cwd,
+        "hook_event_name": event,
+    });
+    let extra = match event {
+        "PreToolUse" => json!({
+            "tool_name": "Bash",
+            "tool_input": { "command": "echo hello", "description": "Print hello" },
+        }),
+        "PostToolUse" => json!({
+            "tool_name": "Bash",
+            "tool_input": { "command": "echo hello", "description": "Print hello" },
+            "tool_response": { "stdout": "hello\n", "stderr": "", "interrupted": false },
+        }),
+        "Notification" => json!({ "message": "Claude needs your permission to use Bash" }),
+        "UserPromptSubmit" => json!({ "prompt": "Write a function that adds two numbers" }),
+        "Stop" | "SubagentStop" => json!({ "stop_hook_active": false }),
+        "PreCompact" => json!({ "trigger": "manual", "custom_instructions": "" }),
+        "SessionStart" => json!({ "source": "startup" }),
+        "SessionEnd" => json!({ "reason": "other" }),
+        _ => json!({}),
+    };
+    if let (Some(payload), Value::Object(extra)) = (payload.as_object_mut(), extra) {
+        payload.extend(extra);
+    }
+    payload
+}
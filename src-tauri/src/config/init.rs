@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+
+use crate::config::{claude_settings, manager};
+use crate::error::KataraError;
+
+/// Line added to `.gitignore` so personal settings stay out of the repo.
+const LOCAL_SETTINGS_IGNORE: &str = ".claude/settings.local.json";
+
+/// What `init_project` should create. Everything is on by default.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InitOptions {
+    #[serde(default = "yes")]
+    pub claude_md: bool,
+    #[serde(default = "yes")]
+    pub settings: bool,
+    #[serde(default = "yes")]
+    pub commands_dir: bool,
+    #[serde(default = "yes")]
+    pub gitignore: bool,
+    /// Replace an existing CLAUDE.md or settings.json instead of skipping it.
+    #[serde(default)]
+    pub overwrite: bool,
+    /// Opening paragraph for the CLAUDE.md.
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl Default for InitOptions {
+    fn default() -> Self {
+        Self {
+            claude_md: true,
+            settings: true,
+            commands_dir: true,
+            gitignore: true,
+            overwrite: false,
+            description: None,
+        }
+    }
+}
+
+fn yes() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InitResult {
+    pub created: Vec<String>,
+    /// Existing files that were changed (`.gitignore`) or replaced.
+    pub updated: Vec<String>,
+    /// Existing files left alone.
+    pub skipped: Vec<String>,
+    /// Toolchains recognized in the project, e.g. `Rust`.
+    pub detected: Vec<String>,
+}
+
+/// A toolchain recognized from a marker file, with its usual commands.
+struct Toolchain {
+    name: String,
+    /// (label, command)
+    commands: Vec<(&'static str, String)>,
+}
+
+/// Set up `working_dir` for Claude Code: a starter CLAUDE.md listing the
+/// project's build and test commands, `.claude/settings.json` allowing
+/// those commands, `.claude/commands/` for slash commands, and a
+/// `.gitignore` entry for `settings.local.json`.
+pub fn init_project(working_dir: &str, options: &InitOptions) -> Result<InitResult, KataraError> {
+    let dir = Path::new(working_dir);
+    if !dir.is_dir() {
+        return Err(KataraError::Config(format!(
+            "{} is not a directory",
+            working_dir
+        )));
+    }
+    let toolchains = detect_toolchains(dir);
+    let mut result = InitResult {
+        detected: toolchains.iter().map(|t| t.name.clone()).collect(),
+        ..Default::default()
+    };
+
+    if options.claude_md {
+        let entry = manager::read_claude_md("project", Some(working_dir))?;
+        if entry.exists && !options.overwrite {
+            result.skipped.push(entry.path);
+        } else {
+            manager::write_claude_md(&entry.path, &claude_md(dir, options, &toolchains))?;
+            record(&mut result, entry.path, entry.exists);
+        }
+    }
+
+    if options.settings {
+        let entry = claude_settings::read_claude_settings("project", Some(working_dir))?;
+        if entry.exists && !options.overwrite {
+            result.skipped.push(entry.path);
+        } else {
+            claude_settings::write_claude_settings(
+                "project",
+                Some(working_dir),
+                &starter_settings(&toolchains),
+            )?;
+            record(&mut result, entry.path, entry.exists);
+        }
+    }
+
+    if options.commands_dir {
+        let commands = dir.join(".claude").join("commands");
+        let path = commands.display().to_string();
+        if commands.is_dir() {
+            result.skipped.push(path);
+        } else {
+            std::fs::create_dir_all(&commands).map_err(KataraError::Io)?;
+            result.created.push(path);
+        }
+    }
+
+    if options.gitignore {
+        let path = dir.join(".gitignore");
+        let existed = path.exists();
+        let existing = std::fs::read_to_string(&path).unwrap_or_default();
+        let display = path.display().to_string();
+        if existing.lines().any(|l| l.trim() == LOCAL_SETTINGS_IGNORE) {
+            result.skipped.push(display);
+        } else {
+            let mut content = existing.clone();
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(LOCAL_SETTINGS_IGNORE);
+            content.push('\n');
+            std::fs::write(&path, content).map_err(KataraError::Io)?;
+            record(&mut result, display, existed);
+        }
+    }
+
+    println!(
+        "[katara] Initialized {} ({} created, {} updated)",
+        working_dir,
+        result.created.len(),
+        result.updated.len()
+    );
+    Ok(result)
+}
+
+fn record(result: &mut InitResult, path: String, existed: bool) {
+    if existed {
+        result.updated.push(path);
+    } else {
+        result.created.push(path);
+    }
+}
+
+fn detect_toolchains(dir: &Path) -> Vec<Toolchain> {
+    let mut found = Vec::new();
+    if dir.join("Cargo.toml").is_file() {
+        found.push(Toolchain {
+            name: "Rust".into(),
+            commands: vec![
+                ("Build", "cargo build".into()),
+                ("Test", "cargo test".into()),
+                ("Lint", "cargo clippy".into()),
+            ],
+        });
+    }
+    if let Ok(content) = std::fs::read_to_string(dir.join("package.json")) {
+        let package: Value = serde_json::from_str(&content).unwrap_or_default();
+        let runner = if dir.join("pnpm-lock.yaml").exists() {
+            "pnpm"
+        } else if dir.join("yarn.lock").exists() {
+            "yarn"
+        } else if dir.join("bun.lockb").exists() || dir.join("bun.lock").exists() {
+            "bun"
+        } else {
+            "npm"
+        };
+        let commands = [("Build", "build"), ("Test", "test"), ("Lint", "lint")]
+            .into_iter()
+            .filter(|(_, script)| package.pointer(&format!("/scripts/{}", script)).is_some())
+            .map(|(label, script)| (label, format!("{} run {}", runner, script)))
+            .collect();
+        found.push(Toolchain {
+            name: "Node.js".into(),
+            commands,
+        });
+    }
+    if dir.join("pyproject.toml").is_file() || dir.join("requirements.txt").is_file() {
+        found.push(Toolchain {
+            name: "Python".into(),
+            commands: vec![("Test", "pytest".into())],
+        });
+    }
+    if dir.join("go.mod").is_file() {
+        found.push(Toolchain {
+            name: "Go".into(),
+            commands: vec![
+                ("Build", "go build ./...".into()),
+                ("Test", "go test ./...".into()),
+            ],
+        });
+    }
+    if dir.join("Makefile").is_file() {
+        found.push(Toolchain {
+            name: "Make".into(),
+            commands: vec![("Build", "make".into())],
+        });
+    }
+    found
+}
+
+fn claude_md(dir: &Path, options: &InitOptions, toolchains: &[Toolchain]) -> String {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Project".into());
+    let description = options
+        .description
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .unwrap_or("Describe what this project does and how the code is organized.");
+
+    let mut content = format!("# {}\n\n{}\n\n## Commands\n\n", name, description.trim());
+    let commands: Vec<_> = toolchains.iter().flat_map(|t| &t.commands).collect();
+    if commands.is_empty() {
+        content.push_str("- Build: `...`\n- Test: `...`\n");
+    } else {
+        for (label, command) in commands {
+            content.push_str(&format!("- {}: `{}`\n", label, command));
+        }
+    }
+    content.push_str(
+        "\n## Conventions\n\n\
+         - Code style, naming and patterns to follow.\n\
+         - Anything Claude should always or never do in this repo.\n",
+    );
+    content
+}
+
+/// Allow the detected build and test commands and keep `.env` files out of
+/// reach.
+fn starter_settings(toolchains: &[Toolchain]) -> Value {
+    let allow: Vec<String> = toolchains
+        .iter()
+        .flat_map(|t| &t.commands)
+        .map(|(_, command)| format!("Bash({}:*)", command))
+        .collect();
+    json!({
+        "permissions": {
+            "allow": allow,
+            "deny": ["Read(./.env)", "Read(./.env.*)"],
+        }
+    })
+}
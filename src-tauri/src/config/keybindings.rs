@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use crate::config::manager::{self, AppSettings};
+use crate::error::KataraError;
+
+/// Keyboard shortcuts: action id -> chord, e.g. `"palette.open": "Ctrl+Shift+P"`.
+pub type Keybindings = BTreeMap<String, String>;
+
+/// Modifiers in the order chords are written, with the names they may be
+/// typed as.
+const MODIFIERS: &[(&str, &[&str])] = &[
+    ("CmdOrCtrl", &["cmdorctrl", "commandorcontrol", "mod"]),
+    ("Ctrl", &["ctrl", "control"]),
+    ("Alt", &["alt", "option", "opt"]),
+    ("Shift", &["shift"]),
+    ("Meta", &["meta", "cmd", "command", "super", "win"]),
+];
+
+/// Multi-letter key names, as written in normalized chords.
+const NAMED_KEYS: &[&str] = &[
+    "Escape",
+    "Enter",
+    "Tab",
+    "Space",
+    "Backspace",
+    "Delete",
+    "Insert",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "ArrowUp",
+    "ArrowDown",
+    "ArrowLeft",
+    "ArrowRight",
+    "Plus",
+    "Minus",
+];
+
+/// Key names accepted under another spelling.
+const KEY_ALIASES: &[(&str, &str)] = &[
+    ("esc", "Escape"),
+    ("return", "Enter"),
+    ("del", "Delete"),
+    ("up", "ArrowUp"),
+    ("down", "ArrowDown"),
+    ("left", "ArrowLeft"),
+    ("right", "ArrowRight"),
+    ("pgup", "PageUp"),
+    ("pgdn", "PageDown"),
+];
+
+pub fn get_keybindings() -> Result<Keybindings, KataraError> {
+    Ok(manager::read_settings()?.keybindings)
+}
+
+/// Bind `action` to `chord`, or remove its binding with `None`. Fails if the
+/// chord is already bound to another action.
+pub fn set_keybinding(action: &str, chord: Option<&str>) -> Result<AppSettings, KataraError> {
+    manager::modify_settings(|settings| {
+        match chord {
+            Some(chord) => {
+                settings
+                    .keybindings
+                    .insert(action.to_string(), chord.to_string());
+            }
+            None => {
+                settings.keybindings.remove(action);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Replace all keybindings.
+pub fn set_keybindings(bindings: Keybindings) -> Result<AppSettings, KataraError> {
+    manager::modify_settings(|settings| {
+        settings.keybindings = bindings;
+        Ok(())
+    })
+}
+
+/// Normalize every chord and reject bindings that can't be parsed or share
+/// a chord. Returns the normalized bindings.
+pub fn validate(bindings: &Keybindings) -> Result<Keybindings, KataraError> {
+    let mut normalized = Keybindings::new();
+    let mut by_chord: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    let mut problems = Vec::new();
+
+    for (action, chord) in bindings {
+        if action.trim().is_empty() {
+            problems.push("Action id cannot be empty".to_string());
+            continue;
+        }
+        match normalize_chord(chord) {
+            Ok(chord) => {
+                by_chord.entry(chord.clone()).or_default().push(action);
+                normalized.insert(action.clone(), chord);
+            }
+            Err(e) => problems.push(format!("{}: {}", action, e)),
+        }
+    }
+    for (chord, actions) in by_chord {
+        if actions.len() > 1 {
+            problems.push(format!("{} is bound to {}", chord, actions.join(", ")));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(normalized)
+    } else {
+        Err(KataraError::Config(format!(
+            "Invalid keybindings: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+/// Canonical form of a chord, or a key sequence of chords separated by
+/// spaces: modifiers in a fixed order, then the key, e.g.
+/// `shift+ctrl+p` -> `Ctrl+Shift+P`.
+pub fn normalize_chord(chord: &str) -> Result<String, String> {
+    let steps: Vec<String> = chord
+        .split_whitespace()
+        .map(normalize_step)
+        .collect::<Result<_, _>>()?;
+    if steps.is_empty() {
+        return Err("chord is empty".into());
+    }
+    Ok(steps.join(" "))
+}
+
+fn normalize_step(step: &str) -> Result<String, String> {
+    let mut modifiers = [false; MODIFIERS.len()];
+    let mut key = None;
+
+    for part in step.split('+') {
+        let lower = part.trim().to_lowercase();
+        if lower.is_empty() {
+            return Err(format!("\"{}\" has an empty key (use Plus for +)", step));
+        }
+        if let Some(i) = MODIFIERS
+            .iter()
+            .position(|(_, names)| names.contains(&lower.as_str()))
+        {
+            modifiers[i] = true;
+        } else if key.is_some() {
+            return Err(format!("\"{}\" has more than one key", step));
+        } else {
+            key = Some(key_name(&lower));
+        }
+    }
+
+    let key = key.ok_or_else(|| format!("\"{}\" has no key besides modifiers", step))?;
+    let mut parts: Vec<String> = MODIFIERS
+        .iter()
+        .zip(modifiers)
+        .filter(|(_, on)| *on)
+        .map(|((name, _), _)| name.to_string())
+        .collect();
+    parts.push(key);
+    Ok(parts.join("+"))
+}
+
+/// `p` -> `P`, `esc` -> `Escape`, `pageup` -> `PageUp`, `f5` -> `F5`.
+fn key_name(lower: &str) -> String {
+    if let Some((_, name)) = KEY_ALIASES.iter().find(|(alias, _)| *alias == lower) {
+        return name.to_string();
+    }
+    if let Some(name) = NAMED_KEYS.iter().find(|k| k.eq_ignore_ascii_case(lower)) {
+        return name.to_string();
+    }
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
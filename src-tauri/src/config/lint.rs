@@ -0,0 +1,349 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::claude_settings::{self, ClaudeSettingsEntry, HOOK_EVENTS};
+use crate::config::hooks::HooksConfig;
+use crate::config::memory;
+use crate::error::KataraError;
+
+/// CLAUDE.md size (with imports expanded) above which the CLI warns that
+/// performance suffers.
+const LARGE_MEMORY_CHARS: usize = 40_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// One problem found by `lint_config`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigWarning {
+    pub severity: Severity,
+    /// Stable identifier, e.g. `shadowed_permission`.
+    pub code: &'static str,
+    pub message: String,
+    /// Level of the file the problem is in.
+    pub level: String,
+    pub path: String,
+    /// Setting path within the file, e.g. `permissions.allow[2]`.
+    pub field: Option<String>,
+}
+
+/// A permission rule and where it was written.
+struct Rule<'a> {
+    list: &'static str,
+    rule: &'a str,
+    index: usize,
+    entry: &'a ClaudeSettingsEntry,
+}
+
+/// Check the Claude Code settings and CLAUDE.md files at every level for
+/// conflicts and likely mistakes. Project levels are only checked with a
+/// `project_dir`. Unreadable files are reported, not fatal.
+pub fn lint_config(project_dir: Option<&str>) -> Result<Vec<ConfigWarning>, KataraError> {
+    let mut warnings = Vec::new();
+    let mut levels = vec!["user"];
+    if project_dir.is_some() {
+        levels.extend(["project", "local"]);
+    }
+
+    let mut entries = Vec::new();
+    for level in &levels {
+        match claude_settings::read_claude_settings(level, project_dir) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                let path = claude_settings::settings_path(level, project_dir)?;
+                warnings.push(ConfigWarning {
+                    severity: Severity::Error,
+                    code: "unreadable_settings",
+                    message: e.to_string(),
+                    level: level.to_string(),
+                    path: path.display().to_string(),
+                    field: None,
+                });
+            }
+        }
+    }
+
+    for entry in entries.iter().filter(|e| e.exists) {
+        for error in claude_settings::validate_settings(&entry.settings) {
+            warnings.push(warning(
+                Severity::Error,
+                "invalid_setting",
+                error.message,
+                entry,
+                Some(error.field),
+            ));
+        }
+        lint_hooks(entry, &mut warnings);
+        if entry.level == "project"
+            && entry.settings.pointer("/permissions/defaultMode")
+                == Some(&Value::from("bypassPermissions"))
+        {
+            warnings.push(warning(
+                Severity::Warning,
+                "shared_bypass_permissions",
+                "bypassPermissions in the shared project settings skips every permission \
+                 prompt for everyone who clones the repo; put it in settings.local.json instead"
+                    .into(),
+                entry,
+                Some("permissions.defaultMode".into()),
+            ));
+        }
+    }
+    lint_permissions(&entries, &mut warnings);
+
+    let mut memory_levels = vec!["user"];
+    if project_dir.is_some() {
+        memory_levels.extend(["project", "local"]);
+    }
+    for level in memory_levels {
+        lint_memory(level, project_dir, &mut warnings)?;
+    }
+    Ok(warnings)
+}
+
+fn lint_hooks(entry: &ClaudeSettingsEntry, warnings: &mut Vec<ConfigWarning>) {
+    let Some(hooks) = entry.settings.get("hooks") else {
+        return;
+    };
+    if let Some(events) = hooks.as_object() {
+        for event in events.keys() {
+            if !HOOK_EVENTS.contains(&event.as_str()) {
+                warnings.push(warning(
+                    Severity::Warning,
+                    "unknown_hook_event",
+                    format!(
+                        "Unknown hook event {}; expected one of {}",
+                        event,
+                        HOOK_EVENTS.join(", ")
+                    ),
+                    entry,
+                    Some(format!("hooks.{}", event)),
+                ));
+            }
+        }
+    }
+    // Structural problems are already reported by validate_settings
+    let Ok(hooks) = serde_json::from_value::<HooksConfig>(hooks.clone()) else {
+        return;
+    };
+    for (event, matchers) in &hooks {
+        for (i, matcher) in matchers.iter().enumerate() {
+            let earlier = matchers[..i].iter().position(|m| {
+                m.matcher.as_deref().unwrap_or("") == matcher.matcher.as_deref().unwrap_or("")
+            });
+            if let Some(first) = earlier {
+                warnings.push(warning(
+                    Severity::Warning,
+                    "duplicate_hook_matcher",
+                    format!(
+                        "{} has two entries for matcher {:?} (#{} and #{}); merge their hooks",
+                        event,
+                        matcher.matcher.as_deref().unwrap_or("*"),
+                        first + 1,
+                        i + 1
+                    ),
+                    entry,
+                    Some(format!("hooks.{}[{}]", event, i)),
+                ));
+            }
+            for (j, hook) in matcher.hooks.iter().enumerate() {
+                if matcher.hooks[..j].iter().any(|h| h.command == hook.command) {
+                    warnings.push(warning(
+                        Severity::Warning,
+                        "duplicate_hook",
+                        format!("{} runs `{}` twice", event, hook.command),
+                        entry,
+                        Some(format!("hooks.{}[{}].hooks[{}]", event, i, j)),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Deny beats ask beats allow, at any level. Report rules that can never
+/// apply because a stronger list covers them, and rules already covered by
+/// another rule in the same list.
+fn lint_permissions(entries: &[ClaudeSettingsEntry], warnings: &mut Vec<ConfigWarning>) {
+    let mut rules = Vec::new();
+    for entry in entries {
+        for list in ["allow", "ask", "deny"] {
+            let Some(items) = entry
+                .settings
+                .pointer(&format!("/permissions/{}", list))
+                .and_then(Value::as_array)
+            else {
+                continue;
+            };
+            for (index, rule) in items.iter().enumerate() {
+                if let Some(rule) = rule.as_str() {
+                    rules.push(Rule {
+                        list,
+                        rule,
+                        index,
+                        entry,
+                    });
+                }
+            }
+        }
+    }
+
+    let strength = |list: &str| match list {
+        "deny" => 2,
+        "ask" => 1,
+        _ => 0,
+    };
+    for (i, rule) in rules.iter().enumerate() {
+        let shadowing = rules.iter().find(|other| {
+            strength(other.list) > strength(rule.list) && covers(other.rule, rule.rule)
+        });
+        if let Some(other) = shadowing {
+            warnings.push(warning(
+                Severity::Warning,
+                "shadowed_permission",
+                format!(
+                    "{} rule {} never applies: {} rule {} in {} settings takes precedence",
+                    rule.list, rule.rule, other.list, other.rule, other.entry.level
+                ),
+                rule.entry,
+                Some(format!("permissions.{}[{}]", rule.list, rule.index)),
+            ));
+            continue;
+        }
+        // Only the later of two identical rules is reported
+        let redundant = rules.iter().enumerate().find(|(j, other)| {
+            *j != i
+                && other.list == rule.list
+                && covers(other.rule, rule.rule)
+                && (other.rule != rule.rule || *j < i)
+        });
+        if let Some((_, other)) = redundant {
+            let message = if other.rule == rule.rule {
+                format!(
+                    "{} rule {} is also in {} settings",
+                    rule.list, rule.rule, other.entry.level
+                )
+            } else {
+                format!(
+                    "{} rule {} is already covered by {} in {} settings",
+                    rule.list, rule.rule, other.rule, other.entry.level
+                )
+            };
+            warnings.push(warning(
+                Severity::Info,
+                "redundant_permission",
+                message,
+                rule.entry,
+                Some(format!("permissions.{}[{}]", rule.list, rule.index)),
+            ));
+        }
+    }
+}
+
+/// Whether permission rule `broad` matches everything `narrow` does:
+/// `Bash` covers `Bash(git push:*)`, and `Bash(git:*)` covers
+/// `Bash(git push:*)`.
+fn covers(broad: &str, narrow: &str) -> bool {
+    let (broad_tool, broad_spec) = split_rule(broad);
+    let (narrow_tool, narrow_spec) = split_rule(narrow);
+    if broad_tool != narrow_tool {
+        return false;
+    }
+    match (broad_spec, narrow_spec) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(b), Some(n)) if b == n => true,
+        (Some(b), Some(n)) => match b.strip_suffix(":*") {
+            Some(prefix) => n.trim_end_matches(":*").starts_with(prefix),
+            None => false,
+        },
+    }
+}
+
+/// `Bash(git push:*)` -> (`Bash`, Some(`git push:*`))
+fn split_rule(rule: &str) -> (&str, Option<&str>) {
+    match rule.split_once('(') {
+        Some((tool, rest)) => (tool, Some(rest.strip_suffix(')').unwrap_or(rest))),
+        None => (rule, None),
+    }
+}
+
+fn lint_memory(
+    level: &str,
+    project_dir: Option<&str>,
+    warnings: &mut Vec<ConfigWarning>,
+) -> Result<(), KataraError> {
+    let resolved = match memory::resolve_claude_md(level, project_dir) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            warnings.push(ConfigWarning {
+                severity: Severity::Error,
+                code: "unreadable_memory",
+                message: e.to_string(),
+                level: level.to_string(),
+                path: String::new(),
+                field: None,
+            });
+            return Ok(());
+        }
+    };
+    if !resolved.exists {
+        return Ok(());
+    }
+    let memory_warning = |severity, code, message| ConfigWarning {
+        severity,
+        code,
+        message,
+        level: level.to_string(),
+        path: resolved.path.clone(),
+        field: None,
+    };
+
+    let chars = resolved.resolved.chars().count();
+    if chars > LARGE_MEMORY_CHARS {
+        warnings.push(memory_warning(
+            Severity::Warning,
+            "large_memory_file",
+            format!(
+                "CLAUDE.md is {} characters with imports (over {}), which is loaded into \
+                 every session's context",
+                chars, LARGE_MEMORY_CHARS
+            ),
+        ));
+    }
+    for import in resolved.imports.iter().filter(|i| !i.resolved) {
+        warnings.push(memory_warning(
+            Severity::Warning,
+            "broken_import",
+            format!(
+                "@{} in {}: {}",
+                import.reference,
+                import.from,
+                import.error.as_deref().unwrap_or("not imported")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn warning(
+    severity: Severity,
+    code: &'static str,
+    message: String,
+    entry: &ClaudeSettingsEntry,
+    field: Option<String>,
+) -> ConfigWarning {
+    ConfigWarning {
+        severity,
+        code,
+        message,
+        level: entry.level.clone(),
+        path: entry.path.clone(),
+        field,
+    }
+}
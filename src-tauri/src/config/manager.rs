@@ -1,7 +1,23 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri_plugin_global_shortcut::Shortcut;
 
+use crate::config::claude_settings::PERMISSION_MODES;
+use crate::config::env_profiles::EnvProfile;
+use crate::config::keybindings::{self, Keybindings};
+use crate::config::themes::Theme;
 use crate::error::KataraError;
+use crate::plugins::host::PluginConfig;
+use crate::process::transcript;
+use crate::skills::runs;
+use crate::stats::activity;
+use crate::terminal::scrollback;
+use crate::terminal::shell::{ShellConfig, SshConfig};
+
+/// Serializes read-modify-write updates of the settings file.
+static SETTINGS_LOCK: Mutex<()> = Mutex::new(());
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudeMdEntry {
@@ -18,6 +34,531 @@ pub struct AppSettings {
     pub skills_directory: String,
     pub terminal_font_size: u16,
     pub terminal_font_family: String,
+    /// Permission mode for sessions spawned without one.
+    #[serde(default = "default_permission_mode")]
+    pub default_permission_mode: String,
+    /// Directory sessions start in when none is given.
+    #[serde(default)]
+    pub default_working_dir: Option<String>,
+    /// Claude CLI executable; `None` runs `claude` from PATH.
+    #[serde(default)]
+    pub claude_binary_path: Option<String>,
+    #[serde(default)]
+    pub server: ServerSettings,
+    #[serde(default)]
+    pub remote: RemoteSettings,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    #[serde(default)]
+    pub history: HistorySettings,
+    #[serde(default)]
+    pub updates: UpdateSettings,
+    /// Token prices by model family, for cost estimates. A model is priced
+    /// by the longest key its name contains, falling back to `sonnet`.
+    #[serde(default = "default_pricing")]
+    pub pricing: BTreeMap<String, ModelPricing>,
+    #[serde(default)]
+    pub terminal: TerminalSettings,
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    /// Custom palettes, selectable by name in `theme`.
+    #[serde(default)]
+    pub themes: Vec<Theme>,
+    /// Named env var sets for `spawn_session` and `spawn_terminal`.
+    #[serde(default)]
+    pub env_profiles: Vec<EnvProfile>,
+    /// Keyboard shortcuts by action id; see `config::keybindings`.
+    #[serde(default)]
+    pub keybindings: Keybindings,
+    /// System-wide shortcut that opens the quick prompt window, e.g.
+    /// `CommandOrControl+Shift+Space`. `None` disables it.
+    #[serde(default = "default_quick_prompt_shortcut")]
+    pub quick_prompt_shortcut: Option<String>,
+    /// Commit the working tree to `refs/katara/checkpoints/<session>` after
+    /// every turn, so agent edits can be rolled back with `rollback_to_turn`.
+    #[serde(default)]
+    pub checkpoints: bool,
+    /// External executables that add commands, receive CLI events and
+    /// review tool requests; see `plugins::protocol`. Started at launch.
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
+}
+
+fn default_permission_mode() -> String {
+    "default".into()
+}
+
+fn default_quick_prompt_shortcut() -> Option<String> {
+    Some("CommandOrControl+Shift+Space".into())
+}
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+fn default_pricing() -> BTreeMap<String, ModelPricing> {
+    let price = |input, output, cache_write, cache_read| ModelPricing {
+        input,
+        output,
+        cache_write,
+        cache_read,
+    };
+    BTreeMap::from([
+        ("opus".to_string(), price(15.0, 75.0, 18.75, 1.5)),
+        ("sonnet".to_string(), price(3.0, 15.0, 3.75, 0.30)),
+        ("haiku".to_string(), price(0.80, 4.0, 1.0, 0.08)),
+    ])
+}
+
+/// Hosts that never go through the proxy.
+const LOOPBACK_HOSTS: [&str; 2] = ["localhost", "127.0.0.1"];
+
+/// Network proxy for the Claude CLI and terminals, passed on as the
+/// standard environment variables.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProxySettings {
+    /// e.g. `http://proxy.corp:8080`
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+    #[serde(default)]
+    pub https_proxy: Option<String>,
+    /// Comma-separated hosts that bypass the proxy.
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+    /// PEM file with an extra CA to trust, for proxies that intercept TLS.
+    /// Added to Node's trusted CAs rather than replacing the system ones.
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+}
+
+impl ProxySettings {
+    /// Environment variables for a spawned process. Both cases of the proxy
+    /// variables are set since tools disagree on which one they read.
+    ///
+    /// `NO_PROXY` always includes loopback so the CLI reaches Katara's own
+    /// servers directly, even behind a proxy inherited from the environment.
+    pub fn env(&self) -> HashMap<String, String> {
+        let mut env = HashMap::new();
+        let mut no_proxy: Vec<&str> = self
+            .no_proxy
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|host| !host.is_empty())
+            .collect();
+        for host in LOOPBACK_HOSTS {
+            if !no_proxy.contains(&host) {
+                no_proxy.push(host);
+            }
+        }
+        let no_proxy = Some(no_proxy.join(","));
+        let vars = [
+            ("HTTP_PROXY", &self.http_proxy),
+            ("HTTPS_PROXY", &self.https_proxy),
+            ("NO_PROXY", &no_proxy),
+        ];
+        for (name, value) in vars {
+            if let Some(value) = value.as_deref().filter(|v| !v.is_empty()) {
+                env.insert(name.to_string(), value.to_string());
+                env.insert(name.to_lowercase(), value.to_string());
+            }
+        }
+        if let Some(ca) = self.ca_cert.as_deref().filter(|v| !v.is_empty()) {
+            env.insert("NODE_EXTRA_CA_CERTS".into(), ca.to_string());
+        }
+        env
+    }
+
+    fn validate(&self) -> Result<(), KataraError> {
+        for url in [&self.http_proxy, &self.https_proxy].into_iter().flatten() {
+            if url.is_empty() {
+                continue;
+            }
+            let parsed = reqwest::Url::parse(url)
+                .map_err(|e| KataraError::Config(format!("Invalid proxy URL {}: {}", url, e)))?;
+            if !matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") {
+                return Err(KataraError::Config(format!(
+                    "Unsupported proxy scheme: {}",
+                    parsed.scheme()
+                )));
+            }
+        }
+        if let Some(ca) = self.ca_cert.as_deref().filter(|v| !v.is_empty()) {
+            if !Path::new(ca).is_file() {
+                return Err(KataraError::Config(format!(
+                    "CA certificate {} not found",
+                    ca
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Ports for the local servers, read at startup. `None` picks a free port,
+/// as does a fixed port that's already in use. The ports in use are
+/// published in `~/.katara/instance.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerSettings {
+    /// WebSocket server the Claude CLI connects back to.
+    #[serde(default)]
+    pub ws_port: Option<u16>,
+    /// AG-UI HTTP server (CopilotKit runtimeUrl).
+    #[serde(default)]
+    pub agui_port: Option<u16>,
+    /// Give spawned sessions Katara's MCP server (`mcp__katara__*` tools),
+    /// so Claude can list sessions, open terminals and notify the user.
+    #[serde(default = "default_true")]
+    pub mcp_enabled: bool,
+    /// Also give them `open_terminal` and `run_command`, which run shell
+    /// commands without the CLI's permission prompts.
+    #[serde(default)]
+    pub mcp_shell_tools: bool,
+    /// Browser origins allowed to call the AG-UI server, like
+    /// `http://localhost:3000`. `*` allows any. Requests still need the
+    /// auth token.
+    #[serde(default = "default_allowed_origins")]
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            ws_port: None,
+            agui_port: None,
+            mcp_enabled: true,
+            mcp_shell_tools: false,
+            allowed_origins: default_allowed_origins(),
+        }
+    }
+}
+
+impl ServerSettings {
+    fn validate(&self) -> Result<(), KataraError> {
+        for origin in self.allowed_origins.iter().filter(|o| *o != "*") {
+            let parsed = reqwest::Url::parse(origin)
+                .map_err(|e| KataraError::Config(format!("Invalid origin {}: {}", origin, e)))?;
+            let bare = matches!(parsed.path(), "" | "/")
+                && parsed.query().is_none()
+                && parsed.fragment().is_none()
+                && parsed.host().is_some();
+            if !bare {
+                return Err(KataraError::Config(format!(
+                    "Origin {} should be just scheme, host and port",
+                    origin
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The app's own webview, in builds and under `tauri dev`.
+fn default_allowed_origins() -> Vec<String> {
+    ["tauri://localhost", "http://tauri.localhost", "http://localhost:1420"]
+        .map(String::from)
+        .to_vec()
+}
+
+/// Opt-in access from other devices (a phone, another machine) over TLS,
+/// for paired devices only. Read at startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Interface to listen on: `0.0.0.0` for all, or one LAN or tunnel
+    /// address.
+    #[serde(default = "default_remote_bind_address")]
+    pub bind_address: String,
+    #[serde(default = "default_remote_port")]
+    pub port: u16,
+    /// PEM certificate chain and private key. With neither set, a
+    /// self-signed certificate is generated on first use.
+    #[serde(default)]
+    pub tls_cert_path: Option<String>,
+    #[serde(default)]
+    pub tls_key_path: Option<String>,
+}
+
+impl Default for RemoteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: default_remote_bind_address(),
+            port: default_remote_port(),
+            tls_cert_path: None,
+            tls_key_path: None,
+        }
+    }
+}
+
+impl RemoteSettings {
+    fn validate(&self) -> Result<(), KataraError> {
+        self.bind_address
+            .parse::<std::net::IpAddr>()
+            .map_err(|_| {
+                KataraError::Config(format!(
+                    "Invalid remote bind address: {}",
+                    self.bind_address
+                ))
+            })?;
+        let set = |p: &Option<String>| p.as_deref().is_some_and(|p| !p.is_empty());
+        if set(&self.tls_cert_path) != set(&self.tls_key_path) {
+            return Err(KataraError::Config(
+                "Remote TLS needs both a certificate and a key".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn default_remote_bind_address() -> String {
+    "0.0.0.0".into()
+}
+
+fn default_remote_port() -> u16 {
+    7443
+}
+
+/// OS notifications for sessions, shown while the window is unfocused.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    /// Notify when a session finishes a turn.
+    #[serde(default)]
+    pub session_finished: bool,
+    /// Notify when a session asks to use a tool.
+    #[serde(default)]
+    pub permission_requests: bool,
+}
+
+/// How much history is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySettings {
+    /// Messages kept per session, oldest dropped first. `None` keeps all.
+    #[serde(default)]
+    pub max_messages_per_session: Option<usize>,
+    /// Skill runs kept in the run history.
+    #[serde(default = "default_max_skill_runs")]
+    pub max_skill_runs: usize,
+    /// Days of activity kept for the dashboard.
+    #[serde(default = "default_activity_retention_days")]
+    pub activity_retention_days: u64,
+    /// Write each session's messages to disk as they arrive, so past
+    /// conversations can be reopened after a restart.
+    #[serde(default = "default_true")]
+    pub persist_transcripts: bool,
+    /// Days a saved conversation is kept after its last message.
+    #[serde(default = "default_transcript_retention_days")]
+    pub transcript_retention_days: u64,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            max_messages_per_session: None,
+            max_skill_runs: default_max_skill_runs(),
+            activity_retention_days: default_activity_retention_days(),
+            persist_transcripts: true,
+            transcript_retention_days: default_transcript_retention_days(),
+        }
+    }
+}
+
+fn default_max_skill_runs() -> usize {
+    runs::DEFAULT_MAX_RUNS
+}
+
+fn default_activity_retention_days() -> u64 {
+    activity::DEFAULT_RETENTION_DAYS
+}
+
+fn default_transcript_retention_days() -> u64 {
+    transcript::DEFAULT_RETENTION_DAYS
+}
+
+/// Release channels `updates.channel` can be set to.
+pub const UPDATE_CHANNELS: &[&str] = &["stable", "beta"];
+
+/// App updates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSettings {
+    /// `stable` or `beta`.
+    #[serde(default = "default_update_channel")]
+    pub channel: String,
+    /// Check for an update when the app starts.
+    #[serde(default = "default_true")]
+    pub check_on_startup: bool,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            channel: default_update_channel(),
+            check_on_startup: true,
+        }
+    }
+}
+
+fn default_update_channel() -> String {
+    "stable".into()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Terminal defaults applied when `spawn_terminal` doesn't override them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalSettings {
+    /// Shell to launch; `None` uses the system default shell.
+    #[serde(default)]
+    pub default_shell: Option<ShellConfig>,
+    /// Show an OS notification when a terminal rings the bell while the
+    /// window is unfocused.
+    #[serde(default)]
+    pub bell_notifications: bool,
+    #[serde(default)]
+    pub profiles: Vec<TerminalProfile>,
+    /// Lines of scrollback kept per terminal.
+    #[serde(default = "default_scrollback_lines")]
+    pub scrollback_lines: usize,
+    /// Optional cap on each terminal's scrollback and replay buffer, in
+    /// bytes.
+    #[serde(default)]
+    pub scrollback_bytes: Option<usize>,
+    /// Optional cap on scrollback across all terminals, in bytes. Split
+    /// evenly between open terminals.
+    #[serde(default)]
+    pub total_scrollback_bytes: Option<usize>,
+}
+
+impl Default for TerminalSettings {
+    fn default() -> Self {
+        Self {
+            default_shell: None,
+            bell_notifications: false,
+            profiles: Vec::new(),
+            scrollback_lines: default_scrollback_lines(),
+            scrollback_bytes: None,
+            total_scrollback_bytes: None,
+        }
+    }
+}
+
+fn default_scrollback_lines() -> usize {
+    scrollback::DEFAULT_MAX_LINES
+}
+
+/// A named terminal preset, selectable via `spawn_terminal`'s `profile`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalProfile {
+    pub name: String,
+    #[serde(default)]
+    pub shell: Option<ShellConfig>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Icon and color are opaque to the backend; the UI decides their format.
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Run the terminal on a remote host instead. Replaces `shell`, and
+    /// `cwd` is ignored in favour of `ssh.remote_cwd`.
+    #[serde(default)]
+    pub ssh: Option<SshConfig>,
+}
+
+impl TerminalProfile {
+    fn validate(&self) -> Result<(), KataraError> {
+        if self.name.trim().is_empty() {
+            return Err(KataraError::Config("Profile name is required".into()));
+        }
+        if self.ssh.as_ref().is_some_and(|s| s.host.trim().is_empty()) {
+            return Err(KataraError::Config("SSH profile requires a host".into()));
+        }
+        Ok(())
+    }
+}
+
+impl AppSettings {
+    /// Prices for `model`; see `pricing`.
+    pub fn pricing_for(&self, model: &str) -> ModelPricing {
+        self.pricing
+            .iter()
+            .filter(|(family, _)| model.contains(family.as_str()))
+            .max_by_key(|(family, _)| family.len())
+            .or_else(|| self.pricing.get_key_value("sonnet"))
+            .map(|(_, price)| *price)
+            .unwrap_or(default_pricing()["sonnet"])
+    }
+
+    fn validate(&self) -> Result<(), KataraError> {
+        if !PERMISSION_MODES.contains(&self.default_permission_mode.as_str()) {
+            return Err(KataraError::Config(format!(
+                "Unknown permission mode: {}",
+                self.default_permission_mode
+            )));
+        }
+        if self.history.max_messages_per_session == Some(0) {
+            return Err(KataraError::Config(
+                "max_messages_per_session must be at least 1".into(),
+            ));
+        }
+        for (i, theme) in self.themes.iter().enumerate() {
+            theme.validate()?;
+            if self.themes[..i].iter().any(|t| t.name == theme.name) {
+                return Err(KataraError::Config(format!(
+                    "Theme {} is defined twice",
+                    theme.name
+                )));
+            }
+        }
+        if let Some(shortcut) = self.quick_prompt_shortcut.as_deref().filter(|s| !s.is_empty()) {
+            shortcut.parse::<Shortcut>().map_err(|e| {
+                KataraError::Config(format!("Invalid quick prompt shortcut {}: {}", shortcut, e))
+            })?;
+        }
+        if !UPDATE_CHANNELS.contains(&self.updates.channel.as_str()) {
+            return Err(KataraError::Config(format!(
+                "Unknown update channel: {} (expected {})",
+                self.updates.channel,
+                UPDATE_CHANNELS.join(" or ")
+            )));
+        }
+        self.proxy.validate()?;
+        self.server.validate()?;
+        self.remote.validate()?;
+        for profile in &self.terminal.profiles {
+            profile.validate()?;
+        }
+        for (i, plugin) in self.plugins.iter().enumerate() {
+            plugin.validate()?;
+            if self.plugins[..i].iter().any(|p| p.name == plugin.name) {
+                return Err(KataraError::Config(format!(
+                    "Plugin {} is defined twice",
+                    plugin.name
+                )));
+            }
+        }
+        for (i, profile) in self.env_profiles.iter().enumerate() {
+            profile.validate()?;
+            if self.env_profiles[..i].iter().any(|p| p.name == profile.name) {
+                return Err(KataraError::Config(format!(
+                    "Env profile {} is defined twice",
+                    profile.name
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for AppSettings {
@@ -33,6 +574,23 @@ impl Default for AppSettings {
             skills_directory: skills_dir.display().to_string(),
             terminal_font_size: 14,
             terminal_font_family: "Consolas, Monaco, 'Courier New', monospace".into(),
+            default_permission_mode: default_permission_mode(),
+            default_working_dir: None,
+            claude_binary_path: None,
+            server: ServerSettings::default(),
+            remote: RemoteSettings::default(),
+            notifications: NotificationSettings::default(),
+            history: HistorySettings::default(),
+            updates: UpdateSettings::default(),
+            pricing: default_pricing(),
+            terminal: TerminalSettings::default(),
+            proxy: ProxySettings::default(),
+            themes: Vec::new(),
+            env_profiles: Vec::new(),
+            keybindings: Keybindings::new(),
+            quick_prompt_shortcut: default_quick_prompt_shortcut(),
+            checkpoints: false,
+            plugins: Vec::new(),
         }
     }
 }
@@ -75,8 +633,9 @@ pub fn read_settings() -> Result<AppSettings, KataraError> {
     }
 }
 
-/// Write application settings to the config directory.
-pub fn write_settings(settings: &AppSettings) -> Result<(), KataraError> {
+/// Write application settings to the config directory. Only for
+/// [`modify_settings`], which validates them and serializes updates.
+fn write_settings(settings: &AppSettings) -> Result<(), KataraError> {
     let path = settings_path();
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(KataraError::Io)?;
@@ -86,6 +645,136 @@ pub fn write_settings(settings: &AppSettings) -> Result<(), KataraError> {
     Ok(())
 }
 
+/// Merge a partial update into the stored settings and save them.
+///
+/// `patch` is a JSON merge patch: objects merge recursively, other values
+/// replace, and `null` resets a field to its default. The result must still
+/// be valid settings, otherwise nothing is written.
+pub fn update_settings(patch: &serde_json::Value) -> Result<AppSettings, KataraError> {
+    modify_settings(|settings| {
+        let current = serde_json::to_value(&*settings).map_err(KataraError::Serde)?;
+        let defaults = serde_json::to_value(AppSettings::default()).map_err(KataraError::Serde)?;
+        let merged = fill_defaults(merge_patch(current, patch), &defaults);
+        *settings = serde_json::from_value(merged)
+            .map_err(|e| KataraError::Config(format!("Invalid settings: {}", e)))?;
+        Ok(())
+    })
+}
+
+/// Read the settings, change them with `f`, validate and save them, with no
+/// other update in between. Nothing is written if `f` or validation fails.
+pub fn modify_settings(
+    f: impl FnOnce(&mut AppSettings) -> Result<(), KataraError>,
+) -> Result<AppSettings, KataraError> {
+    let _guard = SETTINGS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut settings = read_settings()?;
+    f(&mut settings)?;
+    settings.validate()?;
+    settings.keybindings = keybindings::validate(&settings.keybindings)?;
+    write_settings(&settings)?;
+    Ok(settings)
+}
+
+/// RFC 7386 JSON merge patch.
+fn merge_patch(target: serde_json::Value, patch: &serde_json::Value) -> serde_json::Value {
+    let serde_json::Value::Object(patch) = patch else {
+        return patch.clone();
+    };
+    let mut target = match target {
+        serde_json::Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            let current = target.remove(key).unwrap_or(serde_json::Value::Null);
+            target.insert(key.clone(), merge_patch(current, value));
+        }
+    }
+    serde_json::Value::Object(target)
+}
+
+/// Put back default values for fields a patch removed.
+fn fill_defaults(value: serde_json::Value, defaults: &serde_json::Value) -> serde_json::Value {
+    let (mut map, defaults) = match (value, defaults) {
+        (serde_json::Value::Object(map), serde_json::Value::Object(defaults)) => (map, defaults),
+        (value, _) => return value,
+    };
+    for (key, default) in defaults {
+        let filled = match map.remove(key) {
+            Some(current) => fill_defaults(current, default),
+            None => default.clone(),
+        };
+        map.insert(key.clone(), filled);
+    }
+    serde_json::Value::Object(map)
+}
+
+/// Look up a terminal profile by name.
+pub fn get_terminal_profile(name: &str) -> Result<TerminalProfile, KataraError> {
+    read_settings()?
+        .terminal
+        .profiles
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| KataraError::Config(format!("Terminal profile {} not found", name)))
+}
+
+/// Add a new terminal profile. Names must be unique.
+pub fn create_terminal_profile(profile: TerminalProfile) -> Result<AppSettings, KataraError> {
+    profile.validate()?;
+    modify_settings(|settings| {
+        let profiles = &mut settings.terminal.profiles;
+        if profiles.iter().any(|p| p.name == profile.name) {
+            return Err(KataraError::Config(format!(
+                "Terminal profile {} already exists",
+                profile.name
+            )));
+        }
+        profiles.push(profile);
+        Ok(())
+    })
+}
+
+/// Replace the profile called `name`; `profile.name` may rename it.
+pub fn update_terminal_profile(
+    name: &str,
+    profile: TerminalProfile,
+) -> Result<AppSettings, KataraError> {
+    profile.validate()?;
+    modify_settings(|settings| {
+        let profiles = &mut settings.terminal.profiles;
+        if profile.name != name && profiles.iter().any(|p| p.name == profile.name) {
+            return Err(KataraError::Config(format!(
+                "Terminal profile {} already exists",
+                profile.name
+            )));
+        }
+        let slot = profiles
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| KataraError::Config(format!("Terminal profile {} not found", name)))?;
+        *slot = profile;
+        Ok(())
+    })
+}
+
+/// Remove the profile called `name`.
+pub fn delete_terminal_profile(name: &str) -> Result<AppSettings, KataraError> {
+    modify_settings(|settings| {
+        let before = settings.terminal.profiles.len();
+        settings.terminal.profiles.retain(|p| p.name != name);
+        if settings.terminal.profiles.len() == before {
+            return Err(KataraError::Config(format!(
+                "Terminal profile {} not found",
+                name
+            )));
+        }
+        Ok(())
+    })
+}
+
 fn resolve_claude_md_path(level: &str, project_dir: Option<&str>) -> Result<PathBuf, KataraError> {
     match level {
         "user" => Ok(dirs::home_dir()
@@ -116,7 +805,7 @@ fn resolve_claude_md_path(level: &str, project_dir: Option<&str>) -> Result<Path
     }
 }
 
-fn settings_path() -> PathBuf {
+pub fn settings_path() -> PathBuf {
     dirs::config_dir()
         .unwrap_or_default()
         .join("katara")
@@ -0,0 +1,365 @@
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::config::manager;
+use crate::error::KataraError;
+
+/// How deep `@` imports are followed, matching the CLI.
+const MAX_IMPORT_DEPTH: usize = 5;
+
+/// A CLAUDE.md with its `@path` imports expanded.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedClaudeMd {
+    pub level: String,
+    pub path: String,
+    pub exists: bool,
+    /// The file as written.
+    pub raw: String,
+    /// What Claude receives: every import replaced by the imported file.
+    pub resolved: String,
+    /// Each import encountered, in order, including the ones that failed.
+    pub imports: Vec<ClaudeMdImport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaudeMdImport {
+    /// The reference as written after `@`.
+    pub reference: String,
+    pub path: String,
+    /// File containing the reference.
+    pub from: String,
+    /// 1 for imports in the CLAUDE.md itself.
+    pub depth: usize,
+    pub resolved: bool,
+    /// Why the import was left as written (missing file, cycle, too deep).
+    pub error: Option<String>,
+}
+
+/// One CLAUDE.md level with file details, for the memory hierarchy view.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryLevel {
+    pub level: String,
+    pub path: String,
+    pub exists: bool,
+    pub content: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// Unix timestamp (seconds) of the last modification.
+    pub modified_at: Option<u64>,
+    /// The AGENTS.md next to the CLAUDE.md.
+    pub agents_md_path: String,
+    pub agents_md_exists: bool,
+    /// How the two files relate; see `MemoryLink`.
+    pub link: MemoryLink,
+}
+
+/// How a level's CLAUDE.md and AGENTS.md relate.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryLink {
+    /// Neither file exists.
+    None,
+    ClaudeMdOnly,
+    AgentsMdOnly,
+    /// AGENTS.md is a symlink to CLAUDE.md.
+    AgentsMdLinked,
+    /// CLAUDE.md is a symlink to AGENTS.md.
+    ClaudeMdLinked,
+    /// Separate files with the same content.
+    Identical,
+    /// Separate files that differ.
+    Differs,
+}
+
+/// All CLAUDE.md levels, from lowest to highest precedence: enterprise,
+/// user and, with a project, project and local.
+pub fn memory_overview(project_dir: Option<&str>) -> Result<Vec<MemoryLevel>, KataraError> {
+    let mut levels = vec!["enterprise", "user"];
+    if project_dir.is_some() {
+        levels.extend(["project", "local"]);
+    }
+
+    let mut overview = Vec::new();
+    for level in levels {
+        let entry = manager::read_claude_md(level, project_dir)?;
+        let metadata = std::fs::metadata(&entry.path).ok();
+        let modified_at = metadata
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        let agents_md = agents_md_path(level, project_dir)?;
+        overview.push(MemoryLevel {
+            link: memory_link(Path::new(&entry.path), &agents_md),
+            level: entry.level,
+            path: entry.path,
+            exists: entry.exists,
+            content: entry.content,
+            size: metadata.map_or(0, |m| m.len()),
+            modified_at,
+            agents_md_exists: agents_md.exists(),
+            agents_md_path: agents_md.display().to_string(),
+        });
+    }
+    Ok(overview)
+}
+
+/// The AGENTS.md at a level: in the same directory as its CLAUDE.md, so
+/// tools that read either file find one at every level.
+pub fn agents_md_path(level: &str, project_dir: Option<&str>) -> Result<PathBuf, KataraError> {
+    let entry = manager::read_claude_md(level, project_dir)?;
+    Ok(Path::new(&entry.path).with_file_name("AGENTS.md"))
+}
+
+/// Read the AGENTS.md at a level.
+pub fn read_agents_md(
+    level: &str,
+    project_dir: Option<&str>,
+) -> Result<manager::ClaudeMdEntry, KataraError> {
+    let path = agents_md_path(level, project_dir)?;
+    let exists = path.exists();
+    let content = if exists {
+        std::fs::read_to_string(&path).map_err(KataraError::Io)?
+    } else {
+        String::new()
+    };
+    Ok(manager::ClaudeMdEntry {
+        level: level.to_string(),
+        path: path.display().to_string(),
+        content,
+        exists,
+    })
+}
+
+/// Make one of a level's memory files mirror the other.
+///
+/// `source` (`claude_md` or `agents_md`) is the file to keep; the other one
+/// becomes a symlink to it (`mode` `symlink`) or a copy of it (`copy`). An
+/// existing target with different content is only replaced with
+/// `overwrite`. Symlinks may need extra privileges on Windows; use a copy
+/// there if creating one fails.
+pub fn link_memory_files(
+    level: &str,
+    project_dir: Option<&str>,
+    source: &str,
+    mode: &str,
+    overwrite: bool,
+) -> Result<MemoryLevel, KataraError> {
+    let claude_md = PathBuf::from(manager::read_claude_md(level, project_dir)?.path);
+    let agents_md = agents_md_path(level, project_dir)?;
+    let (from, to) = match source {
+        "claude_md" => (claude_md, agents_md),
+        "agents_md" => (agents_md, claude_md),
+        _ => {
+            return Err(KataraError::Config(format!(
+                "Unknown memory file: {}",
+                source
+            )))
+        }
+    };
+    if !from.is_file() {
+        return Err(KataraError::Config(format!(
+            "{} does not exist",
+            from.display()
+        )));
+    }
+    if let Ok(meta) = std::fs::symlink_metadata(&to) {
+        let same = std::fs::read(&to).ok() == std::fs::read(&from).ok();
+        if !same && !overwrite {
+            return Err(KataraError::Config(format!(
+                "{} already exists with different content",
+                to.display()
+            )));
+        }
+        if meta.is_dir() {
+            return Err(KataraError::Config(format!(
+                "{} is a directory",
+                to.display()
+            )));
+        }
+        std::fs::remove_file(&to).map_err(KataraError::Io)?;
+    }
+
+    match mode {
+        // Both files share a directory, so a relative link stays valid if
+        // the project moves
+        "symlink" => {
+            let target = from.file_name().map(PathBuf::from).unwrap_or(from.clone());
+            symlink_file(&target, &to).map_err(KataraError::Io)?;
+        }
+        "copy" => {
+            std::fs::copy(&from, &to).map_err(KataraError::Io)?;
+        }
+        _ => return Err(KataraError::Config(format!("Unknown link mode: {}", mode))),
+    }
+
+    memory_overview(project_dir)?
+        .into_iter()
+        .find(|l| l.level == level)
+        .ok_or_else(|| KataraError::Config(format!("Unknown level: {}", level)))
+}
+
+fn memory_link(claude_md: &Path, agents_md: &Path) -> MemoryLink {
+    let is_link_to = |link: &Path, target: &Path| {
+        std::fs::symlink_metadata(link).is_ok_and(|m| m.file_type().is_symlink())
+            && target.exists()
+            && canonical(link) == canonical(target)
+    };
+    match (claude_md.exists(), agents_md.exists()) {
+        (false, false) => MemoryLink::None,
+        (true, false) => MemoryLink::ClaudeMdOnly,
+        (false, true) => MemoryLink::AgentsMdOnly,
+        _ if is_link_to(agents_md, claude_md) => MemoryLink::AgentsMdLinked,
+        _ if is_link_to(claude_md, agents_md) => MemoryLink::ClaudeMdLinked,
+        _ if std::fs::read(claude_md).ok() == std::fs::read(agents_md).ok() => {
+            MemoryLink::Identical
+        }
+        _ => MemoryLink::Differs,
+    }
+}
+
+#[cfg(unix)]
+fn symlink_file(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink_file(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+/// Read a CLAUDE.md and expand its imports recursively.
+///
+/// `@path` imports a file relative to the importing file (`@~/...` from the
+/// home directory). References inside code spans and fenced blocks are not
+/// imports. Missing files, cycles and imports nested deeper than
+/// `MAX_IMPORT_DEPTH` are left as written and reported in `imports`.
+pub fn resolve_claude_md(
+    level: &str,
+    project_dir: Option<&str>,
+) -> Result<ResolvedClaudeMd, KataraError> {
+    let entry = manager::read_claude_md(level, project_dir)?;
+    let path = PathBuf::from(&entry.path);
+    let mut imports = Vec::new();
+    let resolved = if entry.exists {
+        let mut stack = vec![canonical(&path)];
+        expand(&entry.content, &path, 1, &mut stack, &mut imports)
+    } else {
+        String::new()
+    };
+
+    Ok(ResolvedClaudeMd {
+        level: entry.level,
+        path: entry.path,
+        exists: entry.exists,
+        raw: entry.content,
+        resolved,
+        imports,
+    })
+}
+
+fn expand(
+    content: &str,
+    file: &Path,
+    depth: usize,
+    stack: &mut Vec<PathBuf>,
+    imports: &mut Vec<ClaudeMdImport>,
+) -> String {
+    let reference = Regex::new(r"(^|\s)@([^\s`]+)").expect("import pattern is valid");
+    let base = file.parent().unwrap_or(Path::new("."));
+    let mut in_fence = false;
+    let mut lines = Vec::new();
+
+    for line in content.split('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            lines.push(line.to_string());
+            continue;
+        }
+        if in_fence {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        // Odd segments between backticks are code spans
+        let segments: Vec<String> = line
+            .split('`')
+            .enumerate()
+            .map(|(i, segment)| {
+                if i % 2 == 1 {
+                    return segment.to_string();
+                }
+                reference
+                    .replace_all(segment, |c: &regex::Captures| {
+                        let target = c[2].to_string();
+                        match import(&target, base, file, depth, stack, imports) {
+                            Some(text) => format!("{}{}", &c[1], text),
+                            None => c[0].to_string(),
+                        }
+                    })
+                    .into_owned()
+            })
+            .collect();
+        lines.push(segments.join("`"));
+    }
+    lines.join("\n")
+}
+
+/// Expand one import, or `None` to leave the reference as written.
+fn import(
+    target: &str,
+    base: &Path,
+    from: &Path,
+    depth: usize,
+    stack: &mut Vec<PathBuf>,
+    imports: &mut Vec<ClaudeMdImport>,
+) -> Option<String> {
+    let path = match target.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => base.join(target),
+    };
+    let mut record = ClaudeMdImport {
+        reference: target.to_string(),
+        path: path.display().to_string(),
+        from: from.display().to_string(),
+        depth,
+        resolved: false,
+        error: None,
+    };
+
+    let error = if depth > MAX_IMPORT_DEPTH {
+        Some(format!("Nested deeper than {} imports", MAX_IMPORT_DEPTH))
+    } else if !path.is_file() {
+        Some("File not found".to_string())
+    } else if stack.contains(&canonical(&path)) {
+        Some("Import cycle".to_string())
+    } else {
+        None
+    };
+    if let Some(error) = error {
+        record.error = Some(error);
+        imports.push(record);
+        return None;
+    }
+
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            record.error = Some(e.to_string());
+            imports.push(record);
+            return None;
+        }
+    };
+    record.resolved = true;
+    imports.push(record);
+
+    stack.push(canonical(&path));
+    let expanded = expand(&content, &path, depth + 1, stack, imports);
+    stack.pop();
+    Some(expanded.trim_end().to_string())
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -1 +1,43 @@
+pub mod agents;
+pub mod backup;
+pub mod claude_settings;
+pub mod env_profiles;
+pub mod hooks;
+pub mod init;
+pub mod keybindings;
+pub mod lint;
 pub mod manager;
+pub mod memory;
+pub mod output_styles;
+pub mod recent;
+pub mod secrets;
+pub mod settings_diff;
+pub mod themes;
+pub mod watcher;
+
+use std::path::{Path, PathBuf};
+
+use crate::error::KataraError;
+
+/// Katara's own directory under the OS config directory, where settings
+/// and everything persisted across restarts live. An error on platforms
+/// without a config directory, rather than writing relative to the cwd.
+pub fn katara_dir() -> Result<PathBuf, KataraError> {
+    dirs::config_dir()
+        .map(|dir| dir.join("katara"))
+        .ok_or_else(|| KataraError::Config("No config directory for this user".into()))
+}
+
+/// Create `path` for writing, readable only by the user on Unix, for files
+/// that hold tokens. Fails if it already exists, so a file or symlink
+/// planted there isn't written through.
+pub fn create_private_file(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
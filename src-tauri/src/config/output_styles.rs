@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+use std::path::{Path, PathBuf};
+
+use crate::error::KataraError;
+
+/// Styles built into the CLI, selectable without a file.
+const BUILTIN_STYLES: &[(&str, &str)] = &[
+    ("default", "Claude's standard software engineering behavior"),
+    (
+        "Explanatory",
+        "Explains implementation choices and codebase patterns while working",
+    ),
+    (
+        "Learning",
+        "Collaborative mode that asks you to write small pieces of code yourself",
+    ),
+];
+
+/// A Claude Code output style: a markdown file whose body replaces (or adds
+/// to) the system prompt's response-format instructions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputStyle {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Keep the CLI's coding instructions alongside the style's own.
+    #[serde(default)]
+    pub keep_coding_instructions: bool,
+    #[serde(default)]
+    pub prompt: String,
+    /// `builtin`, `user` or `project`; filled in when read.
+    #[serde(default)]
+    pub level: String,
+    /// Empty for built-in styles.
+    #[serde(default)]
+    pub path: String,
+}
+
+#[derive(Deserialize)]
+struct Frontmatter {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default, rename = "keep-coding-instructions")]
+    keep_coding_instructions: bool,
+}
+
+/// Built-in styles, then user styles and, with a project, project styles.
+pub fn list_output_styles(project_dir: Option<&str>) -> Result<Vec<OutputStyle>, KataraError> {
+    let mut styles: Vec<OutputStyle> = BUILTIN_STYLES
+        .iter()
+        .map(|(name, description)| OutputStyle {
+            name: name.to_string(),
+            description: description.to_string(),
+            keep_coding_instructions: true,
+            prompt: String::new(),
+            level: "builtin".into(),
+            path: String::new(),
+        })
+        .collect();
+    styles.extend(styles_in(&styles_dir("user", None)?)?);
+    if let Some(dir) = project_dir {
+        styles.extend(styles_in(&styles_dir("project", Some(dir))?)?);
+    }
+    Ok(styles)
+}
+
+/// Read and parse one output style file.
+pub fn read_output_style(path: &str) -> Result<OutputStyle, KataraError> {
+    let content = std::fs::read_to_string(path).map_err(KataraError::Io)?;
+    parse_style(&content, path)
+}
+
+/// Save an output style at `user` or `project` level, in a file named after
+/// the style.
+pub fn write_output_style(
+    level: &str,
+    project_dir: Option<&str>,
+    style: &OutputStyle,
+) -> Result<OutputStyle, KataraError> {
+    let name = style.name.trim();
+    if name.is_empty() {
+        return Err(KataraError::Config("Output style name is required".into()));
+    }
+    if BUILTIN_STYLES
+        .iter()
+        .any(|(b, _)| b.eq_ignore_ascii_case(name))
+    {
+        return Err(KataraError::Config(format!(
+            "{} is a built-in output style",
+            name
+        )));
+    }
+    let file_name = file_stem(name);
+    if file_name.is_empty() {
+        return Err(KataraError::Config(format!(
+            "Can't derive a file name from \"{}\"",
+            name
+        )));
+    }
+
+    let mut frontmatter = Mapping::new();
+    frontmatter.insert("name".into(), name.into());
+    frontmatter.insert("description".into(), style.description.clone().into());
+    if style.keep_coding_instructions {
+        frontmatter.insert("keep-coding-instructions".into(), true.into());
+    }
+    let yaml =
+        serde_yaml::to_string(&frontmatter).map_err(|e| KataraError::Config(e.to_string()))?;
+    let content = format!("---\n{}---\n\n{}\n", yaml, style.prompt.trim());
+
+    let dir = styles_dir(level, project_dir)?;
+    std::fs::create_dir_all(&dir).map_err(KataraError::Io)?;
+    let path = dir.join(format!("{}.md", file_name));
+    std::fs::write(&path, content).map_err(KataraError::Io)?;
+    read_output_style(&path.display().to_string())
+}
+
+/// Delete an output style file. Only files directly in the user styles
+/// directory or `project_dir`'s can be deleted.
+pub fn delete_output_style(path: &str, project_dir: Option<&str>) -> Result<(), KataraError> {
+    let not_a_style = || KataraError::Config(format!("Not an output style file: {}", path));
+    if Path::new(path).extension().is_none_or(|e| e != "md") {
+        return Err(not_a_style());
+    }
+    let path = Path::new(path).canonicalize().map_err(KataraError::Io)?;
+    let parent = path.parent().ok_or_else(not_a_style)?;
+    let in_styles_dir = ["user", "project"].iter().any(|level| {
+        styles_dir(level, project_dir)
+            .and_then(|dir| dir.canonicalize().map_err(KataraError::Io))
+            .is_ok_and(|dir| dir == parent)
+    });
+    if !in_styles_dir {
+        return Err(not_a_style());
+    }
+    std::fs::remove_file(path).map_err(KataraError::Io)
+}
+
+/// Directory holding output styles at `user` or `project` level.
+pub fn styles_dir(level: &str, project_dir: Option<&str>) -> Result<PathBuf, KataraError> {
+    match level {
+        "user" => Ok(dirs::home_dir()
+            .unwrap_or_default()
+            .join(".claude")
+            .join("output-styles")),
+        "project" => {
+            let dir = project_dir.ok_or(KataraError::Config("No project directory".into()))?;
+            Ok(Path::new(dir).join(".claude").join("output-styles"))
+        }
+        _ => Err(KataraError::Config(format!("Unknown level: {}", level))),
+    }
+}
+
+fn styles_in(dir: &Path) -> Result<Vec<OutputStyle>, KataraError> {
+    let pattern = format!("{}/*.md", glob::Pattern::escape(&dir.display().to_string()));
+    let mut styles = Vec::new();
+    for path in glob::glob(&pattern)
+        .map_err(|e| KataraError::Config(e.to_string()))?
+        .filter_map(Result::ok)
+    {
+        let content = std::fs::read_to_string(&path).map_err(KataraError::Io)?;
+        if let Ok(style) = parse_style(&content, &path.display().to_string()) {
+            styles.push(style);
+        }
+    }
+    styles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(styles)
+}
+
+/// Frontmatter is optional; without a `name` the file name is used, as the
+/// CLI does.
+fn parse_style(content: &str, path: &str) -> Result<OutputStyle, KataraError> {
+    let (frontmatter, body) = match content.trim_start().strip_prefix("---") {
+        Some(after_first) => {
+            let end_idx = after_first
+                .find("\n---")
+                .ok_or_else(|| KataraError::Config("Unclosed frontmatter".into()))?;
+            let frontmatter: Frontmatter =
+                serde_yaml::from_str(&after_first[..end_idx]).map_err(|e| {
+                    KataraError::Config(format!("Invalid output style frontmatter: {}", e))
+                })?;
+            (Some(frontmatter), &after_first[end_idx + 4..])
+        }
+        None => (None, content),
+    };
+
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (name, description, keep_coding_instructions) = match frontmatter {
+        Some(f) => (
+            f.name.unwrap_or(stem),
+            f.description,
+            f.keep_coding_instructions,
+        ),
+        None => (stem, String::new(), false),
+    };
+    let level = if Path::new(path).starts_with(styles_dir("user", None)?) {
+        "user"
+    } else {
+        "project"
+    };
+    Ok(OutputStyle {
+        name,
+        description,
+        keep_coding_instructions,
+        prompt: body.trim().to_string(),
+        level: level.to_string(),
+        path: path.to_string(),
+    })
+}
+
+/// `My Style!` -> `my-style`
+fn file_stem(name: &str) -> String {
+    let mut stem = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            stem.extend(c.to_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    stem.trim_end_matches('-').to_string()
+}
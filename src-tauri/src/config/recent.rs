@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use crate::error::KataraError;
+
+/// Number of project directories remembered.
+const MAX_RECENT: usize = 10;
+
+/// Project directories sessions were recently started in, most recent
+/// first. Errors are logged and yield an empty list.
+pub fn recent_projects() -> Vec<String> {
+    let Ok(path) = store_path() else {
+        return Vec::new();
+    };
+    if !path.exists() {
+        return Vec::new();
+    }
+    std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            eprintln!("[katara] Failed to load recent projects: {}", e);
+            Vec::new()
+        })
+}
+
+/// Move `dir` to the front of the recent projects.
+pub fn add_recent_project(dir: &str) {
+    let mut projects = recent_projects();
+    projects.retain(|p| p != dir);
+    projects.insert(0, dir.to_string());
+    projects.truncate(MAX_RECENT);
+
+    if let Err(e) = write(&projects) {
+        eprintln!("[katara] Failed to save recent projects: {}", e);
+    }
+}
+
+fn write(projects: &[String]) -> Result<(), String> {
+    let path = store_path().map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(projects).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+fn store_path() -> Result<PathBuf, KataraError> {
+    Ok(crate::config::katara_dir()?.join("recent_projects.json"))
+}
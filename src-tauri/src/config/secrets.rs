@@ -0,0 +1,113 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::config::env_profiles::EnvValue;
+use crate::config::manager;
+use crate::error::KataraError;
+
+/// Keychain service name all Katara secrets are stored under.
+const SERVICE: &str = "katara";
+
+/// Serializes updates of the name index.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Store a secret in the OS keychain, replacing any value under `name`.
+pub fn set_secret(name: &str, value: &str) -> Result<(), KataraError> {
+    validate_name(name)?;
+    entry(name)?.set_password(value).map_err(keychain_error)?;
+    update_index(|names| {
+        names.insert(name.to_string());
+    })
+}
+
+/// Read a secret from the OS keychain.
+pub fn get_secret(name: &str) -> Result<String, KataraError> {
+    entry(name)?.get_password().map_err(|e| match e {
+        keyring::Error::NoEntry => KataraError::Config(format!("Secret {} not found", name)),
+        e => keychain_error(e),
+    })
+}
+
+/// Names of the stored secrets. Keychains can't be listed portably, so
+/// the names are kept in `secrets.json`; values never leave the keychain.
+pub fn get_secret_names() -> Result<Vec<String>, KataraError> {
+    Ok(read_index()?.into_iter().collect())
+}
+
+/// Delete a secret. Fails while an env profile still references it.
+pub fn delete_secret(name: &str) -> Result<(), KataraError> {
+    let users: Vec<String> = manager::read_settings()?
+        .env_profiles
+        .into_iter()
+        .filter(|p| {
+            p.env
+                .values()
+                .any(|v| matches!(v, EnvValue::Secret { secret } if secret == name))
+        })
+        .map(|p| p.name)
+        .collect();
+    if !users.is_empty() {
+        return Err(KataraError::Config(format!(
+            "Secret {} is used by env profile(s) {}",
+            name,
+            users.join(", ")
+        )));
+    }
+
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(keychain_error(e)),
+    }
+    update_index(|names| {
+        names.remove(name);
+    })
+}
+
+fn validate_name(name: &str) -> Result<(), KataraError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(KataraError::Config(format!(
+            "Invalid secret name {:?}: use letters, digits, '_', '-' and '.'",
+            name
+        )))
+    }
+}
+
+fn entry(name: &str) -> Result<keyring::Entry, KataraError> {
+    keyring::Entry::new(SERVICE, name).map_err(keychain_error)
+}
+
+fn keychain_error(e: keyring::Error) -> KataraError {
+    KataraError::Config(format!("Keychain error: {}", e))
+}
+
+fn read_index() -> Result<BTreeSet<String>, KataraError> {
+    let path = index_path()?;
+    if !path.exists() {
+        return Ok(BTreeSet::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(KataraError::Io)?;
+    serde_json::from_str(&content).map_err(KataraError::Serde)
+}
+
+fn update_index(f: impl FnOnce(&mut BTreeSet<String>)) -> Result<(), KataraError> {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut names = read_index()?;
+    f(&mut names);
+    let path = index_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(KataraError::Io)?;
+    }
+    let content = serde_json::to_string_pretty(&names).map_err(KataraError::Serde)?;
+    std::fs::write(&path, content).map_err(KataraError::Io)
+}
+
+fn index_path() -> Result<PathBuf, KataraError> {
+    Ok(crate::config::katara_dir()?.join("secrets.json"))
+}
@@ -0,0 +1,167 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::config::claude_settings;
+use crate::error::KataraError;
+
+/// Settings levels from lowest to highest precedence.
+const LEVELS: &[&str] = &["user", "project", "local", "enterprise"];
+
+/// The settings the CLI ends up with, and where each value came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsDiff {
+    /// All levels merged.
+    pub effective: Value,
+    /// One entry per setting, sorted by key.
+    pub settings: Vec<EffectiveSetting>,
+    /// Files considered, lowest precedence first.
+    pub levels: Vec<SettingsLevel>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsLevel {
+    pub level: String,
+    pub path: String,
+    pub exists: bool,
+    /// Why the file was skipped, e.g. invalid JSON.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveSetting {
+    /// Dotted path, e.g. `permissions.defaultMode` or `env.API_URL`.
+    pub key: String,
+    pub value: Value,
+    /// Level whose value wins. For lists, the highest level adding entries.
+    pub source: String,
+    /// Lists are combined from every level rather than replaced.
+    pub merged: bool,
+    /// Every level that sets the key, lowest precedence first.
+    pub values: Vec<LevelValue>,
+    /// Lower levels whose value lost to `source`. Empty for merged lists.
+    pub overridden: Vec<LevelValue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelValue {
+    pub level: String,
+    pub value: Value,
+}
+
+/// Merge Claude Code's settings levels the way the CLI does and report,
+/// per key, which level supplied the value and what it overrode.
+///
+/// Objects merge key by key, lists are concatenated (without duplicates)
+/// and any other value is replaced by the higher level's. Project levels
+/// are only read with a `project_dir`.
+pub fn diff_settings(project_dir: Option<&str>) -> Result<SettingsDiff, KataraError> {
+    let mut levels = Vec::new();
+    let mut effective = Value::Object(Map::new());
+    let mut by_key: BTreeMap<String, Vec<LevelValue>> = BTreeMap::new();
+
+    for level in LEVELS {
+        if project_dir.is_none() && matches!(*level, "project" | "local") {
+            continue;
+        }
+        let path = claude_settings::settings_path(level, project_dir)?;
+        let mut info = SettingsLevel {
+            level: level.to_string(),
+            path: path.display().to_string(),
+            exists: path.exists(),
+            error: None,
+        };
+        match claude_settings::read_claude_settings(level, project_dir) {
+            Ok(entry) => {
+                let mut leaves = Vec::new();
+                flatten("", &entry.settings, &mut leaves);
+                for (key, value) in leaves {
+                    by_key.entry(key).or_default().push(LevelValue {
+                        level: level.to_string(),
+                        value,
+                    });
+                }
+                effective = merge(effective, entry.settings);
+            }
+            Err(e) => info.error = Some(e.to_string()),
+        }
+        levels.push(info);
+    }
+
+    let settings = by_key
+        .into_iter()
+        .map(|(key, values)| {
+            let top = values.last().expect("keys come from at least one level");
+            let merged = values.iter().all(|v| v.value.is_array()) && values.len() > 1;
+            let value = if merged {
+                values.iter().fold(Value::Array(Vec::new()), |acc, v| {
+                    merge(acc, v.value.clone())
+                })
+            } else {
+                top.value.clone()
+            };
+            let overridden = if merged {
+                Vec::new()
+            } else {
+                values[..values.len() - 1].to_vec()
+            };
+            EffectiveSetting {
+                key,
+                value,
+                source: top.level.clone(),
+                merged,
+                overridden,
+                values,
+            }
+        })
+        .collect();
+
+    Ok(SettingsDiff {
+        effective,
+        settings,
+        levels,
+    })
+}
+
+/// Leaf values by dotted path. Lists and scalars are leaves; empty objects
+/// are skipped.
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&path, value, out);
+            }
+        }
+        _ if prefix.is_empty() => {}
+        _ => out.push((prefix.to_string(), value.clone())),
+    }
+}
+
+fn merge(lower: Value, higher: Value) -> Value {
+    match (lower, higher) {
+        (Value::Object(mut lower), Value::Object(higher)) => {
+            for (key, value) in higher {
+                let merged = match lower.remove(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => value,
+                };
+                lower.insert(key, merged);
+            }
+            Value::Object(lower)
+        }
+        (Value::Array(mut lower), Value::Array(higher)) => {
+            for item in higher {
+                if !lower.contains(&item) {
+                    lower.push(item);
+                }
+            }
+            Value::Array(lower)
+        }
+        (_, higher) => higher,
+    }
+}
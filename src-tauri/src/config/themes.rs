@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::config::manager::{self, AppSettings};
+use crate::error::KataraError;
+
+/// Themes the frontend ships with; custom themes can't take these names.
+pub const BUILTIN_THEMES: &[&str] = &["dark", "light"];
+
+/// A named color palette. Colors are CSS hex strings (`#rgb`, `#rrggbb` or
+/// `#rrggbbaa`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    /// Built-in theme that colors not set here come from.
+    #[serde(default = "default_base")]
+    pub base: String,
+    pub background: String,
+    pub foreground: String,
+    pub accent: String,
+    /// Further UI tokens by name (e.g. `border`, `error`); opaque to the
+    /// backend apart from being colors.
+    #[serde(default)]
+    pub tokens: BTreeMap<String, String>,
+    #[serde(default)]
+    pub terminal: TerminalColors,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TerminalColors {
+    #[serde(default)]
+    pub background: Option<String>,
+    #[serde(default)]
+    pub foreground: Option<String>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub selection: Option<String>,
+    /// The 16 ANSI colors, black to bright white; empty keeps the base's.
+    #[serde(default)]
+    pub ansi: Vec<String>,
+}
+
+fn default_base() -> String {
+    "dark".into()
+}
+
+impl Theme {
+    pub(crate) fn validate(&self) -> Result<(), KataraError> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err(KataraError::Config("Theme name is required".into()));
+        }
+        if BUILTIN_THEMES.iter().any(|b| b.eq_ignore_ascii_case(name)) {
+            return Err(KataraError::Config(format!("{} is a built-in theme", name)));
+        }
+        if !BUILTIN_THEMES.contains(&self.base.as_str()) {
+            return Err(KataraError::Config(format!(
+                "Theme {}: unknown base theme {}",
+                name, self.base
+            )));
+        }
+        if !self.terminal.ansi.is_empty() && self.terminal.ansi.len() != 16 {
+            return Err(KataraError::Config(format!(
+                "Theme {}: terminal.ansi needs 16 colors, got {}",
+                name,
+                self.terminal.ansi.len()
+            )));
+        }
+
+        let terminal = &self.terminal;
+        let mut colors: Vec<(String, &str)> = vec![
+            ("background".into(), &self.background),
+            ("foreground".into(), &self.foreground),
+            ("accent".into(), &self.accent),
+        ];
+        colors.extend(
+            self.tokens
+                .iter()
+                .map(|(k, v)| (format!("tokens.{}", k), v.as_str())),
+        );
+        for (field, value) in [
+            ("terminal.background", &terminal.background),
+            ("terminal.foreground", &terminal.foreground),
+            ("terminal.cursor", &terminal.cursor),
+            ("terminal.selection", &terminal.selection),
+        ] {
+            if let Some(value) = value {
+                colors.push((field.into(), value));
+            }
+        }
+        for (i, value) in terminal.ansi.iter().enumerate() {
+            colors.push((format!("terminal.ansi[{}]", i), value));
+        }
+        match colors.into_iter().find(|(_, value)| !is_hex_color(value)) {
+            Some((field, value)) => Err(KataraError::Config(format!(
+                "Theme {}: {} is not a hex color: {:?}",
+                name, field, value
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn list_themes() -> Result<Vec<Theme>, KataraError> {
+    Ok(manager::read_settings()?.themes)
+}
+
+/// Add a new theme. Names must be unique.
+pub fn create_theme(theme: Theme) -> Result<AppSettings, KataraError> {
+    manager::modify_settings(|settings| {
+        if settings.themes.iter().any(|t| t.name == theme.name) {
+            return Err(already_exists(&theme.name));
+        }
+        settings.themes.push(theme);
+        Ok(())
+    })
+}
+
+/// Replace the theme called `name`; `theme.name` may rename it, in which
+/// case the active theme follows.
+pub fn update_theme(name: &str, theme: Theme) -> Result<AppSettings, KataraError> {
+    manager::modify_settings(|settings| {
+        if theme.name != name && settings.themes.iter().any(|t| t.name == theme.name) {
+            return Err(already_exists(&theme.name));
+        }
+        if settings.theme == name {
+            settings.theme = theme.name.clone();
+        }
+        let slot = settings
+            .themes
+            .iter_mut()
+            .find(|t| t.name == name)
+            .ok_or_else(|| not_found(name))?;
+        *slot = theme;
+        Ok(())
+    })
+}
+
+/// Remove the theme called `name`. If it was active, its base theme
+/// becomes active.
+pub fn delete_theme(name: &str) -> Result<AppSettings, KataraError> {
+    manager::modify_settings(|settings| {
+        let index = settings
+            .themes
+            .iter()
+            .position(|t| t.name == name)
+            .ok_or_else(|| not_found(name))?;
+        let theme = settings.themes.remove(index);
+        if settings.theme == name {
+            settings.theme = theme.base;
+        }
+        Ok(())
+    })
+}
+
+/// Write a theme to a JSON file for sharing.
+pub fn export_theme(name: &str, path: &str) -> Result<(), KataraError> {
+    let theme = list_themes()?
+        .into_iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| not_found(name))?;
+    let content = serde_json::to_string_pretty(&theme).map_err(KataraError::Serde)?;
+    std::fs::write(path, content + "\n").map_err(KataraError::Io)
+}
+
+/// Add a theme from a file written by `export_theme`. An existing theme
+/// with the same name is only replaced with `overwrite`. Returns the theme
+/// and the saved settings.
+pub fn import_theme(path: &str, overwrite: bool) -> Result<(Theme, AppSettings), KataraError> {
+    let content = std::fs::read_to_string(path).map_err(KataraError::Io)?;
+    let theme: Theme = serde_json::from_str(&content)
+        .map_err(|e| KataraError::Config(format!("Invalid theme file: {}", e)))?;
+    let imported = theme.clone();
+    let settings = manager::modify_settings(|settings| {
+        match settings.themes.iter_mut().find(|t| t.name == theme.name) {
+            Some(_) if !overwrite => return Err(already_exists(&theme.name)),
+            Some(existing) => *existing = theme,
+            None => settings.themes.push(theme),
+        }
+        Ok(())
+    })?;
+    Ok((imported, settings))
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+fn already_exists(name: &str) -> KataraError {
+    KataraError::Config(format!("Theme {} already exists", name))
+}
+
+fn not_found(name: &str) -> KataraError {
+    KataraError::Config(format!("Theme {} not found", name))
+}
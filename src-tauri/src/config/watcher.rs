@@ -0,0 +1,252 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{claude_settings, manager, memory};
+use crate::quick_prompt;
+use crate::state::AppState;
+use crate::terminal::pty;
+
+/// Quiet period before changes are reported; the CLI and editors often
+/// write a file in several steps.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Target kind of Katara's own settings.json, which is reloaded into
+/// `AppState::settings` instead of reported as `config:changed`.
+const APP_SETTINGS: &str = "app_settings";
+
+/// Payload for the `config:changed` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChangedPayload {
+    /// `claude_md`, `agents_md` or `settings`.
+    pub kind: &'static str,
+    /// `enterprise`, `user`, `project` or `local`.
+    pub level: &'static str,
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Handle for the config watcher thread.
+pub struct ConfigWatcher {
+    tx: mpsc::Sender<Message>,
+}
+
+impl ConfigWatcher {
+    /// Watch the project-level files of `project_dir` instead of the
+    /// previous project's (or stop watching project files with `None`).
+    pub fn set_project(&self, project_dir: Option<String>) {
+        let _ = self.tx.send(Message::SetProject(project_dir));
+    }
+}
+
+enum Message {
+    Fs(notify::Result<Event>),
+    SetProject(Option<String>),
+}
+
+#[derive(Clone, Copy)]
+struct Target {
+    kind: &'static str,
+    level: &'static str,
+}
+
+/// Watch the CLAUDE.md, AGENTS.md and Claude Code settings files and emit
+/// `config:changed` whenever one is created, modified or deleted, so open
+/// editors can reload. Changes to Katara's settings.json are loaded into
+/// the live settings and announced with `settings:changed`.
+///
+/// User and enterprise files are watched from the start; project files
+/// once a project is set with `ConfigWatcher::set_project`. Runs on a
+/// background thread for the lifetime of the app.
+pub fn start(app_handle: AppHandle) -> Result<ConfigWatcher, String> {
+    if let Some(dir) = manager::settings_path().parent() {
+        // Watch for the settings file even before it is first written
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let (tx, rx) = mpsc::channel();
+    let fs_tx = tx.clone();
+    let watcher = notify::recommended_watcher(move |res| {
+        let _ = fs_tx.send(Message::Fs(res));
+    })
+    .map_err(|e| e.to_string())?;
+
+    std::thread::spawn(move || {
+        let mut state = WatchState {
+            watcher,
+            targets: HashMap::new(),
+            watched: HashSet::new(),
+        };
+        state.set_project(None);
+        let mut pending: HashSet<PathBuf> = HashSet::new();
+
+        loop {
+            let message = if pending.is_empty() {
+                match rx.recv() {
+                    Ok(message) => message,
+                    Err(_) => break,
+                }
+            } else {
+                match rx.recv_timeout(DEBOUNCE) {
+                    Ok(message) => message,
+                    Err(RecvTimeoutError::Timeout) => {
+                        for path in pending.drain() {
+                            state.emit(&app_handle, &path);
+                        }
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            };
+
+            match message {
+                Message::SetProject(project_dir) => state.set_project(project_dir.as_deref()),
+                Message::Fs(Ok(event)) => {
+                    if matches!(event.kind, EventKind::Access(_)) {
+                        continue;
+                    }
+                    // A `.claude` directory may have just been created
+                    state.watch_dirs();
+                    pending.extend(
+                        event
+                            .paths
+                            .iter()
+                            .map(|p| key(p))
+                            .filter(|p| state.targets.contains_key(p)),
+                    );
+                }
+                Message::Fs(Err(e)) => eprintln!("[katara] Config watcher error: {}", e),
+            }
+        }
+    });
+
+    Ok(ConfigWatcher { tx })
+}
+
+struct WatchState {
+    watcher: RecommendedWatcher,
+    /// Watched files by `key`.
+    targets: HashMap<PathBuf, Target>,
+    /// Directories currently being watched.
+    watched: HashSet<PathBuf>,
+}
+
+impl WatchState {
+    fn set_project(&mut self, project_dir: Option<&str>) {
+        self.targets.clear();
+        let mut levels = vec!["enterprise", "user"];
+        if project_dir.is_some() {
+            levels.extend(["project", "local"]);
+        }
+        for level in levels {
+            if let Ok(entry) = manager::read_claude_md(level, project_dir) {
+                self.add(Path::new(&entry.path), "claude_md", level);
+            }
+            if let Ok(path) = memory::agents_md_path(level, project_dir) {
+                self.add(&path, "agents_md", level);
+            }
+            if let Ok(path) = claude_settings::settings_path(level, project_dir) {
+                self.add(&path, "settings", level);
+            }
+        }
+        self.add(&manager::settings_path(), APP_SETTINGS, "app");
+
+        // Stop watching directories no longer needed
+        let needed = self.needed_dirs();
+        for dir in self.watched.clone() {
+            if !needed.contains(&dir) {
+                let _ = self.watcher.unwatch(&dir);
+                self.watched.remove(&dir);
+            }
+        }
+        self.watch_dirs();
+    }
+
+    fn add(&mut self, path: &Path, kind: &'static str, level: &'static str) {
+        self.targets.insert(key(path), Target { kind, level });
+    }
+
+    fn needed_dirs(&self) -> HashSet<PathBuf> {
+        self.targets
+            .keys()
+            .filter_map(|p| p.parent().map(Path::to_path_buf))
+            .collect()
+    }
+
+    /// Start watching target directories that exist but aren't watched yet.
+    fn watch_dirs(&mut self) {
+        for dir in self.needed_dirs() {
+            if self.watched.contains(&dir) || !dir.is_dir() {
+                continue;
+            }
+            match self.watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    self.watched.insert(dir);
+                }
+                Err(e) => eprintln!("[katara] Failed to watch {}: {}", dir.display(), e),
+            }
+        }
+    }
+
+    fn emit(&self, app_handle: &AppHandle, path: &Path) {
+        let Some(target) = self.targets.get(path) else {
+            return;
+        };
+        if target.kind == APP_SETTINGS {
+            reload_app_settings(app_handle);
+            return;
+        }
+        let payload = ConfigChangedPayload {
+            kind: target.kind,
+            level: target.level,
+            path: path.display().to_string(),
+            exists: path.exists(),
+        };
+        let _ = app_handle.emit("config:changed", &payload);
+    }
+}
+
+/// Load settings.json into the live settings and apply the limits that
+/// affect open terminals and sessions. An invalid file is reported and the
+/// previous settings stay in effect.
+fn reload_app_settings(app_handle: &AppHandle) {
+    let state = app_handle.state::<Arc<AppState>>();
+    let settings = match manager::read_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("[katara] Ignoring invalid settings.json: {}", e);
+            return;
+        }
+    };
+    if serde_json::to_value(&settings).ok() == serde_json::to_value(state.settings()).ok() {
+        return;
+    }
+
+    pty::apply_scrollback_limits(&state.terminals.blocking_read(), &settings.terminal);
+    for session in state.sessions.blocking_read().values() {
+        session
+            .history
+            .set_limit(settings.history.max_messages_per_session);
+    }
+    quick_prompt::set_shortcut(app_handle, settings.quick_prompt_shortcut.as_deref());
+    state.set_settings(settings.clone());
+    println!("[katara] Settings reloaded");
+    let _ = app_handle.emit("settings:changed", &settings);
+}
+
+/// Comparable form of a file path: the parent canonicalized (watchers may
+/// report resolved symlinks) and the file name as is, since the file itself
+/// may not exist.
+fn key(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .unwrap_or_else(|_| parent.to_path_buf())
+            .join(name),
+        _ => path.to_path_buf(),
+    }
+}
@@ -1,4 +1,13 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::ser::SerializeStruct;
 use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::claude_settings::SettingsError;
+use crate::skills::inputs::InputError;
+use crate::skills::manager::SkillConflict;
 
 #[derive(Debug, thiserror::Error)]
 pub enum KataraError {
@@ -11,9 +20,18 @@ pub enum KataraError {
     #[error("WebSocket error: {0}")]
     WebSocket(String),
 
+    #[error("WebSocket server not ready yet")]
+    WsNotReady,
+
+    #[error("No WebSocket connection for session {0}")]
+    NotConnected(String),
+
     #[error("Session not found: {0}")]
     SessionNotFound(String),
 
+    #[error("Terminal not found: {0}")]
+    TerminalNotFound(String),
+
     #[error("Terminal error: {0}")]
     Terminal(String),
 
@@ -23,8 +41,121 @@ pub enum KataraError {
     #[error("Skill error: {0}")]
     Skill(String),
 
+    #[error("Invalid skill inputs: {}", join_messages(.0))]
+    InvalidInputs(Vec<InputError>),
+
+    #[error("Invalid settings: {}", join_settings_errors(.0))]
+    InvalidSettings(Vec<SettingsError>),
+
+    #[error("Skill changed on disk: {}", .0.path)]
+    SkillConflict(Box<SkillConflict>),
+
+    #[error("Claude CLI not found ({0}). Is it installed?")]
+    CliNotInstalled(String),
+
     #[error("Process error: {0}")]
     Process(String),
+
+    #[error("Update error: {0}")]
+    Update(String),
+
+    #[error("Git error: {0}")]
+    Git(String),
+
+    #[error("Remote access error: {0}")]
+    Remote(String),
+
+    #[error("Plugin error: {0}")]
+    Plugin(String),
+}
+
+/// Stable identifiers for each kind of error, so the frontend can branch
+/// on `code` instead of matching message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    Io,
+    Serialization,
+    Websocket,
+    WsNotReady,
+    NotConnected,
+    SessionNotFound,
+    TerminalNotFound,
+    Terminal,
+    Config,
+    Skill,
+    InvalidInputs,
+    InvalidSettings,
+    SkillConflict,
+    CliNotInstalled,
+    Process,
+    Update,
+    Git,
+    Remote,
+    Plugin,
+}
+
+impl KataraError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            KataraError::Io(_) => ErrorCode::Io,
+            KataraError::Serde(_) => ErrorCode::Serialization,
+            KataraError::WebSocket(_) => ErrorCode::Websocket,
+            KataraError::WsNotReady => ErrorCode::WsNotReady,
+            KataraError::NotConnected(_) => ErrorCode::NotConnected,
+            KataraError::SessionNotFound(_) => ErrorCode::SessionNotFound,
+            KataraError::TerminalNotFound(_) => ErrorCode::TerminalNotFound,
+            KataraError::Terminal(_) => ErrorCode::Terminal,
+            KataraError::Config(_) => ErrorCode::Config,
+            KataraError::Skill(_) => ErrorCode::Skill,
+            KataraError::InvalidInputs(_) => ErrorCode::InvalidInputs,
+            KataraError::InvalidSettings(_) => ErrorCode::InvalidSettings,
+            KataraError::SkillConflict(_) => ErrorCode::SkillConflict,
+            KataraError::CliNotInstalled(_) => ErrorCode::CliNotInstalled,
+            KataraError::Process(_) => ErrorCode::Process,
+            KataraError::Update(_) => ErrorCode::Update,
+            KataraError::Git(_) => ErrorCode::Git,
+            KataraError::Remote(_) => ErrorCode::Remote,
+            KataraError::Plugin(_) => ErrorCode::Plugin,
+        }
+    }
+
+    /// HTTP status for the error when it's answered over HTTP.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            KataraError::SessionNotFound(_) | KataraError::TerminalNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            KataraError::InvalidInputs(_)
+            | KataraError::InvalidSettings(_)
+            | KataraError::Config(_)
+            | KataraError::Serde(_) => StatusCode::BAD_REQUEST,
+            KataraError::SkillConflict(_) => StatusCode::CONFLICT,
+            KataraError::NotConnected(_) | KataraError::WsNotReady => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            KataraError::Remote(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Context for the UI beyond the message, or null.
+    fn details(&self) -> Value {
+        match self {
+            // Field-level errors so forms can highlight the offending inputs
+            KataraError::InvalidInputs(fields) => json!({ "fields": fields }),
+            KataraError::InvalidSettings(fields) => json!({ "fields": fields }),
+            // Both versions so the editor can offer a merge
+            KataraError::SkillConflict(conflict) => json!({ "conflict": conflict }),
+            KataraError::SessionNotFound(id) | KataraError::NotConnected(id) => {
+                json!({ "session_id": id })
+            }
+            KataraError::TerminalNotFound(id) => json!({ "terminal_id": id }),
+            KataraError::CliNotInstalled(path) => json!({ "path": path }),
+            KataraError::Io(e) => json!({ "kind": format!("{:?}", e.kind()) }),
+            _ => Value::Null,
+        }
+    }
 }
 
 // Tauri commands require Serialize on error types
@@ -33,6 +164,33 @@ impl Serialize for KataraError {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let mut s = serializer.serialize_struct("KataraError", 3)?;
+        s.serialize_field("code", &self.code())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("details", &self.details())?;
+        s.end()
     }
 }
+
+// HTTP clients get the same body `invoke` rejects with
+impl IntoResponse for KataraError {
+    fn into_response(self) -> Response {
+        (self.status(), Json(self)).into_response()
+    }
+}
+
+fn join_messages(errors: &[InputError]) -> String {
+    errors
+        .iter()
+        .map(|e| e.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn join_settings_errors(errors: &[SettingsError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{} {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,264 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::KataraError;
+use crate::git::repo::{self, git, git_env};
+use crate::sink::Sink;
+
+/// Identity for checkpoint commits, so they work without a configured
+/// `user.name`.
+const CHECKPOINT_IDENTITY: &[(&str, &str)] = &[
+    ("GIT_AUTHOR_NAME", "Katara"),
+    ("GIT_AUTHOR_EMAIL", "katara@localhost"),
+    ("GIT_COMMITTER_NAME", "Katara"),
+    ("GIT_COMMITTER_EMAIL", "katara@localhost"),
+];
+
+/// Longest summary put in a checkpoint's subject line.
+const MAX_SUMMARY_CHARS: usize = 72;
+
+/// A snapshot of the working tree after a turn. Turn 0 is the tree as it
+/// was when the session started.
+#[derive(Debug, Clone, Serialize)]
+pub struct Checkpoint {
+    pub turn: u32,
+    pub commit: String,
+    pub summary: String,
+    /// Seconds since the epoch.
+    pub timestamp: i64,
+}
+
+/// Checkpoints live under their own ref, outside `refs/heads`, so they never
+/// show up as a branch or move HEAD. Each one's parent is the previous turn.
+fn checkpoint_ref(session_id: &str) -> String {
+    format!("refs/katara/checkpoints/{}", session_id)
+}
+
+/// Commit the whole working tree (untracked files included, ignored files
+/// not) as the next checkpoint for `session_id`. The user's index, HEAD and
+/// branches are left alone.
+pub async fn create(
+    working_dir: &str,
+    session_id: &str,
+    summary: &str,
+) -> Result<Checkpoint, KataraError> {
+    let top = toplevel(working_dir).await?;
+    let tree = snapshot_tree(&top, session_id).await?;
+    let reference = checkpoint_ref(session_id);
+    let parent = resolve(&top, &reference).await;
+    let turn = match parent {
+        Some(_) => list(working_dir, session_id)
+            .await?
+            .first()
+            .map_or(0, |c| c.turn + 1),
+        None => 0,
+    };
+
+    let summary = summarize(summary);
+    let message = format!("Turn {}: {}", turn, summary);
+    let mut args = vec!["commit-tree", tree.as_str(), "-m", message.as_str()];
+    if let Some(ref parent) = parent {
+        args.extend(["-p", parent.as_str()]);
+    }
+    let commit = git_env(&top, &args, CHECKPOINT_IDENTITY)
+        .await?
+        .trim()
+        .to_string();
+    git(&top, &["update-ref", &reference, &commit]).await?;
+
+    Ok(Checkpoint {
+        turn,
+        commit,
+        summary,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0),
+    })
+}
+
+/// A session's checkpoints, newest first.
+pub async fn list(working_dir: &str, session_id: &str) -> Result<Vec<Checkpoint>, KataraError> {
+    let reference = checkpoint_ref(session_id);
+    if resolve(working_dir, &reference).await.is_none() {
+        return Ok(Vec::new());
+    }
+    let out = git(
+        working_dir,
+        &["log", "--format=%H%x1f%at%x1f%s", &reference],
+    )
+    .await?;
+    Ok(out
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            let commit = fields.next()?.to_string();
+            let timestamp = fields.next()?.parse().unwrap_or(0);
+            let (turn, summary) = fields.next()?.strip_prefix("Turn ")?.split_once(": ")?;
+            Some(Checkpoint {
+                turn: turn.parse().ok()?,
+                commit,
+                summary: summary.to_string(),
+                timestamp,
+            })
+        })
+        .collect())
+}
+
+/// Restore the working tree to how it was after `turn`: files are reset to
+/// their checkpointed contents and files created since are deleted. The
+/// current state is checkpointed first, so a rollback can itself be undone.
+/// Only files under `working_dir` are touched, even if the checkpoint
+/// covers the rest of the repository.
+pub async fn rollback(
+    working_dir: &str,
+    session_id: &str,
+    turn: u32,
+) -> Result<Checkpoint, KataraError> {
+    let target = list(working_dir, session_id)
+        .await?
+        .into_iter()
+        .find(|c| c.turn == turn)
+        .ok_or_else(|| KataraError::Git(format!("No checkpoint for turn {}", turn)))?;
+    let current = create(
+        working_dir,
+        session_id,
+        &format!("Before rollback to turn {}", turn),
+    )
+    .await?;
+
+    let top = toplevel(working_dir).await?;
+    let scope = pathspec(working_dir).await?;
+    let added = git(
+        &top,
+        &[
+            "diff-tree",
+            "-r",
+            "-z",
+            "--name-only",
+            "--no-renames",
+            "--diff-filter=A",
+            &target.commit,
+            &current.commit,
+            "--",
+            &scope,
+        ],
+    )
+    .await?;
+    for path in added.split('\0').filter(|p| !p.is_empty()) {
+        let path = Path::new(&top).join(path);
+        if let Err(e) = std::fs::remove_file(&path) {
+            eprintln!("[katara] Failed to remove {}: {}", path.display(), e);
+        }
+    }
+
+    // Checking out into a throwaway index leaves the user's index alone
+    let index = index_path(&top, session_id).await?;
+    let env = [("GIT_INDEX_FILE", index.as_str())];
+    let restored = git_env(&top, &["checkout", &target.commit, "--", &scope], &env).await;
+    let _ = std::fs::remove_file(&index);
+    restored?;
+
+    println!(
+        "[katara] Rolled back session {} to turn {}",
+        session_id, turn
+    );
+    Ok(target)
+}
+
+/// Checkpoint a finished turn and emit `claude:checkpoint`.
+pub fn record_turn(sink: Sink, session_id: String, working_dir: String, summary: String) {
+    tauri::async_runtime::spawn(async move {
+        if !repo::is_repo(&working_dir).await {
+            return;
+        }
+        match create(&working_dir, &session_id, &summary).await {
+            Ok(checkpoint) => sink.emit_for_session(
+                &session_id,
+                "claude:checkpoint",
+                serde_json::json!({
+                    "session_id": session_id,
+                    "checkpoint": checkpoint,
+                }),
+            ),
+            Err(e) => eprintln!(
+                "[katara] Failed to checkpoint session {}: {}",
+                session_id, e
+            ),
+        }
+    });
+}
+
+/// Write the working tree to a tree object using a throwaway index.
+async fn snapshot_tree(top: &str, session_id: &str) -> Result<String, KataraError> {
+    let index = index_path(top, session_id).await?;
+    let env = [("GIT_INDEX_FILE", index.as_str())];
+    let tree = async {
+        // Seeding from HEAD lets `add` reuse cached stat info
+        if repo::has_head(top).await {
+            git_env(top, &["read-tree", "HEAD"], &env).await?;
+        }
+        git_env(top, &["add", "--all", "--", "."], &env).await?;
+        git_env(top, &["write-tree"], &env).await
+    }
+    .await;
+    let _ = std::fs::remove_file(&index);
+    Ok(tree?.trim().to_string())
+}
+
+/// A fresh index file per call, so a checkpoint and a rollback running at
+/// once don't share one.
+async fn index_path(top: &str, session_id: &str) -> Result<String, KataraError> {
+    let git_dir = git(top, &["rev-parse", "--absolute-git-dir"]).await?;
+    Ok(Path::new(git_dir.trim())
+        .join(format!(
+            "katara-checkpoint-{}-{}.index",
+            session_id,
+            uuid::Uuid::new_v4().simple()
+        ))
+        .display()
+        .to_string())
+}
+
+/// Pathspec, relative to the repository root, covering `working_dir`.
+async fn pathspec(working_dir: &str) -> Result<String, KataraError> {
+    let prefix = git(working_dir, &["rev-parse", "--show-prefix"]).await?;
+    let prefix = prefix.trim();
+    Ok(if prefix.is_empty() {
+        ".".to_string()
+    } else {
+        prefix.to_string()
+    })
+}
+
+async fn toplevel(working_dir: &str) -> Result<String, KataraError> {
+    Ok(git(working_dir, &["rev-parse", "--show-toplevel"])
+        .await?
+        .trim()
+        .to_string())
+}
+
+async fn resolve(working_dir: &str, reference: &str) -> Option<String> {
+    git(
+        working_dir,
+        &["rev-parse", "--verify", "--quiet", reference],
+    )
+    .await
+    .ok()
+    .map(|hash| hash.trim().to_string())
+}
+
+/// First line of the turn's result, shortened for a subject line.
+fn summarize(text: &str) -> String {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty());
+    let Some(line) = line else {
+        return "(no summary)".into();
+    };
+    if line.chars().count() <= MAX_SUMMARY_CHARS {
+        return line.to_string();
+    }
+    let mut short: String = line.chars().take(MAX_SUMMARY_CHARS - 3).collect();
+    short.push_str("...");
+    short
+}
@@ -0,0 +1,332 @@
+use std::process::Stdio;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::config::manager::AppSettings;
+use crate::error::KataraError;
+use crate::git::repo::git;
+
+/// Pull request diffs and drafting input are cut to this many bytes so a
+/// large change doesn't blow up the prompt.
+const MAX_DIFF_BYTES: usize = 40 * 1024;
+
+const DRAFT_PROMPT: &str = "Write a pull request title and description for the changes on \
+stdin. Reply with only the title on the first line, a blank line, then the description in \
+Markdown: a short summary of what changed and why, then how it was tested if that is evident.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GithubItemKind {
+    Issue,
+    PullRequest,
+}
+
+/// An issue or pull request, as fetched by `gh`.
+#[derive(Debug, Clone, Serialize)]
+pub struct GithubItem {
+    pub kind: GithubItemKind,
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    pub url: String,
+    pub state: String,
+    pub author: Option<String>,
+    pub labels: Vec<String>,
+    pub comments: Vec<GithubComment>,
+    /// Pull requests only, truncated to `MAX_DIFF_BYTES`.
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GithubComment {
+    pub author: Option<String>,
+    pub body: String,
+}
+
+impl GithubItem {
+    /// Markdown block to put in front of a message to the agent.
+    pub fn to_context(&self) -> String {
+        let kind = match self.kind {
+            GithubItemKind::Issue => "Issue",
+            GithubItemKind::PullRequest => "Pull request",
+        };
+        let mut out = format!(
+            "{} #{}: {}\n{} ({})\n",
+            kind, self.number, self.title, self.url, self.state
+        );
+        if !self.labels.is_empty() {
+            out.push_str(&format!("Labels: {}\n", self.labels.join(", ")));
+        }
+        if !self.body.trim().is_empty() {
+            out.push_str(&format!("\n{}\n", self.body.trim()));
+        }
+        for comment in &self.comments {
+            let author = comment.author.as_deref().unwrap_or("unknown");
+            out.push_str(&format!(
+                "\n---\n{} commented:\n{}\n",
+                author,
+                comment.body.trim()
+            ));
+        }
+        if let Some(ref diff) = self.diff {
+            out.push_str(&format!("\n```diff\n{}\n```\n", diff.trim_end()));
+        }
+        out
+    }
+}
+
+/// A pull request title and body for the current branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestDraft {
+    pub title: String,
+    pub body: String,
+    /// Branch the changes are on.
+    pub head: String,
+    /// Branch the pull request targets.
+    pub base: String,
+}
+
+/// Fetch an issue or pull request by number, `#number` or URL. Pull
+/// requests are tried first since GitHub numbers both from the same
+/// sequence.
+pub async fn fetch(working_dir: &str, reference: &str) -> Result<GithubItem, KataraError> {
+    let reference = reference.trim().trim_start_matches('#');
+    let fields = "number,title,body,url,state,author,labels,comments";
+    // `--` so a reference starting with `-` can't pass as a flag
+    if let Ok(out) = gh(
+        working_dir,
+        &["pr", "view", "--json", fields, "--", reference],
+    )
+    .await
+    {
+        let mut item = parse_item(GithubItemKind::PullRequest, &out)?;
+        let diff = gh(working_dir, &["pr", "diff", "--", reference]).await?;
+        item.diff = Some(truncate(diff));
+        return Ok(item);
+    }
+    let out = gh(
+        working_dir,
+        &["issue", "view", "--json", fields, "--", reference],
+    )
+    .await?;
+    parse_item(GithubItemKind::Issue, &out)
+}
+
+/// Have Claude write a title and body from the commits and diff between
+/// `base` (the repository's default branch if `None`) and HEAD.
+pub async fn draft_pull_request(
+    working_dir: &str,
+    base: Option<&str>,
+    settings: &AppSettings,
+) -> Result<PullRequestDraft, KataraError> {
+    let head = current_branch(working_dir).await?;
+    let base = match base {
+        Some(base) => base.to_string(),
+        None => default_branch(working_dir).await?,
+    };
+    if head == base {
+        return Err(KataraError::Git(format!(
+            "Already on {}; create a branch for the changes first",
+            base
+        )));
+    }
+
+    // Prefer the remote's copy of the base, which is what the PR compares to
+    let remote_base = format!("origin/{}", base);
+    let from = if git(
+        working_dir,
+        &["rev-parse", "--verify", "--quiet", &remote_base],
+    )
+    .await
+    .is_ok()
+    {
+        remote_base
+    } else {
+        base.clone()
+    };
+    let range = format!("{}...HEAD", from);
+    let commits = git(working_dir, &["log", "--format=%s%n%n%b", &range]).await?;
+    if commits.trim().is_empty() {
+        return Err(KataraError::Git(format!(
+            "No commits between {} and {}",
+            base, head
+        )));
+    }
+    let diff = git(
+        working_dir,
+        &["diff", "--no-color", "--stat", "--patch", &range],
+    )
+    .await?;
+    let input = format!("Commits:\n{}\n\nDiff:\n{}", commits, truncate(diff));
+
+    let program = settings
+        .claude_binary_path
+        .as_deref()
+        .filter(|p| !p.is_empty());
+    let program = program.unwrap_or("claude");
+    let mut child = Command::new(program)
+        .args(["-p", DRAFT_PROMPT])
+        .current_dir(working_dir)
+        .envs(settings.proxy.env())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => KataraError::CliNotInstalled(program.to_string()),
+            _ => KataraError::Process(format!("Failed to run Claude CLI {}: {}", program, e)),
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).await?;
+    }
+    let out = child.wait_with_output().await?;
+    if !out.status.success() {
+        return Err(KataraError::Process(format!(
+            "Claude CLI failed to draft the pull request: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )));
+    }
+
+    let text = String::from_utf8_lossy(&out.stdout);
+    let text = text.trim();
+    let (title, body) = text.split_once('\n').unwrap_or((text, ""));
+    Ok(PullRequestDraft {
+        title: title.trim().trim_start_matches('#').trim().to_string(),
+        body: body.trim().to_string(),
+        head,
+        base,
+    })
+}
+
+/// Push `draft.head` to `origin` and open a pull request for it. Returns
+/// the pull request's URL.
+pub async fn create_pull_request(
+    working_dir: &str,
+    draft: &PullRequestDraft,
+    as_draft: bool,
+) -> Result<String, KataraError> {
+    git(
+        working_dir,
+        &["push", "--set-upstream", "origin", &draft.head],
+    )
+    .await?;
+    let mut args = vec![
+        "pr",
+        "create",
+        "--title",
+        draft.title.as_str(),
+        "--body",
+        draft.body.as_str(),
+        "--base",
+        draft.base.as_str(),
+        "--head",
+        draft.head.as_str(),
+    ];
+    if as_draft {
+        args.push("--draft");
+    }
+    let out = gh(working_dir, &args).await?;
+    // gh prints progress first and the URL last
+    let url = out.lines().last().unwrap_or_default().trim().to_string();
+    println!("[katara] Created pull request {}", url);
+    Ok(url)
+}
+
+pub async fn current_branch(working_dir: &str) -> Result<String, KataraError> {
+    let branch = git(working_dir, &["rev-parse", "--abbrev-ref", "HEAD"]).await?;
+    let branch = branch.trim();
+    if branch == "HEAD" {
+        return Err(KataraError::Git(
+            "HEAD is detached; check out a branch first".into(),
+        ));
+    }
+    Ok(branch.to_string())
+}
+
+pub async fn default_branch(working_dir: &str) -> Result<String, KataraError> {
+    let out = gh(
+        working_dir,
+        &[
+            "repo",
+            "view",
+            "--json",
+            "defaultBranchRef",
+            "--jq",
+            ".defaultBranchRef.name",
+        ],
+    )
+    .await?;
+    Ok(out.trim().to_string())
+}
+
+/// Run `gh` and return its stdout, or its stderr as the error.
+async fn gh(working_dir: &str, args: &[&str]) -> Result<String, KataraError> {
+    let out = Command::new("gh")
+        .args(args)
+        .current_dir(working_dir)
+        // Never block on a prompt; gh asks for missing input otherwise
+        .env("GH_PROMPT_DISABLED", "1")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                KataraError::Git("GitHub CLI (gh) is not installed".into())
+            }
+            _ => KataraError::Git(format!("Failed to run gh: {}", e)),
+        })?;
+    if !out.status.success() {
+        return Err(KataraError::Git(
+            String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+fn parse_item(kind: GithubItemKind, json: &str) -> Result<GithubItem, KataraError> {
+    let v: Value = serde_json::from_str(json)?;
+    let str_field = |v: &Value, key: &str| v[key].as_str().unwrap_or_default().to_string();
+    let login = |v: &Value| v["author"]["login"].as_str().map(str::to_string);
+    Ok(GithubItem {
+        kind,
+        number: v["number"].as_u64().unwrap_or_default(),
+        title: str_field(&v, "title"),
+        body: str_field(&v, "body"),
+        url: str_field(&v, "url"),
+        state: str_field(&v, "state"),
+        author: login(&v),
+        labels: v["labels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| l["name"].as_str().map(str::to_string))
+            .collect(),
+        comments: v["comments"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|c| GithubComment {
+                author: login(c),
+                body: str_field(c, "body"),
+            })
+            .collect(),
+        diff: None,
+    })
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_DIFF_BYTES {
+        let mut end = MAX_DIFF_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("\n... (truncated)\n");
+    }
+    text
+}
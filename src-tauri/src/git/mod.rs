@@ -0,0 +1,3 @@
+pub mod checkpoint;
+pub mod github;
+pub mod repo;
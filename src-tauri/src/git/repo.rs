@@ -0,0 +1,308 @@
+use std::path::Path;
+use std::process::{Output, Stdio};
+
+use serde::Serialize;
+use tokio::process::Command;
+
+use crate::error::KataraError;
+use crate::sink::Sink;
+
+/// Git's well-known empty tree, used as the diff base before the first
+/// commit.
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Diffs larger than this are cut off so a generated file can't flood the
+/// frontend.
+const MAX_DIFF_BYTES: usize = 1024 * 1024;
+
+pub const DEFAULT_LOG_LIMIT: usize = 50;
+
+/// Branch and changed files of a working tree.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GitStatus {
+    /// None when HEAD is detached.
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    pub files: Vec<GitFileStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitFileStatus {
+    pub path: String,
+    /// Previous path of a renamed or copied file.
+    pub orig_path: Option<String>,
+    /// Porcelain status letter for the index (`M`, `A`, `D`, `R`, `?`, ...).
+    pub index: String,
+    /// Porcelain status letter for the working tree.
+    pub worktree: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitDiff {
+    /// Unified diff of the working tree against HEAD, untracked files
+    /// included.
+    pub diff: String,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitCommit {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub email: String,
+    /// Author time, seconds since the epoch.
+    pub timestamp: i64,
+    pub subject: String,
+}
+
+/// `git status` for `working_dir`.
+pub async fn status(working_dir: &str) -> Result<GitStatus, KataraError> {
+    let out = git(
+        working_dir,
+        &[
+            "status",
+            "--porcelain=v1",
+            "--branch",
+            "-z",
+            "--untracked-files=all",
+        ],
+    )
+    .await?;
+    Ok(parse_status(&out))
+}
+
+/// Diff of everything changed since HEAD (staged, unstaged and untracked),
+/// optionally limited to `path`.
+pub async fn diff(working_dir: &str, path: Option<&str>) -> Result<GitDiff, KataraError> {
+    let base = if has_head(working_dir).await {
+        "HEAD"
+    } else {
+        EMPTY_TREE
+    };
+    let mut args = vec!["diff", "--no-color", "--no-ext-diff", base, "--"];
+    args.extend(path);
+    let mut diff = git(working_dir, &args).await?;
+
+    // `git diff` skips untracked files, which are usually most of what an
+    // agent adds
+    let untracked: Vec<String> = status(working_dir)
+        .await?
+        .files
+        .into_iter()
+        .filter(|f| f.index == "?")
+        .map(|f| f.path)
+        .filter(|p| path.is_none_or(|path| p == path || p.starts_with(&format!("{}/", path))))
+        .collect();
+    for file in untracked {
+        if diff.len() >= MAX_DIFF_BYTES {
+            break;
+        }
+        let out = run(
+            working_dir,
+            &[
+                "diff",
+                "--no-color",
+                "--no-index",
+                "--",
+                null_device(),
+                &file,
+            ],
+        )
+        .await?;
+        // Exit code 1 means the files differ, which they always do here
+        if out.status.code() == Some(1) {
+            diff.push_str(&String::from_utf8_lossy(&out.stdout));
+        }
+    }
+
+    let truncated = diff.len() > MAX_DIFF_BYTES;
+    if truncated {
+        let mut end = MAX_DIFF_BYTES;
+        while !diff.is_char_boundary(end) {
+            end -= 1;
+        }
+        diff.truncate(end);
+    }
+    Ok(GitDiff { diff, truncated })
+}
+
+/// The most recent commits on HEAD, newest first.
+pub async fn log(working_dir: &str, limit: usize) -> Result<Vec<GitCommit>, KataraError> {
+    if !has_head(working_dir).await {
+        return Ok(Vec::new());
+    }
+    let limit = format!("--max-count={}", limit);
+    let out = git(
+        working_dir,
+        &[
+            "log",
+            &limit,
+            "--format=%H%x1f%h%x1f%an%x1f%ae%x1f%at%x1f%s%x1e",
+        ],
+    )
+    .await?;
+    Ok(out
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').split('\x1f');
+            Some(GitCommit {
+                hash: fields.next().filter(|h| !h.is_empty())?.to_string(),
+                short_hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                email: fields.next()?.to_string(),
+                timestamp: fields.next()?.parse().unwrap_or(0),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Emit `claude:git_status` for a session, e.g. after a turn that may have
+/// changed files. Nothing is sent if the directory isn't a repository.
+pub fn refresh_session_status(sink: Sink, session_id: String, working_dir: String) {
+    tauri::async_runtime::spawn(async move {
+        if let Ok(status) = status(&working_dir).await {
+            sink.emit_for_session(
+                &session_id,
+                "claude:git_status",
+                serde_json::json!({
+                    "session_id": session_id,
+                    "status": status,
+                }),
+            );
+        }
+    });
+}
+
+/// Whether `working_dir` is inside a git working tree.
+pub async fn is_repo(working_dir: &str) -> bool {
+    run(working_dir, &["rev-parse", "--is-inside-work-tree"])
+        .await
+        .is_ok_and(|out| out.status.success())
+}
+
+pub(crate) async fn has_head(working_dir: &str) -> bool {
+    run(working_dir, &["rev-parse", "--verify", "--quiet", "HEAD"])
+        .await
+        .is_ok_and(|out| out.status.success())
+}
+
+/// Run git and return its stdout, or its stderr as the error.
+pub(crate) async fn git(working_dir: &str, args: &[&str]) -> Result<String, KataraError> {
+    git_env(working_dir, args, &[]).await
+}
+
+/// `git` with extra environment variables, e.g. `GIT_INDEX_FILE`.
+pub(crate) async fn git_env(
+    working_dir: &str,
+    args: &[&str],
+    envs: &[(&str, &str)],
+) -> Result<String, KataraError> {
+    let out = run_env(working_dir, args, envs).await?;
+    if !out.status.success() {
+        return Err(KataraError::Git(
+            String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+pub(crate) async fn run(working_dir: &str, args: &[&str]) -> Result<Output, KataraError> {
+    run_env(working_dir, args, &[]).await
+}
+
+async fn run_env(
+    working_dir: &str,
+    args: &[&str],
+    envs: &[(&str, &str)],
+) -> Result<Output, KataraError> {
+    if !Path::new(working_dir).is_dir() {
+        return Err(KataraError::Git(format!(
+            "Not a directory: {}",
+            working_dir
+        )));
+    }
+    Command::new("git")
+        .args(args)
+        .current_dir(working_dir)
+        // Never block on a credential or editor prompt
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .envs(envs.iter().copied())
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => KataraError::Git("git is not installed".into()),
+            _ => KataraError::Git(format!("Failed to run git: {}", e)),
+        })
+}
+
+fn null_device() -> &'static str {
+    if cfg!(windows) {
+        "NUL"
+    } else {
+        "/dev/null"
+    }
+}
+
+/// Parse `git status --porcelain=v1 --branch -z`.
+fn parse_status(out: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    let mut entries = out.split('\0').filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        if let Some(header) = entry.strip_prefix("## ") {
+            parse_branch(header, &mut status);
+            continue;
+        }
+        if entry.len() < 4 {
+            continue;
+        }
+        let (index, worktree) = (&entry[0..1], &entry[1..2]);
+        // Renames and copies are followed by the original path
+        let orig_path = if matches!(index, "R" | "C") || matches!(worktree, "R" | "C") {
+            entries.next().map(str::to_string)
+        } else {
+            None
+        };
+        status.files.push(GitFileStatus {
+            path: entry[3..].to_string(),
+            orig_path,
+            index: index.to_string(),
+            worktree: worktree.to_string(),
+        });
+    }
+    status
+}
+
+/// Parse the branch header, e.g. `main...origin/main [ahead 1, behind 2]`,
+/// `No commits yet on main` or `HEAD (no branch)`.
+fn parse_branch(header: &str, status: &mut GitStatus) {
+    let (refs, counts) = match header.split_once(" [") {
+        Some((refs, counts)) => (refs, counts.trim_end_matches(']')),
+        None => (header, ""),
+    };
+    let refs = refs
+        .strip_prefix("No commits yet on ")
+        .or_else(|| refs.strip_prefix("Initial commit on "))
+        .unwrap_or(refs);
+    if refs.starts_with("HEAD (no branch)") {
+        return;
+    }
+    match refs.split_once("...") {
+        Some((branch, upstream)) => {
+            status.branch = Some(branch.to_string());
+            status.upstream = Some(upstream.to_string());
+        }
+        None => status.branch = Some(refs.to_string()),
+    }
+    for count in counts.split(", ") {
+        if let Some(n) = count.strip_prefix("ahead ") {
+            status.ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = count.strip_prefix("behind ") {
+            status.behind = n.parse().unwrap_or(0);
+        }
+    }
+}
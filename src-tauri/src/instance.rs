@@ -0,0 +1,155 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+use tokio::net::TcpListener;
+
+use crate::config;
+use crate::error::KataraError;
+use crate::state::AppState;
+use crate::terminal::procinfo;
+
+/// What external tools need to talk to the running app. Written to
+/// `~/.katara/instance.json` once the servers are listening and removed on
+/// exit.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceInfo {
+    pub pid: u32,
+    pub version: String,
+    pub ws_port: u16,
+    pub agui_port: u16,
+    /// Bearer token for the AG-UI server.
+    pub token: String,
+    /// Unix time in milliseconds.
+    pub started_at: u64,
+}
+
+/// A later launch's command line, forwarded to the running instance.
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchArgs {
+    /// Arguments after the program name.
+    pub args: Vec<String>,
+    /// Working directory of the second launch.
+    pub cwd: String,
+    /// First non-flag argument, resolved against `cwd`, if it's a
+    /// directory (e.g. `katara .`).
+    pub directory: Option<String>,
+}
+
+/// Called by the single-instance plugin when Katara is launched while
+/// already running: the new process exits, and its arguments are emitted
+/// here as `app:launch` with the main window brought to the front.
+pub fn on_second_launch(app_handle: &tauri::AppHandle, argv: Vec<String>, cwd: String) {
+    let args: Vec<String> = argv.into_iter().skip(1).collect();
+    let directory = args
+        .iter()
+        .find(|a| !a.starts_with('-'))
+        .map(|a| std::path::Path::new(&cwd).join(a))
+        .filter(|p| p.is_dir())
+        .map(|p| p.canonicalize().unwrap_or(p).display().to_string());
+    println!("[katara] Second launch forwarded: {:?}", args);
+
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let _ = app_handle.emit(
+        "app:launch",
+        LaunchArgs {
+            args,
+            cwd,
+            directory,
+        },
+    );
+}
+
+/// `~/.katara/instance.json`. Without a home directory there's nowhere
+/// clients know to look, so there's no discovery file.
+pub fn discovery_path() -> Result<PathBuf, KataraError> {
+    dirs::home_dir()
+        .map(|home| home.join(".katara").join("instance.json"))
+        .ok_or_else(|| KataraError::Config("No home directory for the discovery file".into()))
+}
+
+/// Listen on `127.0.0.1:port`. If a fixed port is taken (e.g. by another
+/// instance), fall back to a random one rather than failing to start.
+pub async fn bind(port: Option<u16>, server: &str) -> std::io::Result<TcpListener> {
+    let port = port.unwrap_or(0);
+    match TcpListener::bind(("127.0.0.1", port)).await {
+        Err(e) if port != 0 && e.kind() == std::io::ErrorKind::AddrInUse => {
+            eprintln!(
+                "[katara] {} port {} is in use, falling back to a random port",
+                server, port
+            );
+            TcpListener::bind(("127.0.0.1", 0)).await
+        }
+        result => result,
+    }
+}
+
+/// Write the discovery file with the ports known so far. Called by each
+/// server once it's listening.
+pub async fn write_discovery(state: &AppState) {
+    let info = InstanceInfo {
+        pid: std::process::id(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        ws_port: *state.ws_port.read().await,
+        agui_port: *state.axum_port.read().await,
+        token: state.auth_token.clone(),
+        started_at: state.started_at,
+    };
+    let path = match discovery_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("[katara] Not writing the discovery file: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = write_file(&path, &info) {
+        eprintln!("[katara] Failed to write {}: {}", path.display(), e);
+    }
+}
+
+/// Remove the discovery file, unless another instance has since replaced
+/// it.
+pub fn remove_discovery() {
+    if discovery_pid() == Some(std::process::id()) {
+        if let Ok(path) = discovery_path() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// The pid of another Katara that's still running and owns the discovery
+/// file, if there is one.
+pub fn live_instance() -> Option<u32> {
+    let pid = discovery_pid()?;
+    let ours = std::process::id();
+    // A pid reused since a crash belongs to some other program
+    let same_program = procinfo::name_of(pid) == procinfo::name_of(ours);
+    (pid != ours && procinfo::is_alive(pid) && same_program).then_some(pid)
+}
+
+fn discovery_pid() -> Option<u32> {
+    std::fs::read_to_string(discovery_path().ok()?)
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+        .and_then(|v| v.get("pid").and_then(|p| p.as_u64()))
+        .and_then(|pid| u32::try_from(pid).ok())
+}
+
+fn write_file(path: &Path, info: &InstanceInfo) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(info)?;
+    // Write-then-rename so readers never see a partial file
+    let tmp = path.with_extension("json.tmp");
+    // The file holds the auth token, so it's private from the start; a
+    // leftover from a crashed write is replaced rather than written through
+    let _ = std::fs::remove_file(&tmp);
+    config::create_private_file(&tmp)?.write_all(content.as_bytes())?;
+    std::fs::rename(&tmp, path)
+}
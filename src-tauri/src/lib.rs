@@ -1,44 +1,89 @@
 pub mod agui;
+pub mod api;
+pub mod auth;
 pub mod commands;
 pub mod config;
 pub mod error;
+pub mod git;
+pub mod instance;
+pub mod mcp;
+pub mod openai;
+pub mod plugins;
 pub mod process;
+pub mod quick_prompt;
+pub mod remote;
+pub mod search;
+pub mod sink;
 pub mod skills;
 pub mod state;
+pub mod stats;
 pub mod terminal;
+pub mod tray;
+pub mod updater;
 pub mod websocket;
+pub mod window_scope;
 
 use std::sync::Arc;
+use sink::{HeadlessSink, Sink};
 use state::AppState;
+use tauri::Manager;
 
 pub fn run() {
+    if std::env::args().skip(1).any(|arg| arg == "--headless") {
+        return run_headless();
+    }
     let state = Arc::new(AppState::new());
 
     tauri::Builder::default()
+        // Must be registered first so a second launch exits before starting
+        // its own servers
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            instance::on_second_launch(app, argv, cwd);
+        }))
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
         .manage(state.clone())
         .setup(move |app| {
-            let app_handle = app.handle().clone();
-            let state_for_ws = state.clone();
-            let state_for_axum = state.clone();
+            start_services(&state, sink::tauri(app.handle().clone()));
 
-            // Spawn WebSocket server for Claude CLI connections
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = websocket::server::start_ws_server(state_for_ws, app_handle.clone()).await {
-                    eprintln!("WebSocket server error: {}", e);
-                }
-            });
+            // Keep the skill library in sync with edits made outside the app
+            let dir = std::path::PathBuf::from(state.settings().skills_directory);
+            if let Err(e) = skills::watcher::start(app.handle().clone(), dir) {
+                eprintln!("[katara] Skills watcher error: {}", e);
+            }
 
-            // Spawn Axum HTTP server for AG-UI (CopilotKit runtimeUrl)
-            let app_handle_axum = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = agui::server::start_agui_server(state_for_axum, app_handle_axum).await {
-                    eprintln!("AG-UI server error: {}", e);
+            // Refresh config editors when the CLI or another editor changes
+            // CLAUDE.md or settings files
+            match config::watcher::start(app.handle().clone()) {
+                Ok(watcher) => {
+                    let _ = state.config_watcher.set(watcher);
                 }
-            });
+                Err(e) => eprintln!("[katara] Config watcher error: {}", e),
+            }
+
+            if let Err(e) = tray::init(app.handle()) {
+                eprintln!("[katara] Tray error: {}", e);
+            }
+            updater::check_on_startup(app.handle());
+            let shortcut = state.settings().quick_prompt_shortcut;
+            if let Err(e) = quick_prompt::init(app.handle(), shortcut.as_deref()) {
+                eprintln!("[katara] Quick prompt error: {}", e);
+            }
 
             Ok(())
         })
+        .on_window_event(|window, event| {
+            // A closed window's sessions go back to the other windows
+            if let tauri::WindowEvent::Destroyed = event {
+                window
+                    .state::<Arc<AppState>>()
+                    .window_scopes
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .remove(window.label());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             // Claude session commands
             commands::claude::spawn_session,
@@ -47,29 +92,269 @@ pub fn run() {
             commands::claude::approve_tool,
             commands::claude::interrupt_session,
             commands::claude::get_message_history,
+            commands::claude::get_message_history_since,
             commands::claude::list_sessions,
             commands::claude::set_permission_mode,
             commands::claude::get_session_cost,
             commands::claude::resume_session,
+            commands::claude::list_resumable_sessions,
+            commands::claude::submit_quick_prompt,
+            commands::claude::load_persisted_sessions,
+            commands::claude::get_persisted_transcript,
+            commands::claude::reopen_persisted_session,
+            commands::claude::delete_persisted_session,
             // Terminal commands
             commands::terminal::spawn_terminal,
+            commands::terminal::attach_terminal,
             commands::terminal::write_terminal,
+            commands::terminal::paste_terminal,
             commands::terminal::resize_terminal,
             commands::terminal::kill_terminal,
+            commands::terminal::pause_terminal,
+            commands::terminal::resume_terminal,
+            commands::terminal::ack_terminal,
+            commands::terminal::list_terminals,
+            commands::terminal::restore_terminals,
+            commands::terminal::save_terminal_layout,
+            commands::terminal::get_terminal_layout,
+            commands::terminal::run_in_terminal,
+            commands::terminal::start_recording,
+            commands::terminal::stop_recording,
+            commands::terminal::export_recording,
+            commands::terminal::search_terminal,
+            commands::terminal::clear_terminal_buffer,
             // Config commands
             commands::config::read_claude_md,
             commands::config::write_claude_md,
+            commands::config::resolve_claude_md,
+            commands::config::get_memory_overview,
+            commands::config::lint_config,
+            commands::config::diff_settings,
+            commands::config::init_project,
+            commands::config::read_agents_md,
+            commands::config::write_agents_md,
+            commands::config::link_memory_files,
+            commands::config::watch_project_config,
+            commands::config::read_claude_settings,
+            commands::config::write_claude_settings,
+            commands::config::validate_claude_settings,
+            commands::config::read_hooks,
+            commands::config::write_hooks,
+            commands::config::test_hook,
+            commands::config::list_agents,
+            commands::config::read_agent,
+            commands::config::write_agent,
+            commands::config::delete_agent,
+            commands::config::list_output_styles,
+            commands::config::read_output_style,
+            commands::config::write_output_style,
+            commands::config::delete_output_style,
             commands::config::read_settings,
+            commands::mcp::list_mcp_servers,
+            commands::mcp::check_mcp_server,
+            commands::git::git_status,
+            commands::git::git_diff,
+            commands::git::git_log,
+            commands::git::list_checkpoints,
+            commands::git::rollback_to_turn,
+            commands::git::fetch_github_item,
+            commands::git::send_github_context,
+            commands::git::draft_pull_request,
+            commands::git::create_pull_request,
+            commands::search::search_project_files,
+            commands::search::search_project_content,
             commands::config::write_settings,
+            commands::config::update_settings,
+            commands::config::get_keybindings,
+            commands::config::set_keybinding,
+            commands::config::set_keybindings,
+            commands::config::list_terminal_profiles,
+            commands::config::create_terminal_profile,
+            commands::config::update_terminal_profile,
+            commands::config::delete_terminal_profile,
+            commands::config::set_secret,
+            commands::config::get_secret_names,
+            commands::config::delete_secret,
+            commands::config::list_themes,
+            commands::config::create_theme,
+            commands::config::update_theme,
+            commands::config::delete_theme,
+            commands::config::export_theme,
+            commands::config::import_theme,
+            commands::config::list_env_profiles,
+            commands::config::create_env_profile,
+            commands::config::update_env_profile,
+            commands::config::delete_env_profile,
+            commands::config::create_config_backup,
+            commands::config::list_config_backups,
+            commands::config::restore_config_backup,
+            commands::config::export_config,
+            commands::config::import_config,
             // Skill commands
             commands::skills::list_skills,
+            commands::skills::search_skills,
             commands::skills::read_skill,
             commands::skills::write_skill,
             commands::skills::delete_skill,
+            commands::skills::install_builtin_skills,
+            commands::skills::list_skill_templates,
+            commands::skills::create_skill_from_template,
+            commands::skills::duplicate_skill,
+            commands::skills::rename_skill,
+            commands::skills::import_skill_from_url,
+            commands::skills::import_skills_from_repo,
+            commands::skills::export_skills,
+            commands::skills::import_skill_bundle,
+            commands::skills::export_skill_as_command,
+            commands::skills::import_command_as_skill,
+            commands::skills::list_skill_versions,
+            commands::skills::read_skill_version,
+            commands::skills::restore_skill_version,
+            commands::skills::validate_skill,
+            commands::skills::render_skill,
+            commands::skills::preview_skill,
+            commands::skills::run_skill,
+            commands::skills::list_skill_runs,
             // App commands
             commands::app::get_ports,
             commands::app::get_version,
+            commands::app::check_for_updates,
+            commands::app::download_update,
+            commands::app::apply_update,
+            commands::app::set_window_scope,
+            commands::app::get_window_scope,
+            commands::stats::get_dashboard_stats,
+            commands::remote::get_remote_status,
+            commands::remote::get_remote_access_info,
+            commands::remote::create_pairing_code,
+            commands::remote::list_paired_devices,
+            commands::remote::revoke_device,
+            commands::plugins::list_plugins,
+            commands::plugins::invoke_plugin,
+            commands::plugins::reload_plugins,
+            commands::agents::list_agui_agents,
+            commands::agents::create_agui_agent,
+            commands::agents::assign_agui_agent,
+            commands::agents::delete_agui_agent,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running Katara");
+        .build(tauri::generate_context!())
+        .expect("error while running Katara")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                instance::remove_discovery();
+                // Only a crash should leave sessions to recover
+                process::recovery::clear();
+            }
+        });
+}
+
+/// Run without a window (`katara --headless`): the Claude CLI WebSocket
+/// server, the HTTP servers and session management, driven entirely
+/// through AG-UI, MCP and the other HTTP APIs. Clients find the ports and
+/// auth token in the discovery file. Runs until Ctrl-C or SIGTERM, and not
+/// at all while another instance owns the discovery file.
+pub fn run_headless() {
+    // Clients would have no way to find the ports or the token
+    let discovery = match instance::discovery_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("[katara] Can't run headless: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(pid) = instance::live_instance() {
+        eprintln!(
+            "[katara] Katara is already running (pid {}); see {}",
+            pid,
+            discovery.display()
+        );
+        std::process::exit(1);
+    }
+    let state = Arc::new(AppState::new());
+    println!(
+        "[katara] Running headless; ports and auth token go to {}",
+        discovery.display()
+    );
+    tauri::async_runtime::block_on(async move {
+        start_services(&state, Arc::new(HeadlessSink));
+        shutdown_signal().await;
+        println!("[katara] Shutting down");
+        instance::remove_discovery();
+        process::recovery::clear();
+    });
+}
+
+/// Wait for Ctrl-C, or on Unix for SIGTERM, as service managers send.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => eprintln!("[katara] Can't wait for SIGTERM: {}", e),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        eprintln!("[katara] Can't wait for Ctrl-C: {}", e);
+    }
+}
+
+/// Start the servers, plugins and housekeeping shared by the app and
+/// headless mode. Events go to `sink`.
+fn start_services(state: &Arc<AppState>, sink: Sink) {
+    // Spawn WebSocket server for Claude CLI connections
+    let state_for_ws = state.clone();
+    let sink_for_ws = sink.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = websocket::server::start_ws_server(state_for_ws, sink_for_ws).await {
+            eprintln!("WebSocket server error: {}", e);
+        }
+    });
+
+    // Spawn Axum HTTP server for AG-UI (CopilotKit runtimeUrl)
+    let state_for_axum = state.clone();
+    let sink_for_axum = sink.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = agui::server::start_agui_server(state_for_axum, sink_for_axum).await {
+            eprintln!("AG-UI server error: {}", e);
+        }
+    });
+
+    // Spawn the remote access server, if enabled, for paired devices
+    let state_for_remote = state.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = remote::server::start(state_for_remote, sink).await {
+            eprintln!("[katara] Remote access error: {}", e);
+        }
+    });
+
+    // Start the plugins declared in settings
+    let state_for_plugins = state.clone();
+    tauri::async_runtime::spawn(async move {
+        let configs = state_for_plugins.settings().plugins;
+        state_for_plugins
+            .plugins
+            .start(&configs, &state_for_plugins.event_tx)
+            .await;
+    });
+
+    // Seed the skill library on first run
+    let settings = state.settings();
+    if let Err(e) = skills::builtin::install(&settings.skills_directory, false) {
+        eprintln!("[katara] Failed to install built-in skills: {}", e);
+    }
+
+    // Drop dashboard activity and saved conversations past their retention
+    let retention_days = settings.history.activity_retention_days;
+    let transcript_retention_days = settings.history.transcript_retention_days;
+    tauri::async_runtime::spawn_blocking(move || {
+        stats::activity::compact(retention_days);
+        process::transcript::prune(transcript_retention_days);
+    });
 }
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::KataraError;
+
+/// How an MCP server is started or reached, as written in `.mcp.json` /
+/// `~/.claude.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// `stdio` (the default), `sse` or `http`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+}
+
+impl McpServerConfig {
+    pub fn transport(&self) -> &str {
+        self.transport.as_deref().unwrap_or("stdio")
+    }
+
+    /// Copy with `${VAR}` / `${VAR:-default}` references expanded, like the
+    /// CLI does for `.mcp.json`.
+    pub fn expanded(&self) -> Self {
+        Self {
+            transport: self.transport.clone(),
+            command: self.command.as_deref().map(expand_env),
+            args: self.args.iter().map(|a| expand_env(a)).collect(),
+            env: expand_map(&self.env),
+            url: self.url.as_deref().map(expand_env),
+            headers: expand_map(&self.headers),
+        }
+    }
+}
+
+/// A configured server and where it was found.
+#[derive(Debug, Clone, Serialize)]
+pub struct McpServerEntry {
+    pub name: String,
+    /// `user`, `local` (per-project, in `~/.claude.json`) or `project`
+    /// (`.mcp.json`).
+    pub scope: String,
+    pub path: String,
+    pub config: McpServerConfig,
+}
+
+/// All MCP servers visible to a session in `project_dir`. When the same name
+/// is configured at several scopes, local wins over project, which wins over
+/// user (the CLI's precedence).
+pub fn list_servers(project_dir: Option<&str>) -> Result<Vec<McpServerEntry>, KataraError> {
+    let claude_json = claude_json_path();
+    let global = read_json(&claude_json)?;
+
+    let mut entries = servers_in(&global, "user", &claude_json);
+    if let Some(dir) = project_dir {
+        let mcp_json = Path::new(dir).join(".mcp.json");
+        let project = read_json(&mcp_json)?;
+        let local = global
+            .get("projects")
+            .and_then(|p| p.get(project_key(dir)))
+            .cloned()
+            .unwrap_or(Value::Null);
+
+        for entry in servers_in(&project, "project", &mcp_json)
+            .into_iter()
+            .chain(servers_in(&local, "local", &claude_json))
+        {
+            entries.retain(|e| e.name != entry.name);
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Look up one configured server by name.
+pub fn find_server(name: &str, project_dir: Option<&str>) -> Result<McpServerEntry, KataraError> {
+    list_servers(project_dir)?
+        .into_iter()
+        .find(|e| e.name == name)
+        .ok_or_else(|| KataraError::Config(format!("MCP server {} is not configured", name)))
+}
+
+fn servers_in(root: &Value, scope: &str, path: &Path) -> Vec<McpServerEntry> {
+    let Some(servers) = root.get("mcpServers").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    for (name, config) in servers {
+        match serde_json::from_value(config.clone()) {
+            Ok(config) => entries.push(McpServerEntry {
+                name: name.clone(),
+                scope: scope.to_string(),
+                path: path.display().to_string(),
+                config,
+            }),
+            Err(e) => eprintln!(
+                "[katara] Ignoring MCP server {} in {}: {}",
+                name,
+                path.display(),
+                e
+            ),
+        }
+    }
+    entries
+}
+
+fn read_json(path: &Path) -> Result<Value, KataraError> {
+    if !path.exists() {
+        return Ok(Value::Null);
+    }
+    let content = std::fs::read_to_string(path).map_err(KataraError::Io)?;
+    serde_json::from_str(&content).map_err(KataraError::Serde)
+}
+
+fn claude_json_path() -> PathBuf {
+    dirs::home_dir().unwrap_or_default().join(".claude.json")
+}
+
+/// `~/.claude.json` keys per-project settings by absolute path.
+fn project_key(dir: &str) -> String {
+    Path::new(dir)
+        .canonicalize()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| dir.to_string())
+}
+
+fn expand_map(map: &HashMap<String, String>) -> HashMap<String, String> {
+    map.iter()
+        .map(|(k, v)| (k.clone(), expand_env(v)))
+        .collect()
+}
+
+/// Expand `${VAR}` and `${VAR:-default}`; unset variables without a default
+/// become empty.
+fn expand_env(value: &str) -> String {
+    let re = regex::Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?::-([^}]*))?\}")
+        .expect("env pattern is valid");
+    re.replace_all(value, |c: &regex::Captures| {
+        std::env::var(&c[1])
+            .ok()
+            .or_else(|| c.get(2).map(|d| d.as_str().to_string()))
+            .unwrap_or_default()
+    })
+    .into_owned()
+}
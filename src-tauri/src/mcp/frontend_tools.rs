@@ -0,0 +1,183 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, oneshot, Mutex, RwLock};
+
+use crate::error::KataraError;
+
+/// How long a frontend tool call waits for the app to send back a result.
+/// Some actions wait on the user, so this is generous.
+const RESULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// An action the CopilotKit frontend registered (`useCopilotAction`),
+/// offered to the session as an MCP tool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontendTool {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema for the arguments.
+    pub parameters: Value,
+}
+
+impl FrontendTool {
+    /// Read one entry of `RunAgentInput.tools`. `None` for a name MCP
+    /// can't carry.
+    pub fn from_agui(tool: &Value) -> Option<Self> {
+        let name = tool.get("name")?.as_str()?;
+        let valid = !name.is_empty()
+            && name.len() <= 64
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid {
+            return None;
+        }
+        let parameters = tool
+            .get("parameters")
+            .or_else(|| tool.get("jsonSchema"))
+            .filter(|p| p.is_object())
+            .cloned()
+            .unwrap_or_else(|| json!({ "type": "object", "properties": {} }));
+        Some(Self {
+            name: name.to_string(),
+            description: tool
+                .get("description")
+                .and_then(|d| d.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            parameters,
+        })
+    }
+
+    /// The entry for MCP `tools/list`.
+    pub fn definition(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "inputSchema": self.parameters,
+        })
+    }
+}
+
+/// A call waiting for the frontend's result.
+struct PendingCall {
+    name: String,
+    reply: oneshot::Sender<String>,
+}
+
+/// Frontend tools by session ID, and the calls waiting on them.
+///
+/// The frontend runs its actions itself: a call ends the AG-UI run at the
+/// tool call, and CopilotKit sends the result back as a `tool` message in
+/// the next run, which completes the MCP call the CLI is blocked on.
+pub struct FrontendTools {
+    tools: RwLock<HashMap<String, Vec<FrontendTool>>>,
+    pending: Mutex<HashMap<String, VecDeque<PendingCall>>>,
+    /// Session IDs whose tools changed, for `tools/list_changed`.
+    changed: broadcast::Sender<String>,
+}
+
+impl Default for FrontendTools {
+    fn default() -> Self {
+        let (changed, _) = broadcast::channel(64);
+        Self {
+            tools: RwLock::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+            changed,
+        }
+    }
+}
+
+impl FrontendTools {
+    /// Set the session's tools to what the frontend sent with a run.
+    pub async fn register(&self, session_id: &str, tools: Vec<FrontendTool>) {
+        let mut registered = self.tools.write().await;
+        if registered
+            .get(session_id)
+            .map_or(tools.is_empty(), |t| *t == tools)
+        {
+            return;
+        }
+        registered.insert(session_id.to_string(), tools);
+        let _ = self.changed.send(session_id.to_string());
+    }
+
+    pub async fn list(&self, session_id: &str) -> Vec<FrontendTool> {
+        self.tools
+            .read()
+            .await
+            .get(session_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub async fn contains(&self, session_id: &str, name: &str) -> bool {
+        self.tools
+            .read()
+            .await
+            .get(session_id)
+            .is_some_and(|tools| tools.iter().any(|t| t.name == name))
+    }
+
+    /// Wait for the frontend to run `name` and send back its result.
+    pub async fn call(&self, session_id: &str, name: &str) -> Result<String, KataraError> {
+        let (reply, result) = oneshot::channel();
+        self.pending
+            .lock()
+            .await
+            .entry(session_id.to_string())
+            .or_default()
+            .push_back(PendingCall {
+                name: name.to_string(),
+                reply,
+            });
+        match tokio::time::timeout(RESULT_TIMEOUT, result).await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(_)) => Err(KataraError::Process(format!(
+                "{} was cancelled before the app answered",
+                name
+            ))),
+            Err(_) => {
+                // Drop our entry (and any other abandoned one)
+                if let Some(calls) = self.pending.lock().await.get_mut(session_id) {
+                    calls.retain(|c| !c.reply.is_closed());
+                }
+                Err(KataraError::Process(format!(
+                    "The app didn't return a result for {}",
+                    name
+                )))
+            }
+        }
+    }
+
+    /// Hand results from the frontend to the oldest waiting calls of the
+    /// same tools. Returns how many were waiting.
+    pub async fn resolve(&self, session_id: &str, results: Vec<(String, String)>) -> usize {
+        let mut pending = self.pending.lock().await;
+        let Some(calls) = pending.get_mut(session_id) else {
+            return 0;
+        };
+        let mut resolved = 0;
+        for (name, result) in results {
+            if let Some(i) = calls.iter().position(|c| c.name == name) {
+                if let Some(call) = calls.remove(i) {
+                    if call.reply.send(result).is_ok() {
+                        resolved += 1;
+                    }
+                }
+            }
+        }
+        resolved
+    }
+
+    /// Forget a closed session's tools, cancelling its waiting calls.
+    pub async fn remove(&self, session_id: &str) {
+        self.tools.write().await.remove(session_id);
+        self.pending.lock().await.remove(session_id);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.changed.subscribe()
+    }
+}
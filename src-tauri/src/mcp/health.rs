@@ -0,0 +1,516 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+
+use crate::mcp::config::McpServerConfig;
+
+/// Protocol version Katara offers in the handshake.
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Upper bound on the whole check, including server startup (`npx` may
+/// download the package first).
+const CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Stderr kept from a stdio server for the report.
+const MAX_STDERR: usize = 4096;
+
+/// Outcome of probing an MCP server.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct McpHealth {
+    pub name: String,
+    pub transport: String,
+    pub ok: bool,
+    /// `serverInfo` from the initialize response.
+    pub server_info: Option<Value>,
+    pub protocol_version: Option<String>,
+    pub tools: Vec<McpTool>,
+    pub resources: Vec<McpResource>,
+    pub error: Option<String>,
+    /// What a stdio server wrote to stderr, which usually explains a failure.
+    pub stderr: Option<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct McpTool {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct McpResource {
+    pub uri: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Start (or connect to) an MCP server, run the initialize handshake and
+/// list its tools and resources. Stdio servers are stopped afterwards.
+/// They run in `project_dir`, as the CLI runs them in the session's
+/// directory, so relative commands and paths resolve the same way.
+///
+/// Failures are reported in the result rather than as errors, so the UI can
+/// show them next to the server's config.
+pub async fn check_server(
+    name: &str,
+    config: &McpServerConfig,
+    project_dir: Option<&str>,
+) -> McpHealth {
+    let started = Instant::now();
+    let config = config.expanded();
+    let mut health = McpHealth {
+        name: name.to_string(),
+        transport: config.transport().to_string(),
+        ..Default::default()
+    };
+
+    let stderr = Arc::new(Mutex::new(String::new()));
+    let probe = async {
+        let mut transport = Transport::connect(&config, project_dir, stderr.clone()).await?;
+        let result = handshake(&mut transport, &mut health).await;
+        transport.close().await;
+        result
+    };
+    let outcome = match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(format!(
+            "Timed out after {}s waiting for the server",
+            CHECK_TIMEOUT.as_secs()
+        )),
+    };
+
+    match outcome {
+        Ok(()) => health.ok = true,
+        Err(e) => health.error = Some(e),
+    }
+    let stderr = stderr.lock().await.trim().to_string();
+    health.stderr = (!stderr.is_empty()).then_some(stderr);
+    health.duration_ms = started.elapsed().as_millis() as u64;
+    health
+}
+
+async fn handshake(transport: &mut Transport, health: &mut McpHealth) -> Result<(), String> {
+    let init = transport
+        .request(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "katara", "version": env!("CARGO_PKG_VERSION") },
+            }),
+        )
+        .await?;
+    health.server_info = init.get("serverInfo").cloned();
+    health.protocol_version = init
+        .get("protocolVersion")
+        .and_then(Value::as_str)
+        .map(String::from);
+    transport.notify("notifications/initialized").await?;
+
+    let capabilities = init.get("capabilities").cloned().unwrap_or(Value::Null);
+    if capabilities.get("tools").is_some() {
+        let tools = transport.request("tools/list", json!({})).await?;
+        health.tools = tools
+            .get("tools")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|t| {
+                Some(McpTool {
+                    name: t.get("name")?.as_str()?.to_string(),
+                    description: str_field(t, "description"),
+                })
+            })
+            .collect();
+    }
+    if capabilities.get("resources").is_some() {
+        let resources = transport.request("resources/list", json!({})).await?;
+        health.resources = resources
+            .get("resources")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|r| {
+                Some(McpResource {
+                    uri: r.get("uri")?.as_str()?.to_string(),
+                    name: str_field(r, "name"),
+                    description: str_field(r, "description"),
+                })
+            })
+            .collect();
+    }
+    Ok(())
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(String::from)
+}
+
+/// Client side of the three MCP transports, just enough for a handshake.
+enum Transport {
+    Stdio {
+        child: Child,
+        stdin: ChildStdin,
+        stdout: Lines<BufReader<ChildStdout>>,
+        next_id: u64,
+    },
+    /// Streamable HTTP: every message is a POST; responses come back as JSON
+    /// or as a short event stream.
+    Http {
+        client: reqwest::Client,
+        url: String,
+        headers: reqwest::header::HeaderMap,
+        session_id: Option<String>,
+        next_id: u64,
+    },
+    /// Legacy HTTP+SSE: responses arrive on a long-lived event stream, and
+    /// messages are POSTed to the endpoint it announces.
+    Sse {
+        client: reqwest::Client,
+        endpoint: String,
+        headers: reqwest::header::HeaderMap,
+        events: EventStream,
+        next_id: u64,
+    },
+}
+
+impl Transport {
+    async fn connect(
+        config: &McpServerConfig,
+        project_dir: Option<&str>,
+        stderr_buf: Arc<Mutex<String>>,
+    ) -> Result<Self, String> {
+        match config.transport() {
+            "stdio" => {
+                let command = config
+                    .command
+                    .as_deref()
+                    .ok_or("Stdio server has no command")?;
+                let mut cmd = Command::new(command);
+                cmd.args(&config.args)
+                    .envs(&config.env)
+                    .stdin(std::process::Stdio::piped())
+                    .stdout(std::process::Stdio::piped())
+                    .stderr(std::process::Stdio::piped())
+                    .kill_on_drop(true);
+                if let Some(dir) = project_dir {
+                    cmd.current_dir(dir);
+                }
+                let mut child = cmd
+                    .spawn()
+                    .map_err(|e| format!("Failed to start {}: {}", command, e))?;
+                let stdin = child.stdin.take().ok_or("No stdin")?;
+                let stdout = child.stdout.take().ok_or("No stdout")?;
+                if let Some(mut stderr) = child.stderr.take() {
+                    tokio::spawn(async move {
+                        let mut chunk = [0u8; 1024];
+                        while let Ok(n) = stderr.read(&mut chunk).await {
+                            if n == 0 {
+                                break;
+                            }
+                            let mut buf = stderr_buf.lock().await;
+                            buf.push_str(&String::from_utf8_lossy(&chunk[..n]));
+                            if buf.len() > MAX_STDERR {
+                                let cut = buf.len() - MAX_STDERR;
+                                let cut = (cut..buf.len())
+                                    .find(|i| buf.is_char_boundary(*i))
+                                    .unwrap_or(cut);
+                                buf.drain(..cut);
+                            }
+                        }
+                    });
+                }
+                Ok(Transport::Stdio {
+                    child,
+                    stdin,
+                    stdout: BufReader::new(stdout).lines(),
+                    next_id: 1,
+                })
+            }
+            "http" => Ok(Transport::Http {
+                client: reqwest::Client::new(),
+                url: config.url.clone().ok_or("HTTP server has no url")?,
+                headers: header_map(config)?,
+                session_id: None,
+                next_id: 1,
+            }),
+            "sse" => {
+                let url = config.url.as_deref().ok_or("SSE server has no url")?;
+                let client = reqwest::Client::new();
+                let headers = header_map(config)?;
+                let response = client
+                    .get(url)
+                    .headers(headers.clone())
+                    .header("Accept", "text/event-stream")
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+                let mut events = EventStream::new(response);
+                let endpoint = loop {
+                    let event = events
+                        .next()
+                        .await?
+                        .ok_or("Event stream closed before announcing an endpoint")?;
+                    if event.name == "endpoint" {
+                        break event.data;
+                    }
+                };
+                let endpoint = reqwest::Url::parse(url)
+                    .and_then(|base| base.join(endpoint.trim()))
+                    .map_err(|e| format!("Invalid endpoint {}: {}", endpoint, e))?;
+                Ok(Transport::Sse {
+                    client,
+                    endpoint: endpoint.to_string(),
+                    headers,
+                    events,
+                    next_id: 1,
+                })
+            }
+            other => Err(format!("Unknown transport type: {}", other)),
+        }
+    }
+
+    /// Send a request and wait for the response with the same id.
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.take_id();
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+        let response = match self {
+            Transport::Stdio { stdin, stdout, .. } => {
+                write_line(stdin, &message).await?;
+                loop {
+                    let line = stdout
+                        .next_line()
+                        .await
+                        .map_err(|e| e.to_string())?
+                        .ok_or("Server exited before responding")?;
+                    // Servers may log to stdout; only JSON-RPC lines count
+                    if let Ok(value) = serde_json::from_str::<Value>(&line) {
+                        if is_response_to(&value, id) {
+                            break value;
+                        }
+                    }
+                }
+            }
+            Transport::Http {
+                client,
+                url,
+                headers,
+                session_id,
+                ..
+            } => {
+                let mut request = client
+                    .post(url.as_str())
+                    .headers(headers.clone())
+                    .header("Accept", "application/json, text/event-stream")
+                    .json(&message);
+                if let Some(session) = session_id.as_deref() {
+                    request = request
+                        .header("Mcp-Session-Id", session)
+                        .header("MCP-Protocol-Version", PROTOCOL_VERSION);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| format!("{} failed: {}", method, e))?;
+                if let Some(session) = response
+                    .headers()
+                    .get("mcp-session-id")
+                    .and_then(|v| v.to_str().ok())
+                {
+                    *session_id = Some(session.to_string());
+                }
+                let is_stream = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .is_some_and(|t| t.starts_with("text/event-stream"));
+                if is_stream {
+                    EventStream::new(response).response_to(id).await?
+                } else {
+                    response.json().await.map_err(|e| e.to_string())?
+                }
+            }
+            Transport::Sse {
+                client,
+                endpoint,
+                headers,
+                events,
+                ..
+            } => {
+                client
+                    .post(endpoint.as_str())
+                    .headers(headers.clone())
+                    .json(&message)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| format!("{} failed: {}", method, e))?;
+                events.response_to(id).await?
+            }
+        };
+
+        if let Some(error) = response.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            return Err(format!("{} failed: {}", method, message));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<(), String> {
+        let message = json!({ "jsonrpc": "2.0", "method": method });
+        match self {
+            Transport::Stdio { stdin, .. } => write_line(stdin, &message).await,
+            Transport::Http {
+                client,
+                url,
+                headers,
+                session_id,
+                ..
+            } => {
+                let mut request = client
+                    .post(url.as_str())
+                    .headers(headers.clone())
+                    .header("Accept", "application/json, text/event-stream")
+                    .header("MCP-Protocol-Version", PROTOCOL_VERSION)
+                    .json(&message);
+                if let Some(session) = session_id.as_deref() {
+                    request = request.header("Mcp-Session-Id", session);
+                }
+                request.send().await.map(|_| ()).map_err(|e| e.to_string())
+            }
+            Transport::Sse {
+                client,
+                endpoint,
+                headers,
+                ..
+            } => client
+                .post(endpoint.as_str())
+                .headers(headers.clone())
+                .json(&message)
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Stop a stdio server; HTTP sessions are simply dropped.
+    async fn close(self) {
+        if let Transport::Stdio {
+            mut child, stdin, ..
+        } = self
+        {
+            drop(stdin);
+            let _ = child.kill().await;
+        }
+    }
+
+    fn take_id(&mut self) -> u64 {
+        let next_id = match self {
+            Transport::Stdio { next_id, .. }
+            | Transport::Http { next_id, .. }
+            | Transport::Sse { next_id, .. } => next_id,
+        };
+        let id = *next_id;
+        *next_id += 1;
+        id
+    }
+}
+
+async fn write_line(stdin: &mut ChildStdin, message: &Value) -> Result<(), String> {
+    let mut line = message.to_string();
+    line.push('\n');
+    stdin
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write to server: {}", e))
+}
+
+fn is_response_to(value: &Value, id: u64) -> bool {
+    value.get("id").and_then(Value::as_u64) == Some(id)
+        && (value.get("result").is_some() || value.get("error").is_some())
+}
+
+fn header_map(config: &McpServerConfig) -> Result<reqwest::header::HeaderMap, String> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    for (name, value) in &config.headers {
+        let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| format!("Invalid header {}: {}", name, e))?;
+        let value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|e| format!("Invalid value for header {}: {}", name, e))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+struct SseEvent {
+    name: String,
+    data: String,
+}
+
+/// Incremental parser for a `text/event-stream` response body.
+struct EventStream {
+    response: reqwest::Response,
+    buffer: String,
+}
+
+impl EventStream {
+    fn new(response: reqwest::Response) -> Self {
+        Self {
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    /// The next event, or `None` when the stream ends.
+    async fn next(&mut self) -> Result<Option<SseEvent>, String> {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let raw: String = self.buffer.drain(..end + 2).collect();
+                let mut event = SseEvent {
+                    name: "message".into(),
+                    data: String::new(),
+                };
+                for line in raw.lines() {
+                    if let Some(name) = line.strip_prefix("event:") {
+                        event.name = name.trim().to_string();
+                    } else if let Some(data) = line.strip_prefix("data:") {
+                        if !event.data.is_empty() {
+                            event.data.push('\n');
+                        }
+                        event.data.push_str(data.strip_prefix(' ').unwrap_or(data));
+                    }
+                }
+                return Ok(Some(event));
+            }
+            match self.response.chunk().await.map_err(|e| e.to_string())? {
+                Some(chunk) => self
+                    .buffer
+                    .push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n")),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Read events until the JSON-RPC response with `id` arrives.
+    async fn response_to(&mut self, id: u64) -> Result<Value, String> {
+        while let Some(event) = self.next().await? {
+            if let Ok(value) = serde_json::from_str::<Value>(&event.data) {
+                if is_response_to(&value, id) {
+                    return Ok(value);
+                }
+            }
+        }
+        Err("Event stream closed before the server responded".into())
+    }
+}
@@ -0,0 +1,4 @@
+pub mod config;
+pub mod frontend_tools;
+pub mod health;
+pub mod server;
@@ -0,0 +1,502 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Extension, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::{json, Value};
+use tokio_stream::StreamExt;
+
+use crate::auth;
+use crate::error::KataraError;
+use crate::sink::Sink;
+use crate::skills::manager as skill_mgr;
+use crate::state::AppState;
+use crate::terminal::exec;
+use crate::terminal::persist;
+use crate::terminal::pty::{self, PtyHandle, TerminalOptions};
+
+/// Name the server is registered under in spawned sessions, so its tools
+/// show up as `mcp__katara__*`.
+pub const SERVER_NAME: &str = "katara";
+
+/// Protocol version Katara speaks.
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Tools that run shell commands, only offered with
+/// `server.mcp_shell_tools`.
+const SHELL_TOOLS: [&str; 2] = ["open_terminal", "run_command"];
+
+/// `--mcp-config` value that connects a spawned session to Katara's MCP
+/// endpoint on the AG-UI server. `session_token` is the session's own,
+/// so the server knows who's calling.
+pub fn session_mcp_config(axum_port: u16, session_token: &str) -> String {
+    json!({
+        "mcpServers": {
+            SERVER_NAME: {
+                "type": "http",
+                "url": format!("http://127.0.0.1:{}/mcp", axum_port),
+                "headers": {
+                    "Authorization": format!("Bearer {}", session_token),
+                }
+            }
+        }
+    })
+    .to_string()
+}
+
+/// POST /mcp — MCP over Streamable HTTP, answering each JSON-RPC request
+/// with a plain JSON response (no server-initiated messages).
+pub async fn handle(
+    State(state): State<Arc<AppState>>,
+    Extension(sink): Extension<Sink>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> Response {
+    let Some(caller) = caller(&state, &headers).await else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let session_id = caller.session_id();
+
+    let method = request["method"].as_str().unwrap_or_default();
+    // Notifications get no response
+    let Some(id) = request.get("id").cloned() else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": { "listChanged": true } },
+            "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": list_tools(&state, session_id).await })),
+        "tools/call" => {
+            let name = request["params"]["name"].as_str().unwrap_or_default();
+            let args = &request["params"]["arguments"];
+            println!("[katara] MCP tool call: {}", name);
+            // Tool failures are results the model can read, not protocol errors
+            Ok(
+                match call_tool(&state, &sink, session_id, name, args).await {
+                    Ok(value) => tool_result(&value, false),
+                    Err(e) => tool_result(&json!(e.to_string()), true),
+                },
+            )
+        }
+        _ => Err(json!({ "code": -32601, "message": format!("Method not found: {}", method) })),
+    };
+
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    };
+    Json(response).into_response()
+}
+
+/// GET /mcp — the stream for server-initiated messages. Sends
+/// `notifications/tools/list_changed` when the frontend's tools for the
+/// session change.
+pub async fn notifications(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    let Some(caller) = caller(&state, &headers).await else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Caller::Session(session_id) = caller else {
+        // Nothing would ever be sent without a session
+        return StatusCode::METHOD_NOT_ALLOWED.into_response();
+    };
+
+    let mut changed = state.frontend_tools.subscribe();
+    let (tx, rx) = tokio::sync::mpsc::channel::<String>(16);
+    tokio::spawn(async move {
+        loop {
+            match changed.recv().await {
+                Ok(id) if id != session_id => continue,
+                // A lagging receiver may have missed this session's change
+                Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+            let notification =
+                json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" });
+            if tx.send(notification.to_string()).await.is_err() {
+                break;
+            }
+        }
+    });
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx)
+        .map(|data| Ok::<_, Infallible>(Event::default().event("message").data(data)));
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Who's calling the MCP server.
+enum Caller {
+    /// A spawned session, known by the token in its `--mcp-config`.
+    Session(String),
+    /// A client with the app's auth token, which isn't any one session.
+    Client,
+}
+
+impl Caller {
+    fn session_id(&self) -> Option<&str> {
+        match self {
+            Caller::Session(id) => Some(id),
+            Caller::Client => None,
+        }
+    }
+}
+
+/// The caller, or `None` if the request has no valid token.
+async fn caller(state: &AppState, headers: &HeaderMap) -> Option<Caller> {
+    let token = auth::bearer(headers)?;
+    if auth::token_matches(token, &state.auth_token) {
+        return Some(Caller::Client);
+    }
+    let hash = auth::token_hash(token);
+    state
+        .sessions
+        .read()
+        .await
+        .values()
+        .find(|s| {
+            s.mcp_token_hash
+                .as_deref()
+                .is_some_and(|expected| auth::token_matches(&hash, expected))
+        })
+        .map(|s| Caller::Session(s.id.clone()))
+}
+
+/// Katara's own tools, then the frontend's for the calling session. A
+/// frontend tool can't shadow one of Katara's.
+async fn list_tools(state: &AppState, session_id: Option<&str>) -> Value {
+    let Value::Array(mut tools) = tool_definitions() else {
+        return json!([]);
+    };
+    if !state.settings().server.mcp_shell_tools {
+        tools.retain(|t| !SHELL_TOOLS.iter().any(|name| t["name"] == *name));
+    }
+    if let Some(session_id) = session_id {
+        let own: Vec<Value> = tools.iter().map(|t| t["name"].clone()).collect();
+        tools.extend(
+            state
+                .frontend_tools
+                .list(session_id)
+                .await
+                .iter()
+                .filter(|t| !own.contains(&json!(t.name)))
+                .map(|t| t.definition()),
+        );
+    }
+    Value::Array(tools)
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "list_sessions",
+            "description": "List the Claude Code sessions open in Katara with their status and working directory.",
+            "inputSchema": { "type": "object", "properties": {} }
+        },
+        {
+            "name": "read_skill",
+            "description": "Read a Katara skill's prompt template and inputs by name. Omit the name to list the available skills.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "name": { "type": "string" } }
+            }
+        },
+        {
+            "name": "open_terminal",
+            "description": "Open a terminal in Katara where the user can see it, optionally running a command in it. Returns the terminal id and, if a command was given, its output.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "cwd": { "type": "string", "description": "Defaults to the session's working directory." },
+                    "command": { "type": "string" },
+                    "timeout_ms": { "type": "integer" }
+                }
+            }
+        },
+        {
+            "name": "run_command",
+            "description": "Run a command in an open Katara terminal (visible to the user) and return its output and exit code.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "terminal_id": { "type": "string" },
+                    "command": { "type": "string" },
+                    "timeout_ms": { "type": "integer" }
+                },
+                "required": ["terminal_id", "command"]
+            }
+        },
+        {
+            "name": "notify",
+            "description": "Show the user a desktop notification.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "body": { "type": "string" }
+                },
+                "required": ["title"]
+            }
+        },
+        {
+            "name": "get_shared_state",
+            "description": "Read the state this session shares with the app's UI.",
+            "inputSchema": { "type": "object", "properties": {} }
+        },
+        {
+            "name": "update_shared_state",
+            "description": "Change the state this session shares with the app's UI. The patch is merged into it: objects merge key by key, null removes a key, anything else replaces. Returns the new state.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "patch": { "type": "object" } },
+                "required": ["patch"]
+            }
+        }
+    ])
+}
+
+fn tool_result(value: &Value, is_error: bool) -> Value {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    };
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+async fn call_tool(
+    state: &Arc<AppState>,
+    sink: &Sink,
+    session_id: Option<&str>,
+    name: &str,
+    args: &Value,
+) -> Result<Value, KataraError> {
+    let str_arg = |key: &str| args[key].as_str().filter(|s| !s.is_empty());
+    let timeout = args["timeout_ms"]
+        .as_u64()
+        .map_or(DEFAULT_COMMAND_TIMEOUT, Duration::from_millis);
+    if SHELL_TOOLS.contains(&name) && !state.settings().server.mcp_shell_tools {
+        return Err(KataraError::Terminal(
+            "Shell tools are turned off (server.mcp_shell_tools)".into(),
+        ));
+    }
+
+    match name {
+        "list_sessions" => {
+            let sessions = state.sessions.read().await;
+            Ok(Value::Array(
+                sessions
+                    .values()
+                    .map(|s| {
+                        json!({
+                            "id": s.id,
+                            "status": s.status,
+                            "working_dir": s.working_dir,
+                            "model": s.model,
+                            "pending_approvals": s.pending_approvals.len(),
+                            "is_caller": Some(s.id.as_str()) == session_id,
+                        })
+                    })
+                    .collect(),
+            ))
+        }
+        "read_skill" => {
+            let working_dir = session_dir(state, session_id).await;
+            let dir = state.settings().skills_directory;
+            let skills = skill_mgr::list_layered_skills(&dir, working_dir.as_deref())?;
+            match str_arg("name") {
+                Some(name) => {
+                    let skill = skills
+                        .into_iter()
+                        .find(|s| s.metadata.name == name)
+                        .ok_or_else(|| KataraError::Skill(format!("No skill named {}", name)))?;
+                    Ok(json!({
+                        "name": skill.metadata.name,
+                        "description": skill.metadata.description,
+                        "inputs": skill.metadata.inputs,
+                        "prompt_template": skill.prompt_template,
+                    }))
+                }
+                None => Ok(Value::Array(
+                    skills
+                        .iter()
+                        .map(|s| json!({ "name": s.metadata.name, "description": s.metadata.description }))
+                        .collect(),
+                )),
+            }
+        }
+        "open_terminal" => {
+            let cwd = match str_arg("cwd") {
+                Some(cwd) => Some(cwd.to_string()),
+                None => session_dir(state, session_id).await,
+            };
+            let terminal_id = open_terminal(state, sink, session_id, cwd.clone()).await?;
+            let mut result = json!({ "terminal_id": terminal_id, "cwd": cwd });
+            if let Some(command) = str_arg("command") {
+                result["result"] = json!(run_command(state, &terminal_id, command, timeout).await?);
+            }
+            Ok(result)
+        }
+        "run_command" => {
+            let terminal_id = str_arg("terminal_id")
+                .ok_or_else(|| KataraError::Terminal("terminal_id is required".into()))?;
+            let command = str_arg("command")
+                .ok_or_else(|| KataraError::Terminal("command is required".into()))?;
+            let opened_here = match session_id {
+                Some(sid) => state
+                    .sessions
+                    .read()
+                    .await
+                    .get(sid)
+                    .is_some_and(|s| s.mcp_terminals.iter().any(|t| t == terminal_id)),
+                None => false,
+            };
+            if !opened_here {
+                return Err(KataraError::Terminal(format!(
+                    "Terminal {} wasn't opened by this session; use open_terminal",
+                    terminal_id
+                )));
+            }
+            Ok(json!(
+                run_command(state, terminal_id, command, timeout).await?
+            ))
+        }
+        "notify" => {
+            let title = str_arg("title").unwrap_or("Claude");
+            sink.notify(title, str_arg("body").unwrap_or_default())
+                .map_err(|e| KataraError::Process(format!("Notification failed: {}", e)))?;
+            Ok(json!("Notification shown"))
+        }
+        "get_shared_state" => {
+            let thread_id = session_thread(state, session_id).await?;
+            Ok(state
+                .shared_state
+                .get(&thread_id)
+                .await
+                .unwrap_or_else(|| json!({})))
+        }
+        "update_shared_state" => {
+            let thread_id = session_thread(state, session_id).await?;
+            if !args["patch"].is_object() {
+                return Err(KataraError::Process("patch must be an object".into()));
+            }
+            Ok(state.shared_state.merge(&thread_id, &args["patch"]).await)
+        }
+        _ => match session_id {
+            Some(session_id) if state.frontend_tools.contains(session_id, name).await => {
+                // The frontend sees the call in the AG-UI stream, runs it and
+                // sends the result with its next run
+                Ok(json!(state.frontend_tools.call(session_id, name).await?))
+            }
+            _ => Err(KataraError::Process(format!("Unknown tool: {}", name))),
+        },
+    }
+}
+
+async fn session_dir(state: &AppState, session_id: Option<&str>) -> Option<String> {
+    let sessions = state.sessions.read().await;
+    session_id
+        .and_then(|id| sessions.get(id))
+        .map(|s| s.working_dir.clone())
+}
+
+/// The CopilotKit thread the calling session is serving, whose shared
+/// state the state tools work on.
+async fn session_thread(state: &AppState, session_id: Option<&str>) -> Result<String, KataraError> {
+    let threads = state.session_to_thread.read().await;
+    session_id
+        .and_then(|id| threads.get(id))
+        .cloned()
+        .ok_or_else(|| KataraError::Process("This session isn't connected to the app's UI".into()))
+}
+
+/// Spawn a terminal like `spawn_terminal` does and tell the frontend to
+/// show it with `terminal:opened`.
+async fn open_terminal(
+    state: &Arc<AppState>,
+    sink: &Sink,
+    session_id: Option<&str>,
+    cwd: Option<String>,
+) -> Result<String, KataraError> {
+    let app_handle = sink.app_handle().ok_or_else(|| {
+        KataraError::Terminal("Terminals aren't available when Katara runs headless".into())
+    })?;
+    let id = uuid::Uuid::new_v4().to_string();
+    // The session's env profile by name and its own variables as session
+    // env, so neither's secrets are saved with the terminal
+    let (env_profiles, session_env) = match session_id {
+        Some(sid) => state
+            .sessions
+            .read()
+            .await
+            .get(sid)
+            .map(|s| {
+                let profiles = s.env_profile.clone().into_iter().collect();
+                (profiles, s.env_overrides.clone())
+            })
+            .unwrap_or_default(),
+        None => Default::default(),
+    };
+    let opts = TerminalOptions {
+        rows: 24,
+        cols: 80,
+        cwd: cwd.clone(),
+        shell: state.settings().terminal.default_shell,
+        env_profiles,
+        session_env,
+        env: HashMap::new(),
+        title: Some("Claude".into()),
+        banner: None,
+        remote: false,
+    };
+    let handle = PtyHandle::spawn(id.clone(), opts, state.clone(), app_handle.clone())
+        .map_err(KataraError::Terminal)?;
+    {
+        let mut terminals = state.terminals.write().await;
+        terminals.insert(id.clone(), handle);
+        terminals[&id].watch_exit(state.clone(), app_handle.clone());
+        pty::apply_scrollback_limits(&terminals, &state.settings().terminal);
+        persist::save(&terminals);
+    }
+    if let Some(sid) = session_id {
+        if let Some(session) = state.sessions.write().await.get_mut(sid) {
+            session.mcp_terminals.push(id.clone());
+        }
+    }
+
+    let payload = json!({ "terminal_id": id, "session_id": session_id, "cwd": cwd });
+    match session_id {
+        Some(sid) => sink.emit_for_session(sid, "terminal:opened", payload),
+        None => sink.emit_for_terminal(&id, "terminal:opened", payload),
+    }
+    Ok(id)
+}
+
+async fn run_command(
+    state: &AppState,
+    terminal_id: &str,
+    command: &str,
+    timeout: Duration,
+) -> Result<exec::ExecResult, KataraError> {
+    let (rx, token) = {
+        let terminals = state.terminals.read().await;
+        let handle = terminals
+            .get(terminal_id)
+            .ok_or(KataraError::TerminalNotFound(terminal_id.to_string()))?;
+        exec::start_marked_command(handle, command).map_err(KataraError::Terminal)?
+    };
+    Ok(exec::collect_marked_output(rx, &token, timeout).await)
+}
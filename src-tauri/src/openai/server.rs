@@ -0,0 +1,478 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Extension, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt;
+
+use crate::commands::claude;
+use crate::config::recent;
+use crate::error::KataraError;
+use crate::process::manager::SpawnOptions;
+use crate::process::session::SessionStatus;
+use crate::sink::Sink;
+use crate::state::AppState;
+use crate::websocket::protocol::{ClaudeMessage, ContentBlock, WsEvent};
+
+/// Model id that routes to the most recent idle session, or a new one.
+const DEFAULT_MODEL: &str = "katara";
+
+/// Prefix of model ids that route to a specific session, e.g.
+/// `katara/<session id>`.
+const SESSION_MODEL_PREFIX: &str = "katara/";
+
+/// Header naming the session to route to, overriding the model.
+const SESSION_HEADER: &str = "x-katara-session";
+
+/// How long to wait for a session that is still starting to connect.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How often a waiting request checks that its session is still alive.
+const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionRequest {
+    #[serde(default)]
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub stream: bool,
+    #[serde(default)]
+    pub stream_options: Option<StreamOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    /// A string, or an array of content parts of which only text is used.
+    #[serde(default)]
+    pub content: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StreamOptions {
+    #[serde(default)]
+    pub include_usage: bool,
+}
+
+/// What a turn produced, in order.
+enum Piece {
+    Text(String),
+    Done {
+        finish_reason: &'static str,
+        usage: Value,
+    },
+}
+
+/// GET /v1/models — the default model plus one per open session.
+pub async fn models(State(state): State<Arc<AppState>>) -> Response {
+    let model =
+        |id: String| json!({ "id": id, "object": "model", "created": 0, "owned_by": "katara" });
+    let mut data = vec![model(DEFAULT_MODEL.to_string())];
+    data.extend(
+        state
+            .sessions
+            .read()
+            .await
+            .keys()
+            .map(|id| model(format!("{}{}", SESSION_MODEL_PREFIX, id))),
+    );
+    Json(json!({ "object": "list", "data": data })).into_response()
+}
+
+/// POST /v1/chat/completions — run the last user message as a turn in a
+/// Katara session and answer in the OpenAI chat completions format,
+/// streamed as `chat.completion.chunk` events when `stream` is set.
+///
+/// The session keeps its own conversation, so earlier messages are only
+/// used to seed a newly spawned session.
+pub async fn chat_completions(
+    State(state): State<Arc<AppState>>,
+    Extension(sink): Extension<Sink>,
+    headers: HeaderMap,
+    Json(request): Json<ChatCompletionRequest>,
+) -> Response {
+    let requested = headers
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| request.model.strip_prefix(SESSION_MODEL_PREFIX))
+        .map(str::to_string);
+
+    // Subscribe before the message is sent so no output is missed
+    let events = state.event_tx.subscribe();
+    let session_id = match start_turn(&state, sink, requested, &request.messages).await {
+        Ok(session_id) => session_id,
+        Err((status, message)) => return error(status, &message),
+    };
+    println!("[katara] Chat completion routed to session {}", session_id);
+
+    let id = format!("chatcmpl-{}", uuid::Uuid::new_v4().simple());
+    let created = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let model = if request.model.is_empty() {
+        DEFAULT_MODEL.to_string()
+    } else {
+        request.model.clone()
+    };
+    let mut turn = Turn::new(state.clone(), events, session_id);
+
+    if !request.stream {
+        let mut content = String::new();
+        let (finish_reason, usage) = loop {
+            match turn.next().await {
+                Ok(Piece::Text(text)) => content.push_str(&text),
+                Ok(Piece::Done {
+                    finish_reason,
+                    usage,
+                }) => break (finish_reason, usage),
+                Err(message) => return error(StatusCode::BAD_GATEWAY, message),
+            }
+        };
+        return Json(json!({
+            "id": id,
+            "object": "chat.completion",
+            "created": created,
+            "model": model,
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": content },
+                "finish_reason": finish_reason,
+            }],
+            "usage": usage,
+        }))
+        .into_response();
+    }
+
+    let include_usage = request.stream_options.is_some_and(|o| o.include_usage);
+    let (tx, rx) = tokio::sync::mpsc::channel::<String>(128);
+    tokio::spawn(async move {
+        let chunk = |delta: Value, finish_reason: Option<&str>| {
+            json!({
+                "id": id,
+                "object": "chat.completion.chunk",
+                "created": created,
+                "model": model,
+                "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+            })
+        };
+        let first = chunk(json!({ "role": "assistant", "content": "" }), None);
+        if tx.send(first.to_string()).await.is_err() {
+            return;
+        }
+        loop {
+            let data = match turn.next().await {
+                Ok(Piece::Text(text)) => chunk(json!({ "content": text }), None),
+                Ok(Piece::Done {
+                    finish_reason,
+                    usage,
+                }) => {
+                    let last = chunk(json!({}), Some(finish_reason));
+                    let _ = tx.send(last.to_string()).await;
+                    if include_usage {
+                        let mut usage_chunk = chunk(json!({}), None);
+                        usage_chunk["choices"] = json!([]);
+                        usage_chunk["usage"] = usage;
+                        let _ = tx.send(usage_chunk.to_string()).await;
+                    }
+                    break;
+                }
+                Err(message) => {
+                    let body = json!({ "message": message, "type": "server_error", "code": null });
+                    let _ = tx.send(json!({ "error": body }).to_string()).await;
+                    break;
+                }
+            };
+            if tx.send(data.to_string()).await.is_err() {
+                return; // Client disconnected
+            }
+        }
+        let _ = tx.send("[DONE]".to_string()).await;
+    });
+
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx)
+        .map(|data| Ok::<_, Infallible>(Event::default().data(data)));
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// An error body in the shape OpenAI clients expect.
+fn error(status: StatusCode, message: &str) -> Response {
+    let kind = if status.is_client_error() {
+        "invalid_request_error"
+    } else {
+        "server_error"
+    };
+    let body = json!({ "error": { "message": message, "type": kind, "code": null } });
+    (status, Json(body)).into_response()
+}
+
+/// Send the prompt to the requested session, or to the most recent idle
+/// one, spawning a session in the most recent project if there is none.
+/// Returns the session the turn runs in.
+async fn start_turn(
+    state: &Arc<AppState>,
+    sink: Sink,
+    requested: Option<String>,
+    messages: &[ChatMessage],
+) -> Result<String, (StatusCode, String)> {
+    let target = match requested {
+        Some(session_id) => {
+            let sessions = state.sessions.read().await;
+            let session = sessions
+                .get(&session_id)
+                .ok_or((StatusCode::NOT_FOUND, format!("No session {}", session_id)))?;
+            if matches!(session.status, SessionStatus::Active) {
+                return Err((
+                    StatusCode::CONFLICT,
+                    "Session is busy with another turn".to_string(),
+                ));
+            }
+            Some(session_id)
+        }
+        None => state
+            .sessions
+            .read()
+            .await
+            .values()
+            .filter(|s| s.ws_sender.is_some())
+            .filter(|s| matches!(s.status, SessionStatus::Connected | SessionStatus::Idle))
+            .max_by_key(|s| s.created_at)
+            .map(|s| s.id.clone()),
+    };
+
+    let bad_request = |message: &str| (StatusCode::BAD_REQUEST, message.to_string());
+    let server_error = |e: KataraError| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    match target {
+        Some(session_id) => {
+            let content = prompt(messages, false).ok_or_else(|| bad_request("No user message"))?;
+            send_when_connected(state, &session_id, content)
+                .await
+                .map_err(server_error)?;
+            Ok(session_id)
+        }
+        None => {
+            let content = prompt(messages, true).ok_or_else(|| bad_request("No user message"))?;
+            let working_dir = match recent::recent_projects().into_iter().next() {
+                Some(dir) => dir,
+                None => claude::default_working_dir(state, None).map_err(server_error)?,
+            };
+            let opts = SpawnOptions {
+                initial_prompt: Some(content),
+                ..Default::default()
+            };
+            claude::start_session(state, sink, working_dir, opts)
+                .await
+                .map_err(server_error)
+        }
+    }
+}
+
+/// Send a user turn, waiting up to `CONNECT_TIMEOUT` for a session that is
+/// still starting to connect.
+async fn send_when_connected(
+    state: &AppState,
+    session_id: &str,
+    content: String,
+) -> Result<(), KataraError> {
+    let deadline = tokio::time::Instant::now() + CONNECT_TIMEOUT;
+    loop {
+        // Register before checking, so a CLI connecting in between still
+        // wakes us
+        let connected = state.session_connected.notified();
+        tokio::pin!(connected);
+        connected.as_mut().enable();
+
+        let is_connected = state
+            .sessions
+            .read()
+            .await
+            .get(session_id)
+            .ok_or(KataraError::SessionNotFound(session_id.to_string()))?
+            .ws_sender
+            .is_some();
+        if is_connected || tokio::time::timeout_at(deadline, connected).await.is_err() {
+            break;
+        }
+    }
+    claude::send_user_message(state, session_id, content).await
+}
+
+/// The prompt for a request: its last user message. A new session has no
+/// conversation yet, so system messages and earlier turns are put in front.
+fn prompt(messages: &[ChatMessage], new_session: bool) -> Option<String> {
+    let last = messages.iter().rposition(|m| m.role == "user")?;
+    let message = text(&messages[last].content);
+    if message.trim().is_empty() {
+        return None;
+    }
+    if !new_session {
+        return Some(message);
+    }
+
+    let mut system = Vec::new();
+    let mut transcript = Vec::new();
+    for m in &messages[..last] {
+        let content = text(&m.content);
+        if content.trim().is_empty() {
+            continue;
+        }
+        match m.role.as_str() {
+            "system" | "developer" => system.push(content),
+            role => transcript.push(format!("{}: {}", role, content)),
+        }
+    }
+    let mut out = String::new();
+    if !system.is_empty() {
+        out.push_str(&format!("{}\n\n", system.join("\n\n")));
+    }
+    if !transcript.is_empty() {
+        out.push_str(&format!(
+            "[Conversation so far:]\n{}\n\n",
+            transcript.join("\n\n")
+        ));
+    }
+    out.push_str(&message);
+    Some(out)
+}
+
+/// Text of a message's content, joining text parts.
+fn text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Reads one turn of a session off the event bus as text pieces.
+struct Turn {
+    state: Arc<AppState>,
+    events: broadcast::Receiver<WsEvent>,
+    session_id: String,
+    /// The current assistant message's text arrived as stream deltas, so
+    /// the complete message shouldn't be sent again.
+    streamed: bool,
+    /// Text has been sent and the next message's text should be set apart.
+    separate: bool,
+}
+
+impl Turn {
+    fn new(state: Arc<AppState>, events: broadcast::Receiver<WsEvent>, session_id: String) -> Self {
+        Self {
+            state,
+            events,
+            session_id,
+            streamed: false,
+            separate: false,
+        }
+    }
+
+    /// The next piece of the turn. Fails if the session went away before
+    /// it finished, or if events were missed and the reply would have gaps.
+    async fn next(&mut self) -> Result<Piece, &'static str> {
+        const ENDED: &str = "Session ended before the reply";
+        let mut check = tokio::time::interval(SESSION_CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                event = self.events.recv() => match event {
+                    Ok(event) if event.session_id == self.session_id => {
+                        if let Some(piece) = self.translate(event.message) {
+                            return Ok(piece);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        return Err("Fell behind the session and missed part of the reply");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Err(ENDED),
+                },
+                _ = check.tick() => {
+                    let sessions = self.state.sessions.read().await;
+                    match sessions.get(&self.session_id).map(|s| &s.status) {
+                        None
+                        | Some(SessionStatus::Terminated)
+                        | Some(SessionStatus::Error(_))
+                        | Some(SessionStatus::Disconnected) => return Err(ENDED),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    fn translate(&mut self, message: ClaudeMessage) -> Option<Piece> {
+        match message {
+            ClaudeMessage::StreamEvent(msg) if msg.event.event_type == "content_block_delta" => {
+                let delta = msg.event.delta?;
+                if delta.delta_type != "text_delta" {
+                    return None;
+                }
+                let text = delta.text.filter(|t| !t.is_empty())?;
+                self.streamed = true;
+                Some(Piece::Text(self.separated(text)))
+            }
+            // Without partial messages the text only arrives here
+            ClaudeMessage::Assistant(msg) => {
+                let streamed = std::mem::take(&mut self.streamed);
+                let text: String = msg
+                    .message
+                    .content
+                    .iter()
+                    .filter_map(|block| match block {
+                        ContentBlock::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                if text.is_empty() {
+                    return None;
+                }
+                let piece = (!streamed).then(|| Piece::Text(self.separated(text)));
+                self.separate = true;
+                piece
+            }
+            ClaudeMessage::Result(result) => {
+                let finish_reason = match result.subtype.as_deref() {
+                    Some("error_max_turns") => "length",
+                    _ => "stop",
+                };
+                let usage = &result.extra["usage"];
+                let count = |key: &str| usage[key].as_u64().unwrap_or_default();
+                let prompt_tokens = count("input_tokens")
+                    + count("cache_creation_input_tokens")
+                    + count("cache_read_input_tokens");
+                let completion_tokens = count("output_tokens");
+                Some(Piece::Done {
+                    finish_reason,
+                    usage: json!({
+                        "prompt_tokens": prompt_tokens,
+                        "completion_tokens": completion_tokens,
+                        "total_tokens": prompt_tokens + completion_tokens,
+                    }),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn separated(&mut self, text: String) -> String {
+        if std::mem::take(&mut self.separate) {
+            format!("\n\n{}", text)
+        } else {
+            text
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex, RwLock};
+
+use crate::error::KataraError;
+use crate::plugins::protocol::{self, Incoming, PluginManifest, ToolDecision, ToolReview};
+use crate::websocket::protocol::WsEvent;
+
+/// How long a plugin has to answer `initialize`.
+const INIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a plugin command may run.
+const INVOKE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a tool review may hold up a permission request.
+pub(crate) const REVIEW_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An external executable declared in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl PluginConfig {
+    pub(crate) fn validate(&self) -> Result<(), KataraError> {
+        if self.name.trim().is_empty() {
+            return Err(KataraError::Config("Plugin name is required".into()));
+        }
+        if self.command.trim().is_empty() {
+            return Err(KataraError::Config(format!(
+                "Plugin {} has no command",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A plugin as shown in settings.
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub command: String,
+    pub enabled: bool,
+    pub running: bool,
+    /// `None` until it has started.
+    pub manifest: Option<PluginManifest>,
+    /// Why it isn't running, if it failed.
+    pub error: Option<String>,
+}
+
+type Pending = Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>;
+
+/// A running plugin process. Dropping it kills the process.
+struct Plugin {
+    config: PluginConfig,
+    manifest: PluginManifest,
+    stdin: mpsc::Sender<String>,
+    pending: Arc<Pending>,
+    next_id: AtomicU64,
+    exited: Arc<AtomicBool>,
+    _child: Child,
+}
+
+impl Plugin {
+    async fn spawn(config: PluginConfig) -> Result<Self, String> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", config.command, e))?;
+        let mut stdin = child.stdin.take().ok_or("No stdin")?;
+        let stdout = child.stdout.take().ok_or("No stdout")?;
+        if let Some(stderr) = child.stderr.take() {
+            let name = config.name.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    eprintln!("[katara] plugin {}: {}", name, line);
+                }
+            });
+        }
+
+        let (stdin_tx, mut stdin_rx) = mpsc::channel::<String>(256);
+        tokio::spawn(async move {
+            while let Some(line) = stdin_rx.recv().await {
+                if stdin
+                    .write_all(format!("{}\n", line).as_bytes())
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+                let _ = stdin.flush().await;
+            }
+        });
+
+        let pending: Arc<Pending> = Arc::new(Mutex::new(HashMap::new()));
+        let exited = Arc::new(AtomicBool::new(false));
+        let reader_pending = pending.clone();
+        let reader_exited = exited.clone();
+        let name = config.name.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Ok(message) = serde_json::from_str::<Incoming>(&line) else {
+                    eprintln!("[katara] plugin {} sent invalid JSON: {}", name, line);
+                    continue;
+                };
+                let Some(id) = message.id else {
+                    continue;
+                };
+                if let Some(reply) = reader_pending.lock().await.remove(&id) {
+                    let _ = reply.send(match message.error {
+                        Some(error) => Err(error.message),
+                        None => Ok(message.result.unwrap_or(Value::Null)),
+                    });
+                }
+            }
+            println!("[katara] Plugin {} exited", name);
+            reader_exited.store(true, Ordering::Relaxed);
+            // Fail anything still waiting on it
+            reader_pending.lock().await.clear();
+        });
+
+        let mut plugin = Self {
+            config,
+            manifest: PluginManifest::default(),
+            stdin: stdin_tx,
+            pending,
+            next_id: AtomicU64::new(1),
+            exited,
+            _child: child,
+        };
+        let params = serde_json::json!({ "katara_version": env!("CARGO_PKG_VERSION") });
+        let manifest = plugin.request("initialize", params, INIT_TIMEOUT).await?;
+        plugin.manifest = serde_json::from_value(manifest)
+            .map_err(|e| format!("Invalid initialize result: {}", e))?;
+        Ok(plugin)
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, String> {
+        if self.exited.load(Ordering::Relaxed) {
+            return Err("Plugin isn't running".into());
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+        if self
+            .stdin
+            .send(protocol::request(id, method, params))
+            .await
+            .is_err()
+        {
+            self.pending.lock().await.remove(&id);
+            return Err("Plugin isn't running".into());
+        }
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("Plugin exited".into()),
+            Err(_) => {
+                self.pending.lock().await.remove(&id);
+                Err(format!("{} timed out", method))
+            }
+        }
+    }
+
+    /// Forward every CLI message to the plugin until either side goes away.
+    fn forward_events(&self, mut events: broadcast::Receiver<WsEvent>) {
+        let stdin = self.stdin.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let Ok(params) = serde_json::to_value(&event) else {
+                    continue;
+                };
+                if stdin
+                    .send(protocol::notification("event", params))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// The plugins declared in settings, started at launch.
+#[derive(Default)]
+pub struct PluginHost {
+    plugins: RwLock<Vec<Arc<Plugin>>>,
+    /// Start errors by plugin name.
+    errors: RwLock<HashMap<String, String>>,
+}
+
+impl PluginHost {
+    /// Stop any running plugins and start the enabled ones in `configs`.
+    pub async fn start(&self, configs: &[PluginConfig], events: &broadcast::Sender<WsEvent>) {
+        // Stop the old ones first, and don't hold the lock while starting, so
+        // tool reviews aren't held up
+        self.plugins.write().await.clear();
+        let mut plugins = Vec::new();
+        let mut errors = HashMap::new();
+        for config in configs.iter().filter(|c| c.enabled) {
+            match Plugin::spawn(config.clone()).await {
+                Ok(plugin) => {
+                    println!(
+                        "[katara] Started plugin {} ({} command(s))",
+                        config.name,
+                        plugin.manifest.commands.len()
+                    );
+                    if plugin.manifest.events {
+                        plugin.forward_events(events.subscribe());
+                    }
+                    plugins.push(Arc::new(plugin));
+                }
+                Err(e) => {
+                    eprintln!("[katara] Failed to start plugin {}: {}", config.name, e);
+                    errors.insert(config.name.clone(), e);
+                }
+            }
+        }
+        *self.plugins.write().await = plugins;
+        *self.errors.write().await = errors;
+    }
+
+    pub async fn list(&self, configs: &[PluginConfig]) -> Vec<PluginInfo> {
+        let plugins = self.plugins.read().await;
+        let errors = self.errors.read().await;
+        configs
+            .iter()
+            .map(|config| {
+                let plugin = plugins.iter().find(|p| p.config.name == config.name);
+                PluginInfo {
+                    name: config.name.clone(),
+                    command: config.command.clone(),
+                    enabled: config.enabled,
+                    running: plugin.is_some_and(|p| !p.exited.load(Ordering::Relaxed)),
+                    manifest: plugin.map(|p| p.manifest.clone()),
+                    error: errors.get(&config.name).cloned(),
+                }
+            })
+            .collect()
+    }
+
+    /// Run one of a plugin's commands.
+    pub async fn invoke(
+        &self,
+        plugin: &str,
+        command: &str,
+        args: Value,
+    ) -> Result<Value, KataraError> {
+        let target = self
+            .plugins
+            .read()
+            .await
+            .iter()
+            .find(|p| p.config.name == plugin)
+            .cloned()
+            .ok_or_else(|| KataraError::Plugin(format!("Plugin {} isn't running", plugin)))?;
+        if !target.manifest.commands.iter().any(|c| c.name == command) {
+            return Err(KataraError::Plugin(format!(
+                "Plugin {} has no command {}",
+                plugin, command
+            )));
+        }
+        let params = serde_json::json!({ "command": command, "args": args });
+        target
+            .request("invoke", params, INVOKE_TIMEOUT)
+            .await
+            .map_err(|e| KataraError::Plugin(format!("{}: {}", plugin, e)))
+    }
+
+    /// Ask the approval plugins about a tool request. A deny from any of
+    /// them wins; otherwise an allow from any. `None` if none had an
+    /// opinion. Returns the deciding plugin's name.
+    pub async fn review_tool(
+        &self,
+        session_id: &str,
+        working_dir: &str,
+        tool_name: &str,
+        input: &Value,
+    ) -> Option<(bool, String)> {
+        let reviewers: Vec<Arc<Plugin>> = self
+            .plugins
+            .read()
+            .await
+            .iter()
+            .filter(|p| p.manifest.approvals)
+            .cloned()
+            .collect();
+        if reviewers.is_empty() {
+            return None;
+        }
+        let params = serde_json::json!({
+            "session_id": session_id,
+            "working_dir": working_dir,
+            "tool_name": tool_name,
+            "input": input,
+        });
+        let reviews = futures_util::future::join_all(reviewers.iter().map(|plugin| {
+            let params = params.clone();
+            async move {
+                let decision = match plugin.request("review_tool", params, REVIEW_TIMEOUT).await {
+                    Ok(result) => serde_json::from_value::<ToolReview>(result)
+                        .map(|r| r.decision)
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                };
+                decision.unwrap_or_else(|e| {
+                    eprintln!(
+                        "[katara] Plugin {} failed to review {}: {}",
+                        plugin.config.name, tool_name, e
+                    );
+                    ToolDecision::Ask
+                })
+            }
+        }))
+        .await;
+
+        let decided = |wanted: ToolDecision| {
+            reviews
+                .iter()
+                .position(|d| *d == wanted)
+                .map(|i| reviewers[i].config.name.clone())
+        };
+        decided(ToolDecision::Deny)
+            .map(|name| (false, name))
+            .or_else(|| decided(ToolDecision::Allow).map(|name| (true, name)))
+    }
+}
@@ -0,0 +1,76 @@
+// Wire format between Katara and a plugin: newline-delimited JSON-RPC 2.0
+// over the plugin's stdin and stdout. Anything the plugin writes to
+// stderr is logged.
+//
+// Katara sends:
+//   - `initialize` (request) once at startup; the result is a
+//     `PluginManifest`.
+//   - `invoke` (request) `{ command, args }` when the frontend calls one of
+//     the plugin's commands; the result is returned as is.
+//   - `review_tool` (request) `{ session_id, working_dir, tool_name, input }`
+//     for plugins with `approvals`; the result is a `ToolDecision`.
+//   - `event` (notification) with each `WsEvent` from the CLIs, for
+//     plugins with `events`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// What a plugin offers, returned from `initialize`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginManifest {
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Commands the frontend can call with `invoke_plugin`.
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
+    /// Receive every CLI message as an `event` notification.
+    #[serde(default)]
+    pub events: bool,
+    /// Review tool permission requests before the user is asked.
+    #[serde(default)]
+    pub approvals: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A plugin's answer to `review_tool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolDecision {
+    Allow,
+    Deny,
+    /// No opinion; leave it to other plugins or the user.
+    Ask,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ToolReview {
+    pub decision: ToolDecision,
+}
+
+/// A message from the plugin. Only responses are acted on.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Incoming {
+    pub id: Option<u64>,
+    pub result: Option<Value>,
+    pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RpcError {
+    pub message: String,
+}
+
+pub(crate) fn request(id: u64, method: &str, params: Value) -> String {
+    serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+        .to_string()
+}
+
+pub(crate) fn notification(method: &str, params: Value) -> String {
+    serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params }).to_string()
+}
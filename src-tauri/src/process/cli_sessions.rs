@@ -0,0 +1,167 @@
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Lines read from the top of a transcript while looking for its first
+/// prompt, so a huge session doesn't stall the scan.
+const MAX_HEAD_LINES: usize = 200;
+
+/// Characters of the first prompt kept for display.
+const MAX_PROMPT_CHARS: usize = 200;
+
+/// A conversation the Claude CLI saved under `~/.claude/projects`, which
+/// `resume_session` can continue.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResumableSession {
+    /// The CLI's session id, for `resume_session`.
+    pub cli_session_id: String,
+    pub working_dir: String,
+    /// ISO 8601, from the first message.
+    pub started_at: Option<String>,
+    /// Unix timestamp (seconds) the transcript was last written.
+    pub updated_at: u64,
+    pub first_prompt: Option<String>,
+    /// The CLI's own summary of the conversation, if it wrote one.
+    pub summary: Option<String>,
+    pub git_branch: Option<String>,
+}
+
+/// The CLI's saved sessions, most recently used first. With `working_dir`,
+/// only sessions started there.
+pub fn list(working_dir: Option<&str>, limit: usize) -> Vec<ResumableSession> {
+    let Some(root) = dirs::home_dir().map(|h| h.join(".claude").join("projects")) else {
+        return Vec::new();
+    };
+    let Ok(projects) = std::fs::read_dir(&root) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(PathBuf, u64)> = projects
+        .flatten()
+        .filter(|p| p.path().is_dir())
+        .filter_map(|p| std::fs::read_dir(p.path()).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|e| e.path().extension().and_then(|x| x.to_str()) == Some("jsonl"))
+        .map(|e| {
+            let modified = e
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs());
+            (e.path(), modified)
+        })
+        .collect();
+    files.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+
+    let wanted = working_dir.map(normalize);
+    files
+        .into_iter()
+        .filter_map(|(path, modified)| read_head(&path, modified))
+        .filter(|s| {
+            wanted
+                .as_ref()
+                .is_none_or(|dir| normalize(&s.working_dir) == *dir)
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Details from the top of one transcript. `None` for an empty file or
+/// one with no messages, such as a session that never got a prompt.
+fn read_head(path: &Path, updated_at: u64) -> Option<ResumableSession> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut session = ResumableSession {
+        cli_session_id: path.file_stem()?.to_string_lossy().to_string(),
+        working_dir: String::new(),
+        started_at: None,
+        updated_at,
+        first_prompt: None,
+        summary: None,
+        git_branch: None,
+    };
+    let mut has_messages = false;
+
+    for line in std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(MAX_HEAD_LINES)
+    {
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let str_field = |key: &str| entry.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        match entry.get("type").and_then(|t| t.as_str()) {
+            Some("summary") => {
+                if session.summary.is_none() {
+                    session.summary = str_field("summary");
+                }
+                continue;
+            }
+            Some("user") | Some("assistant") => has_messages = true,
+            _ => continue,
+        }
+        if session.working_dir.is_empty() {
+            session.working_dir = str_field("cwd").unwrap_or_default();
+        }
+        if session.started_at.is_none() {
+            session.started_at = str_field("timestamp");
+        }
+        if session.git_branch.is_none() {
+            session.git_branch = str_field("gitBranch").filter(|b| !b.is_empty());
+        }
+        if session.first_prompt.is_none() {
+            session.first_prompt = prompt_text(&entry);
+        }
+        if !session.working_dir.is_empty() && session.first_prompt.is_some() {
+            break;
+        }
+    }
+
+    if !has_messages {
+        return None;
+    }
+    if session.working_dir.is_empty() {
+        // Older transcripts may lack `cwd`; the directory name encodes it
+        // lossily, with every separator turned into '-'
+        session.working_dir = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().replace('-', "/"))
+            .unwrap_or_default();
+    }
+    Some(session)
+}
+
+/// The text the user typed, if `entry` is a prompt rather than a tool
+/// result or a message the CLI injected.
+fn prompt_text(entry: &Value) -> Option<String> {
+    if entry.get("type").and_then(|t| t.as_str()) != Some("user")
+        || entry.get("isMeta").and_then(|v| v.as_bool()) == Some(true)
+    {
+        return None;
+    }
+    let content = entry.get("message")?.get("content")?;
+    let text = match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    let text = text.trim();
+    // Slash commands and their output are wrapped in tags
+    if text.is_empty() || text.starts_with('<') {
+        return None;
+    }
+    Some(text.chars().take(MAX_PROMPT_CHARS).collect())
+}
+
+fn normalize(dir: &str) -> PathBuf {
+    std::fs::canonicalize(dir).unwrap_or_else(|_| PathBuf::from(dir.trim_end_matches(['/', '\\'])))
+}
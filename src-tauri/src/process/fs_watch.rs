@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+
+use crate::sink::Sink;
+use crate::websocket::protocol::ContentBlock;
+
+/// Quiet period before a burst of changes is reported.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Tools whose `file_path` (or `notebook_path`) input names the file they
+/// change.
+const FILE_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+/// Payload for the `fs:changed` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct FsChangedPayload {
+    pub session_id: String,
+    pub changes: Vec<FsChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsChange {
+    /// Relative to the session's working directory, with `/` separators.
+    pub path: String,
+    pub kind: ChangeKind,
+    /// The Edit/Write call that targeted this file during the turn, if any.
+    /// Changes without one came from Bash commands or outside the agent.
+    pub tool_use_id: Option<String>,
+    pub tool_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+#[derive(Debug, Clone)]
+struct ToolCall {
+    id: String,
+    name: String,
+}
+
+/// What the watcher thread needs to know about the current turn.
+#[derive(Default)]
+struct Turn {
+    active: bool,
+    /// File tool calls made this turn, by relative path.
+    tool_calls: HashMap<String, ToolCall>,
+}
+
+/// Watches a session's working directory while it has a turn in progress
+/// and emits `fs:changed` for files the turn touches, skipping anything
+/// `.gitignore`d. Each directory that isn't ignored gets its own watch, so
+/// `node_modules`, `target` and the like cost nothing. Watching stops when
+/// this is dropped.
+pub struct SessionWatcher {
+    root: PathBuf,
+    turn: Arc<Mutex<Turn>>,
+    _watcher: Arc<Mutex<RecommendedWatcher>>,
+}
+
+impl SessionWatcher {
+    pub fn start(sink: Sink, session_id: String, working_dir: &str) -> Result<Self, String> {
+        // Events carry canonical paths on some platforms (e.g. /private/tmp)
+        let root = std::fs::canonicalize(working_dir)
+            .map_err(|e| format!("Failed to resolve {}: {}", working_dir, e))?;
+        let ignore = gitignore(&root);
+        let turn = Arc::new(Mutex::new(Turn::default()));
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+        watcher
+            .watch(&root, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+        watch_subdirs(&mut watcher, &root);
+        let watcher = Arc::new(Mutex::new(watcher));
+
+        let thread_root = root.clone();
+        let thread_turn = turn.clone();
+        // Weak, so dropping the SessionWatcher still drops the watcher and
+        // closes the channel
+        let thread_watcher = Arc::downgrade(&watcher);
+        std::thread::spawn(move || {
+            let mut pending: HashMap<String, ChangeKind> = HashMap::new();
+            loop {
+                // The channel closes when the SessionWatcher is dropped
+                let event = if pending.is_empty() {
+                    match rx.recv() {
+                        Ok(event) => event,
+                        Err(_) => break,
+                    }
+                } else {
+                    match rx.recv_timeout(DEBOUNCE) {
+                        Ok(event) => event,
+                        Err(RecvTimeoutError::Timeout) => {
+                            emit(
+                                &sink,
+                                &session_id,
+                                &thread_turn,
+                                std::mem::take(&mut pending),
+                            );
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                };
+
+                if let Ok(event) = &event {
+                    watch_new_dirs(&thread_watcher, &thread_root, &ignore, event);
+                }
+                let active = thread_turn.lock().unwrap_or_else(|e| e.into_inner()).active;
+                match event {
+                    Ok(event) if active => {
+                        collect(&mut pending, &thread_root, &ignore, event);
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("[katara] File watcher error for {}: {}", session_id, e),
+                }
+            }
+        });
+
+        Ok(Self {
+            root,
+            turn,
+            _watcher: watcher,
+        })
+    }
+
+    /// Report changes only while a turn is running. Starting a turn forgets
+    /// the previous turn's tool calls; they're kept past its end so changes
+    /// still being debounced are attributed.
+    pub fn set_active(&self, active: bool) {
+        let mut turn = self.turn.lock().unwrap_or_else(|e| e.into_inner());
+        if active && !turn.active {
+            turn.tool_calls.clear();
+        }
+        turn.active = active;
+    }
+
+    /// Remember the file tool calls in an assistant message, so changes to
+    /// their files can be attributed to them.
+    pub fn track_tool_calls(&self, content: &[ContentBlock]) {
+        let mut turn = self.turn.lock().unwrap_or_else(|e| e.into_inner());
+        for block in content {
+            let ContentBlock::ToolUse { id, name, input } = block else {
+                continue;
+            };
+            if !FILE_TOOLS.contains(&name.as_str()) {
+                continue;
+            }
+            let path = input["file_path"]
+                .as_str()
+                .or_else(|| input["notebook_path"].as_str());
+            if let Some(path) = path.and_then(|p| self.relative(Path::new(p))) {
+                turn.tool_calls.insert(
+                    path,
+                    ToolCall {
+                        id: id.clone(),
+                        name: name.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn relative(&self, path: &Path) -> Option<String> {
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root.join(path)
+        };
+        // The file may not exist yet (Write), so resolve its parent instead
+        let resolved = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => std::fs::canonicalize(parent)
+                .map(|p| p.join(name))
+                .unwrap_or(path),
+            _ => path,
+        };
+        relative_to(&self.root, &resolved)
+    }
+}
+
+/// Watch every directory under `dir` that isn't ignored, each on its own.
+fn watch_subdirs(watcher: &mut RecommendedWatcher, dir: &Path) {
+    let walk = WalkBuilder::new(dir)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    for entry in walk.flatten() {
+        if entry.depth() > 0 && entry.file_type().is_some_and(|t| t.is_dir()) {
+            let _ = watcher.watch(entry.path(), RecursiveMode::NonRecursive);
+        }
+    }
+}
+
+/// Start watching directories the event created or moved in, unless
+/// they're ignored.
+fn watch_new_dirs(
+    watcher: &Weak<Mutex<RecommendedWatcher>>,
+    root: &Path,
+    ignore: &Gitignore,
+    event: &Event,
+) {
+    let created = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+    );
+    if !created {
+        return;
+    }
+    for path in &event.paths {
+        if !path.is_dir()
+            || !path.starts_with(root)
+            || path.file_name().is_some_and(|name| name == ".git")
+            || ignore.matched_path_or_any_parents(path, true).is_ignore()
+        {
+            continue;
+        }
+        let Some(watcher) = watcher.upgrade() else {
+            return;
+        };
+        let mut watcher = watcher.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = watcher.watch(path, RecursiveMode::NonRecursive);
+        watch_subdirs(&mut watcher, path);
+    }
+}
+
+/// Matcher for the root `.gitignore` and `.git/info/exclude`.
+fn gitignore(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    builder.add(root.join(".gitignore"));
+    builder.add(root.join(".git").join("info").join("exclude"));
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+fn relative_to(root: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(root).ok()?;
+    Some(rel.to_string_lossy().replace('\\', "/"))
+}
+
+/// Fold a file system event into the pending changes.
+fn collect(
+    pending: &mut HashMap<String, ChangeKind>,
+    root: &Path,
+    ignore: &Gitignore,
+    event: Event,
+) {
+    let changes: Vec<(PathBuf, ChangeKind)> = match event.kind {
+        EventKind::Create(_) => with_kind(event.paths, ChangeKind::Added),
+        EventKind::Remove(_) => with_kind(event.paths, ChangeKind::Removed),
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            with_kind(event.paths, ChangeKind::Removed)
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            with_kind(event.paths, ChangeKind::Added)
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let mut paths = event.paths.into_iter();
+            let from = paths.next().map(|p| (p, ChangeKind::Removed));
+            let to = paths.next().map(|p| (p, ChangeKind::Added));
+            from.into_iter().chain(to).collect()
+        }
+        EventKind::Modify(ModifyKind::Name(_)) => event
+            .paths
+            .into_iter()
+            .map(|p| {
+                let kind = if p.exists() {
+                    ChangeKind::Added
+                } else {
+                    ChangeKind::Removed
+                };
+                (p, kind)
+            })
+            .collect(),
+        // Metadata-only changes (chmod, atime) don't change what's shown
+        EventKind::Modify(ModifyKind::Metadata(_)) => Vec::new(),
+        EventKind::Modify(_) => with_kind(event.paths, ChangeKind::Modified),
+        _ => Vec::new(),
+    };
+
+    for (path, kind) in changes {
+        if path.is_dir() {
+            continue;
+        }
+        let Some(rel) = relative_to(root, &path) else {
+            continue;
+        };
+        if rel.is_empty() || rel == ".git" || rel.starts_with(".git/") {
+            continue;
+        }
+        if ignore.matched_path_or_any_parents(&path, false).is_ignore() {
+            continue;
+        }
+        match (pending.get(&rel).copied(), kind) {
+            // Created and deleted within one burst (editor temp files)
+            (Some(ChangeKind::Added), ChangeKind::Removed) => {
+                pending.remove(&rel);
+            }
+            (Some(ChangeKind::Added), ChangeKind::Modified) => {}
+            (Some(ChangeKind::Removed), ChangeKind::Added) => {
+                pending.insert(rel, ChangeKind::Modified);
+            }
+            _ => {
+                pending.insert(rel, kind);
+            }
+        }
+    }
+}
+
+fn with_kind(paths: Vec<PathBuf>, kind: ChangeKind) -> Vec<(PathBuf, ChangeKind)> {
+    paths.into_iter().map(|p| (p, kind)).collect()
+}
+
+fn emit(sink: &Sink, session_id: &str, turn: &Mutex<Turn>, pending: HashMap<String, ChangeKind>) {
+    if pending.is_empty() {
+        return;
+    }
+    let turn = turn.lock().unwrap_or_else(|e| e.into_inner());
+    let mut changes: Vec<FsChange> = pending
+        .into_iter()
+        .map(|(path, kind)| {
+            let call = turn.tool_calls.get(&path);
+            FsChange {
+                tool_use_id: call.map(|c| c.id.clone()),
+                tool_name: call.map(|c| c.name.clone()),
+                path,
+                kind,
+            }
+        })
+        .collect();
+    drop(turn);
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    sink.emit_for_session(
+        session_id,
+        "fs:changed",
+        FsChangedPayload {
+            session_id: session_id.to_string(),
+            changes,
+        },
+    );
+}
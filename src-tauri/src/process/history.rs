@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::process::transcript::Transcript;
+
+/// A session's message history: an append-only log addressed by sequence
+/// number. Messages are stored behind `Arc` so reads copy pointers rather
+/// than JSON, and the log has its own lock so appending doesn't need the
+/// sessions map write lock.
+#[derive(Clone, Default)]
+pub struct MessageHistory {
+    inner: Arc<RwLock<Log>>,
+}
+
+#[derive(Default)]
+struct Log {
+    messages: VecDeque<Arc<Value>>,
+    /// Sequence number of `messages[0]`.
+    first_seq: u64,
+    limit: Option<usize>,
+    /// Where messages are also written, so they outlive the app.
+    transcript: Option<Transcript>,
+}
+
+/// Messages after a cursor.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPage {
+    pub messages: Vec<Arc<Value>>,
+    /// Pass back to fetch only newer messages.
+    pub cursor: u64,
+    /// Messages after the requested cursor were dropped by the history
+    /// limit, so `messages` starts later than asked. Never set for cursor
+    /// 0, which asks for whatever is kept.
+    pub truncated: bool,
+}
+
+impl MessageHistory {
+    /// Append a message, dropping the oldest beyond the limit. Returns the
+    /// message's sequence number.
+    pub fn push(&self, message: Value) -> u64 {
+        let message = Arc::new(message);
+        let mut log = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(transcript) = log.transcript.as_ref() {
+            transcript.append(&message);
+        }
+        log.messages.push_back(message);
+        let seq = log.first_seq + log.messages.len() as u64 - 1;
+        log.trim();
+        seq
+    }
+
+    /// Messages kept; older ones are dropped. `None` keeps all.
+    pub fn set_limit(&self, limit: Option<usize>) {
+        let mut log = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        log.limit = limit;
+        log.trim();
+    }
+
+    /// Also write every message pushed from now on to `transcript`.
+    pub fn persist_to(&self, transcript: Transcript) {
+        self.inner
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .transcript = Some(transcript);
+    }
+
+    /// Every stored message.
+    pub fn all(&self) -> Vec<Arc<Value>> {
+        let log = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        log.messages.iter().cloned().collect()
+    }
+
+    /// Messages with a sequence number at or after `cursor` (all of them
+    /// for 0).
+    pub fn since(&self, cursor: u64) -> HistoryPage {
+        let log = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        let skip = cursor.saturating_sub(log.first_seq) as usize;
+        HistoryPage {
+            messages: log.messages.iter().skip(skip).cloned().collect(),
+            cursor: log.first_seq + log.messages.len() as u64,
+            // A caller with no cursor hasn't missed anything
+            truncated: cursor > 0 && cursor < log.first_seq,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .messages
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Log {
+    fn trim(&mut self) {
+        if let Some(limit) = self.limit {
+            let excess = self.messages.len().saturating_sub(limit);
+            self.messages.drain(..excess);
+            self.first_seq += excess as u64;
+        }
+    }
+}
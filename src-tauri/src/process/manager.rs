@@ -1,12 +1,42 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::process::Command;
 
-use tauri::Emitter;
-
+use crate::config;
+use crate::config::manager::ProxySettings;
 use crate::error::KataraError;
+use crate::process::recovery;
 use crate::process::session::SessionStatus;
+use crate::sink::Sink;
 use crate::state::AppState;
 
+/// Per-spawn options for the Claude CLI.
+#[derive(Debug, Clone, Default)]
+pub struct SpawnOptions {
+    pub initial_prompt: Option<String>,
+    pub model: Option<String>,
+    pub permission_mode: Option<String>,
+    pub resume_session_id: Option<String>,
+    /// Restrict the CLI to these tools (e.g. "Read", "Bash(git log:*)").
+    /// Empty means no restriction.
+    pub allowed_tools: Vec<String>,
+    /// Env profile whose variables (secrets resolved) the CLI gets.
+    pub env_profile: Option<String>,
+    /// Extra environment variables for the CLI process, over the profile's.
+    pub env: HashMap<String, String>,
+    /// Output style for this session, overriding the `outputStyle` setting.
+    pub output_style: Option<String>,
+    /// Claude CLI executable; `None` runs `claude` from PATH.
+    pub claude_path: Option<String>,
+    /// Proxy variables from the settings. `env` may override them.
+    pub proxy: ProxySettings,
+    /// `--mcp-config` JSON with servers to add for this session. Passed in
+    /// a file, since it holds the session's MCP token.
+    pub mcp_config: Option<String>,
+}
+
 /// Spawns a Claude CLI process connected to our WebSocket server.
 ///
 /// With `--sdk-url`, Claude CLI opens a WebSocket back to us for all communication.
@@ -16,10 +46,7 @@ pub async fn spawn_claude(
     ws_port: u16,
     session_id: &str,
     working_dir: &str,
-    initial_prompt: Option<&str>,
-    model: Option<&str>,
-    permission_mode: Option<&str>,
-    resume_session_id: Option<&str>,
+    opts: &SpawnOptions,
 ) -> Result<tokio::process::Child, KataraError> {
     // Embed session ID in the URL path so the WS server can identify the session
     // on connect (same pattern as Companion: /ws/cli/{sessionId})
@@ -37,58 +64,83 @@ pub async fn spawn_claude(
     ];
 
     // Model selection (e.g. "claude-sonnet-4-5-20250929", "claude-opus-4-5-20250918")
-    if let Some(m) = model {
+    if let Some(ref m) = opts.model {
         if !m.is_empty() {
             args.push("--model".to_string());
-            args.push(m.to_string());
+            args.push(m.clone());
         }
     }
 
     // Permission mode (default, plan, acceptEdits, bypassPermissions)
-    if let Some(mode) = permission_mode {
+    if let Some(ref mode) = opts.permission_mode {
         if mode != "default" && !mode.is_empty() {
             args.push("--permission-mode".to_string());
-            args.push(mode.to_string());
+            args.push(mode.clone());
+        }
+    }
+
+    // Tool allowlist; each entry is its own argument since rules may contain spaces
+    if !opts.allowed_tools.is_empty() {
+        args.push("--allowedTools".to_string());
+        args.extend(opts.allowed_tools.iter().cloned());
+    }
+
+    // Per-session output style, layered over the settings files
+    if let Some(ref style) = opts.output_style {
+        if !style.is_empty() {
+            args.push("--settings".to_string());
+            args.push(serde_json::json!({ "outputStyle": style }).to_string());
         }
     }
 
+    // Extra MCP servers, on top of the user's and project's
+    if let Some(ref config) = opts.mcp_config {
+        let path = write_mcp_config(session_id, config)
+            .map_err(|e| KataraError::Process(format!("Failed to write MCP config: {}", e)))?;
+        args.push("--mcp-config".to_string());
+        args.push(path.display().to_string());
+    }
+
     // Resume a previous CLI session
-    if let Some(resume_id) = resume_session_id {
+    if let Some(ref resume_id) = opts.resume_session_id {
         if !resume_id.is_empty() {
             args.push("--resume".to_string());
-            args.push(resume_id.to_string());
+            args.push(resume_id.clone());
         }
     }
 
     // If an initial prompt is provided, use -p to kick off the first turn.
     // Otherwise pass -p "" as a required placeholder for headless/SDK mode
     // (Companion pattern: CLI needs -p to enter prompt mode with --sdk-url).
-    if let Some(prompt) = initial_prompt {
+    if let Some(ref prompt) = opts.initial_prompt {
         args.push("-p".to_string());
-        args.push(prompt.to_string());
+        args.push(prompt.clone());
     } else {
         args.push("-p".to_string());
         args.push(String::new());
     }
 
+    let program = opts.claude_path.as_deref().unwrap_or("claude");
     println!(
-        "[katara] Spawning Claude CLI: claude {}",
+        "[katara] Spawning Claude CLI: {} {}",
+        program,
         args.join(" ")
     );
 
-    let mut child = Command::new("claude")
+    let mut child = Command::new(program)
         .args(&args)
         .current_dir(working_dir)
+        .envs(opts.proxy.env())
+        .envs(&opts.env)
         .stdin(std::process::Stdio::null())
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
         .kill_on_drop(true)
         .spawn()
-        .map_err(|e| {
-            KataraError::Process(format!(
-                "Failed to spawn Claude CLI (is it installed?): {}",
-                e
-            ))
+        .inspect_err(|_| remove_mcp_config(session_id))
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => KataraError::CliNotInstalled(program.to_string()),
+            _ => KataraError::Process(format!("Failed to spawn Claude CLI {}: {}", program, e)),
         })?;
 
     // Capture stderr in a background task for debugging
@@ -126,11 +178,7 @@ pub async fn spawn_claude(
 }
 
 /// Monitors a Claude CLI process and updates session status when it exits.
-pub fn monitor_process(
-    state: Arc<AppState>,
-    app_handle: tauri::AppHandle,
-    session_id: String,
-) {
+pub fn monitor_process(state: Arc<AppState>, sink: Sink, session_id: String) {
     tokio::spawn(async move {
         loop {
             tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
@@ -157,14 +205,18 @@ pub fn monitor_process(
                         );
                         session.status = new_status.clone();
                         session.ws_sender = None;
+                        session.pid = None;
+                        drop(sessions);
 
-                        let _ = app_handle.emit(
+                        sink.emit_for_session(
+                            &session_id,
                             "claude:status",
                             serde_json::json!({
                                 "session_id": session_id,
                                 "status": new_status,
                             }),
                         );
+                        recovery::save(&state).await;
                         break;
                     }
                     Ok(None) => {} // Still running
@@ -180,16 +232,44 @@ pub fn monitor_process(
                 break;
             }
         }
+        // The CLI read it at startup; it's exited or been stopped by now
+        remove_mcp_config(&session_id);
     });
 }
 
+/// Write a session's `--mcp-config` where only the user can read it, rather
+/// than into argv, which every process on the machine can see.
+fn write_mcp_config(session_id: &str, config: &str) -> std::io::Result<PathBuf> {
+    let path = mcp_config_path(session_id);
+    remove_mcp_config(session_id);
+    config::create_private_file(&path)?.write_all(config.as_bytes())?;
+    Ok(path)
+}
+
+/// Delete a session's MCP config once its CLI is gone.
+pub fn remove_mcp_config(session_id: &str) {
+    let path = mcp_config_path(session_id);
+    if let Err(e) = std::fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            eprintln!("[katara] Failed to remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn mcp_config_path(session_id: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("katara-mcp-{}.json", session_id))
+}
+
 /// Check if the Claude CLI is available and supports --sdk-url.
 pub async fn check_claude_cli() -> Result<bool, KataraError> {
     let output = Command::new("claude")
         .arg("--help")
         .output()
         .await
-        .map_err(|e| KataraError::Process(format!("Claude CLI not found: {}", e)))?;
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => KataraError::CliNotInstalled("claude".into()),
+            _ => KataraError::Process(format!("Failed to run Claude CLI: {}", e)),
+        })?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
@@ -1,2 +1,7 @@
+pub mod cli_sessions;
+pub mod fs_watch;
+pub mod history;
 pub mod manager;
+pub mod recovery;
 pub mod session;
+pub mod transcript;
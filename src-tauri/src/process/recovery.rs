@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::commands::claude;
+use crate::config;
+use crate::error::KataraError;
+use crate::process::fs_watch::SessionWatcher;
+use crate::process::manager;
+use crate::process::session::{Session, SessionStatus, UsageTotals};
+use crate::process::transcript::Transcript;
+use crate::sink::Sink;
+use crate::state::AppState;
+use crate::stats::activity;
+use crate::terminal::procinfo;
+
+/// Messages of each session's history kept in the snapshot.
+const SAVED_HISTORY_LIMIT: usize = 200;
+
+/// How long a re-adopted CLI has to reconnect before it's stopped.
+const RECONNECT_GRACE: Duration = Duration::from_secs(30);
+
+/// Numbers snapshots in the order they're taken.
+static NEXT_SNAPSHOT: AtomicU64 = AtomicU64::new(1);
+
+/// The number of the last snapshot written. Held while writing, so writes
+/// don't interleave and an older snapshot never replaces a newer one.
+static WRITTEN: Mutex<u64> = Mutex::new(0);
+
+/// What was running, as of the last change. Removed on a clean exit, so
+/// finding one at launch means the previous run crashed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Snapshot {
+    /// Port the CLIs were told to connect to.
+    ws_port: u16,
+    sessions: Vec<SavedSession>,
+    /// AG-UI thread id to session id.
+    #[serde(default)]
+    threads: HashMap<String, String>,
+}
+
+/// A session as remembered across a crash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedSession {
+    id: String,
+    status: SessionStatus,
+    working_dir: String,
+    model: Option<String>,
+    permission_mode: String,
+    cli_session_id: Option<String>,
+    pid: Option<u32>,
+    /// When `pid` started, to tell the CLI from a process that reused it.
+    #[serde(default)]
+    pid_started: Option<String>,
+    /// The env profile's name; its secrets are resolved again on restore
+    /// rather than written here.
+    #[serde(default)]
+    env_profile: Option<String>,
+    /// Names of the variables given on top of the profile's. Their values
+    /// can be secrets, so they aren't saved and must be given again.
+    #[serde(default)]
+    env_names: Vec<String>,
+    #[serde(default)]
+    usage_totals: UsageTotals,
+    /// The most recent `SAVED_HISTORY_LIMIT` messages.
+    #[serde(default)]
+    history: Vec<Value>,
+    /// So a re-adopted CLI can keep calling the MCP server.
+    #[serde(default)]
+    mcp_token_hash: Option<String>,
+}
+
+/// Payload for `sessions:recovered`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecoveryReport {
+    /// Sessions whose CLI outlived the crash and was re-adopted.
+    pub adopted: Vec<String>,
+    /// Sessions whose CLI died, restored as `Error` with their history.
+    pub lost: Vec<String>,
+    /// Thread mappings dropped because their session is gone.
+    pub stale_threads: usize,
+    /// Sessions whose extra env variables weren't saved, by the names of
+    /// those variables, to be given again before resuming.
+    pub env_dropped: HashMap<String, Vec<String>>,
+}
+
+/// Save the sessions and thread mappings, replacing the previous snapshot.
+///
+/// Called when a session starts, connects, finishes a turn or goes away.
+/// Don't call it while holding the sessions lock. Failures are logged
+/// rather than surfaced; recovery is best-effort.
+pub async fn save(state: &AppState) {
+    let ws_port = *state.ws_port.read().await;
+    let sessions: Vec<SavedSession> = state
+        .sessions
+        .read()
+        .await
+        .values()
+        .map(|s| {
+            let history = s.history.all();
+            let skip = history.len().saturating_sub(SAVED_HISTORY_LIMIT);
+            SavedSession {
+                id: s.id.clone(),
+                status: s.status.clone(),
+                working_dir: s.working_dir.clone(),
+                model: s.model.clone(),
+                permission_mode: s.permission_mode.clone(),
+                cli_session_id: s.cli_session_id.clone(),
+                pid: s.pid,
+                pid_started: s.pid_started.clone(),
+                env_profile: s.env_profile.clone(),
+                env_names: s.env_overrides.keys().cloned().collect(),
+                usage_totals: s.usage_totals.clone(),
+                history: history.iter().skip(skip).map(|m| (**m).clone()).collect(),
+                mcp_token_hash: s.mcp_token_hash.clone(),
+            }
+        })
+        .collect();
+    let threads = state.thread_to_session.read().await.clone();
+    let snapshot = Snapshot {
+        ws_port,
+        sessions,
+        threads,
+    };
+    let number = NEXT_SNAPSHOT.fetch_add(1, Ordering::Relaxed);
+    let result = tokio::task::spawn_blocking(move || {
+        let mut written = WRITTEN.lock().unwrap_or_else(|e| e.into_inner());
+        if *written > number {
+            return Ok(());
+        }
+        write(&snapshot)?;
+        *written = number;
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+    if let Err(e) = result {
+        eprintln!("[katara] Failed to save sessions: {}", e);
+    }
+}
+
+/// Forget the snapshot on a clean exit, so only a crash leaves sessions to
+/// recover.
+pub fn clear() {
+    // No save still in flight may bring it back
+    let mut written = WRITTEN.lock().unwrap_or_else(|e| e.into_inner());
+    *written = u64::MAX;
+    let Ok(path) = store_path() else {
+        return;
+    };
+    if path.exists() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            eprintln!("[katara] Failed to remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// The WebSocket port of a crashed run that left CLIs running, so the
+/// server can listen there again and they can reconnect.
+pub fn previous_ws_port() -> Option<u16> {
+    let snapshot = load()?;
+    let alive = snapshot
+        .sessions
+        .iter()
+        .any(|s| s.pid.is_some_and(|pid| is_same_cli(pid, s)));
+    (alive && snapshot.ws_port != 0).then_some(snapshot.ws_port)
+}
+
+/// Reconcile the previous run's snapshot with what is still running.
+///
+/// Sessions whose CLI is alive and can reach the current WebSocket port
+/// are re-adopted as `Disconnected` until the CLI reconnects; a CLI that
+/// can't, or doesn't within `RECONNECT_GRACE`, is stopped. Sessions whose
+/// CLI is gone come back as `Error` with their last-known history, so they
+/// can be read and resumed. Thread mappings are kept only for re-adopted
+/// sessions. Emits `sessions:recovered` if there was anything to recover.
+///
+/// Called by the WebSocket server once it's listening, before it accepts
+/// connections.
+pub async fn reconcile(state: &Arc<AppState>, sink: &Sink) {
+    let Some(snapshot) = load() else {
+        return;
+    };
+    let ws_port = *state.ws_port.read().await;
+    let history = state.settings().history;
+    let history_limit = history.max_messages_per_session;
+    let persist_transcripts = history.persist_transcripts;
+    let mut report = RecoveryReport::default();
+
+    for saved in snapshot.sessions {
+        let alive = saved.pid.filter(|&pid| is_same_cli(pid, &saved));
+        let status = match (alive, saved.status.clone()) {
+            (Some(_), _) if snapshot.ws_port == ws_port => SessionStatus::Disconnected,
+            (Some(pid), _) => {
+                // It would keep dialing the old port; resume it instead
+                procinfo::terminate(pid);
+                SessionStatus::Error("Katara restarted on another port; resume to continue".into())
+            }
+            (None, SessionStatus::Terminated) => SessionStatus::Terminated,
+            (None, SessionStatus::Error(e)) => SessionStatus::Error(e),
+            (None, _) => SessionStatus::Error("Claude CLI exited while Katara was down".into()),
+        };
+        let adopted = status == SessionStatus::Disconnected;
+
+        let mut session = Session::new(
+            saved.id.clone(),
+            saved.working_dir.clone(),
+            saved.model,
+            Some(saved.permission_mode),
+        );
+        session.status = status;
+        session.cli_session_id = saved.cli_session_id;
+        session.env = claude::session_env(state, saved.env_profile.as_deref(), None)
+            .unwrap_or_else(|e| {
+                eprintln!("[katara] Failed to restore the env of {}: {}", saved.id, e);
+                HashMap::new()
+            });
+        session.env_profile = saved.env_profile;
+        if !saved.env_names.is_empty() {
+            report.env_dropped.insert(saved.id.clone(), saved.env_names);
+        }
+        // Charge the next turn only for its own usage
+        activity::seed_totals(state, &saved.id, &saved.usage_totals);
+        session.usage_totals = saved.usage_totals;
+        session.history.set_limit(history_limit);
+        for message in saved.history {
+            session.history.push(message);
+        }
+        // Carry on writing the transcript the previous run started
+        if persist_transcripts {
+            match Transcript::open(&saved.id) {
+                Ok(t) => session.history.persist_to(t),
+                Err(e) => eprintln!("[katara] Not saving a transcript for {}: {}", saved.id, e),
+            }
+        }
+        if adopted {
+            session.pid = alive;
+            session.pid_started = saved.pid_started;
+            session.mcp_token_hash = saved.mcp_token_hash;
+            session.fs_watcher =
+                SessionWatcher::start(sink.clone(), saved.id.clone(), &saved.working_dir).ok();
+            report.adopted.push(saved.id.clone());
+        } else {
+            manager::remove_mcp_config(&saved.id);
+            report.lost.push(saved.id.clone());
+        }
+        state.sessions.write().await.insert(saved.id, session);
+    }
+
+    for (thread_id, session_id) in snapshot.threads {
+        if !report.adopted.contains(&session_id) {
+            report.stale_threads += 1;
+            continue;
+        }
+        state
+            .thread_to_session
+            .write()
+            .await
+            .insert(thread_id.clone(), session_id.clone());
+        state
+            .session_to_thread
+            .write()
+            .await
+            .insert(session_id, thread_id);
+    }
+
+    if report.adopted.is_empty() && report.lost.is_empty() {
+        return;
+    }
+    println!(
+        "[katara] Recovered from a crash: {} session(s) re-adopted, {} lost, {} stale thread(s)",
+        report.adopted.len(),
+        report.lost.len(),
+        report.stale_threads
+    );
+    for session_id in &report.adopted {
+        watch_adopted(state.clone(), sink.clone(), session_id.clone());
+    }
+    save(state).await;
+    sink.emit("sessions:recovered", &report);
+}
+
+/// Follow a re-adopted CLI, which has no `Child` for `monitor_process` to
+/// wait on: stop it if it doesn't reconnect in time, and mark the session
+/// when it exits.
+fn watch_adopted(state: Arc<AppState>, sink: Sink, session_id: String) {
+    tokio::spawn(async move {
+        let started = tokio::time::Instant::now();
+        loop {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+
+            let mut sessions = state.sessions.write().await;
+            let Some(session) = sessions.get_mut(&session_id) else {
+                break; // Session was removed
+            };
+            let Some(pid) = session.pid else {
+                break;
+            };
+
+            let status = if !procinfo::is_alive(pid) {
+                println!("[katara] Re-adopted CLI for session {} exited", session_id);
+                SessionStatus::Error("Process exited".into())
+            } else if session.ws_sender.is_none() && started.elapsed() >= RECONNECT_GRACE {
+                println!(
+                    "[katara] Re-adopted CLI for session {} didn't reconnect, stopping it",
+                    session_id
+                );
+                procinfo::terminate(pid);
+                SessionStatus::Error("Claude CLI didn't reconnect; resume to continue".into())
+            } else {
+                continue;
+            };
+            session.status = status.clone();
+            session.ws_sender = None;
+            session.pid = None;
+            session.fs_watcher = None;
+            drop(sessions);
+            manager::remove_mcp_config(&session_id);
+
+            sink.emit_for_session(
+                &session_id,
+                "claude:status",
+                serde_json::json!({
+                    "session_id": session_id,
+                    "status": status,
+                }),
+            );
+            save(&state).await;
+            break;
+        }
+    });
+}
+
+/// Whether `pid` is still the saved session's CLI, rather than an
+/// unrelated process that reused its pid. A process whose start time
+/// can't be checked isn't trusted.
+fn is_same_cli(pid: u32, saved: &SavedSession) -> bool {
+    if !procinfo::is_alive(pid) {
+        return false;
+    }
+    let same_start = match (&saved.pid_started, procinfo::start_time(pid)) {
+        (Some(saved), Some(now)) => *saved == now,
+        _ => false,
+    };
+    same_start
+        && procinfo::cwd_of(pid)
+            .is_none_or(|cwd| same_path(Path::new(&cwd), Path::new(&saved.working_dir)))
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn load() -> Option<Snapshot> {
+    let path = store_path().ok()?;
+    let content = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            eprintln!(
+                "[katara] Invalid saved sessions in {}: {}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+fn write(snapshot: &Snapshot) -> Result<(), String> {
+    let path = store_path().map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string(snapshot).map_err(|e| e.to_string())?;
+    // Write then rename, so a crash mid-write can't leave a torn file
+    let tmp = path.with_extension("json.tmp");
+    // It holds chat history, so it's private from the start; a leftover
+    // from a crashed write is replaced rather than written through
+    let _ = std::fs::remove_file(&tmp);
+    config::create_private_file(&tmp)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+fn store_path() -> Result<PathBuf, KataraError> {
+    Ok(config::katara_dir()?.join("sessions.json"))
+}
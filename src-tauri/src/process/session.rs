@@ -1,10 +1,14 @@
-use serde::Serialize;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
 use tokio::process::Child;
 
+use crate::process::fs_watch::SessionWatcher;
+use crate::process::history::MessageHistory;
 use crate::websocket::protocol::Usage;
 
 /// Accumulated token usage for a session.
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UsageTotals {
     pub input_tokens: u64,
     pub output_tokens: u64,
@@ -28,12 +32,19 @@ pub struct Session {
     pub working_dir: String,
     /// The spawned Claude CLI process.
     pub process: Option<Child>,
+    /// The CLI's process id. Set without `process` for a CLI re-adopted
+    /// after a restart, which Katara didn't spawn itself.
+    pub pid: Option<u32>,
+    /// When `pid` started, as `procinfo::start_time` reports it, so a
+    /// reused pid isn't mistaken for the CLI.
+    pub pid_started: Option<String>,
     /// Channel to send messages back to the CLI via WebSocket.
     pub ws_sender: Option<tokio::sync::mpsc::Sender<String>>,
     /// CLI's internal session ID (from system/init), used for --resume.
     pub cli_session_id: Option<String>,
     /// Message history for persistence (replayed when frontend reconnects).
-    pub message_history: Vec<serde_json::Value>,
+    /// Shared, so it can be appended to under a read lock on the sessions.
+    pub history: MessageHistory,
     /// Timestamp when the session was created.
     pub created_at: std::time::Instant,
     /// Model used for this session (e.g. "claude-sonnet-4-5-20250929").
@@ -42,9 +53,34 @@ pub struct Session {
     pub permission_mode: String,
     /// Accumulated token usage across all turns.
     pub usage_totals: UsageTotals,
+    /// Extra environment variables the CLI was spawned with.
+    /// Terminals linked to this session inherit them.
+    pub env: HashMap<String, String>,
+    /// The env profile `env` was resolved from.
+    pub env_profile: Option<String>,
+    /// The variables given on top of the profile's, so `env` can be
+    /// rebuilt without saving the profile's secrets.
+    pub env_overrides: HashMap<String, String>,
+    /// Tool requests waiting for the user to approve or deny them.
+    pub pending_approvals: Vec<PendingApproval>,
+    /// Reports file changes in `working_dir` during turns.
+    pub fs_watcher: Option<SessionWatcher>,
+    /// SHA-256 of the token the CLI calls Katara's MCP server with, which
+    /// is how the server knows which session is calling.
+    pub mcp_token_hash: Option<String>,
+    /// Terminals the session opened through the MCP tools, the only ones
+    /// its `run_command` may use.
+    pub mcp_terminals: Vec<String>,
+}
+
+/// A `can_use_tool` request that wasn't resolved by the permission mode.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingApproval {
+    pub request_id: String,
+    pub tool_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SessionStatus {
     Starting,
     Connected,
@@ -67,13 +103,22 @@ impl Session {
             status: SessionStatus::Starting,
             working_dir,
             process: None,
+            pid: None,
+            pid_started: None,
             ws_sender: None,
             cli_session_id: None,
-            message_history: Vec::new(),
+            history: MessageHistory::default(),
             created_at: std::time::Instant::now(),
             model,
             permission_mode: permission_mode.unwrap_or_else(|| "default".to_string()),
             usage_totals: UsageTotals::default(),
+            env: HashMap::new(),
+            env_profile: None,
+            env_overrides: HashMap::new(),
+            pending_approvals: Vec::new(),
+            fs_watcher: None,
+            mcp_token_hash: None,
+            mcp_terminals: Vec::new(),
         }
     }
 
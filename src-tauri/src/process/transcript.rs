@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::{Arc, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config;
+use crate::error::KataraError;
+use crate::process::session::Session;
+
+/// Transcripts not written to for this long are deleted at startup, unless
+/// the `history.transcript_retention_days` setting says otherwise.
+pub const DEFAULT_RETENTION_DAYS: u64 = 90;
+
+/// Sends lines to the writer thread, which is started on first use.
+static WRITER: OnceLock<mpsc::Sender<Job>> = OnceLock::new();
+
+/// One line of a transcript file: the session's details as of when it was
+/// written, or a history message. Details are appended again when they
+/// change, and the last one wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Line {
+    Meta(TranscriptMeta),
+    Message { message: Arc<Value> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TranscriptMeta {
+    working_dir: String,
+    model: Option<String>,
+    permission_mode: String,
+    /// For `--resume`; unknown until the CLI connects.
+    cli_session_id: Option<String>,
+    /// Unix timestamp (seconds).
+    at: u64,
+}
+
+/// A transcript's details, kept up to date in `index.json` as lines are
+/// written so listing doesn't read every transcript.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Summary {
+    working_dir: String,
+    model: Option<String>,
+    permission_mode: String,
+    cli_session_id: Option<String>,
+    /// Unix timestamps (seconds); `started_at` is 0 until the first
+    /// details line.
+    started_at: u64,
+    updated_at: u64,
+    title: Option<String>,
+    message_count: usize,
+}
+
+/// Work for the writer thread.
+enum Job {
+    Append {
+        session_id: String,
+        line: Line,
+    },
+    /// The session's history is gone; close its file.
+    Close(String),
+    /// The transcript was deleted; drop it from the index.
+    Forget(String),
+}
+
+/// A past conversation, as listed by `load_persisted_sessions`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PersistedSession {
+    pub session_id: String,
+    pub working_dir: String,
+    pub model: Option<String>,
+    pub permission_mode: String,
+    pub cli_session_id: Option<String>,
+    /// Unix timestamps (seconds).
+    pub started_at: u64,
+    pub updated_at: u64,
+    /// The first user message, for display.
+    pub title: Option<String>,
+    pub message_count: usize,
+    /// Whether it's in the current session list.
+    pub open: bool,
+}
+
+/// A session's transcript, attached to its history so every stored message
+/// is queued for the writer thread as it arrives.
+pub struct Transcript {
+    session_id: String,
+}
+
+impl Transcript {
+    /// Write to the transcript for `session_id`, continuing it if it exists.
+    pub fn open(session_id: &str) -> Result<Self, String> {
+        prepare(session_id)?;
+        Ok(Self {
+            session_id: session_id.to_string(),
+        })
+    }
+
+    /// Append a history message. Streaming deltas are skipped; the full
+    /// assistant message that follows has the same content.
+    pub fn append(&self, message: &Arc<Value>) {
+        if message.get("type").and_then(|t| t.as_str()) == Some("stream_event") {
+            return;
+        }
+        send(Job::Append {
+            session_id: self.session_id.clone(),
+            line: Line::Message {
+                message: message.clone(),
+            },
+        });
+    }
+}
+
+impl Drop for Transcript {
+    fn drop(&mut self) {
+        send(Job::Close(std::mem::take(&mut self.session_id)));
+    }
+}
+
+/// Record the session's details in its transcript. Called when it starts
+/// and when the CLI reports its own session id.
+pub fn write_meta(session: &Session) {
+    if let Err(e) = prepare(&session.id) {
+        eprintln!(
+            "[katara] Failed to write transcript for {}: {}",
+            session.id, e
+        );
+        return;
+    }
+    send(Job::Append {
+        session_id: session.id.clone(),
+        line: Line::Meta(TranscriptMeta {
+            working_dir: session.working_dir.clone(),
+            model: session.model.clone(),
+            permission_mode: session.permission_mode.clone(),
+            cli_session_id: session.cli_session_id.clone(),
+            at: now(),
+        }),
+    });
+}
+
+/// Every saved transcript, most recently updated first. `open` is set for
+/// the ids in `open_ids`.
+pub fn list(open_ids: &[String]) -> Vec<PersistedSession> {
+    let Ok(entries) = transcripts_dir().and_then(|dir| Ok(std::fs::read_dir(dir)?)) else {
+        return Vec::new();
+    };
+    let index = read_index();
+    let mut sessions: Vec<PersistedSession> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                return None;
+            }
+            let session_id = path.file_stem()?.to_string_lossy().to_string();
+            // Transcripts from before the index are read in full
+            let summary = match index.get(&session_id) {
+                Some(summary) => summary.clone(),
+                None => summarize(&session_id)?,
+            };
+            let open = open_ids.contains(&session_id);
+            persisted(session_id, summary, open)
+        })
+        .collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    sessions
+}
+
+/// A transcript's details and messages, or `None` if there isn't one.
+pub fn load(session_id: &str) -> Option<(PersistedSession, Vec<Value>)> {
+    let summary = match read_index().remove(session_id) {
+        Some(summary) => summary,
+        None => summarize(session_id)?,
+    };
+    let messages = read_lines(session_id)?
+        .filter_map(|line| match line {
+            Line::Message { message } => Some(Arc::unwrap_or_clone(message)),
+            Line::Meta(_) => None,
+        })
+        .collect();
+    Some((persisted(session_id.to_string(), summary, false)?, messages))
+}
+
+pub fn delete(session_id: &str) -> Result<(), KataraError> {
+    let path = transcript_path(session_id)?;
+    if !valid_id(session_id) || !path.exists() {
+        return Err(KataraError::SessionNotFound(session_id.to_string()));
+    }
+    std::fs::remove_file(path)?;
+    send(Job::Forget(session_id.to_string()));
+    Ok(())
+}
+
+/// Delete transcripts not written to in `retention_days`. Run at startup so
+/// they don't pile up forever.
+pub fn prune(retention_days: u64) {
+    let Ok(entries) = transcripts_dir().and_then(|dir| Ok(std::fs::read_dir(dir)?)) else {
+        return;
+    };
+    let retention = std::time::Duration::from_secs(retention_days.saturating_mul(24 * 60 * 60));
+    let Some(cutoff) = std::time::SystemTime::now().checked_sub(retention) else {
+        return;
+    };
+    let mut pruned = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified < cutoff);
+        let Some(session_id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        if stale && std::fs::remove_file(&path).is_ok() {
+            send(Job::Forget(session_id));
+            pruned += 1;
+        }
+    }
+    if pruned > 0 {
+        println!("[katara] Deleted {} old transcripts", pruned);
+    }
+}
+
+impl Summary {
+    /// Account for a line written at `at`.
+    fn add(&mut self, line: &Line, at: u64) {
+        match line {
+            Line::Meta(m) => {
+                if self.started_at == 0 {
+                    self.started_at = m.at;
+                }
+                self.working_dir = m.working_dir.clone();
+                self.model = m.model.clone();
+                self.permission_mode = m.permission_mode.clone();
+                self.cli_session_id = m.cli_session_id.clone();
+            }
+            Line::Message { message } => {
+                self.message_count += 1;
+                if self.title.is_none()
+                    && message.get("type").and_then(|t| t.as_str()) == Some("user_message")
+                {
+                    self.title = message
+                        .get("content")
+                        .and_then(|c| c.as_str())
+                        .map(|c| c.lines().next().unwrap_or_default().to_string());
+                }
+            }
+        }
+        self.updated_at = at;
+    }
+}
+
+fn persisted(session_id: String, summary: Summary, open: bool) -> Option<PersistedSession> {
+    // Without a details line there's nowhere to reopen it
+    if summary.started_at == 0 {
+        return None;
+    }
+    Some(PersistedSession {
+        session_id,
+        working_dir: summary.working_dir,
+        model: summary.model,
+        permission_mode: summary.permission_mode,
+        cli_session_id: summary.cli_session_id,
+        started_at: summary.started_at,
+        updated_at: summary.updated_at,
+        title: summary.title,
+        message_count: summary.message_count,
+        open,
+    })
+}
+
+/// A transcript's details, read from the whole file.
+fn summarize(session_id: &str) -> Option<Summary> {
+    let mut summary = Summary::default();
+    for line in read_lines(session_id)? {
+        let at = match &line {
+            Line::Meta(m) => m.at,
+            Line::Message { .. } => summary.updated_at,
+        };
+        summary.add(&line, at);
+    }
+    // The file changes with every message; the details only now and then
+    if let Some(modified) = transcript_path(session_id)
+        .ok()
+        .and_then(|path| std::fs::metadata(path).ok())
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+    {
+        summary.updated_at = modified.as_secs();
+    }
+    Some(summary)
+}
+
+/// The transcript's lines, skipping any that don't parse (such as one cut
+/// short by a crash).
+fn read_lines(session_id: &str) -> Option<impl Iterator<Item = Line>> {
+    if !valid_id(session_id) {
+        return None;
+    }
+    let file = std::fs::File::open(transcript_path(session_id).ok()?).ok()?;
+    Some(
+        std::io::BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok()),
+    )
+}
+
+/// Check the id and create the directory, so what's queued can be written.
+fn prepare(session_id: &str) -> Result<(), String> {
+    if !valid_id(session_id) {
+        return Err(format!("Invalid session id: {}", session_id));
+    }
+    let dir = transcripts_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())
+}
+
+/// Queue a job for the writer thread, starting it if it isn't running.
+fn send(job: Job) {
+    let writer = WRITER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || run_writer(rx));
+        tx
+    });
+    let _ = writer.send(job);
+}
+
+/// Write queued lines as they come, and the index once the queue is empty,
+/// so a burst of messages rewrites it once.
+fn run_writer(rx: mpsc::Receiver<Job>) {
+    let mut files: HashMap<String, std::fs::File> = HashMap::new();
+    let mut index = read_index();
+    while let Ok(job) = rx.recv() {
+        let mut next = Some(job);
+        while let Some(job) = next.take().or_else(|| rx.try_recv().ok()) {
+            match job {
+                Job::Append { session_id, line } => {
+                    append(&mut files, &mut index, session_id, &line);
+                }
+                Job::Close(session_id) => {
+                    files.remove(&session_id);
+                }
+                Job::Forget(session_id) => {
+                    files.remove(&session_id);
+                    index.remove(&session_id);
+                }
+            }
+        }
+        if let Err(e) = write_index(&index) {
+            eprintln!("[katara] Failed to write the transcript index: {}", e);
+        }
+    }
+}
+
+fn append(
+    files: &mut HashMap<String, std::fs::File>,
+    index: &mut HashMap<String, Summary>,
+    session_id: String,
+    line: &Line,
+) {
+    // A transcript from before the index starts from what it already holds
+    if !index.contains_key(&session_id) {
+        let summary = summarize(&session_id).unwrap_or_default();
+        index.insert(session_id.clone(), summary);
+    }
+    let file = match files.entry(session_id.clone()) {
+        std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+        std::collections::hash_map::Entry::Vacant(entry) => match open_file(&session_id) {
+            Ok(file) => entry.insert(file),
+            Err(e) => {
+                eprintln!("[katara] Failed to open transcript {}: {}", session_id, e);
+                return;
+            }
+        },
+    };
+    if let Err(e) = write_line(file, line) {
+        eprintln!("[katara] Failed to write transcript: {}", e);
+        return;
+    }
+    if let Some(summary) = index.get_mut(&session_id) {
+        summary.add(line, now());
+    }
+}
+
+/// Open a transcript for appending, readable only by the user on Unix,
+/// since it holds whole conversations.
+fn open_file(session_id: &str) -> Result<std::fs::File, String> {
+    let path = transcript_path(session_id).map_err(|e| e.to_string())?;
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+fn write_line(file: &mut std::fs::File, line: &Line) -> Result<(), String> {
+    let json = serde_json::to_string(line).map_err(|e| e.to_string())?;
+    writeln!(file, "{}", json).map_err(|e| e.to_string())
+}
+
+fn read_index() -> HashMap<String, Summary> {
+    transcripts_dir()
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join("index.json")).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Replace the index through a temp file, so a reader never sees half of
+/// one.
+fn write_index(index: &HashMap<String, Summary>) -> Result<(), String> {
+    let dir = transcripts_dir().map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(index).map_err(|e| e.to_string())?;
+    let tmp = dir.join("index.json.tmp");
+    let _ = std::fs::remove_file(&tmp);
+    let mut file = config::create_private_file(&tmp).map_err(|e| e.to_string())?;
+    file.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, dir.join("index.json")).map_err(|e| e.to_string())
+}
+
+/// Session ids are UUIDs; anything else could point outside the directory.
+fn valid_id(session_id: &str) -> bool {
+    !session_id.is_empty()
+        && session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn transcripts_dir() -> Result<PathBuf, KataraError> {
+    Ok(config::katara_dir()?.join("transcripts"))
+}
+
+fn transcript_path(session_id: &str) -> Result<PathBuf, KataraError> {
+    Ok(transcripts_dir()?.join(format!("{}.jsonl", session_id)))
+}
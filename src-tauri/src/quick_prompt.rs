@@ -0,0 +1,90 @@
+use std::sync::Mutex;
+
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+const WINDOW_LABEL: &str = "quick-prompt";
+
+/// The shortcut currently registered, so it can be swapped when the
+/// setting changes.
+static REGISTERED: Mutex<Option<Shortcut>> = Mutex::new(None);
+
+/// Install the global shortcut plugin and register the configured
+/// shortcut. Submitting the window calls `submit_quick_prompt`.
+pub fn init(app_handle: &tauri::AppHandle, shortcut: Option<&str>) -> tauri::Result<()> {
+    app_handle.plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(|app_handle, _shortcut, event| {
+                if event.state() == ShortcutState::Pressed {
+                    open(app_handle);
+                }
+            })
+            .build(),
+    )?;
+    set_shortcut(app_handle, shortcut);
+    Ok(())
+}
+
+/// Replace the registered shortcut; `None` or empty unregisters it.
+/// Failures (e.g. another app owns the shortcut) are logged.
+pub fn set_shortcut(app_handle: &tauri::AppHandle, shortcut: Option<&str>) {
+    let shortcut = match shortcut
+        .filter(|s| !s.is_empty())
+        .map(str::parse::<Shortcut>)
+    {
+        Some(Ok(shortcut)) => Some(shortcut),
+        Some(Err(e)) => {
+            eprintln!("[katara] Invalid quick prompt shortcut: {}", e);
+            None
+        }
+        None => None,
+    };
+
+    let mut registered = REGISTERED.lock().unwrap_or_else(|e| e.into_inner());
+    if *registered == shortcut {
+        return;
+    }
+    let global = app_handle.global_shortcut();
+    if let Some(old) = registered.take() {
+        let _ = global.unregister(old);
+    }
+    if let Some(new) = shortcut {
+        match global.register(new) {
+            Ok(()) => *registered = Some(new),
+            Err(e) => eprintln!("[katara] Failed to register quick prompt shortcut: {}", e),
+        }
+    }
+}
+
+/// Show the quick prompt window, creating it on first use.
+pub fn open(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window(WINDOW_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+    let built = WebviewWindowBuilder::new(
+        app_handle,
+        WINDOW_LABEL,
+        WebviewUrl::App("index.html".into()),
+    )
+    .title("Quick Prompt")
+    .inner_size(640.0, 72.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .focused(true)
+    .build();
+    if let Err(e) = built {
+        eprintln!("[katara] Failed to open quick prompt: {}", e);
+    }
+}
+
+/// Hide the quick prompt window, keeping it around for next time.
+pub fn close(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window(WINDOW_LABEL) {
+        let _ = window.hide();
+    }
+}
@@ -0,0 +1,3 @@
+pub mod pairing;
+pub mod server;
+pub mod tls;
@@ -0,0 +1,227 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::auth;
+use crate::error::KataraError;
+
+/// How long a pairing code can be used.
+const CODE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Wrong guesses before the current code is thrown away.
+const MAX_CODE_ATTEMPTS: u32 = 5;
+
+/// `last_seen` is saved at most this often per device.
+const LAST_SEEN_RESOLUTION: u64 = 60;
+
+/// The code shown in the app, if one is waiting to be used.
+static CODE: Mutex<Option<PendingCode>> = Mutex::new(None);
+
+/// Paired devices, loaded on first use.
+static DEVICES: Mutex<Option<Vec<StoredDevice>>> = Mutex::new(None);
+
+struct PendingCode {
+    code: String,
+    expires_at: Instant,
+    attempts: u32,
+}
+
+/// A one-time code for pairing a new device.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingCode {
+    /// Six digits.
+    pub code: String,
+    pub expires_in_secs: u64,
+}
+
+/// A device allowed to use the remote server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedDevice {
+    pub id: String,
+    pub name: String,
+    /// Unix timestamps (seconds).
+    pub paired_at: u64,
+    pub last_seen: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredDevice {
+    #[serde(flatten)]
+    device: PairedDevice,
+    /// SHA-256 of the device's token; the token itself isn't kept.
+    token_hash: String,
+}
+
+/// Start pairing: make a new code, replacing any previous one.
+pub fn new_code() -> PairingCode {
+    let code = format!("{:06}", uuid::Uuid::new_v4().as_u128() % 1_000_000);
+    *CODE.lock().unwrap_or_else(|e| e.into_inner()) = Some(PendingCode {
+        code: code.clone(),
+        expires_at: Instant::now() + CODE_TTL,
+        attempts: 0,
+    });
+    PairingCode {
+        code,
+        expires_in_secs: CODE_TTL.as_secs(),
+    }
+}
+
+/// Exchange the current pairing code for a token. The code works once,
+/// and is discarded after `MAX_CODE_ATTEMPTS` wrong guesses. Returns the
+/// new device and its token, which is only ever shown here.
+pub fn pair(code: &str, device_name: &str) -> Result<(PairedDevice, String), KataraError> {
+    {
+        let mut pending = CODE.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(current) = pending.as_mut() else {
+            return Err(KataraError::Remote(
+                "No pairing code is active; create one in Katara".into(),
+            ));
+        };
+        if current.expires_at <= Instant::now() {
+            *pending = None;
+            return Err(KataraError::Remote("The pairing code has expired".into()));
+        }
+        if current.code != code.trim() {
+            current.attempts += 1;
+            if current.attempts >= MAX_CODE_ATTEMPTS {
+                *pending = None;
+            }
+            return Err(KataraError::Remote("Wrong pairing code".into()));
+        }
+        *pending = None;
+    }
+
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    let name = device_name.trim();
+    let device = PairedDevice {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: if name.is_empty() {
+            "Unnamed device".into()
+        } else {
+            name.to_string()
+        },
+        paired_at: now(),
+        last_seen: None,
+    };
+    with_devices(|devices| {
+        devices.push(StoredDevice {
+            device: device.clone(),
+            token_hash: auth::token_hash(&token),
+        });
+        true
+    });
+    println!(
+        "[katara] Paired remote device {} ({})",
+        device.name, device.id
+    );
+    Ok((device, token))
+}
+
+/// The device a bearer token belongs to, if it's paired.
+pub fn authenticate(token: &str) -> Option<PairedDevice> {
+    let token_hash = auth::token_hash(token);
+    let mut found = None;
+    with_devices(|devices| {
+        let Some(stored) = devices
+            .iter_mut()
+            .find(|d| auth::token_matches(&d.token_hash, &token_hash))
+        else {
+            return false;
+        };
+        let now = now();
+        let stale = stored
+            .device
+            .last_seen
+            .is_none_or(|seen| now.saturating_sub(seen) >= LAST_SEEN_RESOLUTION);
+        if stale {
+            stored.device.last_seen = Some(now);
+        }
+        found = Some(stored.device.clone());
+        stale
+    });
+    found
+}
+
+pub fn list_devices() -> Vec<PairedDevice> {
+    let mut devices = Vec::new();
+    with_devices(|stored| {
+        devices = stored.iter().map(|d| d.device.clone()).collect();
+        false
+    });
+    devices
+}
+
+/// Unpair a device; its token stops working immediately.
+pub fn revoke(device_id: &str) -> Result<(), KataraError> {
+    let mut removed = false;
+    with_devices(|devices| {
+        let before = devices.len();
+        devices.retain(|d| d.device.id != device_id);
+        removed = devices.len() != before;
+        removed
+    });
+    if removed {
+        Ok(())
+    } else {
+        Err(KataraError::Remote(format!(
+            "No paired device {}",
+            device_id
+        )))
+    }
+}
+
+/// Run `f` on the device list, saving it if `f` returns true.
+fn with_devices(f: impl FnOnce(&mut Vec<StoredDevice>) -> bool) {
+    let mut devices = DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+    let devices = devices.get_or_insert_with(load);
+    if f(devices) {
+        if let Err(e) = save(devices) {
+            eprintln!("[katara] Failed to save paired devices: {}", e);
+        }
+    }
+}
+
+fn load() -> Vec<StoredDevice> {
+    let Ok(path) = store_path() else {
+        return Vec::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        eprintln!(
+            "[katara] Invalid paired devices in {}: {}",
+            path.display(),
+            e
+        );
+        Vec::new()
+    })
+}
+
+fn save(devices: &[StoredDevice]) -> Result<(), String> {
+    let path = store_path().map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(devices).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn store_path() -> Result<PathBuf, KataraError> {
+    Ok(crate::config::katara_dir()?
+        .join("remote")
+        .join("devices.json"))
+}
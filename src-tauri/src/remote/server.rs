@@ -0,0 +1,222 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::sync::Arc;
+
+use crate::agui::server as agui_server;
+use crate::auth;
+use crate::error::KataraError;
+use crate::remote::pairing::{self, PairedDevice, PairingCode};
+use crate::remote::tls;
+use crate::sink::Sink;
+use crate::state::AppState;
+use axum::{
+    extract::{Extension, Query, Request, State},
+    http::HeaderMap,
+    middleware::{self, Next},
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use futures_util::stream::Stream;
+use serde::{Deserialize, Serialize};
+use tokio_stream::StreamExt;
+
+/// Where the remote server is listening, for the settings page.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteStatus {
+    /// `https://<LAN address>:<port>`; also the CopilotKit runtime URL.
+    pub url: String,
+    /// SHA-256 of the certificate, to check when a device first connects.
+    pub fingerprint: String,
+    pub self_signed: bool,
+}
+
+/// How to connect another machine or a tablet: the server's address and a
+/// fresh pairing code, together as a QR code payload.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteAccessInfo {
+    pub url: String,
+    pub fingerprint: String,
+    pub self_signed: bool,
+    /// Exchange at `POST /pair` for a device token.
+    pub pairing_code: PairingCode,
+    /// JSON with the URL, fingerprint and pairing code, for the app to show
+    /// as a QR code.
+    pub qr_payload: String,
+}
+
+/// Start the remote access server if it's enabled in settings. Every route
+/// but `/pair` needs a paired device's bearer token. Paired devices get
+/// AG-UI, the `/api/v1` REST API, `/events` and `/ws/ui`; the session routes
+/// are the same ones local scripts use.
+///
+/// The local server stays on 127.0.0.1 for the app and its sessions, and
+/// the CLI WebSocket server, which only local CLIs use, isn't exposed.
+pub async fn start(state: Arc<AppState>, sink: Sink) -> Result<(), KataraError> {
+    let settings = state.settings().remote;
+    if !settings.enabled {
+        return Ok(());
+    }
+    let addr: SocketAddr = format!("{}:{}", settings.bind_address, settings.port)
+        .parse()
+        .map_err(|e| KataraError::Remote(format!("Invalid bind address: {}", e)))?;
+    let tls = tls::load(&settings)?;
+
+    let status = RemoteStatus {
+        url: format!(
+            "https://{}",
+            SocketAddr::new(advertised_ip(addr.ip()), addr.port())
+        ),
+        fingerprint: tls.fingerprint.clone(),
+        self_signed: tls.self_signed,
+    };
+    println!(
+        "[katara] Remote access listening on {} (certificate {})",
+        status.url, status.fingerprint
+    );
+    *state.remote.write().await = Some(status.clone());
+    sink.emit("remote:started", &status);
+
+    let router = create_router(state.clone(), sink);
+    let result = axum_server::bind_rustls(addr, RustlsConfig::from_config(tls.config))
+        .serve(router.into_make_service())
+        .await;
+    *state.remote.write().await = None;
+    result.map_err(|e| KataraError::Remote(format!("Can't listen on {}: {}", addr, e)))
+}
+
+/// Connection details for the settings page, with a new pairing code, while
+/// remote access is running.
+pub async fn access_info(state: &AppState) -> Option<RemoteAccessInfo> {
+    let status = state.remote.read().await.clone()?;
+    let pairing_code = pairing::new_code();
+    let qr_payload = serde_json::json!({
+        "url": status.url,
+        "fingerprint": status.fingerprint,
+        "code": pairing_code.code,
+    })
+    .to_string();
+    Some(RemoteAccessInfo {
+        url: status.url,
+        fingerprint: status.fingerprint,
+        self_signed: status.self_signed,
+        pairing_code,
+        qr_payload,
+    })
+}
+
+fn create_router(state: Arc<AppState>, sink: Sink) -> Router {
+    let authenticated = agui_server::agui_routes()
+        .route("/events", get(events))
+        .route("/ws/ui", get(crate::websocket::ui::handle))
+        .layer(middleware::from_fn(require_device));
+
+    Router::new()
+        .route("/pair", post(pair))
+        .merge(authenticated)
+        .layer(agui_server::cors(&state))
+        .layer(Extension(sink))
+        .with_state(state)
+}
+
+/// The address other machines reach us on: `bind` itself, or for
+/// `0.0.0.0` the address of the interface with the default route.
+fn advertised_ip(bind: IpAddr) -> IpAddr {
+    if !bind.is_unspecified() {
+        return bind;
+    }
+    // Connecting a UDP socket picks a route without sending anything
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("192.0.2.1:9")?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .unwrap_or(bind)
+}
+
+/// Reject requests without a paired device's token, and hand the device
+/// to the handler. AG-UI and REST handlers use it to tell a remote request
+/// from a local one.
+async fn require_device(headers: HeaderMap, mut request: Request, next: Next) -> Response {
+    let device = auth::request_token(&headers).and_then(pairing::authenticate);
+    match device {
+        Some(device) => {
+            request.extensions_mut().insert(device);
+            next.run(request).await
+        }
+        None => (
+            axum::http::StatusCode::UNAUTHORIZED,
+            Json(KataraError::Remote(
+                "Missing or revoked device token".into(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct PairRequest {
+    code: String,
+    device_name: String,
+}
+
+#[derive(Serialize)]
+struct PairResponse {
+    device: PairedDevice,
+    /// Send as `Authorization: Bearer <token>`. Not shown again.
+    token: String,
+}
+
+/// POST /pair — exchange the code shown in Katara for a device token.
+async fn pair(
+    Extension(sink): Extension<Sink>,
+    Json(body): Json<PairRequest>,
+) -> Result<Json<PairResponse>, KataraError> {
+    let (device, token) = pairing::pair(&body.code, &body.device_name)?;
+    sink.emit("remote:paired", &device);
+    Ok(Json(PairResponse { device, token }))
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    session_id: Option<String>,
+}
+
+/// GET /events?session_id= — live CLI messages as SSE, for one session or
+/// all of them. Each event is a `{ session_id, message }` object.
+async fn events(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EventsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut event_rx = state.event_tx.subscribe();
+    let (tx, rx) = tokio::sync::mpsc::channel::<String>(128);
+    tokio::spawn(async move {
+        loop {
+            let event = match event_rx.recv().await {
+                Ok(event) => event,
+                // A slow client just misses the dropped events
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            if query
+                .session_id
+                .as_ref()
+                .is_some_and(|id| *id != event.session_id)
+            {
+                continue;
+            }
+            let Ok(data) = serde_json::to_string(&event) else {
+                continue;
+            };
+            if tx.send(data).await.is_err() {
+                break;
+            }
+        }
+    });
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx)
+        .map(|data| Ok::<_, Infallible>(Event::default().data(data)));
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
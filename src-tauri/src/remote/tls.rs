@@ -0,0 +1,104 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use sha2::{Digest, Sha256};
+
+use crate::config;
+use crate::config::manager::RemoteSettings;
+use crate::error::KataraError;
+
+/// TLS setup for the remote server.
+pub struct TlsSetup {
+    pub config: Arc<rustls::ServerConfig>,
+    /// SHA-256 of the certificate, `AB:CD:...`, for devices to pin a
+    /// self-signed certificate.
+    pub fingerprint: String,
+    pub self_signed: bool,
+}
+
+/// Load the configured certificate and key, or the self-signed pair,
+/// generating it the first time.
+pub fn load(settings: &RemoteSettings) -> Result<TlsSetup, KataraError> {
+    let configured = settings
+        .tls_cert_path
+        .as_deref()
+        .zip(settings.tls_key_path.as_deref())
+        .filter(|(cert, key)| !cert.is_empty() && !key.is_empty());
+    let (cert_path, key_path, self_signed) = match configured {
+        Some((cert, key)) => (PathBuf::from(cert), PathBuf::from(key), false),
+        None => {
+            let (cert, key) = self_signed_paths()?;
+            if !cert.exists() || !key.exists() {
+                generate_self_signed(&cert, &key, &settings.bind_address)?;
+            }
+            (cert, key, true)
+        }
+    };
+
+    let certs: Vec<CertificateDer<'static>> = CertificateDer::pem_file_iter(&cert_path)
+        .and_then(|certs| certs.collect())
+        .map_err(|e| tls_error(&cert_path, e))?;
+    let key = PrivateKeyDer::from_pem_file(&key_path).map_err(|e| tls_error(&key_path, e))?;
+    let fingerprint = certs
+        .first()
+        .map(|cert| fingerprint(cert))
+        .ok_or_else(|| KataraError::Remote(format!("No certificate in {}", cert_path.display())))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+        .map_err(|e| KataraError::Remote(format!("Invalid TLS certificate or key: {}", e)))?;
+    Ok(TlsSetup {
+        config: Arc::new(config),
+        fingerprint,
+        self_signed,
+    })
+}
+
+fn generate_self_signed(
+    cert_path: &Path,
+    key_path: &Path,
+    bind_address: &str,
+) -> Result<(), KataraError> {
+    let mut names = vec!["localhost".to_string(), "127.0.0.1".to_string()];
+    if !matches!(bind_address, "0.0.0.0" | "::" | "127.0.0.1") {
+        names.push(bind_address.to_string());
+    }
+    let generated = rcgen::generate_simple_self_signed(names)
+        .map_err(|e| KataraError::Remote(format!("Failed to generate a certificate: {}", e)))?;
+    if let Some(parent) = cert_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(cert_path, generated.cert.pem())?;
+    // Private from the moment it exists; a key left without its
+    // certificate is replaced
+    let _ = std::fs::remove_file(key_path);
+    config::create_private_file(key_path)?
+        .write_all(generated.signing_key.serialize_pem().as_bytes())?;
+    println!(
+        "[katara] Generated a self-signed certificate for remote access in {}",
+        cert_path.display()
+    );
+    Ok(())
+}
+
+fn fingerprint(cert: &CertificateDer) -> String {
+    Sha256::digest(cert.as_ref())
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn tls_error(path: &Path, e: impl std::fmt::Display) -> KataraError {
+    KataraError::Remote(format!("Failed to read {}: {}", path.display(), e))
+}
+
+fn self_signed_paths() -> Result<(PathBuf, PathBuf), KataraError> {
+    let dir = config::katara_dir()?.join("remote");
+    Ok((dir.join("cert.pem"), dir.join("key.pem")))
+}
@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+use std::path::Path;
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::error::KataraError;
+
+/// Larger files are skipped; they're almost always generated or data.
+const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Bytes checked for a NUL to tell binary files apart.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Lines longer than this are cut in results (minified code).
+const MAX_LINE_CHARS: usize = 500;
+
+/// Most lines of context a caller can ask for on each side of a match.
+pub const MAX_CONTEXT: usize = 10;
+
+/// Most matches a caller can ask for in one search.
+pub const MAX_RESULTS: usize = 5_000;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ContentSearchOptions {
+    /// Treat the pattern as a regex instead of literal text.
+    pub regex: bool,
+    /// `None` is smart case: insensitive unless the pattern has an
+    /// uppercase letter.
+    pub case_sensitive: Option<bool>,
+    pub whole_word: bool,
+    /// Lines of context before and after each match.
+    pub context: usize,
+    /// Globs a relative path must match, e.g. `src/**/*.rs`. Empty allows all.
+    pub include: Vec<String>,
+    /// Globs to skip, on top of `.gitignore`.
+    pub exclude: Vec<String>,
+    pub max_results: usize,
+}
+
+impl Default for ContentSearchOptions {
+    fn default() -> Self {
+        Self {
+            regex: false,
+            case_sensitive: None,
+            whole_word: false,
+            context: 2,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_results: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContentSearchResult {
+    pub files: Vec<FileMatches>,
+    pub total_matches: usize,
+    pub files_searched: usize,
+    /// Stopped at `max_results`.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMatches {
+    /// Relative to the working directory, with `/` separators.
+    pub path: String,
+    pub matches: Vec<LineMatch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LineMatch {
+    /// 1-based.
+    pub line_number: usize,
+    pub line: String,
+    /// Character ranges in `line` that matched.
+    pub ranges: Vec<MatchRange>,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MatchRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Search file contents under `working_dir`, skipping `.gitignore`d,
+/// binary and very large files.
+pub async fn search(
+    working_dir: &str,
+    pattern: &str,
+    options: ContentSearchOptions,
+) -> Result<ContentSearchResult, KataraError> {
+    let root = Path::new(working_dir).to_path_buf();
+    if !root.is_dir() {
+        return Err(KataraError::Config(format!(
+            "Not a directory: {}",
+            working_dir
+        )));
+    }
+    if pattern.is_empty() {
+        return Ok(ContentSearchResult::default());
+    }
+    let regex = build_regex(pattern, &options)?;
+    let include = globs(&options.include)?;
+    let exclude = globs(&options.exclude)?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut result = ContentSearchResult::default();
+        let walker = ignore::WalkBuilder::new(&root)
+            .hidden(false)
+            .require_git(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+        for entry in walker.flatten() {
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            if entry.metadata().is_ok_and(|m| m.len() > MAX_FILE_BYTES) {
+                continue;
+            }
+            let Ok(rel) = entry.path().strip_prefix(&root) else {
+                continue;
+            };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if !include.is_empty() && !include.iter().any(|g| g.matches(&rel)) {
+                continue;
+            }
+            if exclude.iter().any(|g| g.matches(&rel)) {
+                continue;
+            }
+            let Ok(bytes) = std::fs::read(entry.path()) else {
+                continue;
+            };
+            if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+                continue;
+            }
+            result.files_searched += 1;
+
+            let remaining = options.max_results - result.total_matches;
+            let text = String::from_utf8_lossy(&bytes);
+            let (matches, more) = search_text(&text, &regex, options.context, remaining);
+            if !matches.is_empty() {
+                result.total_matches += matches.len();
+                result.files.push(FileMatches { path: rel, matches });
+            }
+            if more {
+                result.truncated = true;
+                break;
+            }
+        }
+        result
+    })
+    .await
+    .map_err(|e| KataraError::Config(format!("Content search failed: {}", e)))
+}
+
+/// Matching lines in `text`, at most `limit`, and whether there were more.
+fn search_text(text: &str, regex: &Regex, context: usize, limit: usize) -> (Vec<LineMatch>, bool) {
+    let mut matches: Vec<LineMatch> = Vec::new();
+    let mut before: VecDeque<&str> = VecDeque::with_capacity(context);
+    // Matches still collecting trailing context
+    let mut open: Vec<usize> = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        open.retain(|&m| {
+            let m = &mut matches[m];
+            if m.after.len() < context {
+                m.after.push(clip(line));
+            }
+            m.after.len() < context
+        });
+
+        let ranges: Vec<MatchRange> = regex
+            .find_iter(line)
+            .filter(|m| !m.is_empty())
+            .map(|m| MatchRange {
+                start: line[..m.start()].chars().count(),
+                end: line[..m.end()].chars().count(),
+            })
+            .collect();
+        if !ranges.is_empty() {
+            if matches.len() == limit {
+                return (matches, true);
+            }
+            matches.push(LineMatch {
+                line_number: i + 1,
+                line: clip(line),
+                ranges,
+                before: before.iter().map(|l| clip(l)).collect(),
+                after: Vec::new(),
+            });
+            if context > 0 {
+                open.push(matches.len() - 1);
+            }
+        }
+
+        if context > 0 {
+            if before.len() == context {
+                before.pop_front();
+            }
+            before.push_back(line);
+        }
+    }
+    (matches, false)
+}
+
+fn build_regex(pattern: &str, options: &ContentSearchOptions) -> Result<Regex, KataraError> {
+    let mut source = if options.regex {
+        pattern.to_string()
+    } else {
+        regex::escape(pattern)
+    };
+    if options.whole_word {
+        source = format!(r"\b(?:{})\b", source);
+    }
+    let case_sensitive = options
+        .case_sensitive
+        .unwrap_or_else(|| pattern.chars().any(char::is_uppercase));
+    RegexBuilder::new(&source)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| KataraError::Config(format!("Invalid search pattern: {}", e)))
+}
+
+fn globs(patterns: &[String]) -> Result<Vec<glob::Pattern>, KataraError> {
+    patterns
+        .iter()
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| {
+            glob::Pattern::new(p.trim())
+                .map_err(|e| KataraError::Config(format!("Invalid glob {}: {}", p, e)))
+        })
+        .collect()
+}
+
+fn clip(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::error::KataraError;
+
+/// An index older than this is rebuilt on the next search.
+const INDEX_TTL: Duration = Duration::from_secs(30);
+
+/// Files indexed per project, so a huge tree can't exhaust memory.
+const MAX_INDEXED_FILES: usize = 100_000;
+
+/// Projects kept indexed at once; the oldest index is dropped beyond this.
+const MAX_INDEXES: usize = 8;
+
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Relative file paths per project root.
+static INDEXES: Mutex<Option<HashMap<PathBuf, FileIndex>>> = Mutex::new(None);
+
+#[derive(Clone)]
+struct FileIndex {
+    files: Arc<Vec<String>>,
+    built_at: Instant,
+}
+
+/// A file matching a search, best first.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMatch {
+    /// Relative to the working directory, with `/` separators.
+    pub path: String,
+    pub score: i64,
+    /// Character positions in `path` that matched, for highlighting.
+    pub indices: Vec<usize>,
+}
+
+/// Fuzzy-match `query` against the files under `working_dir`, skipping
+/// anything `.gitignore`d. The file list is cached for `INDEX_TTL`;
+/// `refresh` rebuilds it now.
+pub async fn search(
+    working_dir: &str,
+    query: &str,
+    limit: usize,
+    refresh: bool,
+) -> Result<Vec<FileMatch>, KataraError> {
+    let root = PathBuf::from(working_dir);
+    if !root.is_dir() {
+        return Err(KataraError::Config(format!(
+            "Not a directory: {}",
+            working_dir
+        )));
+    }
+    let files = index(&root, refresh).await?;
+    let query = query.trim().to_string();
+    tokio::task::spawn_blocking(move || rank(&files, &query, limit))
+        .await
+        .map_err(|e| KataraError::Config(format!("File search failed: {}", e)))
+}
+
+async fn index(root: &Path, refresh: bool) -> Result<Arc<Vec<String>>, KataraError> {
+    if !refresh {
+        let indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = indexes.as_ref().and_then(|i| i.get(root)) {
+            if index.built_at.elapsed() < INDEX_TTL {
+                return Ok(index.files.clone());
+            }
+        }
+    }
+
+    let walk_root = root.to_path_buf();
+    let files = tokio::task::spawn_blocking(move || walk(&walk_root))
+        .await
+        .map_err(|e| KataraError::Config(format!("File index failed: {}", e)))?;
+    let files = Arc::new(files);
+    let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+    let indexes = indexes.get_or_insert_with(HashMap::new);
+    // Expired indexes would be rebuilt anyway, so only fresh ones are kept
+    indexes.retain(|_, index| index.built_at.elapsed() < INDEX_TTL);
+    indexes.insert(
+        root.to_path_buf(),
+        FileIndex {
+            files: files.clone(),
+            built_at: Instant::now(),
+        },
+    );
+    while indexes.len() > MAX_INDEXES {
+        let Some(oldest) = indexes
+            .iter()
+            .min_by_key(|(_, index)| index.built_at)
+            .map(|(root, _)| root.clone())
+        else {
+            break;
+        };
+        indexes.remove(&oldest);
+    }
+    Ok(files)
+}
+
+fn walk(root: &Path) -> Vec<String> {
+    let walker = ignore::WalkBuilder::new(root)
+        // Dotfiles like .github/ are worth mentioning; .git itself isn't
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    let mut files = Vec::new();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        if let Ok(rel) = entry.path().strip_prefix(root) {
+            files.push(rel.to_string_lossy().replace('\\', "/"));
+        }
+        if files.len() >= MAX_INDEXED_FILES {
+            break;
+        }
+    }
+    files
+}
+
+fn rank(files: &[String], query: &str, limit: usize) -> Vec<FileMatch> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    // An empty query lists the shallowest files, then the shortest
+    let mut matches: Vec<(i64, &String, Vec<usize>)> = if query.is_empty() {
+        files.iter().map(|path| (0, path, Vec::new())).collect()
+    } else {
+        files
+            .iter()
+            .filter_map(|path| {
+                let (score, indices) = score(path, &query)?;
+                Some((score, path, indices))
+            })
+            .collect()
+    };
+    let depth = |path: &str| path.matches('/').count();
+    matches.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(depth(a.1).cmp(&depth(b.1)))
+            .then(a.1.len().cmp(&b.1.len()))
+            .then_with(|| a.1.cmp(b.1))
+    });
+    matches
+        .into_iter()
+        .take(limit)
+        .map(|(score, path, indices)| FileMatch {
+            path: path.clone(),
+            score,
+            indices,
+        })
+        .collect()
+}
+
+/// Score `path` for `query` as a case-insensitive subsequence. Matches at
+/// word boundaries, runs of consecutive characters and matches within the
+/// file name score higher; gaps and long paths score lower.
+fn score(path: &str, query: &[char]) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<char> = path.chars().collect();
+    let eq = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+
+    // Find the first complete match, then walk back from its end to the
+    // shortest window that still contains the whole query
+    let mut qi = 0;
+    let mut end = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if eq(c, query[qi]) {
+            qi += 1;
+            if qi == query.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+    let mut start = end;
+    let mut qi = query.len();
+    for i in (0..=end).rev() {
+        if eq(chars[i], query[qi - 1]) {
+            qi -= 1;
+            if qi == 0 {
+                start = i;
+                break;
+            }
+        }
+    }
+
+    // Match forward again within the window, recording positions
+    let mut indices = Vec::with_capacity(query.len());
+    let mut qi = 0;
+    for (i, &c) in chars.iter().enumerate().take(end + 1).skip(start) {
+        if qi < query.len() && eq(c, query[qi]) {
+            indices.push(i);
+            qi += 1;
+        }
+    }
+
+    let name_start = chars.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+    let mut score = 0i64;
+    let mut prev: Option<usize> = None;
+    for (&i, &q) in indices.iter().zip(query) {
+        score += 16;
+        if chars[i] == q {
+            score += 1;
+        }
+        let boundary = match i.checked_sub(1).map(|p| chars[p]) {
+            None | Some('/') => 12,
+            Some('_' | '-' | '.' | ' ') => 10,
+            Some(p) if p.is_lowercase() && chars[i].is_uppercase() => 10,
+            _ => 0,
+        };
+        score += boundary;
+        if let Some(prev) = prev {
+            if i == prev + 1 {
+                score += 8;
+            } else {
+                score -= (i - prev - 1).min(8) as i64;
+            }
+        }
+        prev = Some(i);
+    }
+    if indices[0] >= name_start {
+        score += 20;
+    }
+    score -= chars.len() as i64 / 8;
+    Some((score, indices))
+}
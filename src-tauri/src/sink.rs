@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::window_scope::{self, Subject};
+
+/// Where backend events go: the app's windows, or nowhere in particular
+/// when running headless and clients use the HTTP APIs instead.
+pub trait EventSink: Send + Sync {
+    /// Deliver an event, to whoever shows `subject` if it's about one
+    /// session or terminal, otherwise to everyone.
+    fn send(&self, subject: Option<Subject<'_>>, event: &str, payload: Value);
+
+    /// Show an OS notification.
+    fn notify(&self, title: &str, body: &str) -> Result<(), String>;
+
+    /// Whether the user is looking at the app, so there's no need to
+    /// notify them.
+    fn in_focus(&self) -> bool {
+        false
+    }
+
+    /// The Tauri app, for what only works with windows (terminals).
+    fn app_handle(&self) -> Option<&AppHandle> {
+        None
+    }
+}
+
+pub type Sink = Arc<dyn EventSink>;
+
+impl dyn EventSink {
+    pub fn emit<S: Serialize>(&self, event: &str, payload: S) {
+        self.send(None, event, to_value(payload));
+    }
+
+    /// Emit a `claude:*` event about `session_id`.
+    pub fn emit_for_session<S: Serialize>(&self, session_id: &str, event: &str, payload: S) {
+        self.send(Some(Subject::Session(session_id)), event, to_value(payload));
+    }
+
+    /// Emit a `terminal:*` event about `terminal_id`.
+    pub fn emit_for_terminal<S: Serialize>(&self, terminal_id: &str, event: &str, payload: S) {
+        self.send(
+            Some(Subject::Terminal(terminal_id)),
+            event,
+            to_value(payload),
+        );
+    }
+}
+
+fn to_value<S: Serialize>(payload: S) -> Value {
+    serde_json::to_value(payload).unwrap_or_default()
+}
+
+/// Events go to the app's windows, scoped by [`window_scope`].
+pub struct TauriSink {
+    app_handle: AppHandle,
+}
+
+impl EventSink for TauriSink {
+    fn send(&self, subject: Option<Subject<'_>>, event: &str, payload: Value) {
+        window_scope::emit(&self.app_handle, subject, event, payload);
+    }
+
+    fn notify(&self, title: &str, body: &str) -> Result<(), String> {
+        self.app_handle
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+            .map_err(|e| e.to_string())
+    }
+
+    fn in_focus(&self) -> bool {
+        self.app_handle
+            .get_webview_window("main")
+            .and_then(|w| w.is_focused().ok())
+            .unwrap_or(false)
+    }
+
+    fn app_handle(&self) -> Option<&AppHandle> {
+        Some(&self.app_handle)
+    }
+}
+
+pub fn tauri(app_handle: AppHandle) -> Sink {
+    Arc::new(TauriSink { app_handle })
+}
+
+/// Headless mode has no windows: events are dropped, since clients follow
+/// sessions through AG-UI, MCP and the other HTTP APIs, and notifications
+/// go to the log.
+pub struct HeadlessSink;
+
+impl EventSink for HeadlessSink {
+    fn send(&self, _subject: Option<Subject<'_>>, _event: &str, _payload: Value) {}
+
+    fn notify(&self, title: &str, body: &str) -> Result<(), String> {
+        println!("[katara] {}: {}", title, body);
+        Ok(())
+    }
+}
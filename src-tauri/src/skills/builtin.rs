@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::KataraError;
+use crate::skills::manager;
+
+/// Starter skills shipped with the app, as (file name, content).
+const BUILTIN_SKILLS: &[(&str, &str)] = &[
+    ("release-notes.md", include_str!("builtin/release-notes.md")),
+    (
+        "review-changes.md",
+        include_str!("builtin/review-changes.md"),
+    ),
+    ("explain-code.md", include_str!("builtin/explain-code.md")),
+    ("write-tests.md", include_str!("builtin/write-tests.md")),
+];
+
+/// Which built-in skills were installed before, so ones the user deleted
+/// aren't brought back on the next launch.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct InstallRecord {
+    installed: Vec<String>,
+}
+
+/// Copy the built-in skills into `skills_dir`.
+///
+/// Existing files are never overwritten. Unless `reinstall` is set, skills
+/// installed by an earlier call are skipped even if the user has since
+/// deleted them. Returns the paths written.
+pub fn install(skills_dir: &str, reinstall: bool) -> Result<Vec<String>, KataraError> {
+    let mut record = load_record();
+    let mut written = Vec::new();
+
+    for (file_name, content) in BUILTIN_SKILLS {
+        let path = Path::new(skills_dir).join(file_name);
+        let seen = record.installed.iter().any(|f| f == file_name);
+        if path.exists() || (seen && !reinstall) {
+            continue;
+        }
+        let path = path.display().to_string();
+        manager::write_skill(&path, content)?;
+        if !seen {
+            record.installed.push(file_name.to_string());
+        }
+        written.push(path);
+    }
+
+    if !written.is_empty() {
+        println!("[katara] Installed {} built-in skill(s)", written.len());
+        save_record(&record)?;
+    }
+    Ok(written)
+}
+
+fn load_record() -> InstallRecord {
+    record_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_record(record: &InstallRecord) -> Result<(), KataraError> {
+    let path = record_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(KataraError::Io)?;
+    }
+    let content = serde_json::to_string_pretty(record).map_err(KataraError::Serde)?;
+    std::fs::write(&path, content).map_err(KataraError::Io)
+}
+
+fn record_path() -> Result<PathBuf, KataraError> {
+    Ok(crate::config::katara_dir()?.join("builtin_skills.json"))
+}
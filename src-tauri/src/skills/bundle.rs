@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+
+use crate::error::KataraError;
+use crate::skills::import::SkippedFile;
+use crate::skills::manager::{self, SKILL_FILE};
+use crate::skills::parser::{parse_skill, ParsedSkill};
+
+/// Largest skill file accepted from a bundle.
+const MAX_ENTRY_BYTES: u64 = 1024 * 1024;
+
+/// What to do when a bundled skill has the same name as an installed one.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Replace the installed skill's file.
+    Overwrite,
+    /// Keep the installed skill and leave the bundled one out.
+    Skip,
+    /// Import the bundled skill under a new name ("Name 2").
+    Rename,
+}
+
+/// Outcome of importing a bundle.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BundleImport {
+    pub imported: Vec<ParsedSkill>,
+    pub skipped: Vec<SkippedFile>,
+    /// Names of bundled skills that clash with installed ones.
+    pub conflicts: Vec<String>,
+}
+
+/// Write the given skill files into a zip archive at `zip_path`.
+///
+/// Single-file skills are stored flat by file name. Directory skills are
+/// stored as their folder, `SKILL.md` and resources together. Clashing
+/// names get a numeric suffix. Returns the number of skills written.
+pub fn export_skills(paths: &[String], zip_path: &str) -> Result<usize, KataraError> {
+    let file = std::fs::File::create(zip_path).map_err(KataraError::Io)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut used = HashSet::new();
+
+    for path in paths {
+        // Refuse to bundle something that isn't a skill
+        let skill = manager::read_skill(path)?;
+        let content = std::fs::read(path).map_err(KataraError::Io)?;
+
+        if let Some(dir) = manager::skill_dir_of(Path::new(path)) {
+            let base = dir
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| skill.metadata.name.clone());
+            let mut folder = base.clone();
+            let mut n = 2;
+            while !used.insert(folder.clone()) {
+                folder = format!("{}-{}", base, n);
+                n += 1;
+            }
+
+            zip.start_file(format!("{}/{}", folder, SKILL_FILE), options)
+                .map_err(zip_error)?;
+            zip.write_all(&content).map_err(KataraError::Io)?;
+            for resource in &skill.resources {
+                let data = std::fs::read(dir.join(resource)).map_err(KataraError::Io)?;
+                zip.start_file(format!("{}/{}", folder, resource), options)
+                    .map_err(zip_error)?;
+                zip.write_all(&data).map_err(KataraError::Io)?;
+            }
+            continue;
+        }
+
+        let stem = Path::new(path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| skill.metadata.name.clone());
+        let mut name = format!("{}.md", stem);
+        let mut n = 2;
+        while !used.insert(name.clone()) {
+            name = format!("{}-{}.md", stem, n);
+            n += 1;
+        }
+
+        zip.start_file(name, options).map_err(zip_error)?;
+        zip.write_all(&content).map_err(KataraError::Io)?;
+    }
+
+    zip.finish().map_err(zip_error)?;
+    Ok(paths.len())
+}
+
+/// Install the skills in a bundle into `skills_dir`.
+///
+/// A bundled skill conflicts when an installed skill has the same name.
+/// Without `on_conflict`, nothing is written if there are any conflicts and
+/// the result only lists them, so the caller can ask how to resolve them
+/// and call again.
+pub fn import_bundle(
+    zip_path: &str,
+    skills_dir: &str,
+    on_conflict: Option<ConflictStrategy>,
+) -> Result<BundleImport, KataraError> {
+    let file = std::fs::File::open(zip_path).map_err(KataraError::Io)?;
+    let mut archive = zip::ZipArchive::new(file).map_err(zip_error)?;
+    let mut result = BundleImport::default();
+
+    // Top-level folders with a SKILL.md are directory skills; everything
+    // else in them is a resource
+    let skill_dirs: HashSet<String> = archive
+        .file_names()
+        .filter_map(|name| name.strip_suffix(&format!("/{}", SKILL_FILE)))
+        .filter(|folder| !folder.contains('/'))
+        .map(String::from)
+        .collect();
+
+    // Read and validate everything before touching the skills directory
+    let mut bundled = Vec::new();
+    let mut resources: HashMap<String, Vec<(String, Vec<u8>)>> = HashMap::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(zip_error)?;
+        let name = entry.name().to_string();
+        if entry.is_dir() {
+            continue;
+        }
+        let in_skill_dir = name
+            .split_once('/')
+            .filter(|(folder, _)| skill_dirs.contains(*folder));
+        let resource = in_skill_dir.filter(|(_, rest)| *rest != SKILL_FILE);
+        if resource.is_none() && !name.ends_with(".md") {
+            continue;
+        }
+        if entry.size() > MAX_ENTRY_BYTES {
+            result.skipped.push(SkippedFile {
+                path: name,
+                reason: "File is too large".into(),
+            });
+            continue;
+        }
+
+        if let Some((folder, rest)) = resource {
+            let contained = Path::new(rest)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)));
+            if !contained {
+                result.skipped.push(SkippedFile {
+                    path: name.clone(),
+                    reason: "Path leaves the skill folder".into(),
+                });
+                continue;
+            }
+            let mut data = Vec::new();
+            if let Err(e) = entry.read_to_end(&mut data) {
+                result.skipped.push(SkippedFile {
+                    path: name.clone(),
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+            resources
+                .entry(folder.to_string())
+                .or_default()
+                .push((rest.to_string(), data));
+            continue;
+        }
+
+        let mut content = String::new();
+        if let Err(e) = entry.read_to_string(&mut content) {
+            result.skipped.push(SkippedFile {
+                path: name,
+                reason: e.to_string(),
+            });
+            continue;
+        }
+        let folder = in_skill_dir.map(|(folder, _)| folder.to_string());
+        match parse_skill(&content, &name) {
+            Ok(skill) => bundled.push((name, skill.metadata.name, content, folder)),
+            Err(e) => result.skipped.push(SkippedFile {
+                path: name,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    let installed: HashMap<String, String> = manager::list_skills(skills_dir)?
+        .into_iter()
+        .map(|s| (s.metadata.name, s.file_path))
+        .collect();
+    result.conflicts = bundled
+        .iter()
+        .filter(|(_, name, _, _)| installed.contains_key(name))
+        .map(|(_, name, _, _)| name.clone())
+        .collect();
+    let strategy = match on_conflict {
+        Some(strategy) => strategy,
+        // No conflicts means there's nothing to resolve
+        None if result.conflicts.is_empty() => ConflictStrategy::Skip,
+        None => return Ok(result),
+    };
+
+    let dir = Path::new(skills_dir);
+    let mut taken: HashSet<String> = installed.keys().cloned().collect();
+    for (entry_name, name, content, folder) in bundled {
+        let (existing, new_name, content) = match (installed.get(&name), strategy) {
+            (None, _) => (None, name, content),
+            (Some(existing), ConflictStrategy::Overwrite) => {
+                (Some(existing.as_str()), name, content)
+            }
+            (Some(_), ConflictStrategy::Skip) => {
+                result.skipped.push(SkippedFile {
+                    path: entry_name,
+                    reason: format!("A skill named \"{}\" already exists", name),
+                });
+                continue;
+            }
+            (Some(_), ConflictStrategy::Rename) => {
+                let new_name = (2..)
+                    .map(|n| format!("{} {}", name, n))
+                    .find(|n| !taken.contains(n))
+                    .unwrap_or_default();
+                let content = manager::set_name(&content, &new_name)?;
+                (None, new_name, content)
+            }
+        };
+        let files = folder.map(|folder| resources.remove(&folder).unwrap_or_default());
+        let path = install(dir, &new_name, existing, &content, files)?;
+        let skill = manager::read_skill(&path)?;
+        taken.insert(skill.metadata.name.clone());
+        result.imported.push(skill);
+    }
+
+    println!(
+        "[katara] Imported {} skill(s) from {}",
+        result.imported.len(),
+        zip_path
+    );
+    Ok(result)
+}
+
+/// Write a bundled skill into `dir` under `name`, or over the installed
+/// skill at `existing`. `resources` is `Some` for a directory skill, whose
+/// folder is written whole. Returns the path of the skill file.
+fn install(
+    dir: &Path,
+    name: &str,
+    existing: Option<&str>,
+    content: &str,
+    resources: Option<Vec<(String, Vec<u8>)>>,
+) -> Result<String, KataraError> {
+    let Some(resources) = resources else {
+        let path = match existing {
+            Some(existing) => existing.into(),
+            None => manager::available_path(dir, name, None)?,
+        };
+        let path = path.display().to_string();
+        manager::write_skill(&path, content)?;
+        return Ok(path);
+    };
+
+    let folder = match existing.and_then(|p| manager::skill_dir_of(Path::new(p))) {
+        Some(folder) => folder.to_path_buf(),
+        None => manager::available_dir(dir, name, None)?,
+    };
+    let path = folder.join(SKILL_FILE).display().to_string();
+    manager::write_skill(&path, content)?;
+    for (relative, data) in resources {
+        let target = folder.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(KataraError::Io)?;
+        }
+        std::fs::write(&target, data).map_err(KataraError::Io)?;
+    }
+    // A single-file skill replaced by a folder goes away
+    if let Some(existing) = existing.filter(|p| manager::skill_dir_of(Path::new(p)).is_none()) {
+        std::fs::remove_file(existing).map_err(KataraError::Io)?;
+    }
+    Ok(path)
+}
+
+fn zip_error(e: zip::result::ZipError) -> KataraError {
+    KataraError::Skill(format!("Zip error: {}", e))
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::KataraError;
+use crate::skills::manager;
+use crate::skills::parser::{parse_skill, ParsedSkill};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Upper bound on files fetched from one repository.
+const MAX_REPO_FILES: usize = 200;
+
+/// Outcome of importing a repository's skills.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepoImport {
+    pub imported: Vec<ParsedSkill>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// A markdown file that wasn't imported, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// An entry from the GitHub contents API.
+#[derive(Debug, Deserialize)]
+struct ContentEntry {
+    path: String,
+    #[serde(rename = "type")]
+    kind: String,
+    download_url: Option<String>,
+    html_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Contents {
+    Dir(Vec<ContentEntry>),
+    File(ContentEntry),
+}
+
+/// Fetch a single skill file and save it into `skills_dir`.
+///
+/// GitHub `blob` links are fetched from raw.githubusercontent.com. The file
+/// must parse as a skill; the URL is recorded as `source` in its frontmatter.
+pub async fn import_from_url(url: &str, skills_dir: &str) -> Result<ParsedSkill, KataraError> {
+    let client = client()?;
+    let content = fetch_text(&client, &raw_url(url)).await?;
+    save(&content, url, skills_dir)
+}
+
+/// Import every skill file from a GitHub repository (optionally only under
+/// `subdir`) into `skills_dir`.
+///
+/// `repo` is `owner/name` or a github.com URL. Markdown files that aren't
+/// valid skills are reported in `skipped` rather than failing the import.
+pub async fn import_from_repo(
+    repo: &str,
+    subdir: Option<&str>,
+    skills_dir: &str,
+) -> Result<RepoImport, KataraError> {
+    let repo = repo_slug(repo)?;
+    let client = client()?;
+    let mut result = RepoImport::default();
+    let mut fetched = 0;
+    let mut dirs = vec![subdir.unwrap_or("").trim_matches('/').to_string()];
+
+    while let Some(dir) = dirs.pop() {
+        let api = format!("https://api.github.com/repos/{}/contents/{}", repo, dir);
+        let body = fetch_text(&client, &api).await?;
+        let entries = match serde_json::from_str(&body).map_err(KataraError::Serde)? {
+            Contents::Dir(entries) => entries,
+            Contents::File(entry) => vec![entry],
+        };
+
+        for entry in entries {
+            if entry.kind == "dir" {
+                dirs.push(entry.path);
+                continue;
+            }
+            if entry.kind != "file" || !entry.path.ends_with(".md") {
+                continue;
+            }
+            let Some(download_url) = entry.download_url else {
+                continue;
+            };
+            if fetched == MAX_REPO_FILES {
+                result.skipped.push(SkippedFile {
+                    path: entry.path,
+                    reason: format!("More than {} files; stopped here", MAX_REPO_FILES),
+                });
+                return Ok(result);
+            }
+            fetched += 1;
+
+            let source_url = entry.html_url.unwrap_or_else(|| download_url.clone());
+            let imported = match fetch_text(&client, &download_url).await {
+                Ok(content) => save(&content, &source_url, skills_dir),
+                Err(e) => Err(e),
+            };
+            match imported {
+                Ok(skill) => result.imported.push(skill),
+                Err(e) => result.skipped.push(SkippedFile {
+                    path: entry.path,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Validate fetched content, stamp its provenance and write it to a free
+/// file name in `skills_dir`.
+fn save(content: &str, source_url: &str, skills_dir: &str) -> Result<ParsedSkill, KataraError> {
+    let skill = parse_skill(content, source_url)?;
+    let content = with_source(content, source_url)?;
+    let path = manager::available_path(Path::new(skills_dir), &skill.metadata.name, None)?;
+    let path = path.display().to_string();
+    manager::write_skill(&path, &content)?;
+    println!(
+        "[katara] Imported skill \"{}\" from {}",
+        skill.metadata.name, source_url
+    );
+    manager::read_skill(&path)
+}
+
+/// Set the frontmatter `source` to the import URL and time.
+fn with_source(content: &str, url: &str) -> Result<String, KataraError> {
+    let trimmed = content.trim_start();
+    let after_first = trimmed.strip_prefix("---").unwrap_or(trimmed);
+    let end_idx = after_first
+        .find("\n---")
+        .ok_or_else(|| KataraError::Skill("Unclosed frontmatter".into()))?;
+
+    let mut frontmatter: serde_yaml::Mapping = serde_yaml::from_str(&after_first[..end_idx])
+        .map_err(|e| KataraError::Skill(e.to_string()))?;
+    let imported_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut source = serde_yaml::Mapping::new();
+    source.insert("url".into(), url.into());
+    source.insert("imported_at".into(), imported_at.into());
+    frontmatter.insert("source".into(), source.into());
+
+    let yaml =
+        serde_yaml::to_string(&frontmatter).map_err(|e| KataraError::Skill(e.to_string()))?;
+    Ok(format!("---\n{}---{}", yaml, &after_first[end_idx + 4..]))
+}
+
+/// Turn a github.com `blob` link into its raw file URL; other URLs are unchanged.
+fn raw_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("https://github.com/") {
+        let parts: Vec<&str> = rest.splitn(4, '/').collect();
+        if let [owner, repo, "blob", path] = parts.as_slice() {
+            return format!(
+                "https://raw.githubusercontent.com/{}/{}/{}",
+                owner, repo, path
+            );
+        }
+    }
+    url.to_string()
+}
+
+/// `owner/name` from either that form or a github.com URL.
+fn repo_slug(repo: &str) -> Result<String, KataraError> {
+    let repo = repo
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("github.com/")
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    let mut parts = repo.split('/');
+    match (parts.next(), parts.next()) {
+        (Some(owner), Some(name)) if !owner.is_empty() && !name.is_empty() => {
+            Ok(format!("{}/{}", owner, name))
+        }
+        _ => Err(KataraError::Skill(format!(
+            "Not a GitHub repository: {}",
+            repo
+        ))),
+    }
+}
+
+fn client() -> Result<reqwest::Client, KataraError> {
+    reqwest::Client::builder()
+        // GitHub's API rejects requests without a User-Agent
+        .user_agent(concat!("katara/", env!("CARGO_PKG_VERSION")))
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| KataraError::Skill(e.to_string()))
+}
+
+async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<String, KataraError> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| KataraError::Skill(format!("Failed to fetch {}: {}", url, e)))?;
+    if !response.status().is_success() {
+        return Err(KataraError::Skill(format!(
+            "Failed to fetch {}: HTTP {}",
+            url,
+            response.status()
+        )));
+    }
+    response
+        .text()
+        .await
+        .map_err(|e| KataraError::Skill(format!("Failed to read {}: {}", url, e)))
+}
@@ -0,0 +1,133 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::skills::parser::{SkillInput, SkillMetadata};
+
+/// A problem with one input value.
+#[derive(Debug, Clone, Serialize)]
+pub struct InputError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Check input values against a skill's declared inputs and coerce them to
+/// the declared types.
+///
+/// Missing values fall back to the input's `default`; required inputs
+/// without a value are errors. Strings from form fields are converted for
+/// `number`, `boolean` and `list` inputs, and `select` values must be one of
+/// the options. Values for undeclared names pass through unchanged.
+pub fn validate_inputs(
+    meta: &SkillMetadata,
+    mut values: HashMap<String, Value>,
+) -> Result<HashMap<String, Value>, Vec<InputError>> {
+    let mut errors = Vec::new();
+
+    for input in &meta.inputs {
+        let value = values
+            .remove(&input.name)
+            .filter(|v| !is_blank(v))
+            .or_else(|| input.default.clone());
+        let Some(value) = value else {
+            if input.required {
+                errors.push(error(input, "is required"));
+            }
+            continue;
+        };
+        match coerce(input, value) {
+            Ok(value) => {
+                values.insert(input.name.clone(), value);
+            }
+            Err(message) => errors.push(error(input, &message)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+fn coerce(input: &SkillInput, value: Value) -> Result<Value, String> {
+    match input.input_type.as_str() {
+        "number" => match value {
+            Value::Number(_) => Ok(value),
+            Value::String(s) => s
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .ok_or_else(|| format!("must be a number, got \"{}\"", s)),
+            _ => Err("must be a number".into()),
+        },
+        "boolean" => match value {
+            Value::Bool(_) => Ok(value),
+            Value::String(s) => match s.trim().to_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Ok(Value::Bool(true)),
+                "false" | "no" | "off" | "0" => Ok(Value::Bool(false)),
+                _ => Err(format!("must be true or false, got \"{}\"", s)),
+            },
+            _ => Err("must be true or false".into()),
+        },
+        "select" => {
+            let Value::String(s) = &value else {
+                return Err("must be one of the listed options".into());
+            };
+            let options = input.options.as_deref().unwrap_or_default();
+            if options.is_empty() || options.contains(s) {
+                Ok(value)
+            } else {
+                Err(format!("must be one of: {}", options.join(", ")))
+            }
+        }
+        "list" => match value {
+            Value::Array(items) if items.iter().all(Value::is_string) => Ok(Value::Array(items)),
+            Value::Array(_) => Err("must be a list of text items".into()),
+            // One item per line (or comma-separated on a single line)
+            Value::String(s) => {
+                let separator = if s.contains('\n') { '\n' } else { ',' };
+                Ok(Value::Array(
+                    s.split(separator)
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(|item| Value::String(item.to_string()))
+                        .collect(),
+                ))
+            }
+            _ => Err("must be a list".into()),
+        },
+        // text, textarea and unknown types take text
+        _ => match value {
+            Value::String(_) => Ok(value),
+            Value::Number(n) => Ok(Value::String(n.to_string())),
+            Value::Bool(b) => Ok(Value::String(b.to_string())),
+            _ => Err("must be text".into()),
+        },
+    }
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+fn error(input: &SkillInput, message: &str) -> InputError {
+    InputError {
+        field: input.name.clone(),
+        message: format!("{} {}", display_name(input), message),
+    }
+}
+
+fn display_name(input: &SkillInput) -> &str {
+    if input.label.is_empty() {
+        &input.name
+    } else {
+        &input.label
+    }
+}
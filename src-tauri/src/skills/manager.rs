@@ -1,48 +1,425 @@
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
 use crate::error::KataraError;
-use crate::skills::parser::{parse_skill, ParsedSkill};
+use crate::skills::parser::{parse_skill, ParsedSkill, SkillLevel};
+use crate::skills::template;
+use crate::skills::versions;
+
+/// Entry file of a directory skill; the directory's other files are its resources.
+pub const SKILL_FILE: &str = "SKILL.md";
 
 /// Discover all skill files in a directory (recursive glob for *.md).
+///
+/// A folder containing a `SKILL.md` is one skill: its other markdown files
+/// are resources, not skills of their own.
 pub fn list_skills(skills_dir: &str) -> Result<Vec<ParsedSkill>, KataraError> {
     let pattern = format!("{}/**/*.md", skills_dir);
     let mut skills = Vec::new();
 
-    for entry in glob::glob(&pattern).map_err(|e| KataraError::Skill(e.to_string()))? {
-        if let Ok(path) = entry {
-            let content = std::fs::read_to_string(&path).map_err(KataraError::Io)?;
-            // Only include files that have valid frontmatter
-            match parse_skill(&content, &path.display().to_string()) {
-                Ok(skill) => skills.push(skill),
-                Err(_) => continue, // Skip non-skill markdown files
+    let paths: Vec<PathBuf> = glob::glob(&pattern)
+        .map_err(|e| KataraError::Skill(e.to_string()))?
+        .filter_map(Result::ok)
+        .collect();
+    let skill_dirs: Vec<&Path> = paths.iter().filter_map(|p| skill_dir_of(p)).collect();
+
+    for path in &paths {
+        let in_skill_dir = path
+            .ancestors()
+            .skip(1)
+            .any(|dir| skill_dirs.contains(&dir));
+        if in_skill_dir && skill_dir_of(path).is_none() {
+            continue;
+        }
+        let content = std::fs::read_to_string(path).map_err(KataraError::Io)?;
+        // Only include files that have valid frontmatter
+        match parse_skill(&content, &path.display().to_string()) {
+            Ok(mut skill) => {
+                skill.resources = resources_of(path);
+                skills.push(skill);
             }
+            Err(_) => continue, // Skip non-skill markdown files
         }
     }
 
     Ok(skills)
 }
 
+/// Project-level skills directory for a working directory.
+pub fn project_skills_dir(working_dir: &str) -> PathBuf {
+    PathBuf::from(working_dir).join(".claude").join("skills")
+}
+
+/// List user skills merged with the project skills of `working_dir`.
+///
+/// Project skills shadow user skills with the same name: the user skill is
+/// left out and the project skill's `shadows` points at it.
+pub fn list_layered_skills(
+    user_dir: &str,
+    working_dir: Option<&str>,
+) -> Result<Vec<ParsedSkill>, KataraError> {
+    let mut skills = list_skills(user_dir)?;
+    let Some(working_dir) = working_dir else {
+        return Ok(skills);
+    };
+
+    let project_dir = project_skills_dir(working_dir);
+    // Working in the home directory: the project dir is the user dir
+    if same_path(&project_dir, Path::new(user_dir)) {
+        return Ok(skills);
+    }
+
+    let mut project = list_skills(&project_dir.display().to_string())?;
+    for skill in &mut project {
+        skill.level = SkillLevel::Project;
+        if let Some(shadowed) = skills
+            .iter()
+            .find(|s| s.metadata.name == skill.metadata.name)
+        {
+            skill.shadows = Some(shadowed.file_path.clone());
+        }
+    }
+    skills.retain(|s| !project.iter().any(|p| p.metadata.name == s.metadata.name));
+    skills.extend(project);
+    Ok(skills)
+}
+
 /// Read and parse a single skill file.
 pub fn read_skill(path: &str) -> Result<ParsedSkill, KataraError> {
     let content = std::fs::read_to_string(path).map_err(KataraError::Io)?;
-    parse_skill(&content, path)
+    let mut skill = parse_skill(&content, path)?;
+    skill.level = level_of(Path::new(path));
+    skill.resources = resources_of(Path::new(path));
+    Ok(skill)
 }
 
 /// Write skill content to a file (creates parent dirs if needed).
+///
+/// Every write is kept as a version that can be restored later.
 pub fn write_skill(path: &str, content: &str) -> Result<(), KataraError> {
     // Validate the content parses correctly before writing
-    let _ = parse_skill(content, path)?;
+    let skill = parse_skill(content, path)?;
+    template::check_template(&skill.prompt_template)?;
 
     let path_buf = PathBuf::from(path);
     if let Some(parent) = path_buf.parent() {
         std::fs::create_dir_all(parent).map_err(KataraError::Io)?;
     }
+    let previous = std::fs::read_to_string(path).ok();
     std::fs::write(path, content).map_err(KataraError::Io)?;
+    versions::record(path, previous.as_deref(), content);
     Ok(())
 }
 
-/// Delete a skill file.
-pub fn delete_skill(path: &str) -> Result<(), KataraError> {
-    std::fs::remove_file(path).map_err(KataraError::Io)?;
+/// A write rejected because the file changed since the caller read it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillConflict {
+    pub path: String,
+    /// Current file content, or `None` if the file was deleted.
+    pub disk_content: Option<String>,
+    pub disk_hash: Option<String>,
+    /// The content the caller tried to write.
+    pub content: String,
+}
+
+/// Write a skill only if the file still has the content the caller last
+/// read (`expected_hash`, from `ParsedSkill::content_hash`).
+///
+/// If an external editor or sync tool changed or deleted the file in the
+/// meantime, nothing is written and a `SkillConflict` carries both contents.
+/// Without an expected hash this is a plain `write_skill`.
+pub fn write_skill_checked(
+    path: &str,
+    content: &str,
+    expected_hash: Option<&str>,
+) -> Result<(), KataraError> {
+    if let Some(expected) = expected_hash {
+        let disk_content = std::fs::read_to_string(path).ok();
+        let disk_hash = disk_content.as_deref().map(versions::content_hash);
+        if disk_hash.as_deref() != Some(expected) {
+            return Err(KataraError::SkillConflict(Box::new(SkillConflict {
+                path: path.to_string(),
+                disk_content,
+                disk_hash,
+                content: content.to_string(),
+            })));
+        }
+    }
+    write_skill(path, content)
+}
+
+/// Restore a saved version of a skill. The restore is itself a new version,
+/// so it can be undone.
+pub fn restore_skill_version(path: &str, version_id: &str) -> Result<ParsedSkill, KataraError> {
+    let content = versions::read(path, version_id)?;
+    write_skill(path, &content)?;
+    read_skill(path)
+}
+
+/// Delete a skill file. Directory skills are deleted with their resources.
+pub fn delete_skill(path: &str, skills_dir: &str) -> Result<(), KataraError> {
+    let path = Path::new(path);
+    if skill_dir_of(path).is_some() {
+        let dir = owned_skill_dir(path, Path::new(skills_dir))?;
+        std::fs::remove_dir_all(dir).map_err(KataraError::Io)?;
+    } else if path.extension().is_some_and(|ext| ext == "md") {
+        std::fs::remove_file(path).map_err(KataraError::Io)?;
+    } else {
+        return Err(KataraError::Skill(format!(
+            "{} is not a skill file",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Copy a skill under a new name, next to the original.
+///
+/// Only the frontmatter `name` changes; tags, inputs and the prompt are kept
+/// as written. The copy gets a filename derived from the new name that
+/// doesn't clash with existing files.
+pub fn duplicate_skill(
+    path: &str,
+    new_name: &str,
+    skills_dir: &str,
+) -> Result<ParsedSkill, KataraError> {
+    let content = std::fs::read_to_string(path).map_err(KataraError::Io)?;
+    let renamed = set_name(&content, new_name)?;
+
+    // Directory skills are copied whole, resources included
+    if skill_dir_of(Path::new(path)).is_some() {
+        let skill_dir = owned_skill_dir(Path::new(path), Path::new(skills_dir))?;
+        let parent = skill_dir.parent().unwrap_or(Path::new("."));
+        let target_dir = available_dir(parent, new_name, None)?;
+        copy_dir(&skill_dir, &target_dir)?;
+        let target = target_dir.join(SKILL_FILE).display().to_string();
+        write_skill(&target, &renamed)?;
+        return read_skill(&target);
+    }
+
+    let dir = Path::new(path).parent().unwrap_or(Path::new("."));
+    let target = available_path(dir, new_name, None)?;
+    let target = target.display().to_string();
+    write_skill(&target, &renamed)?;
+    read_skill(&target)
+}
+
+/// Rename a skill, moving its file to match the new name.
+pub fn rename_skill(
+    path: &str,
+    new_name: &str,
+    skills_dir: &str,
+) -> Result<ParsedSkill, KataraError> {
+    let content = std::fs::read_to_string(path).map_err(KataraError::Io)?;
+    let renamed = set_name(&content, new_name)?;
+    let source = Path::new(path);
+
+    // Directory skills are renamed by renaming the directory
+    if skill_dir_of(source).is_some() {
+        let skill_dir = owned_skill_dir(source, Path::new(skills_dir))?;
+        let parent = skill_dir.parent().unwrap_or(Path::new("."));
+        let target_dir = available_dir(parent, new_name, Some(&skill_dir))?;
+        write_skill(path, &renamed)?;
+        if !same_path(&target_dir, &skill_dir) {
+            std::fs::rename(&skill_dir, &target_dir).map_err(KataraError::Io)?;
+        }
+        return read_skill(&target_dir.join(SKILL_FILE).display().to_string());
+    }
+
+    let dir = source.parent().unwrap_or(Path::new("."));
+    let target = available_path(dir, new_name, Some(source))?;
+    let target = target.display().to_string();
+    write_skill(&target, &renamed)?;
+    if !same_path(Path::new(&target), source) {
+        std::fs::remove_file(source).map_err(KataraError::Io)?;
+    }
+    read_skill(&target)
+}
+
+/// Replace the frontmatter `name` line, leaving the rest of the file untouched.
+pub(crate) fn set_name(content: &str, new_name: &str) -> Result<String, KataraError> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err(KataraError::Skill("Skill name cannot be empty".into()));
+    }
+    // Let YAML decide whether the name needs quoting
+    let value = serde_yaml::to_string(new_name).map_err(|e| KataraError::Skill(e.to_string()))?;
+    let name_line = format!("name: {}", value.trim_end());
+
+    let mut in_frontmatter = false;
+    let mut replaced = false;
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        if line.starts_with("---") {
+            if in_frontmatter {
+                in_frontmatter = false;
+            } else if !replaced {
+                in_frontmatter = true;
+            }
+            lines.push(line.to_string());
+        } else if in_frontmatter && !replaced && line.starts_with("name:") {
+            lines.push(name_line.clone());
+            replaced = true;
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    if !replaced {
+        return Err(KataraError::Skill(
+            "Skill has no name in its frontmatter".into(),
+        ));
+    }
+
+    let mut renamed = lines.join("\n");
+    if content.ends_with('\n') {
+        renamed.push('\n');
+    }
+    Ok(renamed)
+}
+
+/// A `.md` path in `dir`, named after the skill `name`, that doesn't exist
+/// yet. `current` is allowed to be returned (renaming a file to its own name).
+pub(crate) fn available_path(
+    dir: &Path,
+    name: &str,
+    current: Option<&Path>,
+) -> Result<PathBuf, KataraError> {
+    free_path(dir, name, ".md", current)
+}
+
+/// Like `available_path`, for the folder of a directory skill.
+pub(crate) fn available_dir(
+    dir: &Path,
+    name: &str,
+    current: Option<&Path>,
+) -> Result<PathBuf, KataraError> {
+    free_path(dir, name, "", current)
+}
+
+fn free_path(
+    dir: &Path,
+    name: &str,
+    extension: &str,
+    current: Option<&Path>,
+) -> Result<PathBuf, KataraError> {
+    let slug = slugify(name);
+    if slug.is_empty() {
+        return Err(KataraError::Skill(format!(
+            "Can't derive a file name from \"{}\"",
+            name
+        )));
+    }
+
+    let mut candidate = dir.join(format!("{}{}", slug, extension));
+    let mut n = 2;
+    while candidate.exists() && !current.is_some_and(|c| same_path(&candidate, c)) {
+        candidate = dir.join(format!("{}-{}{}", slug, n, extension));
+        n += 1;
+    }
+    Ok(candidate)
+}
+
+/// The folder of a directory skill, if `path` is its `SKILL.md`.
+pub(crate) fn skill_dir_of(path: &Path) -> Option<&Path> {
+    if path.file_name()? == SKILL_FILE {
+        path.parent()
+    } else {
+        None
+    }
+}
+
+/// The folder of the directory skill at `path`, for operations on the
+/// whole folder. `path` must resolve to `<skills dir>/<name>/SKILL.md`,
+/// where the skills dir is `skills_dir` or a `.claude/skills` (the user's
+/// or a project's), so a `SKILL.md` anywhere else can't take its parent
+/// folder with it.
+fn owned_skill_dir(path: &Path, skills_dir: &Path) -> Result<PathBuf, KataraError> {
+    let path = path.canonicalize().map_err(KataraError::Io)?;
+    let not_a_skill = || {
+        KataraError::Skill(format!(
+            "{} is not a skill folder in a skills directory",
+            path.display()
+        ))
+    };
+    let dir = skill_dir_of(&path).ok_or_else(not_a_skill)?;
+    let root = dir.parent().ok_or_else(not_a_skill)?;
+    let in_skills_dir = skills_dir.canonicalize().is_ok_and(|d| d == root)
+        || root.ends_with(Path::new(".claude").join("skills"));
+    if !in_skills_dir {
+        return Err(not_a_skill());
+    }
+    Ok(dir.to_path_buf())
+}
+
+/// Files bundled with a directory skill, relative to its folder.
+fn resources_of(path: &Path) -> Vec<String> {
+    let Some(dir) = skill_dir_of(path) else {
+        return Vec::new();
+    };
+    let pattern = format!("{}/**/*", glob::Pattern::escape(&dir.display().to_string()));
+    let Ok(entries) = glob::glob(&pattern) else {
+        return Vec::new();
+    };
+    let mut resources: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|p| p.is_file() && p.as_path() != path)
+        .filter_map(|p| {
+            p.strip_prefix(dir)
+                .ok()
+                .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+    resources.sort();
+    resources
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), KataraError> {
+    std::fs::create_dir_all(to).map_err(KataraError::Io)?;
+    for entry in std::fs::read_dir(from).map_err(KataraError::Io)? {
+        let entry = entry.map_err(KataraError::Io)?;
+        let target = to.join(entry.file_name());
+        if entry.file_type().map_err(KataraError::Io)?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target).map_err(KataraError::Io)?;
+        }
+    }
     Ok(())
 }
+
+/// Lowercase, dash-separated file name stem for a skill name.
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Skills under some `<project>/.claude/skills` are project skills; anything
+/// else (including `~/.claude/skills`) is a user skill.
+fn level_of(path: &Path) -> SkillLevel {
+    let user_dir = dirs::home_dir()
+        .unwrap_or_default()
+        .join(".claude")
+        .join("skills");
+    let in_project = path
+        .ancestors()
+        .any(|dir| dir.ends_with(".claude/skills") && !same_path(dir, &user_dir));
+    if in_project {
+        SkillLevel::Project
+    } else {
+        SkillLevel::User
+    }
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
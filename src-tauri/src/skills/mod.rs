@@ -1,2 +1,18 @@
+pub mod builtin;
+pub mod bundle;
+pub mod import;
+pub mod inputs;
 pub mod manager;
+pub mod outputs;
 pub mod parser;
+pub mod preview;
+pub mod runs;
+pub mod sandbox;
+pub mod scaffold;
+pub mod search;
+pub mod slash;
+pub mod template;
+pub mod tools;
+pub mod validate;
+pub mod versions;
+pub mod watcher;
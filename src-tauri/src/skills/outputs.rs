@@ -0,0 +1,88 @@
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::skills::parser::SkillOutput;
+
+/// Extract a skill's declared outputs from the final response text.
+///
+/// For each output, in order of preference:
+/// 1. a named marker: `<output name="summary">...</output>`
+/// 2. a fenced block whose info string is the output name: ```` ```summary ````
+/// 3. a key of the same name in a JSON object (a ```` ```json ```` block or
+///    the whole response)
+///
+/// If a skill declares a single output and none of these match, the whole
+/// response is used. Values are converted according to the output `type`
+/// (`json`, `number`, `boolean`, `list`; anything else stays text).
+pub fn extract_outputs(outputs: &[SkillOutput], text: &str) -> HashMap<String, Value> {
+    let json = find_json_object(text);
+    let mut values = HashMap::new();
+
+    for output in outputs {
+        let raw = find_marker(text, &output.name)
+            .or_else(|| find_fenced(text, &output.name))
+            .map(|s| Value::String(s.trim().to_string()))
+            .or_else(|| json.as_ref().and_then(|obj| obj.get(&output.name).cloned()))
+            .or_else(|| (outputs.len() == 1).then(|| Value::String(text.trim().to_string())));
+
+        if let Some(raw) = raw {
+            values.insert(output.name.clone(), convert(raw, &output.output_type));
+        }
+    }
+    values
+}
+
+fn find_marker(text: &str, name: &str) -> Option<String> {
+    let pattern = format!(
+        r#"(?s)<output\s+name\s*=\s*["']{}["']\s*>(.*?)</output>"#,
+        regex::escape(name)
+    );
+    let re = Regex::new(&pattern).ok()?;
+    re.captures(text).map(|c| c[1].to_string())
+}
+
+fn find_fenced(text: &str, info: &str) -> Option<String> {
+    let pattern = format!(r"(?ms)^```[ \t]*{}[ \t]*\n(.*?)^```", regex::escape(info));
+    let re = Regex::new(&pattern).ok()?;
+    re.captures(text).map(|c| c[1].to_string())
+}
+
+/// The first JSON object in the response: a ```json block, or the whole text.
+fn find_json_object(text: &str) -> Option<serde_json::Map<String, Value>> {
+    let candidate = find_fenced(text, "json").unwrap_or_else(|| text.to_string());
+    match serde_json::from_str(candidate.trim()) {
+        Ok(Value::Object(obj)) => Some(obj),
+        _ => None,
+    }
+}
+
+/// Convert an extracted value to the declared output type, keeping it as-is
+/// when it doesn't fit.
+fn convert(value: Value, output_type: &str) -> Value {
+    let Value::String(s) = value else {
+        return value;
+    };
+    match output_type {
+        "json" => serde_json::from_str(&s).unwrap_or(Value::String(s)),
+        "number" => s
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .unwrap_or(Value::String(s)),
+        "boolean" => match s.to_lowercase().as_str() {
+            "true" | "yes" => Value::Bool(true),
+            "false" | "no" => Value::Bool(false),
+            _ => Value::String(s),
+        },
+        "list" => Value::Array(
+            s.lines()
+                .map(|l| l.trim().trim_start_matches(['-', '*', '•']).trim())
+                .filter(|l| !l.is_empty())
+                .map(|l| Value::String(l.to_string()))
+                .collect(),
+        ),
+        _ => Value::String(s),
+    }
+}
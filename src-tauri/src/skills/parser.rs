@@ -1,12 +1,38 @@
 use serde::{Deserialize, Serialize};
 
 use crate::error::KataraError;
+use crate::skills::versions;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedSkill {
     pub file_path: String,
     pub metadata: SkillMetadata,
     pub prompt_template: String,
+    /// Where the skill was found.
+    #[serde(default)]
+    pub level: SkillLevel,
+    /// Path of the user skill this project skill shadows, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadows: Option<String>,
+    /// For directory skills, the other files bundled with `SKILL.md`
+    /// (relative paths), available to `{% include %}`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<String>,
+    /// Hash of the file content this was parsed from; pass it back to
+    /// `write_skill` to detect changes made on disk in the meantime.
+    #[serde(default)]
+    pub content_hash: String,
+}
+
+/// Skill source, layered like CLAUDE.md: project skills (in
+/// `<working_dir>/.claude/skills`) take precedence over user skills with the
+/// same name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillLevel {
+    #[default]
+    User,
+    Project,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +48,30 @@ pub struct SkillMetadata {
     pub outputs: Vec<SkillOutput>,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Model to run this skill with, overriding the session default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Permission mode to run this skill with (e.g. "plan" for read-only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<String>,
+    /// Tools the skill may use; empty means whatever the session allows.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+    /// Where the skill was imported from, if it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SkillSource>,
+    /// Always run in a fresh session, in plan mode, on a temporary copy of
+    /// the working directory. For untrusted (e.g. community-shared) skills.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sandbox: bool,
+}
+
+/// Provenance of an imported skill.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillSource {
+    pub url: String,
+    /// Unix timestamp (seconds).
+    pub imported_at: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +97,14 @@ pub struct SkillOutput {
     pub output_type: String,
 }
 
+/// Input types the skill form knows how to render.
+pub const INPUT_TYPES: &[&str] = &["text", "textarea", "number", "boolean", "select", "list"];
+
+/// Output types understood by output extraction.
+pub const OUTPUT_TYPES: &[&str] = &[
+    "text", "markdown", "code", "json", "number", "boolean", "list",
+];
+
 fn default_true() -> bool {
     true
 }
@@ -98,5 +156,9 @@ pub fn parse_skill(content: &str, file_path: &str) -> Result<ParsedSkill, Katara
         file_path: file_path.to_string(),
         metadata,
         prompt_template,
+        level: SkillLevel::default(),
+        shadows: None,
+        resources: Vec::new(),
+        content_hash: versions::content_hash(content),
     })
 }
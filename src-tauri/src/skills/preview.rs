@@ -0,0 +1,47 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::error::KataraError;
+use crate::skills::parser::ParsedSkill;
+use crate::skills::sandbox::SANDBOX_PERMISSION_MODE;
+use crate::skills::template;
+
+/// What running a skill would send to Claude.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillPreview {
+    /// Fully rendered prompt.
+    pub prompt: String,
+    /// `None` means the Claude CLI default.
+    pub model: Option<String>,
+    /// `None` means the Claude CLI default.
+    pub permission_mode: Option<String>,
+    /// Empty means whatever the session allows.
+    pub allowed_tools: Vec<String>,
+    /// Runs on a temporary copy of the working directory.
+    pub sandbox: bool,
+}
+
+/// Render a skill with `inputs` and resolve its effective settings. The
+/// skill's own `model` and `permission_mode` take precedence over the
+/// caller's; sandboxed skills always use plan mode.
+pub fn preview(
+    skill: &ParsedSkill,
+    inputs: &HashMap<String, Value>,
+    model: Option<String>,
+    permission_mode: Option<String>,
+) -> Result<SkillPreview, KataraError> {
+    let meta = &skill.metadata;
+    let permission_mode = if meta.sandbox {
+        Some(SANDBOX_PERMISSION_MODE.to_string())
+    } else {
+        meta.permission_mode.clone().or(permission_mode)
+    };
+    Ok(SkillPreview {
+        prompt: template::render_prompt(skill, inputs)?,
+        model: meta.model.clone().or(model),
+        permission_mode,
+        allowed_tools: meta.allowed_tools.clone(),
+        sandbox: meta.sandbox,
+    })
+}
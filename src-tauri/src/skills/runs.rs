@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::error::KataraError;
+use crate::process::session::SessionStatus;
+use crate::state::AppState;
+use crate::websocket::protocol::{
+    AssistantMessage, ClaudeMessage, ContentBlock, ResultMessage, WsEvent,
+};
+
+/// Number of recent runs kept unless the `history.max_skill_runs` setting
+/// says otherwise.
+pub const DEFAULT_MAX_RUNS: usize = 200;
+
+/// How often to check whether the session died while waiting for a result.
+const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest a run may take before it's given up on.
+const RUN_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SkillRunStatus {
+    Running,
+    Succeeded,
+    Failed(String),
+}
+
+/// One execution of a skill.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillRun {
+    pub id: String,
+    pub skill_path: String,
+    pub skill_name: String,
+    pub session_id: String,
+    /// Directory the skill ran in, for re-running with the same inputs.
+    #[serde(default)]
+    pub working_dir: String,
+    /// Temporary copy of `working_dir` a sandboxed skill actually ran in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_dir: Option<String>,
+    pub inputs: HashMap<String, Value>,
+    pub status: SkillRunStatus,
+    /// Final response text.
+    pub result: Option<String>,
+    /// Values extracted for the skill's declared outputs.
+    pub outputs: HashMap<String, Value>,
+    /// Cost reported by the CLI for the run.
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// Unix timestamps (seconds).
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+/// The final result of a session turn.
+#[derive(Debug, Clone)]
+pub struct TurnResult {
+    pub text: String,
+    pub cost_usd: Option<f64>,
+    pub duration_ms: Option<u64>,
+}
+
+impl SkillRun {
+    pub fn new(
+        skill_path: String,
+        skill_name: String,
+        session_id: String,
+        working_dir: String,
+        inputs: HashMap<String, Value>,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            skill_path,
+            skill_name,
+            session_id,
+            working_dir,
+            sandbox_dir: None,
+            inputs,
+            status: SkillRunStatus::Running,
+            result: None,
+            outputs: HashMap::new(),
+            cost_usd: None,
+            duration_ms: None,
+            started_at: now(),
+            finished_at: None,
+        }
+    }
+
+    /// Mark the run finished with the given status.
+    pub fn finish(&mut self, status: SkillRunStatus) {
+        let finished_at = now();
+        self.status = status;
+        self.finished_at = Some(finished_at);
+        // Fall back to wall-clock time when the CLI didn't report a duration
+        self.duration_ms
+            .get_or_insert(finished_at.saturating_sub(self.started_at) * 1000);
+    }
+}
+
+/// Insert or update a run in the history, dropping the oldest beyond the
+/// configured limit, and save the history to disk.
+pub async fn record(state: &AppState, run: &SkillRun) {
+    let mut runs = state.skill_runs.write().await;
+    match runs.iter_mut().find(|r| r.id == run.id) {
+        Some(existing) => *existing = run.clone(),
+        None => runs.push(run.clone()),
+    }
+    let max_runs = state.settings().history.max_skill_runs;
+    let excess = runs.len().saturating_sub(max_runs);
+    runs.drain(..excess);
+    save(&runs);
+}
+
+/// Runs of one skill (or all runs), newest first.
+pub async fn list(state: &AppState, skill_path: Option<&str>) -> Vec<SkillRun> {
+    let runs = state.skill_runs.read().await;
+    runs.iter()
+        .rev()
+        .filter(|r| skill_path.is_none_or(|p| r.skill_path == p))
+        .cloned()
+        .collect()
+}
+
+/// Load the run history saved by previous app runs.
+///
+/// Runs still marked running were cut off when the app exited. Errors are
+/// logged and yield an empty history.
+pub fn load() -> Vec<SkillRun> {
+    let Ok(path) = store_path() else {
+        return Vec::new();
+    };
+    if !path.exists() {
+        return Vec::new();
+    }
+    let loaded = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            serde_json::from_str::<Vec<SkillRun>>(&content).map_err(|e| e.to_string())
+        });
+    match loaded {
+        Ok(mut runs) => {
+            for run in runs
+                .iter_mut()
+                .filter(|r| r.status == SkillRunStatus::Running)
+            {
+                run.status = SkillRunStatus::Failed("Interrupted".into());
+            }
+            runs
+        }
+        Err(e) => {
+            eprintln!("[katara] Failed to load skill runs: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn save(runs: &[SkillRun]) {
+    if let Err(e) = write(runs) {
+        eprintln!("[katara] Failed to save skill runs: {}", e);
+    }
+}
+
+fn write(runs: &[SkillRun]) -> Result<(), String> {
+    let path = store_path().map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(runs).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+fn store_path() -> Result<PathBuf, KataraError> {
+    Ok(crate::config::katara_dir()?.join("skill_runs.json"))
+}
+
+/// Wait for the session's current turn to finish and return its final text
+/// along with the cost and duration the CLI reported.
+///
+/// `events` must be subscribed before the prompt is sent so the result can't
+/// be missed; if it's dropped anyway, it's read from the session's history.
+/// Fails if the turn ends in an error, the session goes away or the run
+/// takes longer than `RUN_TIMEOUT`.
+pub async fn wait_for_result(
+    state: &AppState,
+    mut events: broadcast::Receiver<WsEvent>,
+    session_id: &str,
+) -> Result<TurnResult, String> {
+    let mut last_text = String::new();
+    let mut check = tokio::time::interval(SESSION_CHECK_INTERVAL);
+    let deadline = tokio::time::sleep(RUN_TIMEOUT);
+    tokio::pin!(deadline);
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if event.session_id == session_id => match event.message {
+                    ClaudeMessage::Assistant(msg) => {
+                        let text = text_of(&msg);
+                        if !text.is_empty() {
+                            last_text = text;
+                        }
+                    }
+                    ClaudeMessage::Result(result) => return turn_result(result, last_text),
+                    _ => {}
+                },
+                Ok(_) => {}
+                // The result may have been among the dropped events
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    if let Some(outcome) = finished_turn(state, session_id).await {
+                        return outcome;
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    return Err("Event bus closed".into());
+                }
+            },
+            _ = check.tick() => {
+                let sessions = state.sessions.read().await;
+                match sessions.get(session_id).map(|s| &s.status) {
+                    None | Some(SessionStatus::Terminated) => {
+                        return Err("Session ended before the skill finished".into());
+                    }
+                    Some(SessionStatus::Error(e)) => return Err(e.clone()),
+                    Some(SessionStatus::Idle) => {
+                        drop(sessions);
+                        if let Some(outcome) = finished_turn(state, session_id).await {
+                            return outcome;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ = &mut deadline => {
+                return Err(format!(
+                    "Run took longer than {} minutes",
+                    RUN_TIMEOUT.as_secs() / 60
+                ));
+            }
+        }
+    }
+}
+
+/// The text blocks of an assistant message, joined.
+fn text_of(msg: &AssistantMessage) -> String {
+    msg.message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn turn_result(result: ResultMessage, last_text: String) -> Result<TurnResult, String> {
+    let subtype = result.subtype.unwrap_or_default();
+    if subtype.starts_with("error") {
+        return Err(format!("Run ended with {}", subtype));
+    }
+    Ok(TurnResult {
+        text: result.result.unwrap_or(last_text),
+        cost_usd: result.extra.get("total_cost_usd").and_then(Value::as_f64),
+        duration_ms: result.extra.get("duration_ms").and_then(Value::as_u64),
+    })
+}
+
+/// The outcome of the session's turn, from its history, if the turn is
+/// over. A run's session only ever has the one turn.
+async fn finished_turn(state: &AppState, session_id: &str) -> Option<Result<TurnResult, String>> {
+    let history = state.sessions.read().await.get(session_id)?.history.all();
+    let mut last_text = String::new();
+    for message in history.iter() {
+        match serde_json::from_value::<ClaudeMessage>(message.as_ref().clone()) {
+            Ok(ClaudeMessage::Assistant(msg)) => {
+                let text = text_of(&msg);
+                if !text.is_empty() {
+                    last_text = text;
+                }
+            }
+            Ok(ClaudeMessage::Result(result)) => return Some(turn_result(result, last_text)),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
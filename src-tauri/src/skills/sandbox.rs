@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::KataraError;
+
+/// Permission mode sandboxed skills always run with.
+pub const SANDBOX_PERMISSION_MODE: &str = "plan";
+
+/// Directories left out of the working copy: VCS metadata, dependencies and
+/// build output are large and not needed to read the project.
+const SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    ".venv",
+    "__pycache__",
+];
+
+/// Prefix of the temporary directories copies are made in.
+const COPY_PREFIX: &str = "katara-sandbox-";
+
+/// Largest project copied, in bytes, so a big repo can't fill the temp dir.
+const MAX_COPY_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Most files copied.
+const MAX_COPY_FILES: usize = 20_000;
+
+/// What's left of the copy's size limits.
+struct Budget {
+    bytes: u64,
+    files: usize,
+}
+
+/// Copy `working_dir` into a fresh temporary directory for a sandboxed run,
+/// so even a skill that escapes plan mode can't touch the real project.
+/// The directory is private to the user, since the copy includes files like
+/// `.env`, and projects over `MAX_COPY_BYTES` or `MAX_COPY_FILES` are
+/// refused. Blocking; the copy is deleted with `remove_copy` when the run
+/// finishes.
+pub fn working_copy(working_dir: &str) -> Result<PathBuf, KataraError> {
+    let source = Path::new(working_dir);
+    if !source.is_dir() {
+        return Err(KataraError::Skill(format!(
+            "Working directory not found: {}",
+            working_dir
+        )));
+    }
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".into());
+    let dir = std::env::temp_dir().join(format!("{}{}", COPY_PREFIX, uuid::Uuid::new_v4()));
+    create_private_dir(&dir).map_err(KataraError::Io)?;
+    let target = dir.join(name);
+
+    let mut budget = Budget {
+        bytes: MAX_COPY_BYTES,
+        files: MAX_COPY_FILES,
+    };
+    if let Err(e) = copy_tree(source, &target, &mut budget) {
+        let _ = std::fs::remove_dir_all(&dir);
+        return Err(e);
+    }
+    println!(
+        "[katara] Sandbox copy of {} at {}",
+        working_dir,
+        target.display()
+    );
+    Ok(target)
+}
+
+/// Delete a copy made by `working_copy`. Anything else is left alone.
+pub fn remove_copy(copy: &str) {
+    let Some(dir) = Path::new(copy).parent() else {
+        return;
+    };
+    let ours = dir.parent() == Some(std::env::temp_dir().as_path())
+        && dir
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with(COPY_PREFIX));
+    if !ours {
+        return;
+    }
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => println!("[katara] Removed sandbox copy {}", dir.display()),
+        Err(e) => eprintln!("[katara] Failed to remove {}: {}", dir.display(), e),
+    }
+}
+
+/// Create `dir`, readable only by the user. Fails if it already exists.
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir)
+}
+
+fn copy_tree(from: &Path, to: &Path, budget: &mut Budget) -> Result<(), KataraError> {
+    std::fs::create_dir_all(to).map_err(KataraError::Io)?;
+    for entry in std::fs::read_dir(from).map_err(KataraError::Io)? {
+        let entry = entry.map_err(KataraError::Io)?;
+        let file_type = entry.file_type().map_err(KataraError::Io)?;
+        let name = entry.file_name();
+        let target = to.join(&name);
+        if file_type.is_dir() {
+            if SKIPPED_DIRS.iter().any(|d| name == *d) {
+                continue;
+            }
+            copy_tree(&entry.path(), &target, budget)?;
+        } else if file_type.is_file() {
+            let size = entry.metadata().map_err(KataraError::Io)?.len();
+            if budget.files == 0 || size > budget.bytes {
+                return Err(KataraError::Skill(format!(
+                    "Project is too large to sandbox (limit {} files, {} MB)",
+                    MAX_COPY_FILES,
+                    MAX_COPY_BYTES / (1024 * 1024)
+                )));
+            }
+            budget.files -= 1;
+            budget.bytes -= size;
+            std::fs::copy(entry.path(), &target).map_err(KataraError::Io)?;
+        }
+        // Symlinks are skipped: they could point back into the real project
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::error::KataraError;
+use crate::skills::manager;
+use crate::skills::parser::ParsedSkill;
+
+/// A built-in starting point for a new skill.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    #[serde(skip)]
+    content: &'static str,
+}
+
+pub const TEMPLATES: &[SkillTemplate] = &[
+    SkillTemplate {
+        name: "simple",
+        description: "A single prompt with no inputs",
+        content: r#"---
+name: Untitled
+description: Describe what this skill does
+tags: []
+---
+
+Describe the task for Claude here.
+"#,
+    },
+    SkillTemplate {
+        name: "form",
+        description: "A prompt filled in from a form of inputs",
+        content: r#"---
+name: Untitled
+description: Describe what this skill does
+tags: []
+inputs:
+  - name: topic
+    label: Topic
+    type: text
+    required: true
+    placeholder: What should Claude work on?
+  - name: details
+    label: Details
+    type: textarea
+  - name: tone
+    label: Tone
+    type: select
+    options: [neutral, friendly, formal]
+    default: neutral
+---
+
+Work on {{ topic }} in a {{ tone }} tone.
+{% if details %}
+Details:
+{{ details }}
+{% endif %}
+"#,
+    },
+    SkillTemplate {
+        name: "structured",
+        description: "A prompt whose response is split into named outputs",
+        content: r#"---
+name: Untitled
+description: Describe what this skill does
+tags: []
+inputs:
+  - name: subject
+    label: Subject
+    type: text
+    required: true
+outputs:
+  - name: summary
+    label: Summary
+    type: markdown
+  - name: items
+    label: Action items
+    type: list
+---
+
+Review {{ subject }}.
+
+Reply with the two sections below, each wrapped in its tag:
+
+<output name="summary">A short summary</output>
+<output name="items">
+- One action item per line
+</output>
+"#,
+    },
+];
+
+/// Create a new skill called `name` in `dir` from a built-in template.
+pub fn create_from_template(
+    template_name: &str,
+    name: &str,
+    dir: &str,
+) -> Result<ParsedSkill, KataraError> {
+    let template = TEMPLATES
+        .iter()
+        .find(|t| t.name == template_name)
+        .ok_or_else(|| KataraError::Skill(format!("Unknown skill template: {}", template_name)))?;
+
+    let content = manager::set_name(template.content, name)?;
+    let path = manager::available_path(Path::new(dir), name, None)?;
+    let path = path.display().to_string();
+    manager::write_skill(&path, &content)?;
+    manager::read_skill(&path)
+}
@@ -0,0 +1,72 @@
+use crate::skills::parser::ParsedSkill;
+
+/// Filter skills by a free-text query and required tags, best matches first.
+///
+/// Every whitespace-separated query term must match the skill's name,
+/// description, tags or prompt (case-insensitive). Matches in the name rank
+/// highest, then tags, description and prompt. Skills must carry all of
+/// `tags`. With an empty query the result is sorted by name.
+pub fn search_skills(skills: Vec<ParsedSkill>, query: &str, tags: &[String]) -> Vec<ParsedSkill> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let tags: Vec<String> = tags.iter().map(|t| t.to_lowercase()).collect();
+
+    let mut ranked: Vec<(u32, ParsedSkill)> = skills
+        .into_iter()
+        .filter(|skill| {
+            tags.iter()
+                .all(|tag| skill.metadata.tags.iter().any(|t| t.to_lowercase() == *tag))
+        })
+        .filter_map(|skill| score(&skill, &terms).map(|s| (s, skill)))
+        .collect();
+
+    ranked.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.cmp(a_score).then_with(|| {
+            a.metadata
+                .name
+                .to_lowercase()
+                .cmp(&b.metadata.name.to_lowercase())
+        })
+    });
+    ranked.into_iter().map(|(_, skill)| skill).collect()
+}
+
+/// Relevance of a skill for the query terms, or `None` if a term doesn't match.
+fn score(skill: &ParsedSkill, terms: &[String]) -> Option<u32> {
+    let name = skill.metadata.name.to_lowercase();
+    let description = skill.metadata.description.to_lowercase();
+    let prompt = skill.prompt_template.to_lowercase();
+    let tags: Vec<String> = skill
+        .metadata
+        .tags
+        .iter()
+        .map(|t| t.to_lowercase())
+        .collect();
+
+    let mut total = 0;
+    for term in terms {
+        let mut best = 0;
+        if name == *term {
+            best = 100;
+        } else if name.starts_with(term.as_str()) {
+            best = 60;
+        } else if name.contains(term.as_str()) {
+            best = 40;
+        }
+        if tags.iter().any(|t| t == term) {
+            best = best.max(30);
+        } else if tags.iter().any(|t| t.contains(term.as_str())) {
+            best = best.max(20);
+        }
+        if description.contains(term.as_str()) {
+            best = best.max(15);
+        }
+        if prompt.contains(term.as_str()) {
+            best = best.max(5);
+        }
+        if best == 0 {
+            return None;
+        }
+        total += best;
+    }
+    Some(total)
+}
@@ -0,0 +1,236 @@
+use regex::Regex;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::path::{Path, PathBuf};
+
+use crate::error::KataraError;
+use crate::skills::manager;
+use crate::skills::parser::ParsedSkill;
+
+/// Result of exporting a skill as a Claude Code slash command.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandExport {
+    /// The written `.claude/commands/<name>.md` file.
+    pub path: String,
+    /// Template features that have no slash-command equivalent and were
+    /// copied over as-is.
+    pub warnings: Vec<String>,
+}
+
+/// Write a skill as a slash command in `<project_dir>/.claude/commands`, or
+/// in `~/.claude/commands` without a project.
+///
+/// Input placeholders become positional arguments (`$1`, `$2`, ...) in
+/// declaration order, or `$ARGUMENTS` for a single input. `description`,
+/// `model` and `allowed_tools` map to the command's frontmatter.
+pub fn export_as_command(
+    skill: &ParsedSkill,
+    project_dir: Option<&str>,
+) -> Result<CommandExport, KataraError> {
+    let meta = &skill.metadata;
+    let mut warnings = Vec::new();
+
+    let mut body = skill.prompt_template.clone();
+    let single = meta.inputs.len() == 1;
+    for (i, input) in meta.inputs.iter().enumerate() {
+        let placeholder = if single {
+            "$ARGUMENTS".to_string()
+        } else {
+            format!("${}", i + 1)
+        };
+        let re = Regex::new(&format!(
+            r"\{{\{{-?\s*{}\s*-?\}}\}}",
+            regex::escape(&input.name)
+        ))
+        .map_err(|e| KataraError::Skill(e.to_string()))?;
+        body = re
+            .replace_all(&body, regex::NoExpand(&placeholder))
+            .into_owned();
+    }
+    if body.contains("{%") || body.contains("{{") {
+        warnings.push(
+            "Template logic (conditions, loops, filters) isn't supported by slash commands \
+             and was copied as-is"
+                .into(),
+        );
+    }
+
+    let mut frontmatter = Mapping::new();
+    if !meta.description.is_empty() {
+        frontmatter.insert("description".into(), meta.description.clone().into());
+    }
+    if !meta.inputs.is_empty() {
+        let hint: Vec<String> = meta
+            .inputs
+            .iter()
+            .map(|i| format!("[{}]", i.name))
+            .collect();
+        frontmatter.insert("argument-hint".into(), hint.join(" ").into());
+    }
+    if !meta.allowed_tools.is_empty() {
+        frontmatter.insert("allowed-tools".into(), meta.allowed_tools.join(", ").into());
+    }
+    if let Some(model) = &meta.model {
+        frontmatter.insert("model".into(), model.clone().into());
+    }
+
+    let content = if frontmatter.is_empty() {
+        format!("{}\n", body.trim())
+    } else {
+        let yaml =
+            serde_yaml::to_string(&frontmatter).map_err(|e| KataraError::Skill(e.to_string()))?;
+        format!("---\n{}---\n\n{}\n", yaml, body.trim())
+    };
+
+    let dir = commands_dir(project_dir);
+    std::fs::create_dir_all(&dir).map_err(KataraError::Io)?;
+    let path = manager::available_path(&dir, &meta.name, None)?;
+    std::fs::write(&path, content).map_err(KataraError::Io)?;
+    Ok(CommandExport {
+        path: path.display().to_string(),
+        warnings,
+    })
+}
+
+/// Convert a slash command file into a skill in `skills_dir`.
+///
+/// The command's positional arguments (`$1`, `$2`, ...) become inputs named
+/// after its `argument-hint` where possible; `$ARGUMENTS` becomes a single
+/// `arguments` input. The skill is named after the command's file name.
+pub fn import_command(path: &str, skills_dir: &str) -> Result<ParsedSkill, KataraError> {
+    let content = std::fs::read_to_string(path).map_err(KataraError::Io)?;
+    let (frontmatter, body) = split_frontmatter(&content)?;
+
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "command".into());
+    let name = title_case(&stem);
+
+    let hint_names: Vec<String> = frontmatter
+        .get("argument-hint")
+        .and_then(Value::as_str)
+        .map(|hint| {
+            Regex::new(r"\[([^\]]+)\]")
+                .map(|re| re.captures_iter(hint).map(|c| input_name(&c[1])).collect())
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+
+    // Positional arguments, highest first so $1 doesn't match inside $10
+    let positional = Regex::new(r"\$(\d+)").map_err(|e| KataraError::Skill(e.to_string()))?;
+    let count = positional
+        .captures_iter(&body)
+        .filter_map(|c| c[1].parse::<usize>().ok())
+        .max()
+        .unwrap_or(0);
+
+    let mut inputs = Vec::new();
+    let mut template = body.clone();
+    for n in (1..=count).rev() {
+        let input = hint_names
+            .get(n - 1)
+            .cloned()
+            .unwrap_or_else(|| format!("arg{}", n));
+        template = template.replace(&format!("${}", n), &format!("{{{{ {} }}}}", input));
+        inputs.insert(0, input);
+    }
+    if template.contains("$ARGUMENTS") {
+        template = template.replace("$ARGUMENTS", "{{ arguments }}");
+        inputs.push("arguments".into());
+    }
+
+    let mut meta = Mapping::new();
+    meta.insert("name".into(), name.clone().into());
+    if let Some(description) = frontmatter.get("description") {
+        meta.insert("description".into(), description.clone());
+    }
+    if !inputs.is_empty() {
+        let inputs: Vec<Value> = inputs
+            .iter()
+            .map(|input| {
+                let mut m = Mapping::new();
+                m.insert("name".into(), input.clone().into());
+                m.insert("label".into(), title_case(input).into());
+                let input_type = if input == "arguments" {
+                    "textarea"
+                } else {
+                    "text"
+                };
+                m.insert("type".into(), input_type.into());
+                Value::Mapping(m)
+            })
+            .collect();
+        meta.insert("inputs".into(), Value::Sequence(inputs));
+    }
+    if let Some(model) = frontmatter.get("model") {
+        meta.insert("model".into(), model.clone());
+    }
+    let tools: Vec<Value> = match frontmatter.get("allowed-tools") {
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| t.into())
+            .collect(),
+        Some(Value::Sequence(seq)) => seq.clone(),
+        _ => Vec::new(),
+    };
+    if !tools.is_empty() {
+        meta.insert("allowed_tools".into(), Value::Sequence(tools));
+    }
+
+    let yaml = serde_yaml::to_string(&meta).map_err(|e| KataraError::Skill(e.to_string()))?;
+    let skill_content = format!("---\n{}---\n\n{}\n", yaml, template.trim());
+
+    let target = manager::available_path(Path::new(skills_dir), &name, None)?;
+    let target = target.display().to_string();
+    manager::write_skill(&target, &skill_content)?;
+    manager::read_skill(&target)
+}
+
+/// Split optional YAML frontmatter from a command file.
+fn split_frontmatter(content: &str) -> Result<(Mapping, String), KataraError> {
+    let trimmed = content.trim_start();
+    let Some(after_first) = trimmed.strip_prefix("---") else {
+        return Ok((Mapping::new(), content.to_string()));
+    };
+    let end_idx = after_first
+        .find("\n---")
+        .ok_or_else(|| KataraError::Skill("Unclosed frontmatter".into()))?;
+    let frontmatter = serde_yaml::from_str::<Option<Mapping>>(&after_first[..end_idx])
+        .map_err(|e| KataraError::Skill(e.to_string()))?
+        .unwrap_or_default();
+    let body = after_first[end_idx + 4..].trim().to_string();
+    Ok((frontmatter, body))
+}
+
+fn commands_dir(project_dir: Option<&str>) -> PathBuf {
+    match project_dir {
+        Some(dir) => PathBuf::from(dir).join(".claude").join("commands"),
+        None => dirs::home_dir()
+            .unwrap_or_default()
+            .join(".claude")
+            .join("commands"),
+    }
+}
+
+/// `pr-number` / `pr number` -> `pr_number`
+fn input_name(hint: &str) -> String {
+    let words: Vec<String> = hint
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    words.join("_")
+}
+
+/// `review-pr` -> `Review pr`
+fn title_case(stem: &str) -> String {
+    let spaced = stem.replace(['-', '_'], " ");
+    let mut chars = spaced.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => spaced,
+    }
+}
@@ -0,0 +1,95 @@
+use minijinja::{Environment, ErrorKind};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::KataraError;
+use crate::skills::manager::skill_dir_of;
+use crate::skills::parser::ParsedSkill;
+
+/// Render a skill's prompt template with the given input values.
+///
+/// Templates use Jinja syntax (via minijinja): `{{ topic }}`,
+/// `{% if verbose %}...{% endif %}`, `{% for f in files %}...{% endfor %}` and
+/// `{{ tone | default("neutral") }}`. Declared inputs missing from `values`
+/// fall back to their frontmatter `default`; anything still undefined
+/// renders as empty text.
+///
+/// Directory skills can pull in their bundled files with
+/// `{% include "snippet.md" %}` (or the shorthand `{{include "snippet.md"}}`);
+/// included files are templates too.
+pub fn render_prompt(
+    skill: &ParsedSkill,
+    values: &HashMap<String, serde_json::Value>,
+) -> Result<String, KataraError> {
+    let mut context: HashMap<&str, &serde_json::Value> = skill
+        .metadata
+        .inputs
+        .iter()
+        .filter_map(|input| input.default.as_ref().map(|d| (input.name.as_str(), d)))
+        .collect();
+    context.extend(values.iter().map(|(k, v)| (k.as_str(), v)));
+
+    let mut env = environment();
+    if let Some(dir) = skill_dir_of(Path::new(&skill.file_path)) {
+        env.set_loader(resource_loader(dir.to_path_buf()));
+    }
+    env.render_str(&expand_includes(&skill.prompt_template), context)
+        .map_err(|e| KataraError::Skill(format!("Template error: {}", e)))
+}
+
+/// Check that a prompt template compiles, without rendering it.
+pub fn check_template(template: &str) -> Result<(), KataraError> {
+    environment()
+        .template_from_str(&expand_includes(template))
+        .map(|_| ())
+        .map_err(|e| KataraError::Skill(format!("Template error: {}", e)))
+}
+
+/// Variables a template reads without defining them itself (via `set` or a
+/// `for` loop). Fails with minijinja's error, which carries the line number,
+/// if the template doesn't compile.
+pub fn template_variables(template: &str) -> Result<HashSet<String>, minijinja::Error> {
+    let env = environment();
+    let template = expand_includes(template);
+    let compiled = env.template_from_str(&template)?;
+    Ok(compiled.undeclared_variables(false))
+}
+
+fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    // Prompts are plain text; keep the author's trailing newline as written
+    env.set_keep_trailing_newline(true);
+    env
+}
+
+/// Rewrite the `{{include "file"}}` shorthand as a Jinja include tag.
+fn expand_includes(template: &str) -> String {
+    let re = Regex::new(r#"\{\{-?\s*include\s+("[^"]*"|'[^']*')\s*-?\}\}"#)
+        .expect("include pattern is valid");
+    re.replace_all(template, "{% include $1 %}").into_owned()
+}
+
+/// Loads included templates from a directory skill's folder. Names must be
+/// relative paths that stay inside the folder.
+fn resource_loader(
+    dir: PathBuf,
+) -> impl Fn(&str) -> Result<Option<String>, minijinja::Error> + Send + Sync + 'static {
+    move |name| {
+        let relative = Path::new(name);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Ok(None);
+        }
+        match std::fs::read_to_string(dir.join(relative)) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(minijinja::Error::new(
+                ErrorKind::InvalidOperation,
+                format!("Failed to read {}: {}", name, e),
+            )),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::skills::parser::{ParsedSkill, SkillMetadata};
+
+/// A skill advertised to CopilotKit as an action it can invoke.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillTool {
+    /// Identifier-safe action name, e.g. `skill_generate_release_notes`.
+    pub name: String,
+    pub description: String,
+    /// Skill file the action runs.
+    pub path: String,
+    /// JSON schema of the skill's inputs.
+    pub parameters: Value,
+}
+
+/// Actions for the enabled skills in `skills`.
+pub fn skill_tools(skills: &[ParsedSkill]) -> Vec<SkillTool> {
+    skills
+        .iter()
+        .filter(|s| s.metadata.enabled)
+        .map(|s| SkillTool {
+            name: tool_name(&s.metadata.name),
+            description: if s.metadata.description.is_empty() {
+                s.metadata.name.clone()
+            } else {
+                s.metadata.description.clone()
+            },
+            path: s.file_path.clone(),
+            parameters: input_schema(&s.metadata),
+        })
+        .collect()
+}
+
+/// Action name for a skill: `skill_` plus the name in snake case.
+pub fn tool_name(skill_name: &str) -> String {
+    let words: Vec<String> = skill_name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect();
+    format!("skill_{}", words.join("_"))
+}
+
+/// JSON schema describing a skill's declared inputs.
+pub fn input_schema(meta: &SkillMetadata) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    for input in &meta.inputs {
+        let mut schema = match input.input_type.as_str() {
+            "number" => json!({ "type": "number" }),
+            "boolean" => json!({ "type": "boolean" }),
+            "list" => json!({ "type": "array", "items": { "type": "string" } }),
+            "select" => {
+                json!({ "type": "string", "enum": input.options.clone().unwrap_or_default() })
+            }
+            _ => json!({ "type": "string" }),
+        };
+        let description = if input.label.is_empty() {
+            &input.name
+        } else {
+            &input.label
+        };
+        schema["description"] = json!(description);
+        if let Some(default) = &input.default {
+            schema["default"] = default.clone();
+        }
+        if input.required {
+            required.push(json!(input.name));
+        }
+        properties.insert(input.name.clone(), schema);
+    }
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
@@ -0,0 +1,215 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::skills::parser::{SkillMetadata, INPUT_TYPES, OUTPUT_TYPES};
+use crate::skills::template;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a skill file, for inline display in the editor.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    /// 1-based line in the skill file.
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Check a skill file's frontmatter and template, reporting every problem
+/// found with the line it's on. An empty result means the skill is valid.
+pub fn validate_skill(content: &str) -> Vec<Diagnostic> {
+    let mut diags = Vec::new();
+    let lines: Vec<&str> = content.lines().collect();
+
+    // Locate the frontmatter fences (same rules as `parse_skill`)
+    let Some(open) = lines.iter().position(|l| !l.trim().is_empty()) else {
+        diags.push(error(1, "Skill file is empty"));
+        return diags;
+    };
+    if !lines[open].trim_start().starts_with("---") {
+        diags.push(error(
+            open + 1,
+            "No YAML frontmatter found (expected `---`)",
+        ));
+        return diags;
+    }
+    let Some(close) = (open + 1..lines.len()).find(|&i| lines[i].starts_with("---")) else {
+        diags.push(error(
+            open + 1,
+            "Unclosed frontmatter (missing closing `---`)",
+        ));
+        return diags;
+    };
+
+    let yaml_lines = &lines[open + 1..close];
+    // Line number in the file of the first frontmatter line
+    let yaml_start = open + 2;
+    let yaml = yaml_lines.join("\n");
+
+    let value: serde_yaml::Value = match serde_yaml::from_str(&yaml) {
+        Ok(v) => v,
+        Err(e) => {
+            let line = e.location().map(|l| yaml_start + l.line() - 1);
+            // The message's own location is relative to the frontmatter
+            let message = e.to_string();
+            let message = message.split(" at line ").next().unwrap_or(&message);
+            diags.push(error(line.unwrap_or(open + 1), message));
+            return diags;
+        }
+    };
+
+    let name_line = find_line(yaml_lines, yaml_start, |l| l.starts_with("name:"), 0);
+    let has_name = value
+        .get("name")
+        .and_then(|n| n.as_str())
+        .is_some_and(|n| !n.trim().is_empty());
+    if !has_name {
+        diags.push(error(
+            name_line.unwrap_or(open + 1),
+            "Skill is missing a `name`",
+        ));
+    }
+
+    let meta: SkillMetadata = match serde_yaml::from_value(value) {
+        Ok(m) => m,
+        Err(e) => {
+            if has_name {
+                diags.push(error(open + 1, &format!("Invalid frontmatter: {}", e)));
+            }
+            return diags;
+        }
+    };
+
+    let mut seen = HashSet::new();
+    // Inputs appear in order, so search each one's line after the previous
+    let mut search_from = 0;
+    for input in &meta.inputs {
+        let line = find_line(
+            yaml_lines,
+            yaml_start,
+            |l| l.trim_start_matches("- ").trim() == format!("name: {}", input.name),
+            search_from,
+        )
+        .unwrap_or(open + 1);
+        search_from = line.saturating_sub(yaml_start) + 1;
+        if !seen.insert(input.name.as_str()) {
+            diags.push(error(line, &format!("Duplicate input `{}`", input.name)));
+        }
+        if !INPUT_TYPES.contains(&input.input_type.as_str()) {
+            let type_line = find_line(
+                yaml_lines,
+                yaml_start,
+                |l| l.trim_start_matches("- ").trim().starts_with("type:"),
+                line - yaml_start,
+            );
+            diags.push(error(
+                type_line.unwrap_or(line),
+                &format!(
+                    "Input `{}` has unknown type `{}` (expected one of: {})",
+                    input.name,
+                    input.input_type,
+                    INPUT_TYPES.join(", ")
+                ),
+            ));
+        }
+        if input.input_type == "select" && input.options.as_ref().is_none_or(|o| o.is_empty()) {
+            diags.push(warning(
+                line,
+                &format!("Select input `{}` has no options", input.name),
+            ));
+        }
+    }
+
+    for output in &meta.outputs {
+        if !OUTPUT_TYPES.contains(&output.output_type.as_str()) {
+            let line = find_line(
+                yaml_lines,
+                yaml_start,
+                |l| l.trim_start_matches("- ").trim() == format!("name: {}", output.name),
+                0,
+            );
+            diags.push(warning(
+                line.unwrap_or(open + 1),
+                &format!(
+                    "Output `{}` has unknown type `{}`; it will be returned as text",
+                    output.name, output.output_type
+                ),
+            ));
+        }
+    }
+
+    // Template checks: body starts on the line after the closing fence
+    let body_start = close + 2;
+    let body = lines[close + 1..].join("\n");
+    match template::template_variables(&body) {
+        Ok(vars) => {
+            let declared: HashSet<&str> = meta.inputs.iter().map(|i| i.name.as_str()).collect();
+            let mut undeclared: Vec<&String> = vars
+                .iter()
+                .filter(|v| !declared.contains(v.as_str()))
+                .collect();
+            undeclared.sort();
+            for var in undeclared {
+                let line = variable_line(&lines[close + 1..], var)
+                    .map(|i| body_start + i)
+                    .unwrap_or(body_start);
+                diags.push(warning(
+                    line,
+                    &format!("Template uses `{}`, which is not a declared input", var),
+                ));
+            }
+        }
+        Err(e) => {
+            let line = e.line().map(|l| body_start + l - 1).unwrap_or(body_start);
+            let message = match e.detail() {
+                Some(detail) => format!("Template {}: {}", e.kind(), detail),
+                None => format!("Template {}", e.kind()),
+            };
+            diags.push(error(line, &message));
+        }
+    }
+
+    diags.sort_by_key(|d| d.line);
+    diags
+}
+
+fn error(line: usize, message: &str) -> Diagnostic {
+    Diagnostic {
+        line,
+        severity: Severity::Error,
+        message: message.to_string(),
+    }
+}
+
+fn warning(line: usize, message: &str) -> Diagnostic {
+    Diagnostic {
+        line,
+        severity: Severity::Warning,
+        message: message.to_string(),
+    }
+}
+
+/// File line number of the first frontmatter line at or after `from` matching `pred`.
+fn find_line(
+    yaml_lines: &[&str],
+    yaml_start: usize,
+    pred: impl Fn(&str) -> bool,
+    from: usize,
+) -> Option<usize> {
+    (from..yaml_lines.len())
+        .find(|&i| pred(yaml_lines[i].trim()))
+        .map(|i| yaml_start + i)
+}
+
+/// Index of the first template line that references `var` inside a tag.
+fn variable_line(body: &[&str], var: &str) -> Option<usize> {
+    let pattern = format!(r"\{{[{{%][^}}]*\b{}\b", regex::escape(var));
+    let re = Regex::new(&pattern).ok()?;
+    body.iter().position(|l| re.is_match(l))
+}
@@ -0,0 +1,133 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::error::KataraError;
+
+/// Versions kept per skill; older ones are pruned.
+const MAX_VERSIONS: usize = 50;
+
+/// A saved version of a skill file.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillVersion {
+    /// Identifier to pass to `restore_skill_version`.
+    pub id: String,
+    /// Unix timestamp (milliseconds) when the version was saved.
+    pub saved_at: u64,
+    pub size: u64,
+}
+
+/// Record a new version of a skill after it was written.
+///
+/// `previous` is the file's content before the write; it's kept as the
+/// first version when the skill has no history yet, so edits made before
+/// versioning existed can still be rolled back. Failures are logged rather
+/// than surfaced; history is best-effort and must not block saving.
+pub fn record(path: &str, previous: Option<&str>, content: &str) {
+    if let Err(e) = try_record(path, previous, content) {
+        eprintln!("[katara] Failed to save version of {}: {}", path, e);
+    }
+}
+
+/// Versions of a skill, newest first.
+pub fn list(path: &str) -> Result<Vec<SkillVersion>, KataraError> {
+    let dir = history_dir(path)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut versions = Vec::new();
+    for entry in std::fs::read_dir(&dir).map_err(KataraError::Io)? {
+        let entry = entry.map_err(KataraError::Io)?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(saved_at) = name.strip_suffix(".md").and_then(|s| s.parse::<u64>().ok()) else {
+            continue;
+        };
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        versions.push(SkillVersion {
+            id: saved_at.to_string(),
+            saved_at,
+            size,
+        });
+    }
+    versions.sort_by_key(|v| std::cmp::Reverse(v.saved_at));
+    Ok(versions)
+}
+
+/// Content of a saved version.
+pub fn read(path: &str, id: &str) -> Result<String, KataraError> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(KataraError::Skill(format!("Invalid version id: {}", id)));
+    }
+    let file = history_dir(path)?.join(format!("{}.md", id));
+    if !file.exists() {
+        return Err(KataraError::Skill(format!(
+            "Version {} not found for {}",
+            id, path
+        )));
+    }
+    std::fs::read_to_string(file).map_err(KataraError::Io)
+}
+
+fn try_record(path: &str, previous: Option<&str>, content: &str) -> Result<(), KataraError> {
+    let dir = history_dir(path)?;
+    std::fs::create_dir_all(&dir).map_err(KataraError::Io)?;
+    let versions = list(path)?;
+    let mut now = now_millis();
+
+    match versions.first() {
+        None => {
+            if let Some(previous) = previous.filter(|p| *p != content) {
+                std::fs::write(dir.join(format!("{}.md", now)), previous)
+                    .map_err(KataraError::Io)?;
+                now += 1;
+            }
+        }
+        // Saving without changes doesn't need a new version
+        Some(latest) if read(path, &latest.id)? == content => return Ok(()),
+        Some(latest) => now = now.max(latest.saved_at + 1),
+    }
+    std::fs::write(dir.join(format!("{}.md", now)), content).map_err(KataraError::Io)?;
+
+    for old in list(path)?.iter().skip(MAX_VERSIONS) {
+        let _ = std::fs::remove_file(dir.join(format!("{}.md", old.id)));
+    }
+    Ok(())
+}
+
+/// History lives in the app config dir, one folder per skill file, so it
+/// doesn't show up in the skills directory or its watcher.
+fn history_dir(path: &str) -> Result<PathBuf, KataraError> {
+    let path = Path::new(path);
+    let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(crate::config::katara_dir()?
+        .join("skill_versions")
+        .join(format!(
+            "{}-{:016x}",
+            stem,
+            fnv1a(absolute.to_string_lossy().as_bytes())
+        )))
+}
+
+/// Fingerprint of a skill file's content, used to detect edits made on disk
+/// since the editor loaded the file.
+pub fn content_hash(content: &str) -> String {
+    format!("{:016x}", fnv1a(content.as_bytes()))
+}
+
+/// Stable hash for history folder names and content fingerprints (std's
+/// hasher may change between releases).
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
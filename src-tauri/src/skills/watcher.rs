@@ -0,0 +1,155 @@
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Quiet period before a burst of file events is reported. Editors often
+/// save a file as several events (write to temp, rename over, chmod).
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Payload for the `skills:changed` event.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SkillsChangedPayload {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Change {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// Watch a skills directory and emit `skills:changed` whenever skill files
+/// are added, modified or removed, including by external editors.
+///
+/// Runs on a background thread for the lifetime of the app.
+pub fn start(app_handle: AppHandle, dir: PathBuf) -> Result<(), String> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+    watcher
+        .watch(&dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    println!("[katara] Watching skills in {}", dir.display());
+
+    std::thread::spawn(move || {
+        // Watching stops when the watcher is dropped, so this thread owns it
+        let _watcher = watcher;
+        let mut pending: HashMap<PathBuf, Change> = HashMap::new();
+
+        loop {
+            let event = if pending.is_empty() {
+                match rx.recv() {
+                    Ok(event) => event,
+                    Err(_) => break,
+                }
+            } else {
+                match rx.recv_timeout(DEBOUNCE) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => {
+                        emit(&app_handle, std::mem::take(&mut pending));
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            };
+
+            match event {
+                Ok(event) => collect(&mut pending, event),
+                Err(e) => eprintln!("[katara] Skills watcher error: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Fold a file system event into the pending changes.
+fn collect(pending: &mut HashMap<PathBuf, Change>, event: Event) {
+    let changes: Vec<(PathBuf, Change)> = match event.kind {
+        EventKind::Create(_) => with_change(event.paths, Change::Added),
+        EventKind::Remove(_) => with_change(event.paths, Change::Removed),
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            with_change(event.paths, Change::Removed)
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            with_change(event.paths, Change::Added)
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let mut paths = event.paths.into_iter();
+            let from = paths.next().map(|p| (p, Change::Removed));
+            let to = paths.next().map(|p| (p, Change::Added));
+            from.into_iter().chain(to).collect()
+        }
+        // Rename with no direction: whether the path still exists tells which side it was
+        EventKind::Modify(ModifyKind::Name(_)) => event
+            .paths
+            .into_iter()
+            .map(|p| {
+                let change = if p.exists() {
+                    Change::Added
+                } else {
+                    Change::Removed
+                };
+                (p, change)
+            })
+            .collect(),
+        EventKind::Modify(_) => with_change(event.paths, Change::Modified),
+        _ => Vec::new(),
+    };
+
+    for (path, change) in changes {
+        if !is_skill_file(&path) {
+            continue;
+        }
+        match (pending.get(&path).copied(), change) {
+            // Created and deleted within one burst (editor temp files)
+            (Some(Change::Added), Change::Removed) => {
+                pending.remove(&path);
+            }
+            (Some(Change::Added), Change::Modified) => {}
+            (Some(Change::Removed), Change::Added) => {
+                pending.insert(path, Change::Modified);
+            }
+            _ => {
+                pending.insert(path, change);
+            }
+        }
+    }
+}
+
+fn with_change(paths: Vec<PathBuf>, change: Change) -> Vec<(PathBuf, Change)> {
+    paths.into_iter().map(|p| (p, change)).collect()
+}
+
+fn is_skill_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "md")
+}
+
+fn emit(app_handle: &AppHandle, changes: HashMap<PathBuf, Change>) {
+    if changes.is_empty() {
+        return;
+    }
+    let mut payload = SkillsChangedPayload::default();
+    for (path, change) in changes {
+        let path = path.display().to_string();
+        match change {
+            Change::Added => payload.added.push(path),
+            Change::Modified => payload.modified.push(path),
+            Change::Removed => payload.removed.push(path),
+        }
+    }
+    payload.added.sort();
+    payload.modified.sort();
+    payload.removed.sort();
+    let _ = app_handle.emit("skills:changed", &payload);
+}
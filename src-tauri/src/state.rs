@@ -1,9 +1,21 @@
 use std::collections::{HashMap, VecDeque};
-use tokio::sync::{broadcast, Mutex, RwLock};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::{broadcast, Mutex, Notify, RwLock};
 
-use crate::process::session::Session;
+use crate::agui::agents::AgentRegistry;
+use crate::agui::replay::RunBuffers;
+use crate::agui::server::ActiveRun;
+use crate::agui::state::SharedState;
+use crate::config::manager::{self as config_mgr, AppSettings};
+use crate::config::watcher::ConfigWatcher;
+use crate::mcp::frontend_tools::FrontendTools;
+use crate::plugins::host::PluginHost;
+use crate::process::session::{Session, UsageTotals};
+use crate::remote::server::RemoteStatus;
+use crate::skills::runs::SkillRun;
 use crate::terminal::pty::PtyHandle;
 use crate::websocket::protocol::WsEvent;
+use crate::window_scope::WindowScope;
 
 /// Shared application state, wrapped in Arc by Tauri and shared with Axum.
 pub struct AppState {
@@ -29,11 +41,67 @@ pub struct AppState {
     /// session and associate the connection with it.
     pub pending_connections: Mutex<VecDeque<String>>,
 
+    /// Fires whenever a session gains a WebSocket connection from its CLI,
+    /// for AG-UI runs waiting on one.
+    pub session_connected: Notify,
+
     /// Maps CopilotKit thread IDs to Katara session IDs for multi-session routing.
     pub thread_to_session: RwLock<HashMap<String, String>>,
 
     /// Reverse map: Katara session ID to CopilotKit thread ID.
     pub session_to_thread: RwLock<HashMap<String, String>>,
+
+    /// AG-UI runs currently streaming, keyed by thread ID.
+    pub agui_runs: Mutex<HashMap<String, ActiveRun>>,
+
+    /// Per-thread locks held while an AG-UI run decides whether to start a
+    /// session, so two runs on one thread don't both start one.
+    pub thread_spawns: std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>,
+
+    /// Recent AG-UI runs' events, for resuming a dropped stream.
+    pub agui_buffers: RunBuffers,
+
+    /// AG-UI agent IDs bound to sessions. Persisted across restarts.
+    pub agents: AgentRegistry,
+
+    /// CopilotKit shared state, per thread ID.
+    pub shared_state: SharedState,
+
+    /// CopilotKit frontend actions offered to sessions as MCP tools.
+    pub frontend_tools: FrontendTools,
+
+    /// Each session's usage totals when its last turn was recorded, so a
+    /// turn's own usage is the difference. A std lock; never held across
+    /// an await.
+    pub turn_totals: std::sync::Mutex<HashMap<String, UsageTotals>>,
+
+    /// Recent skill runs, oldest first. Persisted across restarts.
+    pub skill_runs: RwLock<Vec<SkillRun>>,
+
+    /// Watches CLAUDE.md and settings files; set once the app has started.
+    pub config_watcher: OnceLock<ConfigWatcher>,
+
+    /// Live copy of the app settings, reloaded when settings.json changes.
+    /// A std lock so PTY reader threads and the config watcher can use it;
+    /// never held across an await.
+    pub settings: std::sync::RwLock<AppSettings>,
+
+    /// Random per-launch token, published in the discovery file so local
+    /// tools can authenticate to the AG-UI server.
+    pub auth_token: String,
+
+    /// Unix time in milliseconds the app started.
+    pub started_at: u64,
+
+    /// Sessions and terminals each window has subscribed to, keyed by
+    /// window label. A std lock so PTY reader threads can route events.
+    pub window_scopes: std::sync::RwLock<HashMap<String, WindowScope>>,
+
+    /// Where the remote access server is listening, while it's running.
+    pub remote: RwLock<Option<RemoteStatus>>,
+
+    /// Plugin processes declared in settings.
+    pub plugins: PluginHost,
 }
 
 impl AppState {
@@ -46,10 +114,44 @@ impl AppState {
             axum_port: RwLock::new(0),
             event_tx,
             pending_connections: Mutex::new(VecDeque::new()),
+            session_connected: Notify::new(),
             thread_to_session: RwLock::new(HashMap::new()),
             session_to_thread: RwLock::new(HashMap::new()),
+            agui_runs: Mutex::new(HashMap::new()),
+            thread_spawns: std::sync::Mutex::new(HashMap::new()),
+            agui_buffers: RunBuffers::default(),
+            agents: AgentRegistry::load(),
+            shared_state: SharedState::default(),
+            frontend_tools: FrontendTools::default(),
+            turn_totals: std::sync::Mutex::new(HashMap::new()),
+            skill_runs: RwLock::new(crate::skills::runs::load()),
+            config_watcher: OnceLock::new(),
+            settings: std::sync::RwLock::new(config_mgr::read_settings().unwrap_or_else(|e| {
+                eprintln!("[katara] Failed to read settings, using defaults: {}", e);
+                AppSettings::default()
+            })),
+            auth_token: uuid::Uuid::new_v4().simple().to_string(),
+            started_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            window_scopes: std::sync::RwLock::new(HashMap::new()),
+            remote: RwLock::new(None),
+            plugins: PluginHost::default(),
         }
     }
+
+    /// Snapshot of the live settings.
+    pub fn settings(&self) -> AppSettings {
+        self.settings
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn set_settings(&self, settings: AppSettings) {
+        *self.settings.write().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
 }
 
 impl Default for AppState {
@@ -0,0 +1,223 @@
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::KataraError;
+use crate::process::session::UsageTotals;
+use crate::state::AppState;
+
+/// Records older than this are dropped when the log is compacted, unless
+/// the `history.activity_retention_days` setting says otherwise.
+pub const DEFAULT_RETENTION_DAYS: u64 = 365;
+
+/// One thing that happened, appended to the activity log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActivityRecord {
+    SessionStarted {
+        session_id: String,
+        working_dir: String,
+        model: Option<String>,
+        /// Unix timestamp (seconds).
+        at: u64,
+    },
+    Turn {
+        session_id: String,
+        working_dir: String,
+        model: Option<String>,
+        at: u64,
+        duration_ms: Option<u64>,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_creation_input_tokens: u64,
+        cache_read_input_tokens: u64,
+        /// Estimated from the token counts and the prices at the time.
+        cost_usd: f64,
+        is_error: bool,
+    },
+    Approval {
+        session_id: String,
+        working_dir: String,
+        tool_name: String,
+        approved: bool,
+        /// Resolved by the permission mode rather than the user.
+        automatic: bool,
+        at: u64,
+    },
+}
+
+impl ActivityRecord {
+    pub fn at(&self) -> u64 {
+        match self {
+            Self::SessionStarted { at, .. } | Self::Turn { at, .. } | Self::Approval { at, .. } => {
+                *at
+            }
+        }
+    }
+}
+
+/// Record that a session started.
+pub fn session_started(session_id: &str, working_dir: &str, model: Option<&str>) {
+    append(&ActivityRecord::SessionStarted {
+        session_id: session_id.to_string(),
+        working_dir: working_dir.to_string(),
+        model: model.map(str::to_string),
+        at: now(),
+    });
+}
+
+/// Set the usage a session's next turn is measured from, for a session
+/// restored with usage from before a restart.
+pub fn seed_totals(state: &AppState, session_id: &str, totals: &UsageTotals) {
+    state
+        .turn_totals
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(session_id.to_string(), totals.clone());
+}
+
+/// Record a finished turn. `totals` is the session's accumulated usage; the
+/// turn is charged for what was added since its previous turn. The record
+/// is written on a blocking thread, since callers hold the sessions lock.
+pub fn turn_finished(
+    state: &AppState,
+    session_id: &str,
+    working_dir: &str,
+    model: Option<&str>,
+    totals: &UsageTotals,
+    duration_ms: Option<u64>,
+    is_error: bool,
+) {
+    let previous = state
+        .turn_totals
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(session_id.to_string(), totals.clone())
+        .unwrap_or_default();
+    let input_tokens = totals.input_tokens.saturating_sub(previous.input_tokens);
+    let output_tokens = totals.output_tokens.saturating_sub(previous.output_tokens);
+    let cache_creation_input_tokens = totals
+        .cache_creation_input_tokens
+        .saturating_sub(previous.cache_creation_input_tokens);
+    let cache_read_input_tokens = totals
+        .cache_read_input_tokens
+        .saturating_sub(previous.cache_read_input_tokens);
+
+    let price = state.settings().pricing_for(model.unwrap_or_default());
+    let cost_usd = (input_tokens as f64 * price.input
+        + output_tokens as f64 * price.output
+        + cache_creation_input_tokens as f64 * price.cache_write
+        + cache_read_input_tokens as f64 * price.cache_read)
+        / 1_000_000.0;
+
+    let record = ActivityRecord::Turn {
+        session_id: session_id.to_string(),
+        working_dir: working_dir.to_string(),
+        model: model.map(str::to_string),
+        at: now(),
+        duration_ms,
+        input_tokens,
+        output_tokens,
+        cache_creation_input_tokens,
+        cache_read_input_tokens,
+        cost_usd,
+        is_error,
+    };
+    tokio::task::spawn_blocking(move || append(&record));
+}
+
+/// Record how a tool permission request was answered.
+pub fn approval(
+    session_id: &str,
+    working_dir: &str,
+    tool_name: &str,
+    approved: bool,
+    automatic: bool,
+) {
+    append(&ActivityRecord::Approval {
+        session_id: session_id.to_string(),
+        working_dir: working_dir.to_string(),
+        tool_name: tool_name.to_string(),
+        approved,
+        automatic,
+        at: now(),
+    });
+}
+
+/// Every record at or after `since`, oldest first. Unreadable lines are
+/// skipped.
+pub fn load(since: u64) -> Vec<ActivityRecord> {
+    let Ok(file) = store_path().and_then(|path| Ok(std::fs::File::open(path)?)) else {
+        return Vec::new();
+    };
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<ActivityRecord>(&line).ok())
+        .filter(|r| r.at() >= since)
+        .collect()
+}
+
+/// Drop records older than `retention_days`. Run at startup so the log
+/// doesn't grow forever.
+pub fn compact(retention_days: u64) {
+    let Ok(path) = store_path() else {
+        return;
+    };
+    if !path.exists() {
+        return;
+    }
+    let cutoff = now().saturating_sub(retention_days * 24 * 60 * 60);
+    let kept = load(cutoff);
+    let result = kept
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+        .and_then(|lines| {
+            let mut content = lines.join("\n");
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            let tmp = path.with_extension("jsonl.tmp");
+            std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+            std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        eprintln!("[katara] Failed to compact activity log: {}", e);
+    }
+}
+
+/// Append a record. Failures are logged rather than surfaced; the
+/// dashboard is best-effort.
+fn append(record: &ActivityRecord) {
+    let result = store_path()
+        .and_then(|path| Ok((path, serde_json::to_string(record)?)))
+        .map_err(|e| e.to_string())
+        .and_then(|(path, line)| {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| e.to_string())?;
+            writeln!(file, "{}", line).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        eprintln!("[katara] Failed to record activity: {}", e);
+    }
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn store_path() -> Result<PathBuf, KataraError> {
+    Ok(crate::config::katara_dir()?.join("activity.jsonl"))
+}
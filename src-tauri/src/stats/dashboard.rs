@@ -0,0 +1,320 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::skills::runs::{SkillRun, SkillRunStatus};
+use crate::stats::activity::{self, ActivityRecord};
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Projects listed in `top_projects`.
+const TOP_PROJECTS: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DashboardRange {
+    /// The last 7 days.
+    Week,
+    /// The last 30 days.
+    #[default]
+    Month,
+    /// The last 90 days.
+    Quarter,
+    /// The last 365 days.
+    Year,
+    /// Everything in the activity log.
+    All,
+}
+
+impl DashboardRange {
+    fn days(self) -> Option<u64> {
+        match self {
+            Self::Week => Some(7),
+            Self::Month => Some(30),
+            Self::Quarter => Some(90),
+            Self::Year => Some(365),
+            Self::All => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardStats {
+    pub range: DashboardRange,
+    /// Start of the first day covered (Unix seconds, UTC midnight).
+    pub since: u64,
+    pub totals: Totals,
+    /// One entry per day in the range, oldest first, including empty days.
+    pub days: Vec<DayStats>,
+    pub approvals: ApprovalStats,
+    /// Most active projects by turns.
+    pub top_projects: Vec<ProjectStats>,
+    pub skill_runs: SkillRunStats,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Totals {
+    pub sessions: usize,
+    pub turns: usize,
+    pub failed_turns: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    pub avg_turn_duration_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DayStats {
+    /// `YYYY-MM-DD`, UTC.
+    pub date: String,
+    pub sessions: usize,
+    pub turns: usize,
+    pub cost_usd: f64,
+    pub avg_turn_duration_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApprovalStats {
+    /// Answered by the user.
+    pub approved: usize,
+    pub denied: usize,
+    /// Answered by the session's permission mode.
+    pub auto_approved: usize,
+    pub auto_denied: usize,
+    /// Share of the user's answers that approved, if they answered any.
+    pub approval_rate: Option<f64>,
+    /// Requests per tool, most frequent first.
+    pub by_tool: Vec<ToolApprovals>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ToolApprovals {
+    pub tool_name: String,
+    pub approved: usize,
+    pub denied: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectStats {
+    pub working_dir: String,
+    /// Last path component, for display.
+    pub name: String,
+    pub sessions: usize,
+    pub turns: usize,
+    pub cost_usd: f64,
+    /// Unix seconds.
+    pub last_active: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SkillRunStats {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub cost_usd: f64,
+}
+
+/// Running sum for an average.
+#[derive(Default)]
+struct Mean {
+    sum: u64,
+    count: u64,
+}
+
+impl Mean {
+    fn add(&mut self, value: u64) {
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn get(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+    }
+}
+
+/// Aggregate the activity log and the skill run history over `range`.
+pub fn compute(range: DashboardRange, skill_runs: &[SkillRun]) -> DashboardStats {
+    let today = now() / DAY_SECS * DAY_SECS;
+    let cutoff = range
+        .days()
+        .map_or(0, |days| today.saturating_sub((days - 1) * DAY_SECS));
+    let records = activity::load(cutoff);
+    // "All" starts at the first recorded day
+    let since = match range {
+        DashboardRange::All => records
+            .iter()
+            .map(ActivityRecord::at)
+            .min()
+            .map_or(today, |at| at / DAY_SECS * DAY_SECS),
+        _ => cutoff,
+    };
+
+    let mut totals = Totals::default();
+    let mut turn_duration = Mean::default();
+    let mut days: BTreeMap<u64, (DayStats, Mean)> = (since / DAY_SECS..=today / DAY_SECS)
+        .map(|day| (day, (DayStats::default(), Mean::default())))
+        .collect();
+    let mut approvals = ApprovalStats::default();
+    let mut by_tool: HashMap<String, ToolApprovals> = HashMap::new();
+    let mut projects: HashMap<String, ProjectStats> = HashMap::new();
+    let mut sessions_seen: HashSet<String> = HashSet::new();
+
+    for record in &records {
+        let day = days.entry(record.at() / DAY_SECS).or_default();
+        match record {
+            ActivityRecord::SessionStarted {
+                session_id,
+                working_dir,
+                at,
+                ..
+            } => {
+                if !sessions_seen.insert(session_id.clone()) {
+                    continue;
+                }
+                totals.sessions += 1;
+                day.0.sessions += 1;
+                let project = project(&mut projects, working_dir, *at);
+                project.sessions += 1;
+            }
+            ActivityRecord::Turn {
+                working_dir,
+                at,
+                duration_ms,
+                input_tokens,
+                output_tokens,
+                cost_usd,
+                is_error,
+                ..
+            } => {
+                totals.turns += 1;
+                if *is_error {
+                    totals.failed_turns += 1;
+                }
+                totals.input_tokens += input_tokens;
+                totals.output_tokens += output_tokens;
+                totals.cost_usd += cost_usd;
+                if let Some(ms) = duration_ms {
+                    turn_duration.add(*ms);
+                    day.1.add(*ms);
+                }
+                day.0.turns += 1;
+                day.0.cost_usd += cost_usd;
+                let project = project(&mut projects, working_dir, *at);
+                project.turns += 1;
+                project.cost_usd += cost_usd;
+            }
+            ActivityRecord::Approval {
+                tool_name,
+                approved,
+                automatic,
+                ..
+            } => {
+                match (automatic, approved) {
+                    (false, true) => approvals.approved += 1,
+                    (false, false) => approvals.denied += 1,
+                    (true, true) => approvals.auto_approved += 1,
+                    (true, false) => approvals.auto_denied += 1,
+                }
+                let tool = by_tool
+                    .entry(tool_name.clone())
+                    .or_insert_with(|| ToolApprovals {
+                        tool_name: tool_name.clone(),
+                        ..Default::default()
+                    });
+                if *approved {
+                    tool.approved += 1;
+                } else {
+                    tool.denied += 1;
+                }
+            }
+        }
+    }
+
+    totals.avg_turn_duration_ms = turn_duration.get();
+    let answered = approvals.approved + approvals.denied;
+    approvals.approval_rate = (answered > 0).then(|| approvals.approved as f64 / answered as f64);
+    approvals.by_tool = by_tool.into_values().collect();
+    approvals.by_tool.sort_by(|a, b| {
+        (b.approved + b.denied)
+            .cmp(&(a.approved + a.denied))
+            .then_with(|| a.tool_name.cmp(&b.tool_name))
+    });
+
+    let mut top_projects: Vec<ProjectStats> = projects.into_values().collect();
+    top_projects.sort_by(|a, b| {
+        b.turns
+            .cmp(&a.turns)
+            .then(b.sessions.cmp(&a.sessions))
+            .then(b.last_active.cmp(&a.last_active))
+    });
+    top_projects.truncate(TOP_PROJECTS);
+
+    let mut skill_stats = SkillRunStats::default();
+    for run in skill_runs.iter().filter(|r| r.started_at >= since) {
+        skill_stats.total += 1;
+        match run.status {
+            SkillRunStatus::Succeeded => skill_stats.succeeded += 1,
+            SkillRunStatus::Failed(_) => skill_stats.failed += 1,
+            SkillRunStatus::Running => {}
+        }
+        skill_stats.cost_usd += run.cost_usd.unwrap_or_default();
+    }
+
+    DashboardStats {
+        range,
+        since,
+        totals,
+        days: days
+            .into_iter()
+            .map(|(day, (mut stats, duration))| {
+                stats.date = date(day);
+                stats.avg_turn_duration_ms = duration.get();
+                stats
+            })
+            .collect(),
+        approvals,
+        top_projects,
+        skill_runs: skill_stats,
+    }
+}
+
+fn project<'a>(
+    projects: &'a mut HashMap<String, ProjectStats>,
+    working_dir: &str,
+    at: u64,
+) -> &'a mut ProjectStats {
+    let project = projects
+        .entry(working_dir.to_string())
+        .or_insert_with(|| ProjectStats {
+            working_dir: working_dir.to_string(),
+            name: std::path::Path::new(working_dir)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| working_dir.to_string()),
+            ..Default::default()
+        });
+    project.last_active = project.last_active.max(at);
+    project
+}
+
+/// `YYYY-MM-DD` for a count of days since the Unix epoch.
+fn date(days: u64) -> String {
+    // Civil-from-days, per Howard Hinnant's date algorithms
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,208 @@
+/// Longest OSC payload we bother keeping; anything longer is truncated.
+const MAX_OSC_LEN: usize = 4096;
+
+/// Longest CSI parameter string we inspect for mode changes.
+const MAX_CSI_LEN: usize = 32;
+
+/// Streaming ANSI escape stripper.
+///
+/// PTY output arrives in arbitrary chunks, so an escape sequence can be split
+/// across two reads. The stripper keeps its parse state between `feed` calls
+/// and only ever returns printable text (plus `\r`, `\n` and `\t`).
+///
+/// OSC payloads (`ESC ] ... BEL`) are collected along the way so callers can
+/// react to title changes and similar shell notifications. Bracketed paste
+/// mode (`CSI ? 2004 h/l`) is tracked too.
+#[derive(Debug, Default)]
+pub struct AnsiStripper {
+    state: State,
+    /// Whether the current string sequence is an OSC (vs DCS/APC/PM).
+    in_osc: bool,
+    osc_buf: String,
+    completed_osc: Vec<String>,
+    /// A BEL was seen outside of an OSC string.
+    bell: bool,
+    csi_buf: String,
+    /// The application has enabled bracketed paste.
+    bracketed_paste: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum State {
+    #[default]
+    Ground,
+    /// Saw ESC, waiting for the sequence introducer.
+    Escape,
+    /// Inside a CSI sequence (ESC [ ... final byte).
+    Csi,
+    /// Inside an OSC/DCS string (ESC ] ... BEL or ST).
+    Osc,
+    /// Saw ESC inside an OSC string; a following `\` terminates it.
+    OscEscape,
+}
+
+impl AnsiStripper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Strip escape sequences from the next chunk of output.
+    pub fn feed(&mut self, input: &str) -> String {
+        let mut out = String::with_capacity(input.len());
+        for c in input.chars() {
+            self.state = match self.state {
+                State::Ground => match c {
+                    '\x1b' => State::Escape,
+                    '\x07' => {
+                        self.bell = true;
+                        State::Ground
+                    }
+                    '\r' | '\n' | '\t' => {
+                        out.push(c);
+                        State::Ground
+                    }
+                    c if c.is_control() => State::Ground,
+                    c => {
+                        out.push(c);
+                        State::Ground
+                    }
+                },
+                State::Escape => match c {
+                    '[' => {
+                        self.csi_buf.clear();
+                        State::Csi
+                    }
+                    ']' => {
+                        self.in_osc = true;
+                        self.osc_buf.clear();
+                        State::Osc
+                    }
+                    'P' | '_' | '^' => {
+                        self.in_osc = false;
+                        State::Osc
+                    }
+                    // Two-byte escapes (ESC =, ESC >, ESC 7, ...)
+                    _ => State::Ground,
+                },
+                State::Csi => {
+                    if ('@'..='~').contains(&c) {
+                        self.finish_csi(c);
+                        State::Ground
+                    } else {
+                        if self.csi_buf.len() < MAX_CSI_LEN {
+                            self.csi_buf.push(c);
+                        }
+                        State::Csi
+                    }
+                }
+                State::Osc => match c {
+                    '\x07' => {
+                        self.finish_osc();
+                        State::Ground
+                    }
+                    '\x1b' => State::OscEscape,
+                    c => {
+                        if self.in_osc && self.osc_buf.len() < MAX_OSC_LEN {
+                            self.osc_buf.push(c);
+                        }
+                        State::Osc
+                    }
+                },
+                State::OscEscape => match c {
+                    '\\' => {
+                        self.finish_osc();
+                        State::Ground
+                    }
+                    _ => State::Osc,
+                },
+            };
+        }
+        out
+    }
+
+    /// Take the OSC payloads completed since the last call (e.g. `"0;title"`).
+    pub fn take_osc(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.completed_osc)
+    }
+
+    /// Whether a bell rang since the last call.
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell)
+    }
+
+    /// Whether the application currently expects bracketed paste.
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// Track DEC private mode 2004 (`CSI ? 2004 h` / `CSI ? 2004 l`).
+    fn finish_csi(&mut self, final_byte: char) {
+        let enable = match final_byte {
+            'h' => true,
+            'l' => false,
+            _ => return,
+        };
+        if let Some(params) = self.csi_buf.strip_prefix('?') {
+            if params.split(';').any(|p| p == "2004") {
+                self.bracketed_paste = enable;
+            }
+        }
+    }
+
+    fn finish_osc(&mut self) {
+        if self.in_osc {
+            self.completed_osc.push(std::mem::take(&mut self.osc_buf));
+            self.in_osc = false;
+        }
+    }
+}
+
+/// Split an OSC payload into its numeric code and argument (`"2;vim"` -> `(2, "vim")`).
+pub fn parse_osc(payload: &str) -> Option<(u32, &str)> {
+    let (code, rest) = payload.split_once(';').unwrap_or((payload, ""));
+    code.parse().ok().map(|c| (c, rest))
+}
+
+/// Strip all escape sequences from a complete string.
+pub fn strip(input: &str) -> String {
+    AnsiStripper::new().feed(input)
+}
+
+/// Extract a local path from an OSC 7 payload (`file://host/path`).
+///
+/// Shells emit this on every prompt when configured to report their cwd.
+/// The path is percent-decoded; Windows drive paths (`/C:/x`) lose the
+/// leading slash.
+pub fn parse_osc7_path(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("file://")?;
+    let path = &rest[rest.find('/')?..];
+    let decoded = percent_decode(path);
+    let bytes = decoded.as_bytes();
+    if bytes.len() > 2 && bytes[2] == b':' && bytes[1].is_ascii_alphabetic() {
+        return Some(decoded[1..].to_string());
+    }
+    Some(decoded)
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 3 <= bytes.len() {
+            let hex = (hex_value(bytes[i + 1]), hex_value(bytes[i + 2]));
+            if let (Some(hi), Some(lo)) = hex {
+                out.push(hi << 4 | lo);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
@@ -0,0 +1,212 @@
+use portable_pty::{native_pty_system, PtySize};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::terminal::ansi;
+use crate::terminal::pty::PtyHandle;
+use crate::terminal::shell::{self, ShellConfig};
+
+/// Output kept from a command run in a throwaway PTY; the rest is dropped.
+const MAX_CAPTURED_BYTES: usize = 1024 * 1024;
+
+/// Captured result of a programmatically executed command.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecResult {
+    /// Combined stdout/stderr with ANSI escapes stripped and CRLF normalized.
+    pub output: String,
+    /// Exit code, or `None` if the command timed out before finishing.
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    /// Some output was lost, because the reader fell behind the terminal or
+    /// past `MAX_CAPTURED_BYTES`, so `output` is incomplete.
+    pub truncated: bool,
+}
+
+/// Run `command` in a throwaway PTY and capture everything it prints.
+///
+/// A PTY (rather than plain pipes) keeps tools that check `isatty` behaving
+/// the same as they would in the user's terminal.
+pub async fn run_captured(
+    command: &str,
+    cwd: Option<&str>,
+    env: &HashMap<String, String>,
+    shell: Option<&ShellConfig>,
+    timeout: Duration,
+) -> Result<ExecResult, String> {
+    let pair = native_pty_system()
+        .openpty(PtySize {
+            rows: 24,
+            cols: 200,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to open PTY: {}", e))?;
+
+    let mut cmd = shell::build_exec_command(shell, command);
+    if let Some(dir) = cwd {
+        cmd.cwd(dir);
+    }
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+
+    let mut child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to spawn command: {}", e))?;
+    // Drop our copy of the slave so the reader sees EOF once the child exits
+    drop(pair.slave);
+
+    let mut reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("Failed to clone PTY reader: {}", e))?;
+    let mut killer = child.clone_killer();
+
+    // Output is shared so a timeout can still return what was printed so far
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    let truncated = Arc::new(AtomicBool::new(false));
+    let overflowed = truncated.clone();
+    let master = pair.master;
+    let task = tokio::task::spawn_blocking(move || {
+        let mut buf = [0u8; 4096];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    // Keep draining past the limit so the command isn't blocked
+                    if let Ok(mut out) = sink.lock() {
+                        let room = MAX_CAPTURED_BYTES.saturating_sub(out.len());
+                        if n > room {
+                            overflowed.store(true, Ordering::Relaxed);
+                        }
+                        out.extend_from_slice(&buf[..n.min(room)]);
+                    }
+                }
+            }
+        }
+        drop(master);
+        child.wait()
+    });
+
+    let (exit_code, timed_out) = match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(Ok(status))) => (Some(status.exit_code() as i32), false),
+        Ok(Ok(Err(e))) => return Err(format!("Failed to wait for command: {}", e)),
+        Ok(Err(e)) => return Err(format!("Exec task failed: {}", e)),
+        Err(_) => {
+            let _ = killer.kill();
+            (None, true)
+        }
+    };
+
+    let raw = captured.lock().map(|b| b.clone()).unwrap_or_default();
+    Ok(ExecResult {
+        output: clean_output(&String::from_utf8_lossy(&raw)),
+        exit_code,
+        timed_out,
+        truncated: truncated.load(Ordering::Relaxed),
+    })
+}
+
+/// Start `command` inside an existing terminal so its output can be captured.
+///
+/// The command is wrapped in begin/end markers printed by the shell itself;
+/// the end marker carries `$?`. The marker text is assembled by `printf` so
+/// the echoed command line never matches. The command runs in a brace group
+/// closed on its own line, so a trailing comment or `&` in it can't swallow
+/// the end marker. Requires a POSIX-style shell.
+///
+/// Returns the output subscription and marker token to hand to
+/// [`collect_marked_output`] once the terminals lock is released.
+pub fn start_marked_command(
+    handle: &PtyHandle,
+    command: &str,
+) -> Result<(broadcast::Receiver<String>, String), String> {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let rx = handle.subscribe();
+
+    // Leading space keeps the wrapper out of shell history (HISTCONTROL=ignorespace)
+    let line = format!(
+        " printf '\\n__KATARA_%s_BEGIN__\\n' {tok}; {{ {cmd}\r}}; printf '\\n__KATARA_%s_END__%s\\n' {tok} $?\r",
+        tok = token,
+        cmd = command,
+    );
+    handle.write(line.as_bytes())?;
+
+    Ok((rx, token))
+}
+
+/// Collect output from a marker-wrapped command started by [`start_marked_command`].
+pub async fn collect_marked_output(
+    mut rx: broadcast::Receiver<String>,
+    token: &str,
+    timeout: Duration,
+) -> ExecResult {
+    let begin = format!("__KATARA_{}_BEGIN__", token);
+    let end = format!("__KATARA_{}_END__", token);
+    let mut buffer = String::new();
+    let mut truncated = false;
+
+    let collect = async {
+        loop {
+            match rx.recv().await {
+                Ok(chunk) => {
+                    buffer.push_str(&chunk);
+                    if let Some(result) = parse_marked(&buffer, &begin, &end) {
+                        return Some(result);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => truncated = true,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    };
+
+    match tokio::time::timeout(timeout, collect).await {
+        Ok(Some((output, code))) => ExecResult {
+            output,
+            exit_code: Some(code),
+            timed_out: false,
+            truncated,
+        },
+        // Terminal closed mid-command, or it took too long
+        Ok(None) | Err(_) => {
+            let partial = buffer
+                .find(&begin)
+                .map(|i| &buffer[i + begin.len()..])
+                .unwrap_or("");
+            ExecResult {
+                output: clean_output(partial),
+                exit_code: None,
+                timed_out: true,
+                truncated,
+            }
+        }
+    }
+}
+
+/// Extract the text between the markers and the exit code after the end marker.
+fn parse_marked(buffer: &str, begin: &str, end: &str) -> Option<(String, i32)> {
+    let start = buffer.find(begin)? + begin.len();
+    let rest = &buffer[start..];
+    let end_idx = rest.find(end)?;
+    let after = &rest[end_idx + end.len()..];
+    // Wait until the full exit code (terminated by a newline) has arrived
+    let line_end = after.find(['\r', '\n'])?;
+    let code = after[..line_end].trim().parse().unwrap_or(-1);
+    Some((clean_output(&rest[..end_idx]), code))
+}
+
+/// Strip ANSI escape sequences and normalize line endings.
+fn clean_output(raw: &str) -> String {
+    ansi::strip(raw)
+        .replace('\r', "")
+        .trim_matches('\n')
+        .to_string()
+}
@@ -0,0 +1,112 @@
+use std::sync::{Condvar, Mutex};
+
+/// Unacknowledged output above which reading from the PTY pauses.
+const HIGH_WATERMARK: usize = 128 * 1024;
+
+/// Reading resumes once unacknowledged output drops below this.
+const LOW_WATERMARK: usize = 16 * 1024;
+
+/// Flow control between the PTY reader and the frontend.
+///
+/// While paused, the reader thread stops pulling from the PTY; the kernel
+/// buffer fills up and the program writing to the terminal blocks until
+/// reading resumes. Output is measured in UTF-16 code units (JS
+/// `string.length`) so the frontend can ack exactly what it received.
+///
+/// Reading pauses either explicitly (`set_paused`) or automatically when the
+/// frontend falls too far behind on acks. Automatic backpressure only kicks
+/// in after the first ack, so a frontend that never acks is never throttled.
+#[derive(Debug, Default)]
+pub struct FlowControl {
+    state: Mutex<FlowState>,
+    resumed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct FlowState {
+    /// Paused explicitly via `pause_terminal`.
+    paused: bool,
+    /// Paused because too much output is unacknowledged.
+    throttled: bool,
+    unacked: usize,
+    acks_seen: bool,
+    /// The terminal is gone; never block again.
+    closed: bool,
+}
+
+impl FlowState {
+    fn blocked(&self) -> bool {
+        !self.closed && (self.paused || self.throttled)
+    }
+}
+
+impl FlowControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Block the calling (reader) thread while output is paused.
+    pub fn wait(&self) {
+        if let Ok(state) = self.state.lock() {
+            let _guard = self.resumed.wait_while(state, |s| s.blocked());
+        }
+    }
+
+    /// Record output handed to the frontend.
+    pub fn sent(&self, units: usize) {
+        if let Ok(mut s) = self.state.lock() {
+            s.unacked += units;
+            if s.acks_seen && s.unacked >= HIGH_WATERMARK {
+                s.throttled = true;
+            }
+        }
+    }
+
+    /// Record output the frontend has finished processing.
+    pub fn ack(&self, units: usize) {
+        if let Ok(mut s) = self.state.lock() {
+            s.acks_seen = true;
+            s.unacked = s.unacked.saturating_sub(units);
+            if s.throttled && s.unacked < LOW_WATERMARK {
+                s.throttled = false;
+                self.resumed.notify_all();
+            }
+        }
+    }
+
+    /// Pause or resume reading explicitly (e.g. while the window is hidden).
+    pub fn set_paused(&self, paused: bool) {
+        if let Ok(mut s) = self.state.lock() {
+            s.paused = paused;
+            if !s.blocked() {
+                self.resumed.notify_all();
+            }
+        }
+    }
+
+    /// Whether reading is currently paused, explicitly or by backpressure.
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().map(|s| s.blocked()).unwrap_or(false)
+    }
+
+    /// Forget outstanding output, e.g. when a reloaded frontend re-attaches
+    /// and will never ack what its predecessor was sent.
+    pub fn reset(&self) {
+        if let Ok(mut s) = self.state.lock() {
+            s.unacked = 0;
+            s.acks_seen = false;
+            s.throttled = false;
+            if !s.blocked() {
+                self.resumed.notify_all();
+            }
+        }
+    }
+
+    /// Release the reader for good so it can drain to EOF.
+    pub fn close(&self) {
+        if let Ok(mut s) = self.state.lock() {
+            s.closed = true;
+            self.resumed.notify_all();
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::KataraError;
+
+/// Held while the layouts file is read and rewritten, so saves from two
+/// windows don't drop each other's layout.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Terminal pane arrangement for one workspace: tabs, each holding a tree of splits.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TerminalLayout {
+    #[serde(default)]
+    pub tabs: Vec<LayoutTab>,
+    /// Index into `tabs` of the selected tab.
+    #[serde(default)]
+    pub active_tab: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutTab {
+    #[serde(default)]
+    pub title: Option<String>,
+    pub root: LayoutNode,
+    /// Terminal that had focus within this tab.
+    #[serde(default)]
+    pub focused: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LayoutNode {
+    Pane {
+        terminal_id: String,
+    },
+    Split {
+        direction: SplitDirection,
+        children: Vec<LayoutNode>,
+        /// Relative size of each child; same length as `children`.
+        sizes: Vec<f32>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitDirection {
+    Horizontal,
+    Vertical,
+}
+
+impl TerminalLayout {
+    /// Check the layout is well-formed before storing it.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(i) = self.active_tab {
+            if i >= self.tabs.len() {
+                return Err(format!("Active tab {} out of range", i));
+            }
+        }
+        self.tabs.iter().try_for_each(|tab| tab.root.validate())
+    }
+}
+
+impl LayoutNode {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            LayoutNode::Pane { .. } => Ok(()),
+            LayoutNode::Split {
+                children, sizes, ..
+            } => {
+                if children.is_empty() {
+                    return Err("Split has no children".into());
+                }
+                if sizes.len() != children.len() {
+                    return Err(format!(
+                        "Split has {} children but {} sizes",
+                        children.len(),
+                        sizes.len()
+                    ));
+                }
+                if sizes.iter().any(|s| !s.is_finite() || *s < 0.0) {
+                    return Err("Split sizes must be non-negative numbers".into());
+                }
+                children.iter().try_for_each(LayoutNode::validate)
+            }
+        }
+    }
+}
+
+/// Load the saved layout for `workspace`, if any.
+pub fn get(workspace: &str) -> Result<Option<TerminalLayout>, String> {
+    let mut layouts = load_all()?;
+    Ok(layouts.remove(&workspace_key(workspace)))
+}
+
+/// Store the layout for `workspace`, replacing any previous one.
+///
+/// A layouts file that can't be parsed is replaced, so one corrupt file
+/// doesn't stop layouts being saved ever again.
+pub fn save(workspace: &str, layout: TerminalLayout) -> Result<(), String> {
+    layout.validate()?;
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut layouts = load_all().unwrap_or_else(|e| {
+        eprintln!("[katara] {}; starting a new layouts file", e);
+        HashMap::new()
+    });
+    layouts.insert(workspace_key(workspace), layout);
+
+    let path = store_path().map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(&layouts).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+fn load_all() -> Result<HashMap<String, TerminalLayout>, String> {
+    let path = store_path().map_err(|e| e.to_string())?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid terminal layouts: {}", e))
+}
+
+/// Normalize a workspace path so `~/proj` and `~/proj/` share one layout.
+fn workspace_key(workspace: &str) -> String {
+    std::fs::canonicalize(workspace)
+        .unwrap_or_else(|_| Path::new(workspace).to_path_buf())
+        .display()
+        .to_string()
+}
+
+fn store_path() -> Result<PathBuf, KataraError> {
+    Ok(crate::config::katara_dir()?.join("terminal_layouts.json"))
+}
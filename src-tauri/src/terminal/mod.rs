@@ -1 +1,11 @@
+pub mod ansi;
+pub mod exec;
+pub mod flow;
+pub mod layout;
+pub mod persist;
+pub mod procinfo;
 pub mod pty;
+pub mod recording;
+pub mod ring;
+pub mod scrollback;
+pub mod shell;
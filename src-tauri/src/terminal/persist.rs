@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{mpsc, OnceLock};
+use std::time::Duration;
+
+use crate::config;
+use crate::error::KataraError;
+use crate::terminal::pty::PtyHandle;
+use crate::terminal::shell::ShellConfig;
+
+/// How long the writer collects saves before writing the newest, so a burst
+/// of title or cwd changes rewrites the file once.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Queue of snapshots for the writer thread.
+static WRITER: OnceLock<mpsc::Sender<Vec<SavedTerminal>>> = OnceLock::new();
+
+/// A terminal as remembered across app restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedTerminal {
+    pub id: String,
+    pub cwd: Option<String>,
+    pub shell: Option<ShellConfig>,
+    /// Names of the env profiles it used; their secrets are resolved again
+    /// on restore rather than saved.
+    #[serde(default)]
+    pub env_profiles: Vec<String>,
+    /// Names of the variables it inherited from a session. Their values can
+    /// be secrets, so they aren't saved and the restored shell goes without.
+    #[serde(default)]
+    pub dropped_env: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub title: Option<String>,
+    pub rows: u16,
+    pub cols: u16,
+    /// The shell runs on another host (SSH).
+    #[serde(default)]
+    pub remote: bool,
+}
+
+/// Load the terminals saved by the previous run.
+pub fn load() -> Result<Vec<SavedTerminal>, String> {
+    let path = store_path().map_err(|e| e.to_string())?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid saved terminals: {}", e))
+}
+
+/// Save metadata for every open terminal, replacing the previous snapshot.
+///
+/// Called whenever a terminal is opened, closed, or reports a new cwd/title.
+/// Only takes the snapshot; a single writer thread writes the latest one.
+/// Failures are logged rather than surfaced; persistence is best-effort.
+pub fn save(terminals: &HashMap<String, PtyHandle>) {
+    let saved: Vec<SavedTerminal> = terminals.values().map(PtyHandle::snapshot).collect();
+    let writer = WRITER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || run_writer(rx));
+        tx
+    });
+    let _ = writer.send(saved);
+}
+
+/// Write the newest snapshot queued within `SAVE_DEBOUNCE` of the first.
+fn run_writer(rx: mpsc::Receiver<Vec<SavedTerminal>>) {
+    while let Ok(mut saved) = rx.recv() {
+        std::thread::sleep(SAVE_DEBOUNCE);
+        while let Ok(newer) = rx.try_recv() {
+            saved = newer;
+        }
+        if let Err(e) = write(&saved) {
+            eprintln!("[katara] Failed to save terminals: {}", e);
+        }
+    }
+}
+
+fn write(saved: &[SavedTerminal]) -> Result<(), String> {
+    let path = store_path().map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(saved).map_err(|e| e.to_string())?;
+    // Write then rename, so a crash mid-write can't leave a torn file
+    let tmp = path.with_extension("json.tmp");
+    // Cwds, SSH targets and env values are private from the start; a
+    // leftover from a crashed write is replaced rather than written through
+    let _ = std::fs::remove_file(&tmp);
+    config::create_private_file(&tmp)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+fn store_path() -> Result<PathBuf, KataraError> {
+    Ok(config::katara_dir()?.join("terminals.json"))
+}
@@ -0,0 +1,98 @@
+/// Best-effort lookup of a process's current working directory.
+///
+/// Used as a fallback when the shell doesn't report its cwd via OSC 7.
+/// Only implemented on Linux (via /proc); other platforms return `None`.
+pub fn cwd_of(pid: u32) -> Option<String> {
+    if cfg!(target_os = "linux") {
+        std::fs::read_link(format!("/proc/{}/cwd", pid))
+            .ok()
+            .map(|p| p.display().to_string())
+    } else {
+        None
+    }
+}
+
+/// Short command name of a process (e.g. "cargo"), if it can be determined.
+pub fn name_of(pid: u32) -> Option<String> {
+    if cfg!(target_os = "linux") {
+        std::fs::read_to_string(format!("/proc/{}/comm", pid))
+            .ok()
+            .map(|s| s.trim_end().to_string())
+    } else {
+        None
+    }
+}
+
+/// When a process started, in a form only useful for comparing with
+/// another reading: clock ticks since boot on Linux, `ps`'s `lstart` on
+/// other Unixes. `None` if it can't be determined.
+pub fn start_time(pid: u32) -> Option<String> {
+    if cfg!(target_os = "linux") {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        // Field 22; the command name before it may contain spaces
+        let (_, fields) = stat.rsplit_once(')')?;
+        fields.split_whitespace().nth(19).map(str::to_string)
+    } else if cfg!(unix) {
+        let output = std::process::Command::new("ps")
+            .args(["-o", "lstart=", "-p", &pid.to_string()])
+            .output()
+            .ok()?;
+        let started = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !started.is_empty()).then_some(started)
+    } else {
+        None
+    }
+}
+
+/// Whether a process with this pid exists. Always `false` off Unix.
+pub fn is_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        // Signal 0 only checks the process can be signalled
+        unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// Ask a single process to exit (SIGTERM). No-op off Unix.
+pub fn terminate(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(pid as libc::pid_t, libc::SIGTERM);
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// Ask every process in a group to exit (SIGTERM). No-op off Unix.
+pub fn terminate_group(pgid: u32) {
+    #[cfg(unix)]
+    signal_group(pgid, libc::SIGTERM);
+    #[cfg(not(unix))]
+    let _ = pgid;
+}
+
+/// Forcefully kill every process in a group (SIGKILL). No-op off Unix.
+pub fn kill_group(pgid: u32) {
+    #[cfg(unix)]
+    signal_group(pgid, libc::SIGKILL);
+    #[cfg(not(unix))]
+    let _ = pgid;
+}
+
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: libc::c_int) {
+    // A negative pid targets the whole process group
+    let result = unsafe { libc::kill(-(pgid as libc::pid_t), signal) };
+    if result != 0 {
+        eprintln!(
+            "[katara] Failed to signal process group {}: {}",
+            pgid,
+            std::io::Error::last_os_error()
+        );
+    }
+}
@@ -1,8 +1,26 @@
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use portable_pty::{native_pty_system, PtySize};
 use serde::Serialize;
+use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::sync::Mutex;
-use tauri::Emitter;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast;
+
+use crate::config::env_profiles;
+use crate::config::manager::TerminalSettings;
+use crate::state::AppState;
+use crate::terminal::ansi::{self, AnsiStripper};
+use crate::terminal::flow::FlowControl;
+use crate::terminal::persist::{self, SavedTerminal};
+use crate::terminal::procinfo;
+use crate::terminal::recording::Recording;
+use crate::terminal::ring::{self, OutputRing, Replay};
+use crate::terminal::scrollback::{self, Scrollback, SearchMatch};
+use crate::terminal::shell::{self, ShellConfig};
+use crate::window_scope;
 
 /// Handle to a spawned PTY terminal instance.
 ///
@@ -11,10 +29,100 @@ use tauri::Emitter;
 pub struct PtyHandle {
     pub id: String,
     writer: Mutex<Box<dyn Write + Send>>,
-    _child: Box<dyn portable_pty::Child + Send + Sync>,
-    master: Mutex<Box<dyn portable_pty::MasterPty + Send>>,
+    /// Kill handle for the shell; the child itself lives in the exit-watcher thread.
+    killer: Mutex<Box<dyn portable_pty::ChildKiller + Send + Sync>>,
+    /// The shell until `watch_exit` hands it to the exit watcher.
+    child: Mutex<Option<Box<dyn portable_pty::Child + Send + Sync>>>,
+    master: Arc<Mutex<Box<dyn portable_pty::MasterPty + Send>>>,
+    /// Tap on decoded PTY output for in-process consumers (e.g. run_in_terminal).
+    output_tx: broadcast::Sender<String>,
+    /// Current size as (rows, cols).
+    size: Mutex<(u16, u16)>,
+    /// Asciicast recording, if one was started for this terminal.
+    recording: Arc<Mutex<Option<Recording>>>,
+    /// Plain-text scrollback for server-side search.
+    scrollback: Arc<Mutex<Scrollback>>,
+    /// Raw recent output, replayed by `attach`.
+    ring: Arc<Mutex<OutputRing>>,
+    /// Metadata derived from the output stream (title, cwd, ...).
+    meta: Arc<Mutex<TerminalMeta>>,
+    /// OS process id of the shell, when the platform reports one.
+    pid: Option<u32>,
+    /// Pause/resume state for reading PTY output.
+    flow: Arc<FlowControl>,
+    /// Shell, env profiles and extra environment the terminal was launched
+    /// with, kept so it can be respawned after a restart.
+    shell: Option<ShellConfig>,
+    env_profiles: Vec<String>,
+    /// Names of the variables inherited from a session; not their values.
+    session_env: Vec<String>,
+    env: HashMap<String, String>,
+    remote: bool,
+}
+
+/// Terminal state reported by the shell through escape sequences.
+#[derive(Debug, Clone, Default)]
+pub struct TerminalMeta {
+    /// Window title set via OSC 0/2 (e.g. "vim — main.rs").
+    pub title: Option<String>,
+    /// Current working directory of the shell.
+    pub cwd: Option<String>,
+    /// Whether the shell reports its cwd via OSC 7. Once it does, the
+    /// process-based fallback is no longer consulted.
+    pub cwd_from_osc: bool,
+    /// The foreground application has enabled bracketed paste.
+    pub bracketed_paste: bool,
+}
+
+/// Summary of a terminal for `list_terminals`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalInfo {
+    pub id: String,
+    pub title: Option<String>,
+    pub cwd: Option<String>,
+    /// Job running in the foreground, or `None` when the shell is idle.
+    pub process: Option<ProcessInfo>,
+    /// Output reading is paused (explicitly or by backpressure).
+    pub paused: bool,
+    pub rows: u16,
+    pub cols: u16,
 }
 
+/// A process running inside a terminal.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: Option<String>,
+}
+
+/// Outcome of `kill_terminal`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CloseResult {
+    Closed,
+    /// A job is still running in the foreground; nothing was killed.
+    /// Call again with `force` to stop it and close the terminal.
+    ConfirmRequired {
+        process: ProcessInfo,
+    },
+}
+
+/// How long to keep collecting PTY output before emitting a batch.
+const BATCH_WINDOW: Duration = Duration::from_millis(8);
+
+/// Raw reads allowed to queue up between the PTY reader and the batcher.
+const MAX_QUEUED_CHUNKS: usize = 16;
+
+/// How often each terminal's foreground process is checked for changes.
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Minimum gap between bell notifications from one terminal, so a burst of
+/// bells (e.g. failed tab completion) doesn't flood the desktop.
+const BELL_NOTIFY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Emit early once a batch grows this large, so huge bursts still stream.
+const MAX_BATCH_BYTES: usize = 64 * 1024;
+
 // Safety: all non-Sync fields are behind Mutex.
 unsafe impl Sync for PtyHandle {}
 
@@ -22,37 +130,122 @@ unsafe impl Sync for PtyHandle {}
 pub struct TerminalDataPayload {
     pub id: String,
     pub data: String,
+    /// Stream offset just past `data` (see [`OutputRing`]).
+    pub offset: u64,
+}
+
+/// Result of attaching to a terminal: its current state plus buffered output.
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalAttach {
+    pub info: TerminalInfo,
+    pub replay: Replay,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TerminalTitlePayload {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TerminalBellPayload {
+    pub id: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TerminalCwdPayload {
+    pub id: String,
+    pub cwd: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TerminalProcessPayload {
+    pub id: String,
+    /// `None` once the shell is back at its prompt.
+    pub process: Option<ProcessInfo>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TerminalExitPayload {
+    pub id: String,
+    pub exit_code: u32,
+    pub success: bool,
+}
+
+/// How to launch a terminal: size, shell, working directory and environment.
+#[derive(Debug, Clone, Default)]
+pub struct TerminalOptions {
+    pub rows: u16,
+    pub cols: u16,
+    pub cwd: Option<String>,
+    pub shell: Option<ShellConfig>,
+    /// Env profiles whose variables, secrets resolved, are layered over the
+    /// inherited environment in order. Only the names are kept, so secrets
+    /// are never saved with the terminal.
+    pub env_profiles: Vec<String>,
+    /// Variables inherited from a linked session's explicit env, layered
+    /// over the env profiles. They can be secrets, so only their names are
+    /// saved with the terminal.
+    pub session_env: HashMap<String, String>,
+    /// Extra environment variables layered over the session's.
+    pub env: HashMap<String, String>,
+    /// Title to report until the shell sets its own.
+    pub title: Option<String>,
+    /// Text shown in the terminal before any shell output.
+    pub banner: Option<String>,
+    /// The shell runs on another host (e.g. over SSH), so the local
+    /// process's cwd says nothing about the remote one.
+    pub remote: bool,
 }
 
 impl PtyHandle {
-    /// Spawn a new PTY terminal.
+    /// Spawn a new PTY terminal. Call `watch_exit` once the handle is in
+    /// `state.terminals`.
     pub fn spawn(
         id: String,
-        rows: u16,
-        cols: u16,
-        cwd: Option<String>,
+        opts: TerminalOptions,
+        state: Arc<AppState>,
         app_handle: tauri::AppHandle,
     ) -> Result<Self, String> {
         let pty_system = native_pty_system();
 
         let pair = pty_system
             .openpty(PtySize {
-                rows,
-                cols,
+                rows: opts.rows,
+                cols: opts.cols,
                 pixel_width: 0,
                 pixel_height: 0,
             })
             .map_err(|e| format!("Failed to open PTY: {}", e))?;
 
-        let mut cmd = CommandBuilder::new_default_prog();
-        if let Some(ref dir) = cwd {
+        let mut cmd = shell::build_command(opts.shell.as_ref());
+        if let Some(ref dir) = opts.cwd {
             cmd.cwd(dir);
         }
+        // Proxy variables go to local shells only; explicit env overrides them
+        if !opts.remote {
+            for (key, value) in state.settings().proxy.env() {
+                cmd.env(key, value);
+            }
+        }
+        let profiles = state.settings().env_profiles;
+        for name in &opts.env_profiles {
+            let vars =
+                env_profiles::resolve_env_profile(&profiles, name).map_err(|e| e.to_string())?;
+            for (key, value) in vars {
+                cmd.env(key, value);
+            }
+        }
+        for (key, value) in opts.session_env.iter().chain(&opts.env) {
+            cmd.env(key, value);
+        }
 
         let child = pair
             .slave
             .spawn_command(cmd)
             .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+        let pid = child.process_id();
+        let remote = opts.remote;
 
         let writer = pair
             .master
@@ -64,38 +257,282 @@ impl PtyHandle {
             .try_clone_reader()
             .map_err(|e| format!("Failed to clone PTY reader: {}", e))?;
 
-        // Spawn a blocking reader thread that forwards PTY output to the frontend
-        let pty_id = id.clone();
-        tokio::task::spawn_blocking(move || {
+        let (output_tx, _) = broadcast::channel(1024);
+
+        // Reader thread pulls raw bytes off the PTY; the batcher coalesces
+        // them so a burst of output becomes a handful of emits, not thousands.
+        // The channel is bounded so a slow batcher also stalls the reader.
+        let (chunk_tx, chunk_rx) = std::sync::mpsc::sync_channel::<Vec<u8>>(MAX_QUEUED_CHUNKS);
+        let flow = Arc::new(FlowControl::new());
+        let reader_flow = flow.clone();
+        // Queued ahead of the shell so it goes through the same pipeline
+        // (scrollback, recording, terminal:data) as real output
+        if let Some(ref banner) = opts.banner {
+            let _ = chunk_tx.send(banner.clone().into_bytes());
+        }
+        std::thread::spawn(move || {
             let mut buf = [0u8; 4096];
             loop {
+                reader_flow.wait();
                 match reader.read(&mut buf) {
                     Ok(0) => break,
                     Ok(n) => {
-                        let data = String::from_utf8_lossy(&buf[..n]).to_string();
-                        let _ = app_handle.emit(
-                            "terminal:data",
-                            TerminalDataPayload {
+                        if chunk_tx.send(buf[..n].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let recording: Arc<Mutex<Option<Recording>>> = Arc::new(Mutex::new(None));
+        let scrollback = Arc::new(Mutex::new(Scrollback::new(scrollback::DEFAULT_MAX_LINES)));
+
+        let pty_id = id.clone();
+        let reader_handle = app_handle.clone();
+        let reader_tx = output_tx.clone();
+        let reader_recording = recording.clone();
+        let reader_scrollback = scrollback.clone();
+        let ring_bytes = state.settings().terminal.scrollback_bytes;
+        let ring = Arc::new(Mutex::new(OutputRing::new(
+            ring_bytes.unwrap_or(ring::DEFAULT_MAX_BYTES),
+        )));
+        let reader_ring = ring.clone();
+        let reader_state = state.clone();
+        let batch_flow = flow.clone();
+        let meta = Arc::new(Mutex::new(TerminalMeta {
+            title: opts.title.clone(),
+            cwd: opts.cwd.clone().filter(|_| !opts.remote),
+            ..Default::default()
+        }));
+        let reader_meta = meta.clone();
+        std::thread::spawn(move || {
+            let mut stripper = AnsiStripper::new();
+            let mut last_bell_notify: Option<Instant> = None;
+            batch_output(chunk_rx, |data| {
+                if let Ok(mut rec) = reader_recording.lock() {
+                    if let Some(rec) = rec.as_mut() {
+                        rec.output(&data);
+                    }
+                }
+
+                let text = stripper.feed(&data);
+                if let Ok(mut m) = reader_meta.lock() {
+                    m.bracketed_paste = stripper.bracketed_paste();
+                }
+                if let Ok(mut sb) = reader_scrollback.lock() {
+                    sb.push(&text);
+                }
+
+                let mut new_cwd = None;
+                let mut meta_changed = false;
+                for osc in stripper.take_osc() {
+                    match ansi::parse_osc(&osc) {
+                        // OSC 0 sets icon name + title, OSC 2 sets the title
+                        Some((0 | 2, title)) => {
+                            if let Ok(mut m) = reader_meta.lock() {
+                                m.title = Some(title.to_string());
+                            }
+                            meta_changed = true;
+                            window_scope::emit_for_terminal(
+                                &reader_handle,
+                                &pty_id,
+                                "terminal:title",
+                                TerminalTitlePayload {
+                                    id: pty_id.clone(),
+                                    title: title.to_string(),
+                                },
+                            );
+                        }
+                        // OSC 7 reports the shell's cwd as a file:// URL
+                        Some((7, url)) => {
+                            if let Some(path) = ansi::parse_osc7_path(url) {
+                                if let Ok(mut m) = reader_meta.lock() {
+                                    m.cwd_from_osc = true;
+                                }
+                                new_cwd = Some(path);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Shells that don't emit OSC 7: ask the OS where the shell is.
+                // Output usually follows a `cd` (the next prompt), so checking
+                // once per batch keeps this current without a timer.
+                let from_osc = reader_meta.lock().map(|m| m.cwd_from_osc).unwrap_or(false);
+                if new_cwd.is_none() && !from_osc && !remote {
+                    new_cwd = pid.and_then(procinfo::cwd_of);
+                }
+
+                if let Some(cwd) = new_cwd {
+                    let changed = reader_meta
+                        .lock()
+                        .map(|mut m| {
+                            let changed = m.cwd.as_deref() != Some(cwd.as_str());
+                            m.cwd = Some(cwd.clone());
+                            changed
+                        })
+                        .unwrap_or(false);
+                    if changed {
+                        meta_changed = true;
+                        window_scope::emit_for_terminal(
+                            &reader_handle,
+                            &pty_id,
+                            "terminal:cwd",
+                            TerminalCwdPayload {
                                 id: pty_id.clone(),
-                                data,
+                                cwd,
                             },
                         );
                     }
-                    Err(_) => break,
                 }
-            }
+
+                if stripper.take_bell() {
+                    window_scope::emit_for_terminal(
+                        &reader_handle,
+                        &pty_id,
+                        "terminal:bell",
+                        TerminalBellPayload { id: pty_id.clone() },
+                    );
+                    let due = last_bell_notify.is_none_or(|t| t.elapsed() >= BELL_NOTIFY_INTERVAL);
+                    if due {
+                        let title = reader_meta.lock().ok().and_then(|m| m.title.clone());
+                        if notify_bell(&reader_handle, title.as_deref()) {
+                            last_bell_notify = Some(Instant::now());
+                        }
+                    }
+                }
+
+                if meta_changed {
+                    persist::save(&reader_state.terminals.blocking_read());
+                }
+
+                // Buffered whether or not anyone is listening, so a frontend
+                // that reloads can attach and catch up
+                let offset = reader_ring.lock().map(|mut r| r.push(&data)).unwrap_or(0);
+                batch_flow.sent(data.encode_utf16().count());
+                let _ = reader_tx.send(data.clone());
+                window_scope::emit_for_terminal(
+                    &reader_handle,
+                    &pty_id,
+                    "terminal:data",
+                    TerminalDataPayload {
+                        id: pty_id.clone(),
+                        data,
+                        offset,
+                    },
+                );
+            });
         });
 
+        let master = Arc::new(Mutex::new(pair.master));
+
+        // Poll the foreground process group so the UI can show what's running.
+        // Polling (rather than checking per output batch) also catches quiet
+        // jobs like `sleep`. Stops once the handle (and its master) is dropped.
+        if cfg!(unix) {
+            let weak_master = Arc::downgrade(&master);
+            let process_handle = app_handle.clone();
+            let pty_id = id.clone();
+            std::thread::spawn(move || {
+                let mut last = None;
+                while let Some(master) = weak_master.upgrade() {
+                    let current = foreground_process(&master, pid);
+                    drop(master);
+                    let current_pid = current.as_ref().map(|p| p.pid);
+                    if current_pid != last {
+                        last = current_pid;
+                        window_scope::emit_for_terminal(
+                            &process_handle,
+                            &pty_id,
+                            "terminal:process",
+                            TerminalProcessPayload {
+                                id: pty_id.clone(),
+                                process: current,
+                            },
+                        );
+                    }
+                    std::thread::sleep(PROCESS_POLL_INTERVAL);
+                }
+            });
+        }
+
+        let killer = child.clone_killer();
+
         Ok(PtyHandle {
             id,
             writer: Mutex::new(writer),
-            _child: child,
-            master: Mutex::new(pair.master),
+            killer: Mutex::new(killer),
+            child: Mutex::new(Some(child)),
+            master,
+            output_tx,
+            size: Mutex::new((opts.rows, opts.cols)),
+            recording,
+            scrollback,
+            ring,
+            meta,
+            pid,
+            flow,
+            shell: opts.shell,
+            env_profiles: opts.env_profiles,
+            session_env: opts.session_env.into_keys().collect(),
+            env: opts.env,
+            remote: opts.remote,
         })
     }
 
+    /// Start a watcher thread that waits for the shell to exit,
+    /// notifies the frontend, and drops the terminal from state.
+    ///
+    /// Called after the handle is inserted into `state.terminals`, so a shell
+    /// that exits straight away is still removed.
+    pub fn watch_exit(&self, state: Arc<AppState>, app_handle: tauri::AppHandle) {
+        let Some(mut child) = self.child.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+            return;
+        };
+        let pty_id = self.id.clone();
+        std::thread::spawn(move || {
+            let (exit_code, success) = match child.wait() {
+                Ok(status) => (status.exit_code(), status.success()),
+                Err(e) => {
+                    eprintln!("[katara] Error waiting on terminal {}: {}", pty_id, e);
+                    (1, false)
+                }
+            };
+            println!(
+                "[katara] Terminal {} exited with code {}",
+                pty_id, exit_code
+            );
+
+            window_scope::emit_for_terminal(
+                &app_handle,
+                &pty_id,
+                "terminal:exit",
+                TerminalExitPayload {
+                    id: pty_id.clone(),
+                    exit_code,
+                    success,
+                },
+            );
+            // Only a shell that exited on its own is still in the map; killed
+            // terminals were removed (and persisted) by whoever dropped them.
+            let mut terminals = state.terminals.blocking_write();
+            if terminals.remove(&pty_id).is_some() {
+                persist::save(&terminals);
+            }
+        });
+    }
+
+    /// Subscribe to PTY output from this point on.
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.output_tx.subscribe()
+    }
+
     /// Write data (user keystrokes) to the PTY.
     pub fn write(&self, data: &[u8]) -> Result<(), String> {
+        self.with_recording(|rec| rec.input(data));
         self.writer
             .lock()
             .map_err(|e| format!("PTY writer lock poisoned: {}", e))?
@@ -103,8 +540,29 @@ impl PtyHandle {
             .map_err(|e| format!("PTY write error: {}", e))
     }
 
+    /// Paste text into the PTY.
+    ///
+    /// Newlines are normalized to `\r` (what the Enter key sends). If the
+    /// application enabled bracketed paste, the text is wrapped in paste
+    /// markers so shells and REPLs insert it instead of running each line.
+    pub fn paste(&self, text: &str) -> Result<(), String> {
+        let mut normalized = text.replace("\r\n", "\r").replace('\n', "\r");
+        let data = if self.meta().bracketed_paste {
+            // An embedded end marker would let pasted text escape the paste
+            normalized = normalized.replace("\x1b[201~", "");
+            format!("\x1b[200~{}\x1b[201~", normalized)
+        } else {
+            normalized
+        };
+        self.write(data.as_bytes())
+    }
+
     /// Resize the PTY.
     pub fn resize(&self, rows: u16, cols: u16) -> Result<(), String> {
+        if let Ok(mut size) = self.size.lock() {
+            *size = (rows, cols);
+        }
+        self.with_recording(|rec| rec.resize(cols, rows));
         self.master
             .lock()
             .map_err(|e| format!("PTY master lock poisoned: {}", e))?
@@ -116,4 +574,304 @@ impl PtyHandle {
             })
             .map_err(|e| format!("PTY resize error: {}", e))
     }
+
+    /// Current size as (rows, cols).
+    pub fn size(&self) -> (u16, u16) {
+        self.size.lock().map(|s| *s).unwrap_or((24, 80))
+    }
+
+    /// Snapshot of the metadata reported by the shell.
+    pub fn meta(&self) -> TerminalMeta {
+        self.meta.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+
+    /// Summary for `list_terminals`.
+    pub fn info(&self) -> TerminalInfo {
+        let (rows, cols) = self.size();
+        let meta = self.meta();
+        TerminalInfo {
+            id: self.id.clone(),
+            title: meta.title,
+            cwd: meta.cwd,
+            process: self.foreground_process(),
+            paused: self.flow.is_paused(),
+            rows,
+            cols,
+        }
+    }
+
+    /// The job running in the foreground, if it isn't the shell itself.
+    ///
+    /// Only detectable on Unix, where the PTY reports its foreground process group.
+    pub fn foreground_process(&self) -> Option<ProcessInfo> {
+        foreground_process(&self.master, self.pid)
+    }
+
+    /// Pause or resume reading output from the PTY.
+    pub fn set_paused(&self, paused: bool) {
+        self.flow.set_paused(paused);
+    }
+
+    /// Acknowledge output the frontend has processed (in UTF-16 code units).
+    pub fn ack(&self, units: usize) {
+        self.flow.ack(units);
+    }
+
+    /// Metadata needed to respawn this terminal after a restart.
+    pub fn snapshot(&self) -> SavedTerminal {
+        let (rows, cols) = self.size();
+        let meta = self.meta();
+        SavedTerminal {
+            id: self.id.clone(),
+            cwd: meta.cwd,
+            shell: self.shell.clone(),
+            env_profiles: self.env_profiles.clone(),
+            dropped_env: self.session_env.clone(),
+            env: self.env.clone(),
+            title: meta.title,
+            rows,
+            cols,
+            remote: self.remote,
+        }
+    }
+
+    /// Start a fresh recording, discarding any previous one.
+    pub fn start_recording(&self) -> Result<(), String> {
+        let (rows, cols) = self.size();
+        *self
+            .recording
+            .lock()
+            .map_err(|e| format!("Recording lock poisoned: {}", e))? =
+            Some(Recording::new(cols, rows));
+        Ok(())
+    }
+
+    /// Stop capturing; the recording is kept until exported or restarted.
+    pub fn stop_recording(&self) -> Result<(), String> {
+        let mut rec = self
+            .recording
+            .lock()
+            .map_err(|e| format!("Recording lock poisoned: {}", e))?;
+        match rec.as_mut() {
+            Some(r) => {
+                r.active = false;
+                Ok(())
+            }
+            None => Err(format!("Terminal {} has no recording", self.id)),
+        }
+    }
+
+    /// Render the current recording as an asciicast v2 document.
+    pub fn export_recording(&self, title: Option<&str>) -> Result<String, String> {
+        let rec = self
+            .recording
+            .lock()
+            .map_err(|e| format!("Recording lock poisoned: {}", e))?;
+        let rec = rec
+            .as_ref()
+            .ok_or_else(|| format!("Terminal {} has no recording", self.id))?;
+        rec.to_asciicast(title).map_err(|e| e.to_string())
+    }
+
+    /// Apply new scrollback limits to this terminal. The byte limit also
+    /// sizes the replay buffer.
+    pub fn set_scrollback_limits(&self, max_lines: usize, max_bytes: Option<usize>) {
+        if let Ok(mut sb) = self.scrollback.lock() {
+            sb.set_limits(max_lines, max_bytes);
+        }
+        if let Ok(mut ring) = self.ring.lock() {
+            ring.set_max_bytes(max_bytes.unwrap_or(ring::DEFAULT_MAX_BYTES));
+        }
+    }
+
+    /// Discard the stored scrollback and replay buffer (does not clear the
+    /// frontend's screen).
+    pub fn clear_buffer(&self) -> Result<(), String> {
+        self.ring
+            .lock()
+            .map(|mut r| r.clear())
+            .map_err(|e| format!("Output buffer lock poisoned: {}", e))?;
+        self.scrollback
+            .lock()
+            .map(|mut sb| sb.clear())
+            .map_err(|e| format!("Scrollback lock poisoned: {}", e))
+    }
+
+    /// Start (or restart) streaming to a frontend: returns buffered output
+    /// from stream offset `since` and resets flow-control accounting.
+    pub fn attach(&self, since: Option<u64>) -> Result<TerminalAttach, String> {
+        let replay = self
+            .ring
+            .lock()
+            .map(|r| r.since(since))
+            .map_err(|e| format!("Output buffer lock poisoned: {}", e))?;
+        self.flow.reset();
+        Ok(TerminalAttach {
+            info: self.info(),
+            replay,
+        })
+    }
+
+    /// Search the scrollback for `pattern`, returning at most `limit` matches.
+    pub fn search(&self, pattern: &regex::Regex, limit: usize) -> Result<Vec<SearchMatch>, String> {
+        self.scrollback
+            .lock()
+            .map(|sb| sb.search(pattern, limit))
+            .map_err(|e| format!("Scrollback lock poisoned: {}", e))
+    }
+
+    fn with_recording(&self, f: impl FnOnce(&mut Recording)) {
+        if let Ok(mut rec) = self.recording.lock() {
+            if let Some(rec) = rec.as_mut() {
+                f(rec);
+            }
+        }
+    }
+}
+
+impl Drop for PtyHandle {
+    /// Make sure the shell dies with its handle; the exit watcher then reaps it.
+    fn drop(&mut self) {
+        // A paused reader would otherwise never see EOF
+        self.flow.close();
+        if let Ok(mut killer) = self.killer.lock() {
+            let _ = killer.kill();
+        }
+        // Never watched, so reap it here
+        if let Some(mut child) = self.child.get_mut().ok().and_then(|c| c.take()) {
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Apply the configured scrollback limits to every open terminal.
+///
+/// The global byte budget is shared evenly, so this runs whenever a terminal
+/// is opened or closed, and when the settings change.
+pub fn apply_scrollback_limits(
+    terminals: &HashMap<String, PtyHandle>,
+    settings: &TerminalSettings,
+) {
+    let share = settings
+        .total_scrollback_bytes
+        .map(|total| total / terminals.len().max(1));
+    let max_bytes = match (settings.scrollback_bytes, share) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    for handle in terminals.values() {
+        handle.set_scrollback_limits(settings.scrollback_lines, max_bytes);
+    }
+}
+
+/// Show an OS notification for a terminal bell, if the user opted in and the
+/// main window isn't focused. Returns whether a notification was shown.
+fn notify_bell(app_handle: &tauri::AppHandle, title: Option<&str>) -> bool {
+    let focused = app_handle
+        .get_webview_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false);
+    if focused {
+        return false;
+    }
+    let state = app_handle.state::<Arc<AppState>>();
+    if !state.settings().terminal.bell_notifications {
+        return false;
+    }
+    app_handle
+        .notification()
+        .builder()
+        .title("Terminal bell")
+        .body(title.unwrap_or("A terminal needs your attention"))
+        .show()
+        .is_ok()
+}
+
+/// Foreground process group leader of the PTY, unless it is the shell itself.
+fn foreground_process(
+    master: &Mutex<Box<dyn portable_pty::MasterPty + Send>>,
+    shell_pid: Option<u32>,
+) -> Option<ProcessInfo> {
+    #[cfg(unix)]
+    {
+        let pgid = master.lock().ok()?.process_group_leader()? as u32;
+        if Some(pgid) == shell_pid {
+            return None;
+        }
+        Some(ProcessInfo {
+            pid: pgid,
+            name: procinfo::name_of(pgid),
+        })
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (master, shell_pid);
+        None
+    }
+}
+
+/// Coalesce raw PTY chunks into batches and hand each batch to `emit` as text.
+///
+/// A batch closes when `BATCH_WINDOW` elapses after its first chunk or when it
+/// reaches `MAX_BATCH_BYTES`. Incomplete UTF-8 sequences at the end of a batch
+/// are carried over so multi-byte characters are never split.
+fn batch_output(rx: Receiver<Vec<u8>>, mut emit: impl FnMut(String)) {
+    let mut pending: Vec<u8> = Vec::new();
+
+    while let Ok(first) = rx.recv() {
+        pending.extend_from_slice(&first);
+        let deadline = Instant::now() + BATCH_WINDOW;
+        let mut closed = false;
+
+        while pending.len() < MAX_BATCH_BYTES {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match rx.recv_timeout(remaining) {
+                Ok(chunk) => pending.extend_from_slice(&chunk),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+
+        let keep = if closed {
+            0
+        } else {
+            incomplete_utf8_tail(&pending)
+        };
+        let tail = pending.split_off(pending.len() - keep);
+        if !pending.is_empty() {
+            emit(String::from_utf8_lossy(&pending).into_owned());
+        }
+        pending = tail;
+
+        if closed {
+            return;
+        }
+    }
+
+    if !pending.is_empty() {
+        emit(String::from_utf8_lossy(&pending).into_owned());
+    }
+}
+
+/// Number of trailing bytes that form the start of a not-yet-complete UTF-8 character.
+fn incomplete_utf8_tail(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => 0,
+        // error_len() == None means the input ended mid-character
+        Err(e) if e.error_len().is_none() => bytes.len() - e.valid_up_to(),
+        Err(_) => {
+            // Invalid data earlier on; only hold back a trailing partial sequence
+            let start = bytes.len().saturating_sub(3);
+            (start..bytes.len())
+                .find(|&i| std::str::from_utf8(&bytes[i..]).is_err_and(|e| e.error_len().is_none()))
+                .map(|i| bytes.len() - i)
+                .unwrap_or(0)
+        }
+    }
 }
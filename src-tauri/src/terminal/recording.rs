@@ -0,0 +1,84 @@
+use serde::Serialize;
+use std::time::Instant;
+
+/// An in-progress or finished terminal recording in asciicast v2 form.
+///
+/// See https://docs.asciinema.org/manual/asciicast/v2/ for the file format.
+#[derive(Debug)]
+pub struct Recording {
+    started_at: Instant,
+    /// Unix timestamp (seconds) of the start, written to the header.
+    timestamp: u64,
+    width: u16,
+    height: u16,
+    /// Whether new output/input is still being captured.
+    pub active: bool,
+    events: Vec<(f64, &'static str, String)>,
+}
+
+#[derive(Serialize)]
+struct AsciicastHeader<'a> {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+}
+
+impl Recording {
+    pub fn new(cols: u16, rows: u16) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self {
+            started_at: Instant::now(),
+            timestamp,
+            width: cols,
+            height: rows,
+            active: true,
+            events: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, kind: &'static str, data: String) {
+        if self.active {
+            let t = self.started_at.elapsed().as_secs_f64();
+            self.events.push((t, kind, data));
+        }
+    }
+
+    /// Record terminal output.
+    pub fn output(&mut self, data: &str) {
+        self.push("o", data.to_string());
+    }
+
+    /// Record user input written to the PTY.
+    pub fn input(&mut self, data: &[u8]) {
+        self.push("i", String::from_utf8_lossy(data).into_owned());
+    }
+
+    /// Record a terminal resize.
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.push("r", format!("{}x{}", cols, rows));
+    }
+
+    /// Serialize as an asciicast v2 document (header line + one event per line).
+    pub fn to_asciicast(&self, title: Option<&str>) -> Result<String, serde_json::Error> {
+        let header = AsciicastHeader {
+            version: 2,
+            width: self.width,
+            height: self.height,
+            timestamp: self.timestamp,
+            title,
+        };
+        let mut out = serde_json::to_string(&header)?;
+        out.push('\n');
+        for (t, kind, data) in &self.events {
+            out.push_str(&serde_json::to_string(&(t, kind, data))?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,102 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Amount of raw output kept per terminal for re-attaching when no
+/// scrollback byte limit is configured.
+pub const DEFAULT_MAX_BYTES: usize = 1024 * 1024;
+
+/// Raw PTY output (escape sequences included) kept so a frontend can attach
+/// after a reload and redraw exactly what it missed.
+///
+/// Every byte of output has a stream offset counted from the start of the
+/// terminal. `terminal:data` events carry the offset just past their data,
+/// so an attaching frontend can drop events it already got from the replay.
+#[derive(Debug)]
+pub struct OutputRing {
+    chunks: VecDeque<String>,
+    /// Stream offset of the first byte still held.
+    start: u64,
+    /// Stream offset just past the last byte written.
+    end: u64,
+    bytes: usize,
+    max_bytes: usize,
+}
+
+/// Buffered output returned by `attach_terminal`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Replay {
+    pub data: String,
+    /// Stream offset of the first byte of `data`. Greater than the requested
+    /// offset when older output was already evicted.
+    pub start: u64,
+    /// Stream offset just past `data`; events at or below it are duplicates.
+    pub end: u64,
+}
+
+impl OutputRing {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            start: 0,
+            end: 0,
+            bytes: 0,
+            max_bytes,
+        }
+    }
+
+    /// Append a chunk of output, returning the stream offset just past it.
+    pub fn push(&mut self, data: &str) -> u64 {
+        self.end += data.len() as u64;
+        self.bytes += data.len();
+        self.chunks.push_back(data.to_string());
+        self.evict();
+        self.end
+    }
+
+    /// Change the size limit, evicting old output right away if needed.
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.evict();
+    }
+
+    /// Evict whole chunks so the replay never starts mid-batch.
+    fn evict(&mut self) {
+        while self.bytes > self.max_bytes && self.chunks.len() > 1 {
+            if let Some(old) = self.chunks.pop_front() {
+                self.bytes -= old.len();
+                self.start += old.len() as u64;
+            }
+        }
+    }
+
+    /// Everything buffered from stream offset `since` (or from the oldest
+    /// byte still held) onwards.
+    pub fn since(&self, since: Option<u64>) -> Replay {
+        let from = since.unwrap_or(0).clamp(self.start, self.end);
+        let mut data = String::with_capacity((self.end - from) as usize);
+        let mut offset = self.start;
+        for chunk in &self.chunks {
+            let chunk_end = offset + chunk.len() as u64;
+            if chunk_end > from {
+                let mut skip = from.saturating_sub(offset) as usize;
+                while !chunk.is_char_boundary(skip) {
+                    skip -= 1;
+                }
+                data.push_str(&chunk[skip..]);
+            }
+            offset = chunk_end;
+        }
+        Replay {
+            start: self.end - data.len() as u64,
+            end: self.end,
+            data,
+        }
+    }
+
+    /// Drop all buffered output. Offsets keep counting from where they were.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.bytes = 0;
+        self.start = self.end;
+    }
+}
@@ -0,0 +1,134 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Default number of plain-text lines kept per terminal.
+pub const DEFAULT_MAX_LINES: usize = 10_000;
+
+/// Plain-text scrollback for a terminal, used for server-side search.
+///
+/// Output (with escape sequences already stripped) is split into lines. Lines are
+/// numbered from the start of the terminal so positions stay stable while
+/// old lines are evicted from the front, once either the line or byte limit
+/// is exceeded.
+#[derive(Debug)]
+pub struct Scrollback {
+    lines: VecDeque<String>,
+    /// Line currently being written (not yet terminated by `\n`).
+    current: String,
+    /// A `\r` was seen; the next printable char overwrites the line.
+    pending_cr: bool,
+    /// Absolute number of the first line in `lines`.
+    first_line: u64,
+    /// Total bytes held in `lines`.
+    bytes: usize,
+    max_lines: usize,
+    max_bytes: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    /// Absolute line number (0 = first line the terminal ever printed).
+    pub line: u64,
+    /// Column range of the match, in characters.
+    pub start: usize,
+    pub end: usize,
+    /// Full text of the matching line, for previews.
+    pub text: String,
+}
+
+impl Scrollback {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            current: String::new(),
+            pending_cr: false,
+            first_line: 0,
+            bytes: 0,
+            max_lines: max_lines.max(1),
+            max_bytes: None,
+        }
+    }
+
+    /// Change the limits, evicting old lines right away if needed.
+    pub fn set_limits(&mut self, max_lines: usize, max_bytes: Option<usize>) {
+        self.max_lines = max_lines.max(1);
+        self.max_bytes = max_bytes;
+        self.evict();
+    }
+
+    /// Drop all stored lines. Line numbering continues where it left off.
+    pub fn clear(&mut self) {
+        self.first_line += self.lines.len() as u64;
+        self.lines.clear();
+        self.current.clear();
+        self.pending_cr = false;
+        self.bytes = 0;
+    }
+
+    /// Append a chunk of output that has already had escapes stripped.
+    pub fn push(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    let line = std::mem::take(&mut self.current);
+                    self.bytes += line.len();
+                    self.lines.push_back(line);
+                    self.pending_cr = false;
+                }
+                '\r' => self.pending_cr = true,
+                c => {
+                    // Bare carriage return: progress bars and prompts redraw the line
+                    if self.pending_cr {
+                        self.current.clear();
+                        self.pending_cr = false;
+                    }
+                    self.current.push(c);
+                }
+            }
+        }
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        let over_bytes = |sb: &Self| sb.max_bytes.is_some_and(|max| sb.bytes > max);
+        while self.lines.len() > self.max_lines || (over_bytes(self) && !self.lines.is_empty()) {
+            if let Some(line) = self.lines.pop_front() {
+                self.bytes -= line.len();
+            }
+            self.first_line += 1;
+        }
+    }
+
+    /// Iterate over all lines (including the unterminated current one) with
+    /// their absolute line numbers.
+    pub fn lines(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.lines
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(self.current.as_str()))
+            .enumerate()
+            .map(|(i, l)| (self.first_line + i as u64, l))
+    }
+
+    /// Find every match of `pattern` in the scrollback, oldest first.
+    pub fn search(&self, pattern: &regex::Regex, limit: usize) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        for (line_no, line) in self.lines() {
+            for m in pattern.find_iter(line) {
+                if m.start() == m.end() {
+                    continue; // Skip empty matches (e.g. `^`)
+                }
+                matches.push(SearchMatch {
+                    line: line_no,
+                    start: line[..m.start()].chars().count(),
+                    end: line[..m.end()].chars().count(),
+                    text: line.to_string(),
+                });
+                if matches.len() >= limit {
+                    return matches;
+                }
+            }
+        }
+        matches
+    }
+}
@@ -0,0 +1,129 @@
+use portable_pty::CommandBuilder;
+use serde::{Deserialize, Serialize};
+
+/// Which shell a terminal should run.
+///
+/// `program` is either one of the well-known shell names ("bash", "zsh",
+/// "fish", "pwsh") or a path to a custom executable. Extra `args` are passed
+/// through verbatim.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellConfig {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl ShellConfig {
+    /// Build the PTY command for this shell.
+    pub fn to_command(&self) -> CommandBuilder {
+        let mut cmd = CommandBuilder::new(resolve_program(&self.program));
+        // PowerShell prints a banner on every start unless told otherwise
+        if self.args.is_empty() && matches!(self.program.as_str(), "pwsh" | "powershell") {
+            cmd.arg("-NoLogo");
+        }
+        cmd.args(&self.args);
+        cmd
+    }
+}
+
+/// Connection details for a terminal that runs on a remote host over SSH.
+///
+/// The system `ssh` client is launched inside the PTY, so the user's
+/// `~/.ssh/config`, known hosts and agent all apply as usual.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshConfig {
+    pub host: String,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub identity_file: Option<String>,
+    /// Forward the local SSH agent (`-A`).
+    #[serde(default)]
+    pub forward_agent: bool,
+    /// Directory to `cd` into on the remote host before starting its shell.
+    #[serde(default)]
+    pub remote_cwd: Option<String>,
+    /// Extra arguments passed to `ssh` before the destination.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+impl SshConfig {
+    /// The `ssh` invocation for this connection, as a shell to launch.
+    pub fn to_shell_config(&self) -> ShellConfig {
+        // -t: we run a remote command when changing directory, which would
+        // otherwise disable the remote PTY
+        let mut args = vec!["-t".to_string()];
+        if let Some(port) = self.port {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        if let Some(ref identity) = self.identity_file {
+            args.extend(["-i".to_string(), identity.clone()]);
+        }
+        if self.forward_agent {
+            args.push("-A".to_string());
+        }
+        args.extend(self.extra_args.iter().cloned());
+        args.push(match self.user {
+            Some(ref user) => format!("{}@{}", user, self.host),
+            None => self.host.clone(),
+        });
+        if let Some(ref dir) = self.remote_cwd {
+            args.push(format!(
+                "cd '{}' && exec \"$SHELL\" -l",
+                dir.replace('\'', "'\\''")
+            ));
+        }
+        ShellConfig {
+            program: "ssh".to_string(),
+            args,
+        }
+    }
+}
+
+/// Map a well-known shell name to the executable to launch.
+/// Anything else is treated as a path/command and used as-is.
+fn resolve_program(program: &str) -> String {
+    match program {
+        "bash" | "zsh" | "fish" | "pwsh" | "sh" => program.to_string(),
+        "powershell" => "powershell.exe".to_string(),
+        "cmd" => "cmd.exe".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Build the command for a terminal, falling back to the user's login shell
+/// when no shell is configured.
+pub fn build_command(shell: Option<&ShellConfig>) -> CommandBuilder {
+    match shell {
+        Some(s) if !s.program.is_empty() => s.to_command(),
+        _ => CommandBuilder::new_default_prog(),
+    }
+}
+
+/// Build a command that runs `command` non-interactively and exits.
+///
+/// Uses the configured shell's "run this string" flag, or `$SHELL -c` /
+/// `cmd /C` when no shell is configured.
+pub fn build_exec_command(shell: Option<&ShellConfig>, command: &str) -> CommandBuilder {
+    let program = match shell {
+        Some(s) if !s.program.is_empty() => resolve_program(&s.program),
+        _ if cfg!(windows) => "cmd.exe".to_string(),
+        _ => std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string()),
+    };
+
+    let mut cmd = CommandBuilder::new(&program);
+    let name = std::path::Path::new(&program)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match name.as_str() {
+        "cmd" => cmd.args(["/C", command]),
+        "pwsh" | "powershell" => cmd.args(["-NoLogo", "-NoProfile", "-Command", command]),
+        _ => cmd.args(["-c", command]),
+    }
+    cmd
+}
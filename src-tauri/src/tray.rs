@@ -0,0 +1,250 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tauri::menu::{Menu, MenuBuilder, MenuEvent, MenuItemBuilder, SubmenuBuilder};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::claude;
+use crate::config::recent;
+use crate::process::manager::SpawnOptions;
+use crate::process::session::SessionStatus;
+use crate::sink;
+use crate::state::AppState;
+
+const TRAY_ID: &str = "main";
+
+/// How often the tray checks whether the sessions changed.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What the tray shows for one session.
+#[derive(Debug, Clone, PartialEq)]
+struct TraySession {
+    id: String,
+    project: String,
+    status: SessionStatus,
+    pending_approvals: usize,
+}
+
+impl TraySession {
+    fn is_running(&self) -> bool {
+        matches!(
+            self.status,
+            SessionStatus::Starting | SessionStatus::Connected | SessionStatus::Active
+        )
+    }
+}
+
+/// Add the tray icon and keep its menu in sync with the sessions.
+pub fn init(app_handle: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app_handle, &[])?;
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Katara")
+        .menu(&menu)
+        .on_menu_event(on_menu_event);
+    if let Some(icon) = app_handle.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app_handle)?;
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut shown = Vec::new();
+        loop {
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+            let sessions = snapshot(&app_handle).await;
+            if sessions != shown {
+                if let Err(e) = update(&app_handle, &sessions) {
+                    eprintln!("[katara] Failed to update tray: {}", e);
+                }
+                shown = sessions;
+            }
+        }
+    });
+    Ok(())
+}
+
+async fn snapshot(app_handle: &AppHandle) -> Vec<TraySession> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let sessions = state.sessions.read().await;
+    let mut sessions: Vec<_> = sessions.values().collect();
+    sessions.sort_by_key(|s| s.created_at);
+    sessions
+        .into_iter()
+        .map(|s| TraySession {
+            id: s.id.clone(),
+            project: std::path::Path::new(&s.working_dir)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| s.working_dir.clone()),
+            status: s.status.clone(),
+            pending_approvals: s.pending_approvals.len(),
+        })
+        .collect()
+}
+
+fn update(app_handle: &AppHandle, sessions: &[TraySession]) -> tauri::Result<()> {
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    tray.set_menu(Some(build_menu(app_handle, sessions)?))?;
+    tray.set_tooltip(Some(format!("Katara: {}", summary(sessions))))?;
+    Ok(())
+}
+
+/// e.g. "2 running, 1 awaiting approval".
+fn summary(sessions: &[TraySession]) -> String {
+    if sessions.is_empty() {
+        return "No sessions".into();
+    }
+    let running = sessions.iter().filter(|s| s.is_running()).count();
+    let awaiting = sessions.iter().filter(|s| s.pending_approvals > 0).count();
+    format!("{} running, {} awaiting approval", running, awaiting)
+}
+
+fn build_menu(app_handle: &AppHandle, sessions: &[TraySession]) -> tauri::Result<Menu<tauri::Wry>> {
+    let pending: usize = sessions.iter().map(|s| s.pending_approvals).sum();
+    let running = sessions.iter().any(TraySession::is_running);
+
+    let mut menu = MenuBuilder::new(app_handle)
+        .item(
+            &MenuItemBuilder::with_id("summary", summary(sessions))
+                .enabled(false)
+                .build(app_handle)?,
+        )
+        .separator();
+    for session in sessions {
+        let mut label = format!("{}: {}", session.project, status_label(&session.status));
+        if session.pending_approvals > 0 {
+            label.push_str(&format!(" ({} pending)", session.pending_approvals));
+        }
+        menu = menu.text(format!("focus:{}", session.id), label);
+    }
+    if !sessions.is_empty() {
+        menu = menu.separator();
+    }
+
+    let recent = recent::recent_projects();
+    let mut new_session =
+        SubmenuBuilder::new(app_handle, "New Session In").enabled(!recent.is_empty());
+    for dir in &recent {
+        new_session = new_session.text(format!("spawn:{}", dir), dir);
+    }
+
+    menu.item(
+        &MenuItemBuilder::with_id(
+            "approve-all",
+            format!("Approve Pending Tools ({})", pending),
+        )
+        .enabled(pending > 0)
+        .build(app_handle)?,
+    )
+    .item(
+        &MenuItemBuilder::with_id("interrupt-all", "Interrupt All")
+            .enabled(running)
+            .build(app_handle)?,
+    )
+    .item(&new_session.build()?)
+    .separator()
+    .text("show", "Show Katara")
+    .text("quit", "Quit")
+    .build()
+}
+
+fn status_label(status: &SessionStatus) -> &str {
+    match status {
+        SessionStatus::Starting => "starting",
+        SessionStatus::Connected => "connected",
+        SessionStatus::Active => "working",
+        SessionStatus::Idle => "idle",
+        SessionStatus::Disconnected => "disconnected",
+        SessionStatus::Error(_) => "error",
+        SessionStatus::Terminated => "terminated",
+    }
+}
+
+fn on_menu_event(app_handle: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref().to_string();
+    let state = app_handle.state::<Arc<AppState>>().inner().clone();
+    let app_handle = app_handle.clone();
+
+    match id.as_str() {
+        "show" => show_main_window(&app_handle),
+        "quit" => app_handle.exit(0),
+        "approve-all" => {
+            tauri::async_runtime::spawn(async move {
+                let pending: Vec<(String, String)> = state
+                    .sessions
+                    .read()
+                    .await
+                    .values()
+                    .flat_map(|s| {
+                        s.pending_approvals
+                            .iter()
+                            .map(|p| (s.id.clone(), p.request_id.clone()))
+                    })
+                    .collect();
+                for (session_id, request_id) in pending {
+                    if let Err(e) =
+                        claude::respond_to_tool(&state, &session_id, request_id, true, None).await
+                    {
+                        eprintln!("[katara] Tray approve failed for {}: {}", session_id, e);
+                    }
+                }
+            });
+        }
+        "interrupt-all" => {
+            tauri::async_runtime::spawn(async move {
+                let running: Vec<String> = snapshot(&app_handle)
+                    .await
+                    .into_iter()
+                    .filter(TraySession::is_running)
+                    .map(|s| s.id)
+                    .collect();
+                for session_id in running {
+                    if let Err(e) = claude::send_interrupt(&state, &session_id).await {
+                        eprintln!("[katara] Tray interrupt failed for {}: {}", session_id, e);
+                    }
+                }
+            });
+        }
+        _ => {
+            if let Some(session_id) = id.strip_prefix("focus:") {
+                focus_session(&app_handle, session_id);
+            } else if let Some(dir) = id.strip_prefix("spawn:") {
+                let dir = dir.to_string();
+                tauri::async_runtime::spawn(async move {
+                    recent::add_recent_project(&dir);
+                    let started = claude::start_session(
+                        &state,
+                        sink::tauri(app_handle.clone()),
+                        dir,
+                        SpawnOptions::default(),
+                    )
+                    .await;
+                    match started {
+                        Ok(session_id) => focus_session(&app_handle, &session_id),
+                        Err(e) => eprintln!("[katara] Tray spawn failed: {}", e),
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Bring the window up and ask the frontend to switch to the session.
+fn focus_session(app_handle: &AppHandle, session_id: &str) {
+    show_main_window(app_handle);
+    let _ = app_handle.emit(
+        "tray:focus-session",
+        serde_json::json!({ "session_id": session_id }),
+    );
+}
+
+fn show_main_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::error::KataraError;
+use crate::process::session::SessionStatus;
+use crate::state::AppState;
+
+/// Update manifest for the beta channel. Stable uses the endpoints in
+/// `tauri.conf.json` (`plugins.updater.endpoints`).
+const BETA_ENDPOINT: &str =
+    "https://github.com/DaveDushi/katara/releases/download/beta/latest.json";
+
+/// How long `apply` waits for working sessions to finish their turn.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(120);
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A downloaded update waiting to be installed.
+static PENDING: Mutex<Option<(Update, Vec<u8>)>> = Mutex::new(None);
+
+/// An available update.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    /// Release notes.
+    pub notes: Option<String>,
+    pub channel: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DownloadProgress {
+    downloaded: u64,
+    /// Size of the download, if the server reported it.
+    total: Option<u64>,
+}
+
+/// The newest release on the configured channel, if it's newer than this
+/// build.
+pub async fn check(app_handle: &tauri::AppHandle) -> Result<Option<UpdateInfo>, KataraError> {
+    let channel = channel(app_handle);
+    Ok(find_update(app_handle, &channel)
+        .await?
+        .map(|update| info(&update, &channel)))
+}
+
+/// Download the available update, emitting `update:progress` as it
+/// arrives and `update:ready` once it can be applied with a restart.
+pub async fn download(app_handle: &tauri::AppHandle) -> Result<UpdateInfo, KataraError> {
+    let (update, bytes, info) = fetch(app_handle).await?;
+    *PENDING.lock().unwrap_or_else(|e| e.into_inner()) = Some((update, bytes));
+    let _ = app_handle.emit("update:ready", &info);
+    Ok(info)
+}
+
+/// Download the available update, emitting `update:progress`. The plugin
+/// checks the download's signature against the configured key.
+async fn fetch(
+    app_handle: &tauri::AppHandle,
+) -> Result<(Update, Vec<u8>, UpdateInfo), KataraError> {
+    let channel = channel(app_handle);
+    let update = find_update(app_handle, &channel)
+        .await?
+        .ok_or_else(|| KataraError::Update("Already up to date".into()))?;
+    let info = info(&update, &channel);
+    println!("[katara] Downloading update {}", info.version);
+
+    let mut downloaded = 0u64;
+    let bytes = update
+        .download(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = app_handle.emit("update:progress", DownloadProgress { downloaded, total });
+            },
+            || {},
+        )
+        .await
+        .map_err(|e| KataraError::Update(e.to_string()))?;
+    Ok((update, bytes, info))
+}
+
+/// Install the update and restart, downloading it first if that hasn't
+/// happened yet. Only once the download's signature has checked out are
+/// sessions still working given time to finish their turn (unless `force`),
+/// then every session is stopped so no CLI process is orphaned by the
+/// restart.
+pub async fn apply(app_handle: &tauri::AppHandle, force: bool) -> Result<(), KataraError> {
+    let pending = PENDING.lock().unwrap_or_else(|e| e.into_inner()).take();
+    let (update, bytes) = match pending {
+        Some(pending) => pending,
+        None => {
+            let (update, bytes, _) = fetch(app_handle).await?;
+            (update, bytes)
+        }
+    };
+
+    let state = app_handle.state::<Arc<AppState>>().inner().clone();
+    let timeout = if force { Duration::ZERO } else { DRAIN_TIMEOUT };
+    drain_sessions(app_handle, &state, timeout).await;
+
+    println!("[katara] Installing update {}", update.version);
+    if let Err(e) = update.install(&bytes) {
+        // Keep it so the user can retry
+        *PENDING.lock().unwrap_or_else(|e| e.into_inner()) = Some((update, bytes));
+        return Err(KataraError::Update(e.to_string()));
+    }
+    app_handle.restart()
+}
+
+/// Check for an update in the background if `updates.check_on_startup` is
+/// on, emitting `update:available` if there is one.
+pub fn check_on_startup(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<Arc<AppState>>();
+    if !state.settings().updates.check_on_startup || !signed_updates(app_handle) {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        match check(&app_handle).await {
+            Ok(Some(info)) => {
+                println!("[katara] Update available: {}", info.version);
+                let _ = app_handle.emit("update:available", &info);
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("[katara] Update check failed: {}", e),
+        }
+    });
+}
+
+/// Wait up to `timeout` for sessions to stop working, emitting
+/// `update:draining` with the number still busy, then kill every session.
+async fn drain_sessions(app_handle: &tauri::AppHandle, state: &AppState, timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let busy = state
+            .sessions
+            .read()
+            .await
+            .values()
+            .filter(|s| matches!(s.status, SessionStatus::Starting | SessionStatus::Active))
+            .count();
+        let _ = app_handle.emit("update:draining", serde_json::json!({ "busy": busy }));
+        if busy == 0 || Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+
+    let mut sessions = state.sessions.write().await;
+    for (id, mut session) in sessions.drain() {
+        if let Some(ref mut child) = session.process {
+            println!("[katara] Stopping session {} for update", id);
+            let _ = child.kill().await;
+        }
+    }
+}
+
+async fn find_update(
+    app_handle: &tauri::AppHandle,
+    channel: &str,
+) -> Result<Option<Update>, KataraError> {
+    if !signed_updates(app_handle) {
+        return Err(KataraError::Update(
+            "This build has no update signing key, so it can't update itself".into(),
+        ));
+    }
+    let builder = app_handle.updater_builder();
+    let builder = match channel {
+        "beta" => {
+            let url = reqwest::Url::parse(BETA_ENDPOINT)
+                .map_err(|e| KataraError::Update(e.to_string()))?;
+            builder.endpoints(vec![url])
+        }
+        _ => Ok(builder),
+    };
+    builder
+        .and_then(|builder| builder.build())
+        .map_err(|e| KataraError::Update(e.to_string()))?
+        .check()
+        .await
+        .map_err(|e| KataraError::Update(e.to_string()))
+}
+
+/// Whether the build has the public key releases are signed with. Release
+/// builds set `plugins.updater.pubkey` (and `bundle.createUpdaterArtifacts`)
+/// with `tauri build --config`; without a key, nothing can be verified.
+fn signed_updates(app_handle: &tauri::AppHandle) -> bool {
+    app_handle
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(|key| key.as_str())
+        .is_some_and(|key| !key.is_empty())
+}
+
+fn channel(app_handle: &tauri::AppHandle) -> String {
+    app_handle
+        .state::<Arc<AppState>>()
+        .settings()
+        .updates
+        .channel
+}
+
+fn info(update: &Update, channel: &str) -> UpdateInfo {
+    UpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        notes: update.body.clone(),
+        channel: channel.to_string(),
+    }
+}
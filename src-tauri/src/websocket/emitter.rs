@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::sink::Sink;
+use crate::websocket::protocol::{ClaudeMessage, StreamEventMessage};
+
+/// How long text deltas are held so consecutive ones can be sent as a
+/// single `claude:message`. About one frame, which also caps streaming
+/// events at ~60/s per connection.
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(16);
+
+enum Emit {
+    Message {
+        session_id: String,
+        message: Box<ClaudeMessage>,
+    },
+    Event {
+        session_id: String,
+        event: &'static str,
+        payload: serde_json::Value,
+    },
+}
+
+/// A pending delta and when it has to go out.
+struct Pending {
+    session_id: String,
+    stream: StreamEventMessage,
+    deadline: Instant,
+}
+
+/// Forwards a connection's events to the frontend, coalescing streaming
+/// text and tool-input deltas per session. Everything else (tool use,
+/// approvals, results, status) is sent immediately, after any held delta,
+/// so the frontend sees events in the order they arrived.
+#[derive(Clone)]
+pub struct MessageEmitter {
+    tx: mpsc::UnboundedSender<Emit>,
+}
+
+impl MessageEmitter {
+    /// Start the emitter task. It flushes and exits once every clone is
+    /// dropped.
+    pub fn spawn(sink: Sink) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run(sink, rx));
+        Self { tx }
+    }
+
+    /// Emit a CLI message as `claude:message`.
+    pub fn message(&self, session_id: &str, message: ClaudeMessage) {
+        let _ = self.tx.send(Emit::Message {
+            session_id: session_id.to_string(),
+            message: Box::new(message),
+        });
+    }
+
+    /// Emit any other session event, in order with the messages.
+    pub fn emit(&self, session_id: &str, event: &'static str, payload: serde_json::Value) {
+        let _ = self.tx.send(Emit::Event {
+            session_id: session_id.to_string(),
+            event,
+            payload,
+        });
+    }
+}
+
+async fn run(sink: Sink, mut rx: mpsc::UnboundedReceiver<Emit>) {
+    let mut pending: Option<Pending> = None;
+
+    loop {
+        let next = match pending {
+            Some(ref p) => tokio::select! {
+                next = rx.recv() => next,
+                _ = tokio::time::sleep_until(p.deadline) => {
+                    flush(&sink, pending.take());
+                    continue;
+                }
+            },
+            None => rx.recv().await,
+        };
+        let Some(next) = next else {
+            flush(&sink, pending.take());
+            return;
+        };
+
+        match next {
+            Emit::Message {
+                session_id,
+                message,
+            } => match *message {
+                ClaudeMessage::StreamEvent(stream) if delta_text(&stream).is_some() => {
+                    if let Some(ref mut p) = pending {
+                        if p.session_id == session_id && append(&mut p.stream, &stream) {
+                            continue;
+                        }
+                    }
+                    flush(&sink, pending.take());
+                    pending = Some(Pending {
+                        session_id,
+                        stream,
+                        deadline: Instant::now() + FLUSH_INTERVAL,
+                    });
+                }
+                message => {
+                    flush(&sink, pending.take());
+                    emit_message(&sink, &session_id, &message);
+                }
+            },
+            Emit::Event {
+                session_id,
+                event,
+                payload,
+            } => {
+                flush(&sink, pending.take());
+                sink.emit_for_session(&session_id, event, payload);
+            }
+        }
+    }
+}
+
+fn flush(sink: &Sink, pending: Option<Pending>) {
+    if let Some(p) = pending {
+        emit_message(sink, &p.session_id, &ClaudeMessage::StreamEvent(p.stream));
+    }
+}
+
+fn emit_message(sink: &Sink, session_id: &str, message: &ClaudeMessage) {
+    sink.emit_for_session(
+        session_id,
+        "claude:message",
+        serde_json::json!({
+            "session_id": session_id,
+            "message": message,
+        }),
+    );
+}
+
+/// The streamed text of a `content_block_delta`, if it has any.
+fn delta_text(stream: &StreamEventMessage) -> Option<&str> {
+    if stream.event.event_type != "content_block_delta" {
+        return None;
+    }
+    let delta = stream.event.delta.as_ref()?;
+    match delta.delta_type.as_str() {
+        "text_delta" => delta.text.as_deref(),
+        "input_json_delta" => delta.partial_json.as_deref(),
+        _ => None,
+    }
+}
+
+/// Append `next`'s delta to `into` if both continue the same content block.
+fn append(into: &mut StreamEventMessage, next: &StreamEventMessage) -> bool {
+    if into.event.index != next.event.index {
+        return false;
+    }
+    let (Some(delta), Some(next_delta)) = (into.event.delta.as_mut(), next.event.delta.as_ref())
+    else {
+        return false;
+    };
+    if delta.delta_type != next_delta.delta_type {
+        return false;
+    }
+    let Some(text) = delta_text(next) else {
+        return false;
+    };
+    let target = match delta.delta_type.as_str() {
+        "text_delta" => &mut delta.text,
+        _ => &mut delta.partial_json,
+    };
+    target.get_or_insert_with(String::new).push_str(text);
+    true
+}
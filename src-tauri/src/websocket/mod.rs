@@ -1,2 +1,4 @@
+pub mod emitter;
 pub mod protocol;
 pub mod server;
+pub mod ui;
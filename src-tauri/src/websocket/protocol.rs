@@ -91,6 +91,18 @@ pub enum ContentBlock {
         tool_use_id: String,
         content: serde_json::Value,
     },
+
+    /// Extended thinking: the model's reasoning before its answer.
+    #[serde(rename = "thinking")]
+    Thinking {
+        thinking: String,
+        #[serde(default)]
+        signature: Option<String>,
+    },
+
+    /// Thinking the API returned encrypted; there's nothing to show.
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -135,6 +147,10 @@ pub struct StreamDelta {
     pub delta_type: String,
     pub text: Option<String>,
     pub partial_json: Option<String>,
+    /// For `thinking_delta`.
+    pub thinking: Option<String>,
+    /// For `signature_delta`, which closes a thinking block.
+    pub signature: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
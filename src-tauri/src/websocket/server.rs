@@ -1,10 +1,17 @@
 use std::sync::Arc;
 
-use tauri::Emitter;
 use tokio_tungstenite::tungstenite::http;
 
+use crate::config::manager::NotificationSettings;
 use crate::error::KataraError;
+use crate::instance;
+use crate::plugins::host::REVIEW_TIMEOUT;
+use crate::process::recovery;
+use crate::process::session::PendingApproval;
+use crate::sink::Sink;
 use crate::state::AppState;
+use crate::stats::activity;
+use crate::websocket::emitter::MessageEmitter;
 use crate::websocket::protocol::{ClaudeMessage, WsEvent};
 
 /// Starts the WebSocket server that Claude CLI processes connect to via --sdk-url.
@@ -12,13 +19,18 @@ use crate::websocket::protocol::{ClaudeMessage, WsEvent};
 /// The server accepts connections at ws://127.0.0.1:{port}/ws/cli/{sessionId}.
 /// The session ID is embedded in the URL path so we can associate each
 /// CLI connection with the correct session immediately on connect.
-pub async fn start_ws_server(
-    state: Arc<AppState>,
-    app_handle: tauri::AppHandle,
-) -> Result<(), KataraError> {
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+pub async fn start_ws_server(state: Arc<AppState>, sink: Sink) -> Result<(), KataraError> {
+    // After a crash, listen where surviving CLIs will try to reconnect
+    let port = state
+        .settings()
+        .server
+        .ws_port
+        .or_else(recovery::previous_ws_port);
+    let listener = instance::bind(port, "WebSocket")
         .await
-        .map_err(|e| KataraError::WebSocket(e.to_string()))?;
+        .map_err(|e| {
+            KataraError::WebSocket(format!("Can't listen on port {}: {}", port.unwrap_or(0), e))
+        })?;
 
     let port = listener
         .local_addr()
@@ -27,15 +39,19 @@ pub async fn start_ws_server(
 
     *state.ws_port.write().await = port;
     println!("[katara] WebSocket server listening on port {}", port);
+    instance::write_discovery(&state).await;
 
     // Notify frontend of the WS port
-    let _ = app_handle.emit("ws:port", port);
+    sink.emit("ws:port", port);
+
+    // Restore the previous run's sessions before their CLIs can reconnect
+    recovery::reconcile(&state, &sink).await;
 
     while let Ok((stream, addr)) = listener.accept().await {
         println!("[katara] WebSocket connection from {}", addr);
         let state = state.clone();
-        let app_handle = app_handle.clone();
-        tokio::spawn(handle_connection(stream, state, app_handle));
+        let sink = sink.clone();
+        tokio::spawn(handle_connection(stream, state, sink));
     }
 
     Ok(())
@@ -54,11 +70,7 @@ fn extract_session_id_from_request(req: &http::Request<()>) -> Option<String> {
     }
 }
 
-async fn handle_connection(
-    stream: tokio::net::TcpStream,
-    state: Arc<AppState>,
-    app_handle: tauri::AppHandle,
-) {
+async fn handle_connection(stream: tokio::net::TcpStream, state: Arc<AppState>, sink: Sink) {
     // Use accept_hdr_async to inspect the HTTP upgrade request and extract
     // the session ID from the URL path before completing the handshake.
     let url_session_id: Arc<std::sync::Mutex<Option<String>>> =
@@ -93,6 +105,9 @@ async fn handle_connection(
 
     let (mut write, mut read) = futures_util::StreamExt::split(ws_stream);
 
+    // Streaming deltas are coalesced before reaching the webview
+    let emitter = MessageEmitter::spawn(sink.clone());
+
     // Create a channel for sending messages back to CLI
     let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(64);
 
@@ -113,7 +128,21 @@ async fn handle_connection(
         let mut sessions = state.sessions.write().await;
         if let Some(session) = sessions.get_mut(&session_id) {
             session.ws_sender = Some(tx.clone());
+            state.session_connected.notify_waiters();
             println!("[katara] Session {} CLI connected (from URL path)", session_id);
+            // A CLI coming back (e.g. re-adopted after a crash) won't send
+            // system/init again
+            if session.status == crate::process::session::SessionStatus::Disconnected {
+                session.status = crate::process::session::SessionStatus::Connected;
+                emitter.emit(
+                    &session_id,
+                    "claude:status",
+                    serde_json::json!({
+                        "session_id": session_id,
+                        "status": "Connected",
+                    }),
+                );
+            }
         } else {
             eprintln!("[katara] URL session_id {} not found in state", session_id);
         }
@@ -170,6 +199,7 @@ async fn handle_connection(
                     let mut sessions = state.sessions.write().await;
                     if let Some(session) = sessions.get_mut(&session_id) {
                         session.ws_sender = Some(tx.clone());
+                        state.session_connected.notify_waiters();
                         session.status =
                             crate::process::session::SessionStatus::Connected;
 
@@ -185,13 +215,17 @@ async fn handle_connection(
                         if let Some(ref mode) = sys.permission_mode {
                             session.permission_mode = mode.clone();
                         }
+                        if state.settings().history.persist_transcripts {
+                            crate::process::transcript::write_meta(session);
+                        }
 
                         println!(
                             "[katara] Session {} system/init received (CLI session_id: {:?}, model: {:?}, permissionMode: {:?})",
                             session_id, sys.session_id, sys.model, sys.permission_mode
                         );
 
-                        let _ = app_handle.emit(
+                        emitter.emit(
+                            &session_id,
                             "claude:status",
                             serde_json::json!({
                                 "session_id": session_id,
@@ -204,6 +238,8 @@ async fn handle_connection(
                             session_id
                         );
                     }
+                    drop(sessions);
+                    recovery::save(&state).await;
                 }
             }
 
@@ -218,7 +254,11 @@ async fn handle_connection(
                         || session.status == crate::process::session::SessionStatus::Idle
                     {
                         session.status = crate::process::session::SessionStatus::Active;
-                        let _ = app_handle.emit(
+                        if let Some(ref watcher) = session.fs_watcher {
+                            watcher.set_active(true);
+                        }
+                        emitter.emit(
+                            &session_id,
                             "claude:status",
                             serde_json::json!({
                                 "session_id": session_id,
@@ -229,13 +269,22 @@ async fn handle_connection(
                 }
             }
 
+            // Attribute upcoming file changes to this message's Edit/Write calls
+            if let ClaudeMessage::Assistant(ref assistant) = claude_msg {
+                let sessions = state.sessions.read().await;
+                if let Some(watcher) = sessions.get(&session_id).and_then(|s| s.fs_watcher.as_ref()) {
+                    watcher.track_tool_calls(&assistant.message.content);
+                }
+            }
+
             // Track token usage from assistant messages
             if let ClaudeMessage::Assistant(ref assistant) = claude_msg {
                 if let Some(ref usage) = assistant.message.usage {
                     let mut sessions = state.sessions.write().await;
                     if let Some(session) = sessions.get_mut(&session_id) {
                         session.usage_totals.add(usage);
-                        let _ = app_handle.emit(
+                        emitter.emit(
+                            &session_id,
                             "claude:usage",
                             serde_json::json!({
                                 "session_id": session_id,
@@ -250,14 +299,15 @@ async fn handle_connection(
             // Intercept before broadcast so the frontend never sees auto-handled requests.
             if let ClaudeMessage::ControlRequest(ref ctrl) = claude_msg {
                 if ctrl.request.subtype == "can_use_tool" {
-                    let (perm_mode, ws_sender) = {
+                    let (perm_mode, ws_sender, working_dir) = {
                         let sessions = state.sessions.read().await;
                         sessions.get(&session_id).map(|s| {
-                            (s.permission_mode.clone(), s.ws_sender.clone())
-                        }).unwrap_or(("default".to_string(), None))
+                            (s.permission_mode.clone(), s.ws_sender.clone(), s.working_dir.clone())
+                        }).unwrap_or(("default".to_string(), None, String::new()))
                     };
 
-                    let auto_behavior = match perm_mode.as_str() {
+                    let mut decided_by = format!("permission_mode={}", perm_mode);
+                    let mut auto_behavior = match perm_mode.as_str() {
                         "bypassPermissions" => Some("allow"),
                         "plan" => Some("deny"),
                         "acceptEdits" => {
@@ -271,6 +321,30 @@ async fn handle_connection(
                         _ => None, // "default" — ask user
                     };
 
+                    // Plugin policies can deny anything and allow what the
+                    // user would otherwise be asked about
+                    if auto_behavior != Some("deny") {
+                        let tool_name = ctrl.request.tool_name.as_deref().unwrap_or("unknown");
+                        let input = ctrl.request.input.clone().unwrap_or_default();
+                        // Bounded as a whole, since this holds up every later
+                        // message from the CLI, interrupts included
+                        let review = tokio::time::timeout(
+                            REVIEW_TIMEOUT,
+                            state.plugins.review_tool(&session_id, &working_dir, tool_name, &input),
+                        )
+                        .await
+                        .unwrap_or_else(|_| {
+                            eprintln!("[katara] Plugins took too long to review {}", tool_name);
+                            None
+                        });
+                        if let Some((allowed, plugin)) = review {
+                            if !allowed || auto_behavior.is_none() {
+                                auto_behavior = Some(if allowed { "allow" } else { "deny" });
+                                decided_by = format!("plugin {}", plugin);
+                            }
+                        }
+                    }
+
                     if let Some(behavior) = auto_behavior {
                         if let (Some(ref req_id), Some(ref ws_tx)) = (&ctrl.request.request_id, &ws_sender) {
                             use crate::websocket::protocol::{
@@ -293,29 +367,94 @@ async fn handle_connection(
                             let json = serde_json::to_string(&msg).unwrap_or_default();
                             let _ = ws_tx.send(format!("{}\n", json)).await;
                             println!(
-                                "[katara] Auto-{} tool {} (permission_mode={})",
+                                "[katara] Auto-{} tool {} ({})",
                                 behavior,
                                 ctrl.request.tool_name.as_deref().unwrap_or("unknown"),
-                                perm_mode
+                                decided_by
+                            );
+                            activity::approval(
+                                &session_id,
+                                &working_dir,
+                                ctrl.request.tool_name.as_deref().unwrap_or("unknown"),
+                                behavior == "allow",
+                                true,
                             );
                             continue; // Skip broadcast — handled automatically
                         }
                     }
+
+                    let tool_name = ctrl.request.tool_name.as_deref().unwrap_or("a tool");
+                    if let Some(ref request_id) = ctrl.request.request_id {
+                        let mut sessions = state.sessions.write().await;
+                        if let Some(session) = sessions.get_mut(&session_id) {
+                            session.pending_approvals.push(PendingApproval {
+                                request_id: request_id.clone(),
+                                tool_name: tool_name.to_string(),
+                            });
+                        }
+                    }
+                    notify(
+                        &state,
+                        &sink,
+                        |n| n.permission_requests,
+                        "Permission needed",
+                        &format!("Claude wants to use {}", tool_name),
+                    );
                 }
             }
 
             // Mark Idle on result
-            if matches!(claude_msg, ClaudeMessage::Result(_)) {
+            if let ClaudeMessage::Result(ref result) = claude_msg {
                 let mut sessions = state.sessions.write().await;
                 if let Some(session) = sessions.get_mut(&session_id) {
                     session.status = crate::process::session::SessionStatus::Idle;
-                    let _ = app_handle.emit(
+                    if let Some(ref watcher) = session.fs_watcher {
+                        watcher.set_active(false);
+                    }
+                    emitter.emit(
+                        &session_id,
                         "claude:status",
                         serde_json::json!({
                             "session_id": session_id,
                             "status": "Idle",
                         }),
                     );
+                    let project = std::path::Path::new(&session.working_dir)
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| session.working_dir.clone());
+                    notify(
+                        &state,
+                        &sink,
+                        |n| n.session_finished,
+                        "Claude finished",
+                        &format!("Session in {} is waiting for you", project),
+                    );
+                    // The turn may have edited files
+                    crate::git::repo::refresh_session_status(
+                        sink.clone(),
+                        session_id.clone(),
+                        session.working_dir.clone(),
+                    );
+                    let is_error = result.subtype.as_deref().is_some_and(|s| s.starts_with("error"))
+                        || result.extra.get("is_error").and_then(|v| v.as_bool()) == Some(true);
+                    activity::turn_finished(
+                        &state,
+                        &session_id,
+                        &session.working_dir,
+                        session.model.as_deref(),
+                        &session.usage_totals,
+                        result.extra.get("duration_ms").and_then(|v| v.as_u64()),
+                        is_error,
+                    );
+                    if state.settings().checkpoints {
+                        crate::git::checkpoint::record_turn(
+                            sink.clone(),
+                            session_id.clone(),
+                            session.working_dir.clone(),
+                            result.result.clone().unwrap_or_default(),
+                        );
+                    }
                 }
             }
 
@@ -329,14 +468,22 @@ async fn handle_connection(
                     | ClaudeMessage::KeepAlive {}
                     | ClaudeMessage::AuthStatus(_)
             ) {
-                let mut sessions = state.sessions.write().await;
-                if let Some(session) = sessions.get_mut(&session_id) {
-                    if let Ok(val) = serde_json::to_value(&claude_msg) {
-                        session.message_history.push(val);
-                    }
+                let history = state
+                    .sessions
+                    .read()
+                    .await
+                    .get(&session_id)
+                    .map(|s| s.history.clone());
+                if let (Some(history), Ok(val)) = (history, serde_json::to_value(&claude_msg)) {
+                    history.push(val);
                 }
             }
 
+            // Remember the finished turn in case the app crashes
+            if matches!(claude_msg, ClaudeMessage::Result(_)) {
+                recovery::save(&state).await;
+            }
+
             // Broadcast to event bus and frontend
             let event = WsEvent {
                 session_id: session_id.clone(),
@@ -344,13 +491,7 @@ async fn handle_connection(
             };
             let _ = state.event_tx.send(event);
 
-            let _ = app_handle.emit(
-                "claude:message",
-                serde_json::json!({
-                    "session_id": session_id,
-                    "message": claude_msg,
-                }),
-            );
+            emitter.message(&session_id, claude_msg);
         }
     }
 
@@ -364,8 +505,11 @@ async fn handle_connection(
     if let Some(session) = sessions.get_mut(&session_id) {
         session.status = crate::process::session::SessionStatus::Disconnected;
         session.ws_sender = None;
+        session.pending_approvals.clear();
+        session.fs_watcher = None;
 
-        let _ = app_handle.emit(
+        emitter.emit(
+            &session_id,
             "claude:status",
             serde_json::json!({
                 "session_id": session_id,
@@ -373,4 +517,23 @@ async fn handle_connection(
             }),
         );
     }
+    drop(sessions);
+    recovery::save(&state).await;
+}
+
+/// Show an OS notification if the setting picked by `enabled` is on and the
+/// main window isn't focused.
+fn notify(
+    state: &AppState,
+    sink: &Sink,
+    enabled: fn(&NotificationSettings) -> bool,
+    title: &str,
+    body: &str,
+) {
+    if sink.in_focus() {
+        return;
+    }
+    if enabled(&state.settings().notifications) {
+        let _ = sink.notify(title, body);
+    }
 }
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Extension, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::auth;
+use crate::remote::pairing::PairedDevice;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct UiQuery {
+    /// Only relay this session's messages.
+    session_id: Option<String>,
+}
+
+/// GET /ws/ui — every CLI message as it arrives, for web UIs and
+/// dashboards outside the app. Each text frame is a `{ session_id,
+/// message }` object, or `{ gap }` with the number of messages dropped
+/// while the client was too slow.
+///
+/// Needs the auth token (a paired device's token on the remote server) as
+/// a bearer token. Browsers offer the subprotocols `katara` and
+/// `bearer.<token>` instead.
+pub async fn handle(
+    State(state): State<Arc<AppState>>,
+    device: Option<Extension<PairedDevice>>,
+    headers: HeaderMap,
+    Query(query): Query<UiQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    // The remote server has already checked the device's token
+    let token = auth::request_token(&headers);
+    if device.is_none() && !token.is_some_and(|t| auth::token_matches(t, &state.auth_token)) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    ws.protocols([auth::WS_PROTOCOL])
+        .on_upgrade(move |socket| relay(socket, state, query.session_id))
+}
+
+async fn relay(mut socket: WebSocket, state: Arc<AppState>, session_id: Option<String>) {
+    // Subscribed after the upgrade, so a client sees messages from when it
+    // connected; history is in GET /api/v1/sessions/{id}/messages
+    let mut events = state.event_tx.subscribe();
+    println!(
+        "[katara] UI WebSocket connected ({})",
+        session_id.as_deref().unwrap_or("all sessions")
+    );
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    // Tell a slow client it missed some, so it can refetch
+                    // the history
+                    Err(RecvError::Lagged(missed)) => {
+                        let gap = serde_json::json!({ "gap": missed }).to_string();
+                        if socket.send(Message::Text(gap.into())).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if session_id.as_ref().is_some_and(|id| *id != event.session_id) {
+                    continue;
+                }
+                let Ok(data) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                if socket.send(Message::Text(data.into())).await.is_err() {
+                    break;
+                }
+            }
+            // Nothing is expected from the client but pings and the close
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    println!("[katara] UI WebSocket disconnected");
+}
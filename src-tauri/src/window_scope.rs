@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, EventTarget, Manager};
+
+use crate::state::AppState;
+
+/// Sessions and terminals a window shows. Windows without a scope receive
+/// events for every session and terminal that no scoped window has claimed,
+/// so a single-window app behaves as before.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowScope {
+    #[serde(default)]
+    pub sessions: HashSet<String>,
+    #[serde(default)]
+    pub terminals: HashSet<String>,
+}
+
+impl WindowScope {
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty() && self.terminals.is_empty()
+    }
+}
+
+/// What an event is about.
+#[derive(Debug, Clone, Copy)]
+pub enum Subject<'a> {
+    Session(&'a str),
+    Terminal(&'a str),
+}
+
+/// Emit a `terminal:*` event to the windows showing `terminal_id`.
+pub fn emit_for_terminal<S: Serialize + Clone>(
+    app_handle: &AppHandle,
+    terminal_id: &str,
+    event: &str,
+    payload: S,
+) {
+    emit(
+        app_handle,
+        Some(Subject::Terminal(terminal_id)),
+        event,
+        payload,
+    );
+}
+
+/// Emit an event to the windows showing `subject`, or to every window.
+pub fn emit<S: Serialize + Clone>(
+    app_handle: &AppHandle,
+    subject: Option<Subject<'_>>,
+    event: &str,
+    payload: S,
+) {
+    let Some(subject) = subject else {
+        let _ = app_handle.emit(event, payload);
+        return;
+    };
+    let state = app_handle.state::<Arc<AppState>>();
+    let targets: Vec<String> = {
+        let scopes = state
+            .window_scopes
+            .read()
+            .unwrap_or_else(|e| e.into_inner());
+        if scopes.is_empty() {
+            let _ = app_handle.emit(event, payload);
+            return;
+        }
+        let claimed: Vec<String> = scopes
+            .iter()
+            .filter(|(_, scope)| match subject {
+                Subject::Session(id) => scope.sessions.contains(id),
+                Subject::Terminal(id) => scope.terminals.contains(id),
+            })
+            .map(|(label, _)| label.clone())
+            .collect();
+        if claimed.is_empty() {
+            app_handle
+                .webview_windows()
+                .into_keys()
+                .filter(|label| !scopes.contains_key(label))
+                .collect()
+        } else {
+            claimed
+        }
+    };
+    for label in targets {
+        let _ = app_handle.emit_to(EventTarget::webview_window(label), event, payload.clone());
+    }
+}
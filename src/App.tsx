@@ -35,7 +35,8 @@ const TABS = [
 ] as const;
 
 export default function App() {
-  const { activeTab, setActiveTab, setPorts, runtimeUrl } = useAppStore();
+  const { activeTab, setActiveTab, setPorts, setAuthToken, runtimeUrl, authToken } =
+    useAppStore();
 
   // Initialize session event listeners (Tauri events from Rust)
   useEffect(() => {
@@ -44,12 +45,15 @@ export default function App() {
 
   // Fetch ports from Rust on mount (servers start before frontend loads)
   useEffect(() => {
-    invoke<{ ws_port: number; axum_port: number }>("get_ports").then((ports) => {
-      if (ports.ws_port > 0 || ports.axum_port > 0) {
-        setPorts(ports.ws_port, ports.axum_port);
-      }
-    });
-  }, [setPorts]);
+    invoke<{ ws_port: number; axum_port: number; auth_token: string }>("get_ports").then(
+      (ports) => {
+        setAuthToken(ports.auth_token);
+        if (ports.ws_port > 0 || ports.axum_port > 0) {
+          setPorts(ports.ws_port, ports.axum_port);
+        }
+      },
+    );
+  }, [setPorts, setAuthToken]);
 
   // Also listen for port events (in case servers restart or start late)
   useEffect(() => {
@@ -73,6 +77,12 @@ export default function App() {
   // So we must set the full run endpoint URL.
   // Type assertion needed: top-level @ag-ui/client and the one bundled
   // inside @copilotkitnext/react are duplicate packages with identical APIs.
+  // Every AG-UI endpoint needs the per-launch token.
+  const authHeaders = useMemo<Record<string, string>>(
+    () => (authToken ? { Authorization: `Bearer ${authToken}` } : {}),
+    [authToken],
+  );
+
   const agents = useMemo(() => {
     if (!runtimeUrl) return undefined;
     return {
@@ -80,9 +90,10 @@ export default function App() {
         url: `${runtimeUrl}/agent/default/run`,
         agentId: "default",
         description: "Claude Code AI agent",
+        headers: authHeaders,
       }),
     } as Record<string, any>;
-  }, [runtimeUrl]);
+  }, [runtimeUrl, authHeaders]);
 
   const ActiveComponent =
     TABS.find((t) => t.id === activeTab)?.component ?? AgentChat;
@@ -119,10 +130,11 @@ export default function App() {
   );
 
   // Wrap with CopilotKit when AG-UI backend is ready
-  if (runtimeUrl) {
+  if (runtimeUrl && authToken) {
     return (
       <CopilotKit
         runtimeUrl={runtimeUrl}
+        headers={authHeaders}
         agent="default"
         showDevConsole={false}
         agents__unsafe_dev_only={agents}
@@ -0,0 +1,49 @@
+import { useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { isKataraError } from "./types/error";
+
+/** Window opened by the global quick prompt shortcut. */
+export default function QuickPrompt() {
+  const [text, setText] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  // The window is hidden rather than closed, so refocus on every show
+  useEffect(() => {
+    const unlisten = getCurrentWindow().onFocusChanged(({ payload: focused }) => {
+      if (focused) inputRef.current?.focus();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const submit = async () => {
+    try {
+      await invoke("submit_quick_prompt", { content: text });
+      setText("");
+      setError(null);
+    } catch (e) {
+      setError(isKataraError(e) ? e.message : String(e));
+    }
+  };
+
+  return (
+    <div className="h-screen flex flex-col justify-center px-4 bg-slate-900 text-slate-200">
+      <input
+        ref={inputRef}
+        autoFocus
+        value={text}
+        onChange={(e) => setText(e.target.value)}
+        onKeyDown={(e) => {
+          if (e.key === "Enter" && text.trim()) submit();
+          if (e.key === "Escape") getCurrentWindow().hide();
+        }}
+        placeholder="Ask Claude…"
+        className="w-full bg-transparent text-lg outline-none placeholder:text-slate-500"
+      />
+      {error && <div className="text-xs text-red-400 mt-1">{error}</div>}
+    </div>
+  );
+}
@@ -5,9 +5,12 @@ interface AppState {
   runtimeUrl: string | null;
   wsPort: number;
   axumPort: number;
+  /** Bearer token the AG-UI server requires. */
+  authToken: string | null;
   setActiveTab: (tab: string) => void;
   setRuntimeUrl: (url: string) => void;
   setPorts: (ws: number, axum: number) => void;
+  setAuthToken: (token: string) => void;
 }
 
 export const useAppStore = create<AppState>((set) => ({
@@ -15,6 +18,7 @@ export const useAppStore = create<AppState>((set) => ({
   runtimeUrl: null,
   wsPort: 0,
   axumPort: 0,
+  authToken: null,
   setActiveTab: (tab) => set({ activeTab: tab }),
   setRuntimeUrl: (url) => set({ runtimeUrl: url }),
   setPorts: (ws, axum) =>
@@ -23,4 +27,5 @@ export const useAppStore = create<AppState>((set) => ({
       axumPort: axum,
       runtimeUrl: axum > 0 ? `http://127.0.0.1:${axum}` : null,
     }),
+  setAuthToken: (token) => set({ authToken: token }),
 }));
@@ -0,0 +1,32 @@
+/** Stable error codes returned by every Tauri command. */
+export type ErrorCode =
+  | "IO"
+  | "SERIALIZATION"
+  | "WEBSOCKET"
+  | "WS_NOT_READY"
+  | "NOT_CONNECTED"
+  | "SESSION_NOT_FOUND"
+  | "TERMINAL_NOT_FOUND"
+  | "TERMINAL"
+  | "CONFIG"
+  | "SKILL"
+  | "INVALID_INPUTS"
+  | "INVALID_SETTINGS"
+  | "SKILL_CONFLICT"
+  | "CLI_NOT_INSTALLED"
+  | "PROCESS"
+  | "UPDATE"
+  | "GIT"
+  | "REMOTE"
+  | "PLUGIN";
+
+/** Error shape rejected by `invoke`. `details` depends on `code`. */
+export interface KataraError<D = Record<string, unknown> | null> {
+  code: ErrorCode;
+  message: string;
+  details: D;
+}
+
+export function isKataraError(e: unknown): e is KataraError {
+  return typeof e === "object" && e !== null && "code" in e && "message" in e;
+}
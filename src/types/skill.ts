@@ -1,9 +1,17 @@
+import type { KataraError } from "./error";
+
 export interface ParsedSkill {
   file_path: string;
   metadata: SkillMetadata;
   prompt_template: string;
+  level: SkillLevel;
+  shadows?: string;
+  resources?: string[];
+  content_hash: string;
 }
 
+export type SkillLevel = "user" | "project";
+
 export interface SkillMetadata {
   name: string;
   description: string;
@@ -11,6 +19,16 @@ export interface SkillMetadata {
   inputs: SkillInput[];
   outputs: SkillOutput[];
   tags: string[];
+  model?: string;
+  permission_mode?: string;
+  allowed_tools?: string[];
+  source?: SkillSource;
+  sandbox?: boolean;
+}
+
+export interface SkillSource {
+  url: string;
+  imported_at: number;
 }
 
 export interface SkillInput {
@@ -28,3 +46,59 @@ export interface SkillOutput {
   label: string;
   output_type: string;
 }
+
+export interface SkillDiagnostic {
+  line: number;
+  severity: "error" | "warning";
+  message: string;
+}
+
+export interface SkillsChangedPayload {
+  added: string[];
+  modified: string[];
+  removed: string[];
+}
+
+export type SkillRunStatus = "Running" | "Succeeded" | { Failed: string };
+
+export interface SkillRun {
+  id: string;
+  skill_path: string;
+  skill_name: string;
+  session_id: string;
+  working_dir: string;
+  sandbox_dir?: string;
+  inputs: Record<string, unknown>;
+  status: SkillRunStatus;
+  result: string | null;
+  outputs: Record<string, unknown>;
+  cost_usd: number | null;
+  duration_ms: number | null;
+  started_at: number;
+  finished_at: number | null;
+}
+
+/** Error returned by `run_skill` / `preview_skill` when inputs don't validate. */
+export interface SkillInputErrors
+  extends KataraError<{ fields: { field: string; message: string }[] }> {
+  code: "INVALID_INPUTS";
+}
+
+/** Result of `export_skill_as_command`. */
+export interface CommandExport {
+  path: string;
+  warnings: string[];
+}
+
+/** Error returned by `write_skill` when the file changed on disk. */
+export interface SkillConflictError
+  extends KataraError<{
+    conflict: {
+      path: string;
+      disk_content: string | null;
+      disk_hash: string | null;
+      content: string;
+    };
+  }> {
+  code: "SKILL_CONFLICT";
+}